@@ -1,9 +1,11 @@
 use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::num::NonZeroUsize;
 use std::rc::Rc;
 
 use lru::LruCache;
 
+use crate::model::overflow_page::OverflowPage;
 use crate::model::page::Page;
 use crate::model::page_id::PageId;
 use crate::storage::disk_manager::SharedDiskManager;
@@ -20,6 +22,11 @@ use crate::wal::wal::Wal;
 pub struct BufferPool {
     // current not supporting concurrency
     page_table: LruCache<PageId, Rc<RefCell<Page>>>, // page_table keeping track of page in-mem caching
+    // b-tree pages modified in memory and not written to disk yet. They are kept
+    // out of page_table so they are not evicted before being written.
+    dirty_pages: BTreeMap<PageId, Rc<RefCell<Page>>>,
+    // bytes of the other pages (overflow, freelist) modified and not written yet
+    dirty_raw_pages: BTreeMap<PageId, Vec<u8>>,
     disk_manager: SharedDiskManager,
     // When a transaction is committed, all dirty pages (modified in mem not written to disk)
     // are gathered and written to WAL.
@@ -33,6 +40,8 @@ impl BufferPool {
         let capacity = NonZeroUsize::new(capacity).expect("Capacity must be non-zero");
         BufferPool {
             page_table: LruCache::new(capacity),
+            dirty_pages: BTreeMap::new(),
+            dirty_raw_pages: BTreeMap::new(),
             disk_manager: disk_manager.clone(),
             wal: Wal::new(disk_manager.clone()).unwrap(),
         }
@@ -43,20 +52,97 @@ impl BufferPool {
     /// If not in cache, it should be read from the DiskManager, saved in buffer,
     /// then return. If there are insufficient buffer space, a page in the buffer
     /// should be evicted based on the policy and new page added.
-    pub fn get_page(&mut self, page_id: PageId) -> Rc<RefCell<Page>> {
-        if !self.page_table.contains(&page_id) {
-            let page = self
-                .disk_manager
-                .borrow_mut()
-                .read_page(page_id)
-                .expect("Failed to read page from disk");
-            let mut_page_ref = Rc::new(RefCell::new(page));
-            self.page_table.put(page_id, mut_page_ref.clone());
+    pub fn get_page(&mut self, page_id: PageId) -> anyhow::Result<Rc<RefCell<Page>>> {
+        if let Some(page) = self.dirty_pages.get(&page_id) {
+            return Ok(page.clone());
+        }
+        if let Some(page) = self.page_table.get(&page_id) {
+            return Ok(page.clone());
+        }
+        let page = match self.dirty_raw_pages.get(&page_id) {
+            Some(data) => Page::from_bytes(page_id.page_number, data.clone())?,
+            None => self.disk_manager.borrow().read_page(page_id)?,
+        };
+        let mut_page_ref = Rc::new(RefCell::new(page));
+        self.page_table.put(page_id, mut_page_ref.clone());
+        Ok(mut_page_ref)
+    }
+
+    /// Reads an overflow page.
+    /// Overflow pages are not cached: the chain of a payload is read once
+    /// when its cell is parsed and does not need to stay in the buffer.
+    pub fn get_overflow_page(&mut self, page_id: PageId) -> anyhow::Result<OverflowPage> {
+        let bytes = self.get_raw_page(page_id)?;
+        OverflowPage::parse(page_id.page_number, &bytes)
+    }
+
+    /// Reads the bytes of a page, including the changes not written to disk yet.
+    pub fn get_raw_page(&mut self, page_id: PageId) -> anyhow::Result<Vec<u8>> {
+        if let Some(data) = self.dirty_raw_pages.get(&page_id) {
+            return Ok(data.clone());
+        }
+        if let Some(page) = self.dirty_pages.get(&page_id) {
+            return Ok(page.borrow().data.clone());
+        }
+        self.disk_manager.borrow().read_raw_page(page_id)
+    }
+
+    /// Marks a b-tree page modified in memory: it stays in the buffer until
+    /// the dirty pages are flushed.
+    pub fn mark_dirty(&mut self, page: &Rc<RefCell<Page>>) {
+        let page_id = page.borrow().page_id;
+        self.page_table.pop(&page_id);
+        self.dirty_pages.insert(page_id, page.clone());
+    }
+
+    /// Adds a new b-tree page, e.g. a page allocated by a split, replacing any
+    /// version of the page in the buffer. The page is dirty.
+    pub fn put_page(&mut self, page: Page) -> Rc<RefCell<Page>> {
+        let page_id = page.page_id;
+        self.page_table.pop(&page_id);
+        self.dirty_raw_pages.remove(&page_id);
+        let page = Rc::new(RefCell::new(page));
+        self.dirty_pages.insert(page_id, page.clone());
+        page
+    }
+
+    /// Sets the bytes of a page which is not a b-tree page (overflow or freelist page),
+    /// replacing any version of the page in the buffer. The page is dirty.
+    pub fn put_raw_page(&mut self, page_id: PageId, data: Vec<u8>) {
+        self.page_table.pop(&page_id);
+        self.dirty_pages.remove(&page_id);
+        self.dirty_raw_pages.insert(page_id, data);
+    }
+
+    /// Returns true if pages were modified and not written to disk yet.
+    pub fn has_dirty_pages(&self) -> bool {
+        !self.dirty_pages.is_empty() || !self.dirty_raw_pages.is_empty()
+    }
+
+    /// Writes the dirty pages to disk in page number order. The b-tree pages
+    /// written stay in the cache.
+    pub fn flush_dirty_pages(&mut self) -> anyhow::Result<()> {
+        let mut disk_manager = self.disk_manager.borrow_mut();
+        for (page_id, data) in &self.dirty_raw_pages {
+            disk_manager.write_raw_page(*page_id, data)?;
+        }
+        for (page_id, page) in &self.dirty_pages {
+            disk_manager.write_page(*page_id, &page.borrow())?;
+        }
+        drop(disk_manager);
+        self.dirty_raw_pages.clear();
+        for (page_id, page) in std::mem::take(&mut self.dirty_pages) {
+            self.page_table.put(page_id, page);
         }
-        self.page_table
-            .get(&page_id)
-            .expect("Failed to get page from buffer pool")
-            .clone()
+        Ok(())
+    }
+
+    /// Drops the changes not written to disk. Cached pages are dropped too: the
+    /// pages modified in place are read again from disk.
+    pub fn discard_dirty_pages(&mut self) {
+        self.dirty_pages.clear();
+        self.dirty_raw_pages.clear();
+        self.page_table.clear();
     }
 
     /// Flushes a page to disk.
@@ -92,13 +178,21 @@ mod tests {
     fn test_buffer_pool_evict_page_when_over_capacity() {
         let mut buffer_pool = BufferPool::new(2, ref_disk_manager().clone());
 
-        buffer_pool.get_page(PageId { page_number: 4 });
-        buffer_pool.get_page(PageId { page_number: 2 });
-        buffer_pool.get_page(PageId { page_number: 3 });
+        buffer_pool.get_page(PageId { page_number: 4 }).unwrap();
+        buffer_pool.get_page(PageId { page_number: 2 }).unwrap();
+        buffer_pool.get_page(PageId { page_number: 3 }).unwrap();
 
         // should evict first page added because of 2 capacity
         assert!(!buffer_pool.have_page(PageId { page_number: 4 }));
         assert!(buffer_pool.have_page(PageId { page_number: 2 }));
         assert!(buffer_pool.have_page(PageId { page_number: 3 }));
     }
+
+    #[test]
+    fn test_buffer_pool_read_error() {
+        let mut buffer_pool = BufferPool::new(2, ref_disk_manager().clone());
+
+        // sample.db has 4 pages
+        assert!(buffer_pool.get_page(PageId { page_number: 100 }).is_err());
+    }
 }
@@ -1,16 +1,17 @@
 use clap::{App, Arg, SubCommand};
 use datafusion_sql::planner::SqlToRel;
 use datafusion_sql::sqlparser::ast::Statement;
-use datafusion_sql::sqlparser::dialect::AnsiDialect;
-use datafusion_sql::sqlparser::parser::Parser;
 use std::cell::RefCell;
+use std::process;
 use std::rc::Rc;
-use std::sync::Arc;
+
+use anyhow::{bail, Result};
 
 use log::info;
 use rsql::model::database::Database;
 use rsql::physical::physical_planner::PhysicalPlanner;
 use rsql::sql::context_provider::SqliteContextProvider;
+use rsql::sql::{functions, parsing, rewrite};
 use rsql::util::presentation;
 
 fn main() {
@@ -24,6 +25,11 @@ fn main() {
                     Arg::with_name("sql")
                         .help("SQL string to execute")
                         .required(true),
+                )
+                .arg(
+                    Arg::with_name("batch")
+                        .long("batch")
+                        .help("Execute the query on batches of columns (Arrow RecordBatch)"),
                 ),
         )
         .get_matches();
@@ -32,29 +38,82 @@ fn main() {
         ("sql", Some(_matches)) => {
             let db_file_path = _matches.value_of("db_file_path").unwrap();
             let sqlstr = _matches.value_of("sql").unwrap();
-            let db = Database::new(db_file_path).unwrap();
-            info!("Executing '{sqlstr}' against db {db_file_path}");
+            let batch = _matches.is_present("batch");
+            if let Err(err) = run_sql(db_file_path, sqlstr, batch) {
+                eprintln!("Error: {err:#}");
+                process::exit(1);
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+// Executes the statements of sqlstr in order, each one sees the schema changed by
+// the previous ones.
+fn run_sql(db_file_path: &str, sqlstr: &str, batch: bool) -> Result<()> {
+    let db = Rc::new(RefCell::new(Database::new(db_file_path)?));
+    info!("Executing '{sqlstr}' against db {db_file_path}");
+    let statements = parsing::split_statements(sqlstr)?;
+    if statements.is_empty() {
+        bail!("no sql statement to execute");
+    }
+    for statement in statements {
+        run_statement(&db, &statement, batch)?;
+    }
+    Ok(())
+}
 
+fn run_statement(db: &Rc<RefCell<Database>>, sqlstr: &str, batch: bool) -> Result<()> {
+    let physical_planner = PhysicalPlanner::new(db.clone());
+    let mut exec = match parsing::parse_ddl_statement(sqlstr)? {
+        Some(ddl) => physical_planner.plan_ddl(ddl),
+        None => {
             // sql to unoptimized logical plan
-            let dialect = AnsiDialect {};
-            let ast: Vec<Statement> = Parser::parse_sql(&dialect, sqlstr).unwrap();
-            let statement = &ast[0];
-            // create logical query plan
-            let schema_provider = SqliteContextProvider::new_for_db(&db);
-            let sql_to_rel = SqlToRel::new(&schema_provider);
-            let logical_plan = sql_to_rel.sql_statement_to_plan(statement.clone()).unwrap();
-            let db_ref = Rc::new(RefCell::new(db));
-            let physical_planner = PhysicalPlanner {
-                database: db_ref.clone(),
+            let Some(mut statement) = parsing::parse_statement(sqlstr)? else {
+                bail!("no sql statement to execute");
             };
-            let mut ref_plan = physical_planner.plan(&logical_plan);
-            // get a mutable ref to execute Exec, need to be sure no other refs to Arc
-            let exec = Arc::get_mut(&mut ref_plan).unwrap();
-            info!("Physical plan: {exec:?}");
-            let records = exec.execute();
-            info!("Returned records: {records:?}");
-            presentation::sqlite_show(records);
+            functions::rewrite_distinct_aggregates(&mut statement);
+            rewrite::sqlite_operators(&mut statement);
+            rewrite::sqlite_nulls_order(&mut statement);
+            let rowid_columns = rewrite::sqlite_rowid_columns(&mut statement, &db.borrow());
+            // create logical query plan, with the tables of the current schema
+            let mut schema_provider = SqliteContextProvider::new_for_db(&db.borrow());
+            if rowid_columns {
+                let insert_table = match &statement {
+                    Statement::Insert { table_name, .. } => Some(table_name.to_string()),
+                    _ => None,
+                };
+                schema_provider =
+                    schema_provider.with_rowid_columns(&db.borrow(), insert_table.as_deref());
+            }
+            let sql_to_rel = SqlToRel::new(&schema_provider);
+            rewrite::sqlite_limit_values(&mut statement, &sql_to_rel)?;
+            match statement {
+                Statement::Insert { .. } => {
+                    physical_planner.plan_insert(&statement, &sql_to_rel)?
+                }
+                Statement::Update { .. } => {
+                    physical_planner.plan_update(&statement, &sql_to_rel)?
+                }
+                _ => {
+                    let logical_plan = sql_to_rel.sql_statement_to_plan(statement)?;
+                    physical_planner.plan(&logical_plan)?
+                }
+            }
+        }
+    };
+    info!("Physical plan: {exec:?}");
+
+    // records are printed as they are pulled from the plan
+    exec.open()?;
+    if batch {
+        while let Some(record_batch) = exec.next_batch()? {
+            presentation::sqlite_show_batch(&record_batch)?;
+        }
+    } else {
+        while let Some(record) = exec.next()? {
+            presentation::sqlite_show_record(&record);
         }
-        _ => unreachable!(),
     }
+    exec.close()
 }
@@ -1,17 +1,39 @@
 use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::ops::RangeInclusive;
 use std::rc::Rc;
 
 use anyhow::{bail, Result};
 use log::info;
 
+use crate::btree::bt_write;
+use crate::model::cell_index_interior::CellIndexInterior;
+use crate::model::cell_index_leaf::LeafIndexCell;
 use crate::model::cell_table_leaf::LeafTableCell;
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
 use crate::model::database::Database;
+use crate::model::index_key::KeyInfo;
 use crate::model::page::Page;
 use crate::model::page_id::PageId;
+use crate::varint::decode_varint;
 
 // reference to a page in memory which is managed by BufferPool
 type PageRef = Rc<RefCell<Page>>;
 
+/// Entry a seek moves the cursor to, relative to the searched key.
+/// Equivalent to the sqlite opcodes OP_SeekGE, OP_SeekLE and a seek followed by a
+/// key comparison for equality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekOp {
+    /// the entry with the key
+    Eq,
+    /// the first entry whose key is greater than or equal to the key
+    Ge,
+    /// the last entry whose key is less than or equal to the key
+    Le,
+}
+
 pub struct BtCursor {
     /// Rc for multiple references to same object
     /// RefCell allows mutable borrowing because we would want to modify contained obj
@@ -39,6 +61,10 @@ pub struct BtCursor {
     /// `&cell_index_stack[1]` is the cell index of page at level 1 in page_stack `&page_stack[1]`
     /// that the cursor is/ was accessing.
     cell_index_stack: Vec<u16>,
+
+    /// Set when the cursor moved past the last entry of the btree (or the btree is empty).
+    /// Equivalent to sqlite `pCur->eState == CURSOR_INVALID`.
+    eof: bool,
 }
 
 /// Not including some not-needed fields in Debug str (database, page, etc.)
@@ -51,17 +77,18 @@ impl std::fmt::Debug for BtCursor {
             .field("index_current_page", &self.index_current_page)
             .field("page_stack", &self.page_stack)
             .field("cell_index_stack", &self.cell_index_stack)
+            .field("eof", &self.eof)
             .finish()
     }
 }
 
 impl BtCursor {
     /// root_page_number is 0-indexed. Db first page with db meta has page number 0.
-    pub fn new(database: Rc<RefCell<Database>>, root_page_number: u32) -> Self {
+    pub fn new(database: Rc<RefCell<Database>>, root_page_number: u32) -> Result<Self> {
         let page_id = PageId::new(root_page_number);
-        let page = database.borrow_mut().buffer_pool.get_page(page_id);
+        let page = database.borrow_mut().buffer_pool.get_page(page_id)?;
 
-        BtCursor {
+        Ok(BtCursor {
             database,
             page,
             root_page_number,
@@ -69,15 +96,34 @@ impl BtCursor {
             index_current_page: 0,
             page_stack: vec![],
             cell_index_stack: vec![],
+            eof: false,
+        })
+    }
+
+    /// Returns an iterator over all cells of the table btree, in rowid order.
+    pub fn scan_page(self) -> TableScanIterator {
+        self.scan_rowids(i64::MIN..=i64::MAX)
+    }
+
+    /// Returns an iterator over the cells of the table btree whose rowid is in rowids,
+    /// in rowid order. The scan starts with a seek to the first rowid and stops at the
+    /// first cell past the last one: the pages of the other cells are not read.
+    pub fn scan_rowids(self, rowids: RangeInclusive<i64>) -> TableScanIterator {
+        TableScanIterator {
+            cursor: self,
+            rowids,
+            descending: false,
+            started: false,
         }
     }
 
-    pub fn scan_page(&mut self) -> TableScanIterator {
-        let page_id = PageId::new(self.root_page_number);
+    /// Returns an iterator over the cells of the table btree whose rowid is in rowids,
+    /// in descending rowid order: the scan starts with a seek to the last rowid and
+    /// walks the btree backward.
+    pub fn scan_rowids_desc(self, rowids: RangeInclusive<i64>) -> TableScanIterator {
         TableScanIterator {
-            database: self.database.clone(),
-            current_page_id: Some(page_id),
-            index: 0,
+            descending: true,
+            ..self.scan_rowids(rowids)
         }
     }
 
@@ -85,40 +131,48 @@ impl BtCursor {
         self.page.clone()
     }
 
-    /// Advance cursor to next entry in btree.
-    pub fn move_to_next(&mut self) -> Result<()> {
-        let page = self.page_ref();
+    /// Returns true if the cursor moved past the last entry or the btree is empty.
+    pub fn is_eof(&self) -> bool {
+        self.eof
+    }
 
-        // if cursor is at the last cell of current page, go to next page
-        let index_next_cell = self.index_current_cell + 1;
-        if index_next_cell >= page.borrow().get_number_of_cells() {
-            return self.next_entry_next_page();
+    /// Parses the table leaf cell the cursor is pointing to,
+    /// following its overflow pages if the payload spills.
+    pub fn table_leaf_cell(&mut self) -> Result<LeafTableCell> {
+        if self.eof {
+            bail!("cursor does not point to an entry")
         }
-
-        // we know that we are not at the last cell of page, so cursor can advance.
-        self.index_current_cell = index_next_cell;
-        // if page is an interior, we want to move to left most leaf
-        // so that cursor can point to the next entry.
-        if page.borrow().is_leaf() {
-            Ok(())
-        } else {
-            self.move_to_left_most_leaf_entry()
+        let page_rc = self.page_ref();
+        let mut page = page_rc.borrow_mut();
+        if !page.page_header.is_table_leaf() {
+            bail!(
+                "cursor points to page {} which is not a table leaf page",
+                page.page_id.page_number
+            )
         }
+        let cell_ptr = page.get_cell_ptr(self.index_current_cell as usize);
+        let mut database = self.database.borrow_mut();
+        let usable_size = database.db_meta.db_header.usable_size();
+        LeafTableCell::parse_with_overflow(
+            &page.data[cell_ptr..],
+            usable_size,
+            &mut database.buffer_pool,
+        )
     }
 
-    /// Move to the next entry in the next page in case the cursor
-    /// is currently at the last cell of current page (not possible to
-    /// advance cell counter in current page).
+    /// Advance cursor to next entry in btree.
     ///
     /// Similar to btreeNext(BtCursor *pCur) in sqlite source.
-    fn next_entry_next_page(&mut self) -> Result<()> {
+    pub fn move_to_next(&mut self) -> Result<()> {
         /*
         steps
         - increment cursor current cell index to point to next cell
         - if index >= last cell (current page cell num)
-            - leaf page -> cursor pass last entry in whole Btree
-                -> INVALID cursor, somehow should signal caller we're done.
-            - not leaf page (interior) -> move cursor to child page, then move to leftmost cell in child page.
+            - not leaf page (interior) -> cursor finished the child of the last cell,
+            move to the right child page, then to its leftmost leaf entry.
+            - leaf page -> move up to the parent pages until finding one that still
+            has cells to visit. If cursor reaches the root, it passed the last entry
+            in whole Btree -> INVALID cursor, signal caller we're done.
         - else: case index within current page
             - current page is leaf
                 - return Ok
@@ -127,32 +181,46 @@ impl BtCursor {
             Remind that interior Btree has structure | Ptr0 | Key0 | Ptr1 | Key1 | ...
             Here it's not an data entry.
          */
+        if self.eof {
+            // TODO find a better mechanism to signal caller than bail with anyhow::Error
+            bail!("iterated pass the last entry!")
+        }
         self.index_current_cell += 1;
-        let current_index = self.index_current_cell;
-        let page_rc = self.page_ref();
-
-        let page = page_rc.borrow();
+        let page = self.page_ref();
+        let number_of_cells = page.borrow().get_number_of_cells();
 
-        if page.is_leaf() {
-            if current_index < page.get_number_of_cells() {
-                Ok(())
-            } else {
-                // index >= page num cells -> cursor pass last entry in whole Btree
-                // TODO find a better mechanism to signal caller than bail with anyhow::Error
-                bail!("iterated pass the last entry!")
+        if self.index_current_cell >= number_of_cells {
+            if page.borrow().is_interior() {
+                let right_child_page_no = page.borrow().page_header.right_child_page_number;
+                self.move_to_child(right_child_page_no.unwrap())?;
+                return self.move_to_left_most_leaf_entry();
             }
-        } else if page_rc.borrow().is_interior() {
-            // case current page is not a leaf page:
-            // - extract child page number from the cell at current index of current page.
-            // - move cursor to child page the cell at current index is pointing to.
-            // - then, move cursor to leftmost cell of the child page.
-            // Why leftmost? because in a B-Tree, the leftmost cell in a child page
-            // is the next cell in ascending order after the parent cell.
-            let child_page_num = self.get_child_page_num();
-            self.move_to_child(child_page_num)?;
-            self.move_to_left_most_leaf_entry()
+            loop {
+                if self.page_stack.is_empty() {
+                    self.eof = true;
+                    bail!("iterated pass the last entry!")
+                }
+                self.move_to_parent();
+                if self.index_current_cell < self.page.borrow().get_number_of_cells() {
+                    break;
+                }
+            }
+            // Table interior cells are only keys: the next entry is in the next child page.
+            // Index interior cells are entries themselves: the cursor now points to one.
+            return if self.page.borrow().page_header.is_table_interior() {
+                self.move_to_next()
+            } else {
+                Ok(())
+            };
+        }
+
+        // we know that we are not at the last cell of page, so cursor can advance.
+        // if page is an interior, we want to move to left most leaf
+        // so that cursor can point to the next entry.
+        if page.borrow().is_leaf() {
+            Ok(())
         } else {
-            bail!("page type invalid: not leaf nor interior!")
+            self.move_to_left_most_leaf_entry()
         }
     }
 
@@ -216,7 +284,7 @@ impl BtCursor {
         // calling BufferPool to get the page and save the ref to BtCursor state
         let child_page_id = PageId::new(child_page_no);
         let mut db_ref = self.database.borrow_mut();
-        let page = db_ref.buffer_pool.get_page(child_page_id);
+        let page = db_ref.buffer_pool.get_page(child_page_id)?;
         self.page = page;
 
         // TODO child page integrity check
@@ -224,19 +292,437 @@ impl BtCursor {
         Ok(())
     }
 
-    pub fn move_to_previous(&mut self) -> Option<Rc<RefCell<Page>>> {
-        // Move the cursor to the previous cell
-        todo!()
+    /// Move cursor up to the parent page, restoring the cell index
+    /// the cursor had on the parent page before moving to the child.
+    ///
+    /// Equivalent to sqlite `static void moveToParent(BtCursor *pCur)`
+    fn move_to_parent(&mut self) {
+        if let (Some(page), Some(cell_index)) = (self.page_stack.pop(), self.cell_index_stack.pop())
+        {
+            self.page = page;
+            self.index_current_cell = cell_index;
+            self.index_current_page -= 1;
+        }
     }
 
-    /// Move cursor to last entry in the table.
+    /// Move cursor back to the previous entry in btree.
+    ///
+    /// Similar to btreePrevious(BtCursor *pCur) in sqlite source.
+    pub fn move_to_previous(&mut self) -> Result<()> {
+        /*
+        steps, the mirror of move_to_next
+        - current page is interior (index btree, the cursor is on an interior entry)
+            -> the previous entry is the right-most leaf entry of the left child
+            of the current cell.
+        - current page is leaf and index > 0 -> decrement index, done.
+        - leaf page and index == 0 -> move up to the parent pages until finding one
+        where the cursor did not come from the left-most child. If cursor reaches the
+        root, it passed the first entry in whole Btree -> INVALID cursor.
+        Then the cursor moves to the cell before the child it came from:
+            - table interior: cells are only keys, the previous entry is the right-most
+            leaf entry of the left child of that cell.
+            - index interior: the cell is the previous entry.
+         */
+        if self.eof {
+            bail!("iterated pass the first entry!")
+        }
+        if self.page.borrow().is_interior() {
+            let child_page_no = self.get_child_page_num();
+            self.move_to_child(child_page_no)?;
+            return self.move_to_right_most_leaf_entry();
+        }
+        if self.index_current_cell > 0 {
+            self.index_current_cell -= 1;
+            return Ok(());
+        }
+        loop {
+            if self.page_stack.is_empty() {
+                self.eof = true;
+                bail!("iterated pass the first entry!")
+            }
+            self.move_to_parent();
+            if self.index_current_cell > 0 {
+                break;
+            }
+        }
+        self.index_current_cell -= 1;
+        if self.page.borrow().page_header.is_table_interior() {
+            let child_page_no = self.get_child_page_num();
+            self.move_to_child(child_page_no)?;
+            self.move_to_right_most_leaf_entry()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Move cursor to last entry in the table, the one with the largest key.
+    /// If the btree is empty, the cursor is at eof.
     pub fn move_to_last(&mut self) -> Result<()> {
-        todo!()
+        self.move_to_root()?;
+        if self.is_empty() {
+            self.eof = true;
+            return Ok(());
+        }
+        self.move_to_right_most_leaf_entry()
+    }
+
+    /// Move cursor to first entry in the table, the one with the smallest key.
+    /// If the btree is empty, the cursor is at eof.
+    pub fn move_to_first(&mut self) -> Result<()> {
+        self.move_to_root()?;
+        if self.is_empty() {
+            self.eof = true;
+            return Ok(());
+        }
+        self.move_to_left_most_leaf_entry()
+    }
+
+    /// Moves the cursor to the entry of a table btree with the given rowid, binary searching
+    /// the cells of each page from the root down to a leaf: only O(depth) pages are read.
+    ///
+    /// Returns how the entry the cursor points to compares with rowid: Equal if the rowid
+    /// is found, otherwise Less or Greater for the neighbour leaf entry the cursor is on.
+    /// If the btree is empty, the cursor is at eof and Less is returned.
+    ///
+    /// Equivalent to sqlite `sqlite3BtreeTableMoveto` whose pRes is <0, 0 or >0.
+    pub fn table_move_to(&mut self, rowid: i64) -> Result<Ordering> {
+        self.move_to_root()?;
+        if self.is_empty() {
+            self.eof = true;
+            return Ok(Ordering::Less);
+        }
+        loop {
+            // first cell whose rowid is >= rowid. The key of an interior cell is the
+            // largest rowid of its left child.
+            let index = self.lower_bound(|cursor, i| Ok(cursor.table_cell_rowid(i).cmp(&rowid)))?;
+            let number_of_cells = self.page.borrow().get_number_of_cells();
+            if self.page.borrow().is_leaf() {
+                if index == number_of_cells {
+                    self.index_current_cell = number_of_cells - 1;
+                    return Ok(Ordering::Less);
+                }
+                self.index_current_cell = index;
+                return Ok(self.table_cell_rowid(index).cmp(&rowid));
+            }
+            self.move_to_child_of_cell(index)?;
+        }
+    }
+
+    /// Moves the cursor to the entry of a table btree found by op for rowid, reading
+    /// O(depth) pages. Returns false and leaves the cursor at eof if there is no such entry.
+    pub fn table_seek(&mut self, rowid: i64, op: SeekOp) -> Result<bool> {
+        let found = match (self.table_move_to(rowid)?, op) {
+            _ if self.eof => false,
+            (Ordering::Equal, _) => true,
+            (_, SeekOp::Eq) => false,
+            (Ordering::Greater, SeekOp::Ge) | (Ordering::Less, SeekOp::Le) => true,
+            // the cursor is on the last entry of a leaf, the next one is in the next leaf
+            (Ordering::Less, SeekOp::Ge) => match self.move_to_next() {
+                Ok(()) => true,
+                Err(_) if self.eof => false,
+                Err(err) => return Err(err),
+            },
+            (Ordering::Greater, SeekOp::Le) => self.move_to_smaller_entry()?,
+        };
+        self.eof = !found;
+        Ok(found)
+    }
+
+    // Moves the cursor from a leaf entry to the entry before it, the right-most leaf entry
+    // of the previous subtree if it is in a previous leaf. Returns false if the cursor is
+    // on the first entry.
+    fn move_to_smaller_entry(&mut self) -> Result<bool> {
+        if self.index_current_cell > 0 {
+            self.index_current_cell -= 1;
+            return Ok(true);
+        }
+        // the entries of the left child of the cell before the child of an ancestor are
+        // smaller. The row of the key of this cell can be deleted: SQLite keeps the key
+        // of an interior cell when the row with that rowid is deleted.
+        loop {
+            if self.page_stack.is_empty() {
+                return Ok(false);
+            }
+            self.move_to_parent();
+            if self.index_current_cell > 0 {
+                self.index_current_cell -= 1;
+                let child_page_no = self.get_child_page_num();
+                self.move_to_child(child_page_no)?;
+                self.move_to_right_most_leaf_entry()?;
+                return Ok(true);
+            }
+        }
+    }
+
+    /// Moves the cursor to the first entry of an index btree whose key is not less than
+    /// key, comparing only the fields of key: `[a]` finds the first entry starting with a.
+    /// The cursor is at eof if all entries are smaller.
+    ///
+    /// Equivalent to sqlite `sqlite3BtreeIndexMoveto` followed by a move to the next
+    /// entry when the cursor stops on a smaller one.
+    pub fn index_move_to(&mut self, key: &[ColumnValue], key_info: &KeyInfo) -> Result<()> {
+        self.move_to_root()?;
+        if self.is_empty() {
+            self.eof = true;
+            return Ok(());
+        }
+        loop {
+            let index = self.lower_bound(|cursor, i| {
+                Ok(key_info.compare(&cursor.index_cell_key(i)?.values, key))
+            })?;
+            let number_of_cells = self.page.borrow().get_number_of_cells();
+            if self.page.borrow().is_leaf() {
+                if index < number_of_cells {
+                    self.index_current_cell = index;
+                    return Ok(());
+                }
+                // all entries of the leaf are smaller: the entry is the interior cell
+                // above the leaf, if any
+                self.index_current_cell = number_of_cells - 1;
+                return match self.move_to_next() {
+                    Err(_) if self.eof => Ok(()),
+                    result => result,
+                };
+            }
+            self.move_to_child_of_cell(index)?;
+        }
+    }
+
+    /// Parses the key of the index entry the cursor is pointing to, a leaf or an
+    /// interior cell: the indexed columns followed by the rowid.
+    pub fn index_key(&mut self) -> Result<DataRecord> {
+        if self.eof {
+            bail!("cursor does not point to an entry")
+        }
+        self.index_cell_key(self.index_current_cell)
+    }
+
+    /// Inserts a row into a table btree: a leaf cell with the rowid and the record as
+    /// payload, in rowid order. The row with the rowid is replaced if there is one.
+    /// Pages are split if needed, the cursor is moved to the root.
+    ///
+    /// Equivalent to sqlite `sqlite3BtreeInsert` on a table btree.
+    pub fn table_insert(&mut self, rowid: i64, record: &DataRecord) -> Result<()> {
+        let ordering = self.table_move_to(rowid)?;
+        let index = match ordering {
+            // empty btree
+            _ if self.eof => 0,
+            Ordering::Equal | Ordering::Greater => self.index_current_cell as usize,
+            Ordering::Less => self.index_current_cell as usize + 1,
+        };
+        let database_ref = self.database.clone();
+        let mut database = database_ref.borrow_mut();
+        let payload = record.serialize(database.db_meta.db_header.schema_format);
+        let cell = bt_write::table_leaf_cell(&mut database, rowid, &payload)?;
+        let replace = ordering.is_eq() && !self.eof;
+        bt_write::insert_cell(&mut database, &self.path(index), cell, replace)?;
+        drop(database);
+        self.move_to_root()
+    }
+
+    /// Inserts a key into an index btree: the indexed columns followed by the rowid,
+    /// compared with key_info. The key is inserted on a leaf page.
+    pub fn index_insert(&mut self, key: &DataRecord, key_info: &KeyInfo) -> Result<()> {
+        let (index, found) = self.index_move_to_leaf(&key.values, key_info)?;
+        if found {
+            bail!("index entry already exists")
+        }
+        let database_ref = self.database.clone();
+        let mut database = database_ref.borrow_mut();
+        let payload = key.serialize(database.db_meta.db_header.schema_format);
+        let cell = bt_write::index_leaf_cell(&mut database, &payload)?;
+        bt_write::insert_cell(&mut database, &self.path(index as usize), cell, false)?;
+        drop(database);
+        self.move_to_root()
+    }
+
+    /// Deletes the row with rowid from a table btree and frees its overflow pages.
+    /// Underfull pages are merged with their siblings, the cursor is moved to the root.
+    ///
+    /// Equivalent to sqlite `sqlite3BtreeDelete` on a table btree.
+    pub fn table_delete(&mut self, rowid: i64) -> Result<()> {
+        if self.table_move_to(rowid)?.is_ne() || self.eof {
+            bail!("no row with rowid {rowid}")
+        }
+        let database_ref = self.database.clone();
+        let mut database = database_ref.borrow_mut();
+        bt_write::delete_cell(&mut database, &self.path(self.index_current_cell as usize))?;
+        drop(database);
+        self.move_to_root()
+    }
+
+    /// Deletes a key from an index btree, compared with key_info. A key on an interior
+    /// page is replaced by the previous key, moved from the right-most leaf entry of
+    /// its left child. The cursor is moved to the root.
+    ///
+    /// Equivalent to sqlite `sqlite3BtreeDelete` on an index btree.
+    pub fn index_delete(&mut self, key: &DataRecord, key_info: &KeyInfo) -> Result<()> {
+        self.index_move_to(&key.values, key_info)?;
+        if self.eof
+            || key_info
+                .compare(&self.index_key()?.values, &key.values)
+                .is_ne()
+        {
+            bail!("index entry not found")
+        }
+        let database_ref = self.database.clone();
+        let mut database = database_ref.borrow_mut();
+        let cell_index = self.index_current_cell;
+        if self.page.borrow().is_leaf() {
+            bt_write::delete_cell(&mut database, &self.path(cell_index as usize))?;
+            drop(database);
+            return self.move_to_root();
+        }
+
+        let usable_size = database.db_meta.db_header.usable_size();
+        let interior_path = self.path(cell_index as usize);
+        let deleted_cell = self
+            .page
+            .borrow_mut()
+            .cell(cell_index as usize, usable_size)
+            .to_vec();
+        drop(database);
+        self.move_to_child_of_cell(cell_index)?;
+        self.move_to_right_most_leaf_entry()?;
+        let previous_key = self.index_key()?;
+        let leaf_index = self.index_current_cell as usize;
+        let leaf_cell = self
+            .page
+            .borrow_mut()
+            .cell(leaf_index, usable_size)
+            .to_vec();
+        // the interior cell keeps the left child pointer, the overflow pages of the leaf
+        // cell are now the ones of the interior cell
+        let mut cell = deleted_cell[..4].to_vec();
+        cell.extend(leaf_cell);
+        let mut database = database_ref.borrow_mut();
+        bt_write::remove_cell(&mut database, &self.path(leaf_index))?;
+        bt_write::insert_cell(&mut database, &interior_path, cell, true)?;
+        drop(database);
+        // the interior page may have been split: the leaf is searched again, the key
+        // moved up is the first one larger than all its entries
+        let (leaf_index, _) = self.index_move_to_leaf(&previous_key.values, key_info)?;
+        let mut database = database_ref.borrow_mut();
+        bt_write::balance_underfull(&mut database, &self.path(leaf_index as usize))?;
+        drop(database);
+        self.move_to_root()
+    }
+
+    // Moves the cursor down to the leaf where key is inserted, following the child
+    // before the first entry not less than key on each interior page. Returns the index
+    // of the key on the leaf, and whether an entry equal to key was found on the way.
+    fn index_move_to_leaf(
+        &mut self,
+        key: &[ColumnValue],
+        key_info: &KeyInfo,
+    ) -> Result<(u16, bool)> {
+        self.move_to_root()?;
+        let mut found = false;
+        loop {
+            let index = self.lower_bound(|cursor, i| {
+                Ok(key_info.compare(&cursor.index_cell_key(i)?.values, key))
+            })?;
+            found |= index < self.page.borrow().get_number_of_cells()
+                && key_info
+                    .compare(&self.index_cell_key(index)?.values, key)
+                    .is_eq();
+            if self.page.borrow().is_leaf() {
+                return Ok((index, found));
+            }
+            self.move_to_child_of_cell(index)?;
+        }
+    }
+
+    // Pages from the root to the current page with the index of the child followed
+    // on each interior page, and cell_index on the current page.
+    fn path(&self, cell_index: usize) -> Vec<bt_write::PathEntry> {
+        self.page_stack
+            .iter()
+            .zip(&self.cell_index_stack)
+            .map(|(page, &index)| (page.clone(), index as usize))
+            .chain([(self.page.clone(), cell_index)])
+            .collect()
+    }
+
+    // Binary search of the first cell of the current page which is not less than the
+    // searched key, compare returns the ordering of a cell with the key.
+    // Returns the number of cells if all cells are less.
+    fn lower_bound(
+        &mut self,
+        mut compare: impl FnMut(&mut Self, u16) -> Result<Ordering>,
+    ) -> Result<u16> {
+        let (mut low, mut high) = (0, self.page.borrow().get_number_of_cells());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if compare(self, middle)?.is_lt() {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        Ok(low)
+    }
+
+    // Moves down to the left child of cell index of the current interior page,
+    // or to its right child if index is the number of cells.
+    fn move_to_child_of_cell(&mut self, index: u16) -> Result<()> {
+        self.index_current_cell = index;
+        let child_page_no = if index < self.page.borrow().get_number_of_cells() {
+            self.get_child_page_num()
+        } else {
+            // Knowing this is an interior page, the option is not none, just unwrap.
+            self.page
+                .borrow()
+                .page_header
+                .right_child_page_number
+                .unwrap()
+        };
+        self.move_to_child(child_page_no)
     }
 
-    pub fn move_to_first(&mut self) -> Option<Rc<RefCell<Page>>> {
-        // Move the cursor to the first cell in the current page
-        todo!()
+    // Rowid of cell index of the current table page: the key of an interior cell or
+    // the rowid of a leaf cell, read without parsing the payload.
+    fn table_cell_rowid(&mut self, index: u16) -> i64 {
+        let page_rc = self.page_ref();
+        let mut page = page_rc.borrow_mut();
+        let cell_ptr = page.get_cell_ptr(index as usize);
+        let cell = &page.data[cell_ptr..];
+        if page.page_header.is_table_interior() {
+            // 4-byte left child pointer then the rowid
+            decode_varint(&cell[4..]).0
+        } else {
+            // payload size then the rowid
+            let (_, bytes_read) = decode_varint(cell);
+            decode_varint(&cell[bytes_read..]).0
+        }
+    }
+
+    // Key of cell index of the current index page, following overflow pages.
+    fn index_cell_key(&mut self, index: u16) -> Result<DataRecord> {
+        let page_rc = self.page_ref();
+        let mut page = page_rc.borrow_mut();
+        let cell_ptr = page.get_cell_ptr(index as usize);
+        let mut database = self.database.borrow_mut();
+        let usable_size = database.db_meta.db_header.usable_size();
+        let cell = &page.data[cell_ptr..];
+        if page.page_header.is_index_leaf() {
+            Ok(
+                LeafIndexCell::parse_with_overflow(cell, usable_size, &mut database.buffer_pool)?
+                    .payload,
+            )
+        } else if page.page_header.is_index_interior() {
+            Ok(CellIndexInterior::parse_with_overflow(
+                cell,
+                usable_size,
+                &mut database.buffer_pool,
+            )?
+            .payload)
+        } else {
+            bail!(
+                "cursor points to page {} which is not an index page",
+                page.page_id.page_number
+            )
+        }
     }
 
     /// Move the cursor to the right-most leaf entry beneath the page
@@ -293,56 +779,116 @@ impl BtCursor {
             if self.page.borrow().is_leaf() || action_result.is_err() {
                 break;
             }
-            assert!(self.index_current_cell < self.page.borrow().get_number_of_cells());
-            let child_page_no = self.get_child_page_num();
-            action_result = self.move_to_child(child_page_no);
+            // the right child if the page has no cells (see is_empty)
+            action_result = self.move_to_child_of_cell(self.index_current_cell);
         }
 
         action_result
     }
 
+    // Whether the btree is empty, the cursor being on the root page. A root page
+    // without cells can be an interior page with only a right child: a root page 1
+    // whose cells do not fit on it but fit on its child.
+    fn is_empty(&self) -> bool {
+        let page = self.page.borrow();
+        page.is_leaf() && page.get_number_of_cells() == 0
+    }
+
     /// Move cursor to root page of its BTree.
     fn move_to_root(&mut self) -> Result<()> {
         // checks if the cursor is already at the root page (pCur->iPage >= 0).
         // If yes, release any pages that the cursor may have descended into
         // and returns to the root page.
+        if !self.page_stack.is_empty() {
+            self.page = self.page_stack[0].clone();
+            self.page_stack.clear();
+            self.cell_index_stack.clear();
+        }
+        self.index_current_page = 0;
+        self.index_current_cell = 0;
+        self.eof = false;
         Ok(())
     }
 }
 
+/// Iterates over the cells of a table btree whose rowid is in a range, from the
+/// left-most leaf entry to the right-most one, or backward if descending.
 pub struct TableScanIterator {
-    database: Rc<RefCell<Database>>,
-    current_page_id: Option<PageId>,
-    index: usize,
+    cursor: BtCursor,
+    rowids: RangeInclusive<i64>,
+    descending: bool,
+    started: bool,
+}
+
+impl TableScanIterator {
+    /// Returns the cells of the next leaf page, or None when the scan is done.
+    pub fn next_leaf_page(&mut self) -> Option<Result<Vec<LeafTableCell>>> {
+        let mut cells = match self.next()? {
+            Ok(cell) => vec![cell],
+            Err(err) => return Some(Err(err)),
+        };
+        // the cursor stays on the leaf page until it moves past its last cell
+        // (its first cell when descending)
+        loop {
+            let index = self.cursor.index_current_cell;
+            let next_on_page = if self.descending {
+                index > 0
+            } else {
+                index + 1 < self.cursor.page.borrow().get_number_of_cells()
+            };
+            if !next_on_page {
+                break;
+            }
+            match self.next() {
+                Some(Ok(cell)) => cells.push(cell),
+                Some(Err(err)) => return Some(Err(err)),
+                None => break,
+            }
+        }
+        Some(Ok(cells))
+    }
+}
+
+impl std::fmt::Debug for TableScanIterator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TableScanIterator")
+            .field("root_page_number", &self.cursor.root_page_number)
+            .field("rowids", &self.rowids)
+            .field("descending", &self.descending)
+            .field("started", &self.started)
+            .finish()
+    }
 }
 
 impl Iterator for TableScanIterator {
-    type Item = LeafTableCell;
+    type Item = Result<LeafTableCell>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            match self.current_page_id {
-                Some(page_id) => {
-                    let buffer_pool = &mut self.database.borrow_mut().buffer_pool;
-                    let page = buffer_pool.get_page(page_id);
-                    let mut page = page.borrow_mut();
-
-                    if self.index < page.page_header.number_of_cells as usize {
-                        let cell_ptr = page.get_cell_ptr(self.index);
-                        self.index += 1;
-                        match LeafTableCell::parse(&page.data[cell_ptr..]) {
-                            Ok(cell) => return Some(cell),
-                            // TODO error handling for parsing failed
-                            Err(_) => return None, // If parsing fails, end the iteration
-                        }
-                    } else {
-                        // Move to the next page
-                        self.current_page_id =
-                            page.page_header.right_child_page_number.map(PageId::new);
-                        self.index = 0;
-                    }
+        let (start, end) = (*self.rowids.start(), *self.rowids.end());
+        let moved = match (self.started, self.descending) {
+            (true, false) => self.cursor.move_to_next(),
+            (true, true) => self.cursor.move_to_previous(),
+            (false, false) if start == i64::MIN => self.cursor.move_to_first(),
+            (false, false) => self.cursor.table_seek(start, SeekOp::Ge).map(|_| ()),
+            (false, true) if end == i64::MAX => self.cursor.move_to_last(),
+            (false, true) => self.cursor.table_seek(end, SeekOp::Le).map(|_| ()),
+        };
+        self.started = true;
+        match moved {
+            Ok(()) if self.cursor.is_eof() => None,
+            Ok(()) => {
+                let rowid = self.cursor.table_cell_rowid(self.cursor.index_current_cell);
+                if !self.rowids.contains(&rowid) {
+                    self.cursor.eof = true;
+                    return None;
                 }
-                None => return None, // No more pages
+                Some(self.cursor.table_leaf_cell())
+            }
+            // moving past the last (or first) entry is the end of the scan, not an error
+            Err(_) if self.cursor.is_eof() => None,
+            Err(err) => {
+                self.cursor.eof = true;
+                Some(Err(err))
             }
         }
     }
@@ -354,6 +900,8 @@ mod tests {
     use std::path::PathBuf;
     use std::rc::Rc;
 
+    use crate::model::collation::Collation;
+    use crate::model::index_key::SortOrder;
     use crate::test_utils::setup;
 
     use super::*;
@@ -378,7 +926,7 @@ mod tests {
 
     #[test]
     fn test_scan() {
-        let mut cursor = BtCursor::new(db_ref_superheroes().clone(), 2);
+        let cursor = BtCursor::new(db_ref_superheroes().clone(), 2).unwrap();
 
         assert_eq!(cursor.root_page_number, 2);
 
@@ -392,11 +940,11 @@ mod tests {
     #[test]
     fn test_move_to_root() {
         // should has no problem if cursor already pointed to root page
-        let cursor = BtCursor::new(db_ref_superheroes().clone(), 0);
+        let cursor = BtCursor::new(db_ref_superheroes().clone(), 0).unwrap();
         assert_eq!(cursor.root_page_number, 0);
 
         // should work when cursor moved away from root page
-        let mut cursor = BtCursor::new(db_ref_superheroes().clone(), 2);
+        let mut cursor = BtCursor::new(db_ref_superheroes().clone(), 2).unwrap();
         assert_eq!(cursor.root_page_number, 2);
         cursor.move_to_last().unwrap();
         assert_eq!(cursor.root_page_number, 2);
@@ -404,7 +952,8 @@ mod tests {
 
     #[test]
     fn test_move_to_child_ok() {
-        let mut cursor = BtCursor::new(db_ref_superheroes().clone(), TABLE_SUPERHEROES_ROOT_PAGE);
+        let mut cursor =
+            BtCursor::new(db_ref_superheroes().clone(), TABLE_SUPERHEROES_ROOT_PAGE).unwrap();
 
         cursor.move_to_child(3).unwrap();
         assert_eq!(cursor.root_page_number, TABLE_SUPERHEROES_ROOT_PAGE);
@@ -433,7 +982,7 @@ mod tests {
     #[test]
     fn test_move_to_next_table_single_page() {
         let table_apples_root_page = 2;
-        let mut cursor = BtCursor::new(db_ref_sample().clone(), table_apples_root_page);
+        let mut cursor = BtCursor::new(db_ref_sample().clone(), table_apples_root_page).unwrap();
 
         /*
         To parse cell content from cursor
@@ -478,17 +1027,94 @@ mod tests {
     fn test_move_to_next_table_2_pages() {}
 
     #[test]
-    fn test_move_to_previous() {}
+    fn test_move_to_next_table_many_pages() {
+        // index.db table people has 200 rows on 7 leaf pages under interior root page 2.
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/index.db");
+        let db = Rc::new(RefCell::new(
+            Database::new(db_path.to_str().unwrap()).unwrap(),
+        ));
+        let mut cursor = BtCursor::new(db, 2).unwrap();
+
+        cursor.move_to_first().unwrap();
+        assert_cursor_points_to_leaf_page(&cursor);
+        let mut rowids = vec![];
+        while !cursor.is_eof() {
+            rowids.push(cursor.table_leaf_cell().unwrap().rowid);
+            if cursor.move_to_next().is_err() {
+                break;
+            }
+        }
+        assert!(cursor.is_eof());
+        assert_eq!(rowids, (1..=200).collect::<Vec<i64>>());
+
+        // scan iterator walks the same entries
+        let scanned: Vec<i64> = cursor.scan_page().map(|cell| cell.unwrap().rowid).collect();
+        assert_eq!(scanned, rowids);
+    }
 
     #[test]
-    fn test_move_to_last() {}
+    fn test_move_to_previous() {
+        // walking the table btree backward visits the entries of the forward scan,
+        // in reverse, crossing the interior pages of the 3 levels
+        let mut cursor = BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE).unwrap();
+        cursor.move_to_last().unwrap();
+        let mut rowids = vec![current_rowid(&mut cursor)];
+        while cursor.move_to_previous().is_ok() {
+            rowids.push(current_rowid(&mut cursor));
+        }
+        assert!(cursor.is_eof());
+        assert_eq!(rowids.len(), 2000);
+        assert!(rowids.iter().rev().copied().eq((1..=2000).map(|i| i * 3)));
+
+        // index btree: the interior cells are entries too
+        let mut cursor = BtCursor::new(db_ref_orders(), INDEX_CUSTOMERS_EMAIL_ROOT_PAGE).unwrap();
+        cursor.move_to_first().unwrap();
+        let mut forward = vec![cursor.index_key().unwrap()];
+        while cursor.move_to_next().is_ok() {
+            forward.push(cursor.index_key().unwrap());
+        }
+        cursor.move_to_last().unwrap();
+        let mut backward = vec![cursor.index_key().unwrap()];
+        while cursor.move_to_previous().is_ok() {
+            backward.push(cursor.index_key().unwrap());
+        }
+        backward.reverse();
+        assert_eq!(forward.len(), 2000);
+        assert_eq!(backward, forward);
+    }
 
     #[test]
-    fn test_move_to_first() {}
+    fn test_move_to_last() {
+        let mut cursor = BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE).unwrap();
+        cursor.move_to_last().unwrap();
+        assert_eq!(current_rowid(&mut cursor), 6000);
+        assert_eq!(cursor.page_stack.len(), 2);
+        assert!(cursor.move_to_next().is_err());
+        assert!(cursor.is_eof());
+
+        let mut cursor = BtCursor::new(db_ref_orders(), INDEX_CUSTOMERS_EMAIL_ROOT_PAGE).unwrap();
+        cursor.move_to_last().unwrap();
+        assert_eq!(
+            cursor.index_key().unwrap().values[0],
+            ColumnValue::Text("c9@example.com".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_move_to_first() {
+        let mut cursor = BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE).unwrap();
+        cursor.move_to_last().unwrap();
+        // from anywhere in the btree, back to the smallest rowid
+        cursor.move_to_first().unwrap();
+        assert_eq!(current_rowid(&mut cursor), 3);
+        assert_eq!(cursor.page_stack.len(), 2);
+        assert!(cursor.move_to_previous().is_err());
+        assert!(cursor.is_eof());
+    }
 
     #[test]
     fn test_move_to_right_most_table_single_page() {
-        let mut cursor = BtCursor::new(db_ref_sample().clone(), TABLE_APPLES_ROOT_PAGE);
+        let mut cursor = BtCursor::new(db_ref_sample().clone(), TABLE_APPLES_ROOT_PAGE).unwrap();
         cursor.move_to_right_most_leaf_entry().unwrap();
 
         // Check if cursor is at the right-most leaf entry
@@ -500,7 +1126,8 @@ mod tests {
     #[test]
     fn test_move_to_right_most_table_many_pages() {
         setup();
-        let mut cursor = BtCursor::new(db_ref_superheroes().clone(), TABLE_SUPERHEROES_ROOT_PAGE);
+        let mut cursor =
+            BtCursor::new(db_ref_superheroes().clone(), TABLE_SUPERHEROES_ROOT_PAGE).unwrap();
 
         assert_eq!(cursor.page.borrow().page_id.page_number, 2);
         assert!(cursor.page.borrow().is_interior());
@@ -513,4 +1140,181 @@ mod tests {
         // rightmost leaf page has xxx cells -> index  (0-indexed)
         assert_eq!(cursor.index_current_cell, 48);
     }
+
+    fn db_ref_orders() -> Rc<RefCell<Database>> {
+        // orders.db has 512 bytes pages: table customers (ids 3, 6, ..., 6000) is 3 levels
+        // deep, idx_customers_email indexes its email column COLLATE NOCASE.
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/orders.db");
+        let db = Database::new(db_path.as_path().to_str().unwrap()).unwrap();
+        Rc::new(RefCell::new(db))
+    }
+
+    const TABLE_CUSTOMERS_ROOT_PAGE: u32 = 2;
+    const INDEX_CUSTOMERS_EMAIL_ROOT_PAGE: u32 = 3;
+
+    fn current_rowid(cursor: &mut BtCursor) -> i64 {
+        cursor.table_leaf_cell().unwrap().rowid
+    }
+
+    #[test]
+    fn test_table_move_to() {
+        let mut cursor = BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE).unwrap();
+        for rowid in [3, 1500, 6000] {
+            assert_eq!(cursor.table_move_to(rowid).unwrap(), Ordering::Equal);
+            assert_eq!(current_rowid(&mut cursor), rowid);
+            assert_eq!(cursor.page_stack.len(), 2);
+        }
+        // missing rowids: the cursor is on a neighbour entry
+        assert_eq!(cursor.table_move_to(0).unwrap(), Ordering::Greater);
+        assert_eq!(current_rowid(&mut cursor), 3);
+        assert_eq!(cursor.table_move_to(1501).unwrap(), Ordering::Greater);
+        assert_eq!(current_rowid(&mut cursor), 1503);
+        assert_eq!(cursor.table_move_to(6001).unwrap(), Ordering::Less);
+        assert_eq!(current_rowid(&mut cursor), 6000);
+
+        // the scan continues from the entry found
+        cursor.table_move_to(2997).unwrap();
+        let mut count = 1;
+        while cursor.move_to_next().is_ok() {
+            count += 1;
+        }
+        assert!(cursor.is_eof());
+        assert_eq!(count, 1002);
+    }
+
+    #[test]
+    fn test_table_seek() {
+        let mut cursor = BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE).unwrap();
+        let mut seek = |rowid, op| match cursor.table_seek(rowid, op).unwrap() {
+            true => Some(current_rowid(&mut cursor)),
+            false => None,
+        };
+        assert_eq!(seek(1500, SeekOp::Eq), Some(1500));
+        assert_eq!(seek(1501, SeekOp::Eq), None);
+        assert_eq!(seek(1501, SeekOp::Ge), Some(1503));
+        assert_eq!(seek(1501, SeekOp::Le), Some(1500));
+        assert_eq!(seek(i64::MIN, SeekOp::Ge), Some(3));
+        assert_eq!(seek(i64::MAX, SeekOp::Le), Some(6000));
+        assert_eq!(seek(6001, SeekOp::Ge), None);
+        assert_eq!(seek(2, SeekOp::Le), None);
+
+        // the neighbour entry is on another leaf: every leaf boundary is crossed
+        let mut rowids = vec![];
+        let scan = BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE)
+            .unwrap()
+            .scan_page();
+        for cell in scan {
+            rowids.push(cell.unwrap().rowid);
+        }
+        for rowid in rowids {
+            assert_eq!(seek(rowid + 1, SeekOp::Le), Some(rowid));
+            assert_eq!(seek(rowid - 1, SeekOp::Ge), Some(rowid));
+        }
+    }
+
+    #[test]
+    fn test_table_seek_deleted_interior_key() {
+        // deleted.db has 512 bytes pages: table t (ids 1 to 300) was written by sqlite3,
+        // then rows 101, 102 and 138 were deleted. The interior keys 101 and 138 have no
+        // row anymore, 101 was the last row of its leaf and 102 the first of the next one.
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/deleted.db");
+        let db = Database::new(db_path.as_path().to_str().unwrap()).unwrap();
+        let mut cursor = BtCursor::new(Rc::new(RefCell::new(db)), 2).unwrap();
+        let mut seek = |rowid, op| match cursor.table_seek(rowid, op).unwrap() {
+            true => Some(current_rowid(&mut cursor)),
+            false => None,
+        };
+        assert_eq!(seek(101, SeekOp::Eq), None);
+        assert_eq!(seek(102, SeekOp::Le), Some(100));
+        assert_eq!(seek(101, SeekOp::Le), Some(100));
+        assert_eq!(seek(138, SeekOp::Le), Some(137));
+        assert_eq!(seek(101, SeekOp::Ge), Some(103));
+        assert_eq!(seek(138, SeekOp::Ge), Some(139));
+    }
+
+    #[test]
+    fn test_scan_rowids_desc_deleted_interior_key() {
+        // see test_table_seek_deleted_interior_key
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/deleted.db");
+        let db = Rc::new(RefCell::new(
+            Database::new(db_path.as_path().to_str().unwrap()).unwrap(),
+        ));
+        let scan_rowids_desc = |rowids| {
+            BtCursor::new(db.clone(), 2)
+                .unwrap()
+                .scan_rowids_desc(rowids)
+                .map(|cell| cell.unwrap().rowid)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scan_rowids_desc(98..=102), vec![100, 99, 98]);
+        assert_eq!(scan_rowids_desc(135..=138), vec![137, 136, 135]);
+        assert_eq!(scan_rowids_desc(i64::MIN..=3), vec![3, 2, 1]);
+        assert_eq!(scan_rowids_desc(i64::MIN..=i64::MAX).len(), 297);
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn test_scan_rowids() {
+        let scan_rowids = |rowids| {
+            BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE)
+                .unwrap()
+                .scan_rowids(rowids)
+                .map(|cell| cell.unwrap().rowid)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scan_rowids(1499..=1512), vec![1500, 1503, 1506, 1509, 1512]);
+        assert_eq!(scan_rowids(5995..=i64::MAX), vec![5997, 6000]);
+        assert_eq!(scan_rowids(i64::MIN..=7), vec![3, 6]);
+        assert_eq!(scan_rowids(1500..=1500), vec![1500]);
+        assert!(scan_rowids(1501..=1502).is_empty());
+        assert!(scan_rowids(7000..=8000).is_empty());
+        assert!(scan_rowids(10..=9).is_empty());
+
+        let scan_rowids_desc = |rowids| {
+            BtCursor::new(db_ref_orders(), TABLE_CUSTOMERS_ROOT_PAGE)
+                .unwrap()
+                .scan_rowids_desc(rowids)
+                .map(|cell| cell.unwrap().rowid)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(scan_rowids_desc(1499..=1510), vec![1509, 1506, 1503, 1500]);
+        assert_eq!(scan_rowids_desc(5995..=i64::MAX), vec![6000, 5997]);
+        assert_eq!(scan_rowids_desc(i64::MIN..=7), vec![6, 3]);
+        assert_eq!(scan_rowids_desc(i64::MIN..=i64::MAX).len(), 2000);
+        assert!(scan_rowids_desc(1501..=1502).is_empty());
+        assert!(scan_rowids_desc(10..=9).is_empty());
+    }
+
+    #[test]
+    fn test_index_move_to() {
+        let mut cursor = BtCursor::new(db_ref_orders(), INDEX_CUSTOMERS_EMAIL_ROOT_PAGE).unwrap();
+        let key_info = KeyInfo::new(vec![SortOrder::Asc], vec![Collation::NoCase]);
+        let key = [ColumnValue::Text("C7@EXAMPLE.com".to_owned())];
+
+        // the entries of c7@example.com are the rows 21 and 4521
+        cursor.index_move_to(&key, &key_info).unwrap();
+        let mut rowids = vec![];
+        loop {
+            let entry = cursor.index_key().unwrap();
+            if key_info.compare(&key, &entry.values).is_ne() {
+                break;
+            }
+            rowids.push(entry.values[1].as_i64().unwrap());
+            cursor.move_to_next().unwrap();
+        }
+        assert_eq!(rowids, vec![21, 4521]);
+
+        // smaller than all entries: the first entry, larger: eof
+        cursor
+            .index_move_to(&[ColumnValue::Text(String::new())], &key_info)
+            .unwrap();
+        assert_eq!(
+            cursor.index_key().unwrap().values[0],
+            ColumnValue::Text("c0@example.com".to_owned())
+        );
+        cursor
+            .index_move_to(&[ColumnValue::Text("d".to_owned())], &key_info)
+            .unwrap();
+        assert!(cursor.is_eof());
+    }
 }
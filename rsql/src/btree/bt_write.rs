@@ -0,0 +1,934 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::{bail, Result};
+
+use crate::btree::free_list::{allocate_page, free_page};
+use crate::model::database::Database;
+use crate::model::page::Page;
+use crate::model::page_header::PageType;
+use crate::model::page_id::PageId;
+use crate::model::payload::local_payload_size;
+use crate::varint::{decode_varint, encode_varint};
+
+/*
+Insertion into and deletion from a b-tree, following sqlite insertCell, dropCell and
+balance in btree.c.
+
+    The cell is inserted in the leaf found by the cursor. If the leaf overflows,
+    the page is balanced with its siblings:
+    - balance_deeper: the root overflows. Its content moves to a new child page and
+      the root becomes an interior page with the child as right child. The root page
+      number of a b-tree never changes.
+    - balance_quick: a cell is appended to the right-most leaf of a table b-tree,
+      e.g. inserting increasing rowids. The new cell goes on a new right-most leaf,
+      the full leaf is left as is.
+    - balance_nonroot: the cells of the page and up to 2 siblings, with the dividers
+      of the parent between them, are redistributed on as many pages as needed.
+      The parent gets new dividers and is balanced in turn if it overflows.
+      It is also used to merge a page less than a third full with its siblings.
+    - balance_shallower: the root is an interior page without cells, its only child
+      is copied into it and freed.
+
+    A cell that fits on the leaf is inserted in place, in a freeblock or the unallocated
+    space (see Page::insert_cell). A deleted cell is removed in place, its space becomes
+    a freeblock (see Page::remove_cell). Balanced pages are rebuilt from their list of
+    cells (see Page::rebuild).
+ */
+
+/// A page on the path from the root to a leaf, with the index of the child followed
+/// on an interior page (the number of cells for the right child), or the index of
+/// the cell inserted on the leaf.
+pub type PathEntry = (Rc<RefCell<Page>>, usize);
+
+// Cells of a page, in order.
+type Cells = Vec<Vec<u8>>;
+
+/// Builds a cell of a table leaf page: the payload size, the rowid and the payload.
+/// The part of the payload that does not fit on the page is written to overflow pages.
+pub fn table_leaf_cell(database: &mut Database, rowid: i64, payload: &[u8]) -> Result<Vec<u8>> {
+    let mut header = encode_varint(payload.len() as i64);
+    header.extend(encode_varint(rowid));
+    spill_payload(database, PageType::LeafTable, header, payload)
+}
+
+/// Builds a cell of an index leaf page: the payload size and the payload, the key.
+pub fn index_leaf_cell(database: &mut Database, payload: &[u8]) -> Result<Vec<u8>> {
+    let header = encode_varint(payload.len() as i64);
+    spill_payload(database, PageType::LeafIndex, header, payload)
+}
+
+// Appends the local part of payload to the cell header, then the number of the first
+// overflow page holding the rest. Each overflow page starts with the number of the
+// next page of the chain, 0 for the last one.
+fn spill_payload(
+    database: &mut Database,
+    page_type: PageType,
+    mut cell: Vec<u8>,
+    payload: &[u8],
+) -> Result<Vec<u8>> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let page_size = database.db_meta.db_header.page_size();
+    let local_size = local_payload_size(payload.len(), usable_size, &page_type);
+    cell.extend_from_slice(&payload[..local_size]);
+    if local_size == payload.len() {
+        return Ok(cell);
+    }
+    let chunks: Vec<&[u8]> = payload[local_size..].chunks(usable_size - 4).collect();
+    let page_numbers = (0..chunks.len())
+        .map(|_| allocate_page(database))
+        .collect::<Result<Vec<u32>>>()?;
+    for (i, chunk) in chunks.iter().enumerate() {
+        let next_page_number = page_numbers.get(i + 1).copied().unwrap_or(0);
+        let mut data = vec![0; page_size];
+        data[..4].copy_from_slice(&next_page_number.to_be_bytes());
+        data[4..4 + chunk.len()].copy_from_slice(chunk);
+        database
+            .buffer_pool
+            .put_raw_page(PageId::new(page_numbers[i]), data);
+    }
+    cell.extend_from_slice(&page_numbers[0].to_be_bytes());
+    Ok(cell)
+}
+
+/// Inserts cell on the leaf at the end of path, at the cell index of the leaf,
+/// then balances the b-tree if the leaf overflows. If replace, the cell replaces the
+/// one at the index and the overflow pages of the old cell are freed.
+pub fn insert_cell(
+    database: &mut Database,
+    path: &[PathEntry],
+    cell: Vec<u8>,
+    replace: bool,
+) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let Some((leaf, index)) = path.last() else {
+        bail!("cannot insert a cell without a page")
+    };
+    let page_type = leaf.borrow().page_header.page_type;
+    // appending to the right-most leaf of a table allows balance_quick
+    let append = !replace && *index == leaf.borrow().get_number_of_cells() as usize;
+    if replace {
+        let old_cell = leaf.borrow_mut().cell(*index, usable_size).to_vec();
+        free_overflow_pages(database, &old_cell, page_type)?;
+        leaf.borrow_mut().remove_cell(*index, usable_size)?;
+    }
+    // like sqlite, the page is balanced only if it overflows, not if it is underfull
+    let fits = leaf.borrow().free_space() >= Page::cells_space([&cell]);
+    if fits {
+        leaf.borrow_mut().insert_cell(*index, &cell, usable_size)?;
+        database.buffer_pool.mark_dirty(leaf);
+        return Ok(());
+    }
+    let mut cells = leaf.borrow_mut().cells(usable_size);
+    cells.insert(*index, cell);
+    let right_child = leaf.borrow().page_header.right_child_page_number;
+    balance(database, path, cells, right_child, append)
+}
+
+/// Removes the cell at the cell index of the leaf at the end of path and frees its
+/// overflow pages. The leaf is merged with its siblings if it is underfull.
+///
+/// Equivalent to sqlite `sqlite3BtreeDelete` of a leaf entry.
+pub fn delete_cell(database: &mut Database, path: &[PathEntry]) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let Some((leaf, index)) = path.last() else {
+        bail!("cannot delete a cell without a page")
+    };
+    let page_type = leaf.borrow().page_header.page_type;
+    let cell = leaf.borrow_mut().cell(*index, usable_size).to_vec();
+    free_overflow_pages(database, &cell, page_type)?;
+    remove_cell(database, path)?;
+    balance_underfull(database, path)
+}
+
+/// Removes the cell at the cell index of the page at the end of path, keeping its
+/// overflow pages, without balancing: an index entry moving to an interior page.
+pub fn remove_cell(database: &mut Database, path: &[PathEntry]) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let Some((page, index)) = path.last() else {
+        bail!("cannot delete a cell without a page")
+    };
+    page.borrow_mut().remove_cell(*index, usable_size)?;
+    database.buffer_pool.mark_dirty(page);
+    Ok(())
+}
+
+/// Merges the page at the end of path with its siblings if it is not the root and
+/// more than two thirds of it is free, like sqlite.
+pub fn balance_underfull(database: &mut Database, path: &[PathEntry]) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let Some((page, _)) = path.last() else {
+        bail!("cannot balance without a page")
+    };
+    if path.len() == 1 || !is_underfull(page.borrow().free_space(), usable_size) {
+        return Ok(());
+    }
+    let cells = page.borrow_mut().cells(usable_size);
+    let right_child = page.borrow().page_header.right_child_page_number;
+    balance_nonroot(database, path, cells, right_child, false)
+}
+
+// Whether a page with free_space free bytes is merged with its siblings.
+fn is_underfull(free_space: usize, usable_size: usize) -> bool {
+    free_space > usable_size * 2 / 3
+}
+
+// Writes cells on the page at the end of path if they fit, otherwise splits the page.
+// An underfull page other than the root is merged with its siblings, a root without
+// cells takes the content of its child.
+// append: the last of cells is a new cell appended to the page.
+fn balance(
+    database: &mut Database,
+    path: &[PathEntry],
+    cells: Cells,
+    right_child: Option<u32>,
+    append: bool,
+) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let (page, _) = path.last().unwrap();
+    let page_number = page.borrow().page_id.page_number;
+    let page_type = page.borrow().page_header.page_type;
+    let capacity = Page::capacity(page_number, page_type, usable_size);
+    let cells_space = Page::cells_space(&cells);
+    if cells_space <= capacity {
+        if path.len() > 1 && is_underfull(capacity - cells_space, usable_size) {
+            return balance_nonroot(database, path, cells, right_child, false);
+        }
+        page.borrow_mut()
+            .rebuild(page_type, &cells, right_child, usable_size)?;
+        database.buffer_pool.mark_dirty(page);
+        if path.len() == 1 && cells.is_empty() {
+            if let Some(child_page_number) = right_child {
+                return balance_shallower(database, page, child_page_number);
+            }
+        }
+        return Ok(());
+    }
+    if path.len() > 1 {
+        return balance_nonroot(database, path, cells, right_child, append);
+    }
+
+    // balance_deeper: the root keeps its page number and becomes the parent of a new
+    // page taking its content
+    let child_page_number = allocate_page(database)?;
+    let page_size = database.db_meta.db_header.page_size();
+    let mut child = Page::new(child_page_number, page_type, page_size, usable_size);
+    child.page_header.right_child_page_number = right_child;
+    let child = database.buffer_pool.put_page(child);
+    page.borrow_mut().rebuild(
+        page_type.interior(),
+        &[],
+        Some(child_page_number),
+        usable_size,
+    )?;
+    database.buffer_pool.mark_dirty(page);
+    let path = [(page.clone(), 0), (child, 0)];
+    balance_nonroot(database, &path, cells, right_child, append)
+}
+
+// The root has no cell and one child: the content of the child is copied to the root,
+// which keeps its page number, and the child is freed. The b-tree is one level less
+// deep. Nothing changes if the content does not fit on the root, page 1 being smaller.
+//
+// Equivalent to sqlite balance_shallower in balance_nonroot.
+fn balance_shallower(
+    database: &mut Database,
+    root: &Rc<RefCell<Page>>,
+    child_page_number: u32,
+) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let child = database
+        .buffer_pool
+        .get_page(PageId::new(child_page_number))?;
+    let mut child = child.borrow_mut();
+    let child_type = child.page_header.page_type;
+    let cells = child.cells(usable_size);
+    let root_number = root.borrow().page_id.page_number;
+    if Page::cells_space(&cells) > Page::capacity(root_number, child_type, usable_size) {
+        return Ok(());
+    }
+    let right_child = child.page_header.right_child_page_number;
+    drop(child);
+    root.borrow_mut()
+        .rebuild(child_type, &cells, right_child, usable_size)?;
+    database.buffer_pool.mark_dirty(root);
+    free_page(database, child_page_number)
+}
+
+// Splits the page at the end of path, whose cells do not fit, with its siblings, or
+// merges an underfull page with its siblings.
+//
+// Equivalent to sqlite balance_nonroot, and balance_quick when a cell is appended
+// to the right-most leaf of a table.
+fn balance_nonroot(
+    database: &mut Database,
+    path: &[PathEntry],
+    cells: Cells,
+    right_child: Option<u32>,
+    append: bool,
+) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let (page, _) = &path[path.len() - 1];
+    let (parent, child_index) = &path[path.len() - 2];
+    let child_index = *child_index;
+    let page_type = page.borrow().page_header.page_type;
+    let page_number = page.borrow().page_id.page_number;
+    let mut parent_cells = parent.borrow_mut().cells(usable_size);
+    let mut parent_right_child = parent.borrow().page_header.right_child_page_number;
+    let parent_number = parent.borrow().page_id.page_number;
+    let parent_path = &path[..path.len() - 1];
+
+    if append
+        && page_type == PageType::LeafTable
+        && child_index == parent_cells.len()
+        && parent_number != Page::PAGE_NUM_DB_ROOT
+    {
+        // balance_quick: the full leaf keeps its cells, the new cell goes on a new
+        // right-most leaf. The divider is the largest rowid of the full leaf.
+        let mut cells = cells;
+        let new_cell = cells.pop().unwrap();
+        let new_page_number = allocate_page(database)?;
+        write_page(database, page_number, false, page_type, &cells, None)?;
+        write_page(
+            database,
+            new_page_number,
+            true,
+            page_type,
+            &[new_cell],
+            None,
+        )?;
+        let mut divider = page_number.to_be_bytes().to_vec();
+        divider.extend(encode_varint(table_cell_rowid(cells.last().unwrap())));
+        parent_cells.push(divider);
+        return balance(
+            database,
+            parent_path,
+            parent_cells,
+            Some(new_page_number),
+            false,
+        );
+    }
+
+    // children of the parent: the left child of each cell then the right child
+    let children: Vec<u32> = parent_cells
+        .iter()
+        .map(|cell| u32::from_be_bytes(cell[..4].try_into().unwrap()))
+        .chain(parent_right_child)
+        .collect();
+    // up to 3 siblings around the page, like sqlite
+    let first = match child_index {
+        0 => 0,
+        i if i == parent_cells.len() => i.saturating_sub(2),
+        i => i - 1,
+    };
+    let last = (first + 2).min(parent_cells.len());
+
+    // all cells of the siblings, with the dividers between them unless the page is a
+    // table leaf whose dividers are only keys
+    let mut all_cells = vec![];
+    let mut last_right_child = None;
+    for (j, &sibling) in children.iter().enumerate().take(last + 1).skip(first) {
+        let sibling_right_child = if j == child_index {
+            all_cells.extend(cells.iter().cloned());
+            right_child
+        } else {
+            let sibling_page = database.buffer_pool.get_page(PageId::new(sibling))?;
+            let mut sibling_page = sibling_page.borrow_mut();
+            all_cells.extend(sibling_page.cells(usable_size));
+            sibling_page.page_header.right_child_page_number
+        };
+        if j == last {
+            last_right_child = sibling_right_child;
+            break;
+        }
+        let divider = &parent_cells[j];
+        match page_type {
+            PageType::LeafTable => {}
+            // the divider goes down to the leaf without its child page number
+            PageType::LeafIndex => all_cells.push(divider[4..].to_vec()),
+            // the right child of the sibling becomes the left child of the divider
+            PageType::InteriorTable | PageType::InteriorIndex => {
+                let mut cell = sibling_right_child.unwrap_or(0).to_be_bytes().to_vec();
+                cell.extend_from_slice(&divider[4..]);
+                all_cells.push(cell);
+            }
+        }
+    }
+
+    let (pages, dividers) = distribute(all_cells, page_type, usable_size)?;
+
+    // page numbers of the new pages: the siblings first, then new pages. Siblings
+    // that are not used anymore are freed.
+    let old_page_numbers = &children[first..=last];
+    let mut page_numbers = vec![];
+    for k in 0..pages.len() {
+        match old_page_numbers.get(k) {
+            Some(&page_number) => page_numbers.push(page_number),
+            None => page_numbers.push(allocate_page(database)?),
+        }
+    }
+    for &page_number in old_page_numbers.iter().skip(pages.len()) {
+        free_page(database, page_number)?;
+    }
+
+    let mut new_dividers = vec![];
+    for (k, page_cells) in pages.iter().enumerate() {
+        let is_new = k >= old_page_numbers.len();
+        if k == pages.len() - 1 {
+            write_page(
+                database,
+                page_numbers[k],
+                is_new,
+                page_type,
+                page_cells,
+                last_right_child,
+            )?;
+            break;
+        }
+        let mut divider = page_numbers[k].to_be_bytes().to_vec();
+        let page_right_child = match page_type {
+            PageType::LeafTable => {
+                divider.extend(encode_varint(table_cell_rowid(page_cells.last().unwrap())));
+                None
+            }
+            PageType::LeafIndex => {
+                divider.extend_from_slice(&dividers[k]);
+                None
+            }
+            // the left child of the divider becomes the right child of the page
+            PageType::InteriorTable | PageType::InteriorIndex => {
+                divider.extend_from_slice(&dividers[k][4..]);
+                Some(u32::from_be_bytes(dividers[k][..4].try_into().unwrap()))
+            }
+        };
+        write_page(
+            database,
+            page_numbers[k],
+            is_new,
+            page_type,
+            page_cells,
+            page_right_child,
+        )?;
+        new_dividers.push(divider);
+    }
+
+    // the parent points to the new pages: the dividers of the old siblings are
+    // replaced, and the pointer to the last sibling now points to the last new page
+    let last_page_number = *page_numbers.last().unwrap();
+    if last < parent_cells.len() {
+        parent_cells[last][..4].copy_from_slice(&last_page_number.to_be_bytes());
+    } else {
+        parent_right_child = Some(last_page_number);
+    }
+    parent_cells.splice(first..last, new_dividers);
+    balance(
+        database,
+        parent_path,
+        parent_cells,
+        parent_right_child,
+        false,
+    )
+}
+
+// Distributes cells on pages of page_type: each page is filled in order, then cells
+// move to the right to even out the last pages. Except on table leaves, the cell
+// between two pages is not on a page: it is the divider of the parent.
+// Returns the cells of the pages and the dividers.
+fn distribute(
+    cells: Cells,
+    page_type: PageType,
+    usable_size: usize,
+) -> Result<(Vec<Cells>, Cells)> {
+    // siblings are never the first page
+    let capacity = Page::capacity(2, page_type, usable_size);
+    let has_dividers = page_type != PageType::LeafTable;
+    let space = |cell: &Vec<u8>| Page::cells_space([cell]);
+
+    let mut pages: Vec<Cells> = vec![vec![]];
+    let mut dividers = vec![];
+    let mut page_space = 0;
+    for cell in cells {
+        let cell_space = space(&cell);
+        if page_space + cell_space <= capacity {
+            page_space += cell_space;
+            pages.last_mut().unwrap().push(cell);
+        } else if pages.last().unwrap().is_empty() {
+            bail!("cell of {} bytes does not fit on a page", cell.len())
+        } else if has_dividers {
+            dividers.push(cell);
+            pages.push(vec![]);
+            page_space = 0;
+        } else {
+            pages.push(vec![cell]);
+            page_space = cell_space;
+        }
+    }
+    if pages.len() > 1 && pages.last().unwrap().is_empty() {
+        // the last cell is a divider: the last cell of the previous page becomes the
+        // divider and the last one goes on the last page
+        let last_divider = dividers.pop().unwrap();
+        pages.pop();
+        let divider = pages.last_mut().unwrap().pop().unwrap();
+        dividers.push(divider);
+        pages.push(vec![last_divider]);
+    }
+
+    // like sqlite, cells move from a page to the next one while the next one
+    // stays smaller
+    let page_space = |page: &Cells| Page::cells_space(page);
+    for k in (1..pages.len()).rev() {
+        loop {
+            let (left, right) = pages.split_at_mut(k);
+            let (left, right) = (left.last_mut().unwrap(), &mut right[0]);
+            if left.len() <= 1 {
+                break;
+            }
+            let left_last = left.last().unwrap();
+            let moving = if has_dividers {
+                &dividers[k - 1]
+            } else {
+                left_last
+            };
+            let right_space = page_space(right) + space(moving);
+            let left_space = page_space(left) - space(left_last);
+            if right_space > capacity || right_space > left_space {
+                break;
+            }
+            let left_last = left.pop().unwrap();
+            if has_dividers {
+                let divider = std::mem::replace(&mut dividers[k - 1], left_last);
+                right.insert(0, divider);
+            } else {
+                right.insert(0, left_last);
+            }
+        }
+    }
+    Ok((pages, dividers))
+}
+
+/// Creates an empty b-tree: its root is a new leaf page of page_type, LeafTable for
+/// a table or LeafIndex for an index. Returns the root page number.
+///
+/// Equivalent to sqlite `btreeCreateTable` without auto-vacuum.
+pub fn create_btree(database: &mut Database, page_type: PageType) -> Result<u32> {
+    let page_number = allocate_page(database)?;
+    let header = &database.db_meta.db_header;
+    let page = Page::new(
+        page_number,
+        page_type,
+        header.page_size(),
+        header.usable_size(),
+    );
+    database.buffer_pool.put_page(page);
+    Ok(page_number)
+}
+
+/// Frees every page of the b-tree rooted at root_page_number: the interior and leaf
+/// pages, including the root, and the overflow pages of the cells.
+///
+/// Equivalent to sqlite `clearDatabasePage` followed by freeing the root.
+pub fn drop_btree(database: &mut Database, root_page_number: u32) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let page = database
+        .buffer_pool
+        .get_page(PageId::new(root_page_number))?;
+    let page_type = page.borrow().page_header.page_type;
+    let cells = page.borrow_mut().cells(usable_size);
+    let right_child = page.borrow().page_header.right_child_page_number;
+    for cell in &cells {
+        if right_child.is_some() {
+            // an interior cell starts with its left child page number
+            drop_btree(database, u32::from_be_bytes(cell[..4].try_into()?))?;
+        }
+        free_overflow_pages(database, cell, page_type)?;
+    }
+    if let Some(right_child) = right_child {
+        drop_btree(database, right_child)?;
+    }
+    free_page(database, root_page_number)
+}
+
+/// Frees the overflow pages of the payload of a cell of a page of page_type.
+pub fn free_overflow_pages(
+    database: &mut Database,
+    cell: &[u8],
+    page_type: PageType,
+) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let payload_offset = match page_type {
+        PageType::InteriorTable => return Ok(()),
+        PageType::InteriorIndex => 4,
+        PageType::LeafTable | PageType::LeafIndex => 0,
+    };
+    let (payload_size, _) = decode_varint(&cell[payload_offset..]);
+    let payload_size = payload_size as usize;
+    let local_size = local_payload_size(payload_size, usable_size, &page_type);
+    if local_size == payload_size {
+        return Ok(());
+    }
+    // the first overflow page number ends the cell
+    let mut page_number = u32::from_be_bytes(cell[cell.len() - 4..].try_into()?);
+    let page_count = (payload_size - local_size).div_ceil(usable_size - 4);
+    for _ in 0..page_count {
+        let data = database
+            .buffer_pool
+            .get_raw_page(PageId::new(page_number))?;
+        free_page(database, page_number)?;
+        page_number = u32::from_be_bytes(data[..4].try_into()?);
+    }
+    Ok(())
+}
+
+// Rowid of a table leaf cell, after the payload size.
+fn table_cell_rowid(cell: &[u8]) -> i64 {
+    let (_, bytes_read) = decode_varint(cell);
+    decode_varint(&cell[bytes_read..]).0
+}
+
+// Rebuilds a page with cells, a new page is added to the buffer pool.
+fn write_page(
+    database: &mut Database,
+    page_number: u32,
+    is_new: bool,
+    page_type: PageType,
+    cells: &[Vec<u8>],
+    right_child: Option<u32>,
+) -> Result<()> {
+    let usable_size = database.db_meta.db_header.usable_size();
+    let page = if is_new {
+        let page_size = database.db_meta.db_header.page_size();
+        let page = Page::new(page_number, page_type, page_size, usable_size);
+        database.buffer_pool.put_page(page)
+    } else {
+        database.buffer_pool.get_page(PageId::new(page_number))?
+    };
+    page.borrow_mut()
+        .rebuild(page_type, cells, right_child, usable_size)?;
+    database.buffer_pool.mark_dirty(&page);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::btree::bt_cursor::BtCursor;
+    use crate::model::column_value::ColumnValue;
+    use crate::model::data_record::DataRecord;
+    use crate::model::index_key::KeyInfo;
+    use crate::test_utils::copy_db;
+
+    use super::*;
+
+    fn open(path: &std::path::Path) -> Rc<RefCell<Database>> {
+        Rc::new(RefCell::new(Database::new(path.to_str().unwrap()).unwrap()))
+    }
+
+    fn text(len: usize, rowid: i64) -> ColumnValue {
+        ColumnValue::Text(format!("{rowid}:{}", "x".repeat(len)))
+    }
+
+    // rowids and record of the rows of the table btree at root_page_number
+    fn scan(database: Rc<RefCell<Database>>, root_page_number: u32) -> Vec<(i64, DataRecord)> {
+        BtCursor::new(database, root_page_number)
+            .unwrap()
+            .scan_page()
+            .map(|cell| {
+                let cell = cell.unwrap();
+                (cell.rowid, cell.payload)
+            })
+            .collect()
+    }
+
+    // a new empty index btree, returns its root page number
+    fn new_index(database: &Rc<RefCell<Database>>) -> u32 {
+        create_btree(&mut database.borrow_mut(), PageType::LeafIndex).unwrap()
+    }
+
+    // keys of the index btree at root_page_number, in order
+    fn index_keys(database: Rc<RefCell<Database>>, root_page_number: u32) -> Vec<Vec<ColumnValue>> {
+        let mut cursor = BtCursor::new(database, root_page_number).unwrap();
+        cursor.move_to_first().unwrap();
+        let mut keys = vec![];
+        while !cursor.is_eof() {
+            keys.push(cursor.index_key().unwrap().values);
+            if cursor.move_to_next().is_err() {
+                break;
+            }
+        }
+        keys
+    }
+
+    fn index_key(rowid: i64) -> DataRecord {
+        DataRecord {
+            values: vec![
+                text(rowid as usize % 200, rowid % 300),
+                ColumnValue::int64(rowid),
+            ],
+            rowid: None,
+        }
+    }
+
+    #[test]
+    fn test_insert_increasing_rowids() {
+        // apples is on page 2 with rowids 1 to 4
+        let (_dir, path) = copy_db("sample.db");
+        let database = open(&path);
+        let mut cursor = BtCursor::new(database.clone(), 2).unwrap();
+        for rowid in 5..=2000 {
+            let record = DataRecord {
+                values: vec![ColumnValue::Null, text(rowid as usize % 50, rowid)],
+                rowid: None,
+            };
+            cursor.table_insert(rowid, &record).unwrap();
+        }
+        // replacing a row frees the overflow pages of the old one: a payload of 10010
+        // bytes has 1826 bytes on the leaf and 2 overflow pages
+        let big = DataRecord {
+            values: vec![ColumnValue::Null, text(10000, 1000)],
+            rowid: None,
+        };
+        cursor.table_insert(1000, &big).unwrap();
+        assert_eq!(database.borrow().db_meta.db_header.freelist_page_count, 0);
+        let record = DataRecord {
+            values: vec![ColumnValue::Null, text(1000 % 50, 1000)],
+            rowid: None,
+        };
+        cursor.table_insert(1000, &record).unwrap();
+        assert_eq!(database.borrow().db_meta.db_header.freelist_page_count, 2);
+        database.borrow_mut().commit().unwrap();
+
+        let database = open(&path);
+        let rows = scan(database.clone(), 2);
+        assert_eq!(rows.len(), 2000);
+        for (i, (rowid, record)) in rows.iter().enumerate() {
+            assert_eq!(*rowid, i as i64 + 1);
+            if *rowid > 4 {
+                assert_eq!(record.values[1], text(*rowid as usize % 50, *rowid));
+            }
+        }
+        let root = database
+            .borrow_mut()
+            .buffer_pool
+            .get_page(PageId::new(2))
+            .unwrap();
+        assert_eq!(root.borrow().page_header.page_type, PageType::InteriorTable);
+        let header = &database.borrow().db_meta.db_header;
+        assert!(header.db_page_count > 10);
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len(),
+            header.db_page_count as u64 * 4096
+        );
+    }
+
+    #[test]
+    fn test_insert_random_rowids_with_overflow() {
+        let (_dir, path) = copy_db("sample.db");
+        let database = open(&path);
+        let mut cursor = BtCursor::new(database.clone(), 2).unwrap();
+        // rowids 5 to 1013 in a scrambled order, 1009 is prime
+        let rowids: Vec<i64> = (0..1009).map(|i| (i * 389) % 1009 + 5).collect();
+        // every 10th row spills to overflow pages
+        let len = |rowid: i64| {
+            if rowid % 10 == 0 {
+                9000
+            } else {
+                rowid as usize % 300
+            }
+        };
+        for &rowid in &rowids {
+            let record = DataRecord {
+                values: vec![text(len(rowid), rowid)],
+                rowid: None,
+            };
+            cursor.table_insert(rowid, &record).unwrap();
+        }
+        database.borrow_mut().commit().unwrap();
+
+        let rows = scan(open(&path), 2);
+        assert_eq!(rows.len(), 1013);
+        for (i, (rowid, record)) in rows.iter().enumerate().skip(4) {
+            assert_eq!(*rowid, i as i64 + 1);
+            assert_eq!(record.values, vec![text(len(*rowid), *rowid)]);
+        }
+    }
+
+    #[test]
+    fn test_insert_index_keys() {
+        let (_dir, path) = copy_db("sample.db");
+        let database = open(&path);
+        let root_page_number = new_index(&database);
+        let key = index_key;
+        let key_info = KeyInfo::default();
+        let mut cursor = BtCursor::new(database.clone(), root_page_number).unwrap();
+        for rowid in (0..2003).map(|i| (i * 787) % 2003) {
+            cursor.index_insert(&key(rowid), &key_info).unwrap();
+        }
+        assert!(cursor.index_insert(&key(5), &key_info).is_err());
+        database.borrow_mut().commit().unwrap();
+
+        let mut expected: Vec<Vec<ColumnValue>> =
+            (0..2003).map(|rowid| key(rowid).values).collect();
+        expected.sort_by(|a, b| key_info.compare(a, b));
+        let keys = index_keys(open(&path), root_page_number);
+        // integers are stored with the smallest serial type, compare the values
+        assert_eq!(keys.len(), expected.len());
+        for (key, expected) in keys.iter().zip(&expected) {
+            assert!(
+                key_info.compare(key, expected).is_eq(),
+                "{key:?} {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_delete_rows() {
+        let (_dir, path) = copy_db("sample.db");
+        let database = open(&path);
+        let page_count = database.borrow().db_meta.db_header.db_page_count;
+        let mut cursor = BtCursor::new(database.clone(), 2).unwrap();
+        // every 100th row spills to 2 overflow pages
+        let len = |rowid: i64| {
+            if rowid % 100 == 0 {
+                9000
+            } else {
+                rowid as usize % 300
+            }
+        };
+        for rowid in 5..=3000 {
+            let record = DataRecord {
+                values: vec![text(len(rowid), rowid)],
+                rowid: None,
+            };
+            cursor.table_insert(rowid, &record).unwrap();
+        }
+        // in a scrambled order, 3001 is prime
+        for rowid in (0..3001).map(|i| (i * 1999) % 3001) {
+            if rowid % 7 != 0 && rowid > 0 {
+                cursor.table_delete(rowid).unwrap();
+            }
+        }
+        assert!(cursor.table_delete(1).is_err());
+        database.borrow_mut().commit().unwrap();
+
+        let database = open(&path);
+        let rows = scan(database.clone(), 2);
+        let rowids: Vec<i64> = rows.iter().map(|(rowid, _)| *rowid).collect();
+        assert_eq!(rowids, (1..=428).map(|i| i * 7).collect::<Vec<_>>());
+        for (rowid, record) in rows.iter().skip(1) {
+            assert_eq!(record.values, vec![text(len(*rowid), *rowid)]);
+        }
+        let freelist_page_count = database.borrow().db_meta.db_header.freelist_page_count;
+        assert!(freelist_page_count > 0);
+
+        // emptied pages go to the freelist, the root is a leaf again
+        let mut cursor = BtCursor::new(database.clone(), 2).unwrap();
+        for (rowid, _) in rows {
+            cursor.table_delete(rowid).unwrap();
+        }
+        database.borrow_mut().commit().unwrap();
+        let database = open(&path);
+        let (freelist_page_count, db_page_count) = {
+            let header = &database.borrow().db_meta.db_header;
+            (header.freelist_page_count, header.db_page_count)
+        };
+        assert_eq!(freelist_page_count, db_page_count - page_count);
+        let root = database
+            .borrow_mut()
+            .buffer_pool
+            .get_page(PageId::new(2))
+            .unwrap();
+        assert_eq!(root.borrow().page_header.page_type, PageType::LeafTable);
+        assert_eq!(root.borrow().get_number_of_cells(), 0);
+    }
+
+    #[test]
+    fn test_delete_index_keys() {
+        let (_dir, path) = copy_db("sample.db");
+        let database = open(&path);
+        let root_page_number = new_index(&database);
+        let key_info = KeyInfo::default();
+        let mut cursor = BtCursor::new(database.clone(), root_page_number).unwrap();
+        for rowid in 0..2003 {
+            cursor.index_insert(&index_key(rowid), &key_info).unwrap();
+        }
+        // keys on interior pages are replaced by the previous key
+        for rowid in (0..2003).map(|i| (i * 787) % 2003) {
+            if rowid % 10 != 0 {
+                cursor.index_delete(&index_key(rowid), &key_info).unwrap();
+            }
+        }
+        assert!(cursor.index_delete(&index_key(1), &key_info).is_err());
+        database.borrow_mut().commit().unwrap();
+
+        let database = open(&path);
+        assert!(database.borrow().db_meta.db_header.freelist_page_count > 0);
+        let mut expected: Vec<Vec<ColumnValue>> = (0..2003)
+            .filter(|rowid| rowid % 10 == 0)
+            .map(|rowid| index_key(rowid).values)
+            .collect();
+        expected.sort_by(|a, b| key_info.compare(a, b));
+        let keys = index_keys(database, root_page_number);
+        assert_eq!(keys.len(), expected.len());
+        for (key, expected) in keys.iter().zip(&expected) {
+            assert!(key_info.compare(key, expected).is_eq());
+        }
+    }
+
+    #[test]
+    fn test_create_and_drop_btrees() {
+        let (_dir, path) = copy_db("sample.db");
+        let database = open(&path);
+        let page_count = database.borrow().db_meta.db_header.db_page_count;
+        let table_root = create_btree(&mut database.borrow_mut(), PageType::LeafTable).unwrap();
+        let index_root = new_index(&database);
+        assert_eq!((table_root, index_root), (page_count + 1, page_count + 2));
+
+        let mut cursor = BtCursor::new(database.clone(), table_root).unwrap();
+        let mut index_cursor = BtCursor::new(database.clone(), index_root).unwrap();
+        for rowid in 1..=1000 {
+            // every 50th row spills to overflow pages
+            let len = if rowid % 50 == 0 { 5000 } else { 100 };
+            let record = DataRecord {
+                values: vec![text(len, rowid)],
+                rowid: None,
+            };
+            cursor.table_insert(rowid, &record).unwrap();
+            index_cursor
+                .index_insert(&index_key(rowid), &KeyInfo::default())
+                .unwrap();
+        }
+        database.borrow_mut().commit().unwrap();
+        let database = open(&path);
+        assert_eq!(scan(database.clone(), table_root).len(), 1000);
+        assert_eq!(index_keys(database.clone(), index_root).len(), 1000);
+
+        // every page of both btrees is freed, the other tables are unchanged
+        drop_btree(&mut database.borrow_mut(), table_root).unwrap();
+        drop_btree(&mut database.borrow_mut(), index_root).unwrap();
+        database.borrow_mut().commit().unwrap();
+        let database = open(&path);
+        let (freelist_page_count, db_page_count) = {
+            let header = &database.borrow().db_meta.db_header;
+            (header.freelist_page_count, header.db_page_count)
+        };
+        assert_eq!(freelist_page_count, db_page_count - page_count);
+        assert_eq!(scan(database, 2).len(), 4);
+    }
+
+    #[test]
+    fn test_distribute_table_leaf_cells() {
+        // 3 pages of 4092 bytes: 2 full pages then the right balance pass
+        let cells = vec![vec![0; 998]; 9];
+        let (pages, dividers) = distribute(cells, PageType::LeafTable, 4096).unwrap();
+        assert!(dividers.is_empty());
+        assert_eq!(
+            pages.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![4, 3, 2]
+        );
+
+        // the cell between 2 pages is a divider
+        let cells = (0..9u8).map(|i| vec![i; 998]).collect();
+        let (pages, dividers) = distribute(cells, PageType::LeafIndex, 4096).unwrap();
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), vec![4, 4]);
+        assert_eq!(dividers, vec![vec![4; 998]]);
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::{bail, Result};
+
+use crate::model::database::Database;
+use crate::model::page_id::PageId;
+
+/*
+Freelist https://www.sqlite.org/fileformat.html#the_freelist
+    Unused pages are kept in a linked list of trunk pages starting at the page number
+    at offset 32 of the db header. A trunk page is:
+    - 4 bytes: page number of the next trunk page, 0 for the last one
+    - 4 bytes: number L of leaf page numbers on the trunk
+    - L * 4 bytes: page numbers of freelist leaf pages
+    The content of a leaf page is not used. The db header counts all freelist
+    pages (trunks and leaves) at offset 36.
+ */
+
+// Offset of the number of leaves on a trunk page.
+const TRUNK_LEAF_COUNT_OFFSET: usize = 4;
+// Offset of the first leaf page number on a trunk page.
+const TRUNK_LEAVES_OFFSET: usize = 8;
+
+/// Page number of the page holding the lock byte at offset 2^30 of the file,
+/// it is never used for content.
+pub fn pending_byte_page(page_size: usize) -> u32 {
+    (0x4000_0000 / page_size) as u32 + 1
+}
+
+/// Max number of leaves on a trunk page. Like sqlite, 6 entries are left unused
+/// for compatibility with older versions which did not read the last ones.
+fn max_trunk_leaves(usable_size: usize) -> usize {
+    usable_size / 4 - 8
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn write_u32(data: &mut [u8], offset: usize, value: u32) {
+    data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+}
+
+/// Returns the number of an unused page: a page of the freelist, or a page appended
+/// at the end of the file. The content of the page is not initialized, the caller
+/// writes the whole page.
+///
+/// Equivalent to sqlite `allocateBtreePage` without the search for a page near
+/// a given one.
+pub fn allocate_page(database: &mut Database) -> Result<u32> {
+    let header = &mut database.db_meta.db_header;
+    if header.version_valid_for != header.file_change_counter {
+        // written by sqlite before 3.7.0, the page count is computed from the file size
+        bail!("the page count of the database header is not valid")
+    }
+    let trunk_page_number = header.first_freelist_page;
+    if trunk_page_number == 0 {
+        header.db_page_count += 1;
+        if header.db_page_count == pending_byte_page(header.page_size()) {
+            header.db_page_count += 1;
+        }
+        return Ok(header.db_page_count);
+    }
+
+    let trunk_id = PageId::new(trunk_page_number);
+    let mut trunk = database.buffer_pool.get_raw_page(trunk_id)?;
+    let header = &mut database.db_meta.db_header;
+    header.freelist_page_count -= 1;
+    let leaf_count = read_u32(&trunk, TRUNK_LEAF_COUNT_OFFSET) as usize;
+    if leaf_count == 0 {
+        // the trunk itself is used, the next trunk becomes the first one
+        header.first_freelist_page = read_u32(&trunk, 0);
+        return Ok(trunk_page_number);
+    }
+    let last_leaf_offset = TRUNK_LEAVES_OFFSET + 4 * (leaf_count - 1);
+    let leaf_page_number = read_u32(&trunk, last_leaf_offset);
+    write_u32(&mut trunk, TRUNK_LEAF_COUNT_OFFSET, leaf_count as u32 - 1);
+    database.buffer_pool.put_raw_page(trunk_id, trunk);
+    Ok(leaf_page_number)
+}
+
+/// Adds a page to the freelist: as a leaf of the first trunk page if it has room,
+/// otherwise the page becomes the first trunk page.
+///
+/// Equivalent to sqlite `freePage2` without secure delete.
+pub fn free_page(database: &mut Database, page_number: u32) -> Result<()> {
+    let header = &database.db_meta.db_header;
+    if page_number < 2 || page_number > header.db_page_count {
+        bail!("cannot free page {page_number}")
+    }
+    let usable_size = header.usable_size();
+    let page_size = header.page_size();
+    let trunk_page_number = header.first_freelist_page;
+    if trunk_page_number != 0 {
+        let trunk_id = PageId::new(trunk_page_number);
+        let mut trunk = database.buffer_pool.get_raw_page(trunk_id)?;
+        let leaf_count = read_u32(&trunk, TRUNK_LEAF_COUNT_OFFSET) as usize;
+        if leaf_count < max_trunk_leaves(usable_size) {
+            write_u32(
+                &mut trunk,
+                TRUNK_LEAVES_OFFSET + 4 * leaf_count,
+                page_number,
+            );
+            write_u32(&mut trunk, TRUNK_LEAF_COUNT_OFFSET, leaf_count as u32 + 1);
+            database.buffer_pool.put_raw_page(trunk_id, trunk);
+            database.db_meta.db_header.freelist_page_count += 1;
+            return Ok(());
+        }
+    }
+    // new trunk page without leaves, pointing to the previous first trunk
+    let mut trunk = vec![0; page_size];
+    write_u32(&mut trunk, 0, trunk_page_number);
+    database
+        .buffer_pool
+        .put_raw_page(PageId::new(page_number), trunk);
+    let header = &mut database.db_meta.db_header;
+    header.first_freelist_page = page_number;
+    header.freelist_page_count += 1;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    fn sample_db() -> Database {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sample.db");
+        Database::new(path.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_allocate_page_at_end_of_file() {
+        let mut db = sample_db();
+        let page_count = db.db_meta.db_header.db_page_count;
+        assert_eq!(db.db_meta.db_header.first_freelist_page, 0);
+        assert_eq!(allocate_page(&mut db).unwrap(), page_count + 1);
+        assert_eq!(allocate_page(&mut db).unwrap(), page_count + 2);
+        assert_eq!(db.db_meta.db_header.db_page_count, page_count + 2);
+    }
+
+    #[test]
+    fn test_free_and_allocate_pages() {
+        let mut db = sample_db();
+        let page_count = db.db_meta.db_header.db_page_count;
+        let first = allocate_page(&mut db).unwrap();
+        let second = allocate_page(&mut db).unwrap();
+        let third = allocate_page(&mut db).unwrap();
+
+        // the first page freed is a trunk, the next ones are its leaves
+        free_page(&mut db, first).unwrap();
+        free_page(&mut db, second).unwrap();
+        free_page(&mut db, third).unwrap();
+        let header = &db.db_meta.db_header;
+        assert_eq!(header.first_freelist_page, first);
+        assert_eq!(header.freelist_page_count, 3);
+        let trunk = db.buffer_pool.get_raw_page(PageId::new(first)).unwrap();
+        assert_eq!(read_u32(&trunk, 0), 0);
+        assert_eq!(read_u32(&trunk, 4), 2);
+        assert_eq!(read_u32(&trunk, 8), second);
+        assert_eq!(read_u32(&trunk, 12), third);
+
+        // leaves are used before the trunk
+        assert_eq!(allocate_page(&mut db).unwrap(), third);
+        assert_eq!(allocate_page(&mut db).unwrap(), second);
+        assert_eq!(allocate_page(&mut db).unwrap(), first);
+        let header = &db.db_meta.db_header;
+        assert_eq!(header.first_freelist_page, 0);
+        assert_eq!(header.freelist_page_count, 0);
+        assert_eq!(allocate_page(&mut db).unwrap(), page_count + 4);
+        assert!(free_page(&mut db, 1).is_err());
+    }
+
+    #[test]
+    fn test_pending_byte_page() {
+        assert_eq!(pending_byte_page(4096), 262145);
+        assert_eq!(pending_byte_page(512), 2097153);
+    }
+}
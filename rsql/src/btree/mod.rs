@@ -1 +1,3 @@
 pub mod bt_cursor;
+pub mod bt_write;
+pub mod free_list;
@@ -0,0 +1,139 @@
+use std::fmt;
+
+use anyhow::{bail, Result};
+use arrow_schema::DataType;
+
+/// Type affinity of a column: the preferred storage class of the values stored in it.
+/// https://www.sqlite.org/datatype3.html#type_affinity
+///
+/// SQLite is dynamically typed: any column can store any value, the affinity only
+/// drives the conversions applied to the values written to or compared with the column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Affinity {
+    Text,
+    Numeric,
+    Integer,
+    Real,
+    Blob,
+}
+
+impl Affinity {
+    /// Determines the affinity of a column from its declared type,
+    /// the rules are applied in order (sqlite3AffinityType).
+    /// https://www.sqlite.org/datatype3.html#determination_of_column_affinity
+    ///
+    /// 1. contains "INT": INTEGER
+    /// 2. contains "CHAR", "CLOB" or "TEXT": TEXT
+    /// 3. contains "BLOB" or no declared type: BLOB
+    /// 4. contains "REAL", "FLOA" or "DOUB": REAL
+    /// 5. otherwise: NUMERIC
+    pub fn from_decl_type(decl_type: &str) -> Self {
+        let decl_type = decl_type.to_ascii_uppercase();
+        let contains_any = |patterns: &[&str]| patterns.iter().any(|p| decl_type.contains(p));
+
+        if decl_type.contains("INT") {
+            Affinity::Integer
+        } else if contains_any(&["CHAR", "CLOB", "TEXT"]) {
+            Affinity::Text
+        } else if decl_type.contains("BLOB") || decl_type.trim().is_empty() {
+            Affinity::Blob
+        } else if contains_any(&["REAL", "FLOA", "DOUB"]) {
+            Affinity::Real
+        } else {
+            Affinity::Numeric
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "TEXT" => Affinity::Text,
+            "NUMERIC" => Affinity::Numeric,
+            "INTEGER" => Affinity::Integer,
+            "REAL" => Affinity::Real,
+            "BLOB" => Affinity::Blob,
+            _ => bail!("Unknown affinity: {name}"),
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Affinity::Text => "TEXT",
+            Affinity::Numeric => "NUMERIC",
+            Affinity::Integer => "INTEGER",
+            Affinity::Real => "REAL",
+            Affinity::Blob => "BLOB",
+        }
+    }
+
+    /// Returns the arrow type of the column, used by datafusion to plan expressions.
+    /// The values keep their storage class and are evaluated with SQLite's rules.
+    /// - integers are 64-bit in SQLite.
+    /// - NUMERIC columns store integers and reals, Float64 holds both.
+    /// - BLOB columns (and columns without a type) store values as they are: Null has no
+    ///   type, datafusion coerces it to the type of the other operand of an expression.
+    pub fn data_type(&self) -> DataType {
+        match self {
+            Affinity::Text => DataType::Utf8,
+            Affinity::Numeric | Affinity::Real => DataType::Float64,
+            Affinity::Integer => DataType::Int64,
+            Affinity::Blob => DataType::Null,
+        }
+    }
+}
+
+impl fmt::Display for Affinity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affinity_from_decl_type() {
+        // examples of https://www.sqlite.org/datatype3.html#affinity_name_examples
+        for decl_type in ["INT", "integer", "TINYINT", "UNSIGNED BIG INT", "INT8"] {
+            assert_eq!(Affinity::from_decl_type(decl_type), Affinity::Integer);
+        }
+        for decl_type in [
+            "CHARACTER(20)",
+            "varchar(255)",
+            "NATIVE CHARACTER(70)",
+            "TEXT",
+            "CLOB",
+        ] {
+            assert_eq!(Affinity::from_decl_type(decl_type), Affinity::Text);
+        }
+        for decl_type in ["BLOB", ""] {
+            assert_eq!(Affinity::from_decl_type(decl_type), Affinity::Blob);
+        }
+        for decl_type in ["REAL", "DOUBLE", "DOUBLE PRECISION", "FLOAT"] {
+            assert_eq!(Affinity::from_decl_type(decl_type), Affinity::Real);
+        }
+        for decl_type in ["NUMERIC", "DECIMAL(10,5)", "BOOLEAN", "DATE", "DATETIME"] {
+            assert_eq!(Affinity::from_decl_type(decl_type), Affinity::Numeric);
+        }
+        // rules are applied in order
+        assert_eq!(Affinity::from_decl_type("CHARINT"), Affinity::Integer);
+        assert_eq!(
+            Affinity::from_decl_type("FLOATING POINT"),
+            Affinity::Integer
+        );
+        assert_eq!(Affinity::from_decl_type("STRING"), Affinity::Numeric);
+    }
+
+    #[test]
+    fn test_affinity_data_type() {
+        assert_eq!(Affinity::Integer.data_type(), DataType::Int64);
+        assert_eq!(Affinity::Text.data_type(), DataType::Utf8);
+        assert_eq!(Affinity::Blob.data_type(), DataType::Null);
+        assert_eq!(Affinity::Real.data_type(), DataType::Float64);
+        assert_eq!(Affinity::Numeric.data_type(), DataType::Float64);
+        assert_eq!(
+            Affinity::from_name(Affinity::Numeric.name()).unwrap(),
+            Affinity::Numeric
+        );
+    }
+}
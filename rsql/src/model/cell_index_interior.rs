@@ -0,0 +1,146 @@
+use anyhow::{bail, Result};
+
+use crate::access::buffer_pool::BufferPool;
+use crate::model::data_record::DataRecord;
+use crate::model::page_header::PageType;
+use crate::model::payload::read_payload;
+use crate::varint::decode_varint;
+
+/// `CellIndexInterior` represents an Index B-Tree Interior Cell (header 0x02).
+/// - 4-byte big-endian page number which is the left child pointer,
+/// - a varint which is the total number of bytes of key payload, including any overflow,
+/// - the initial portion of the payload that does not spill to overflow pages,
+/// - a 4-byte big-endian page number for the first overflow page, omitted if no overflow.
+///
+/// Unlike table interior cells, the key of an index interior cell is a full index entry:
+/// every key in the left child is less than it.
+/// https://www.sqlite.org/fileformat.html#b_tree_pages
+#[derive(Debug)]
+pub struct CellIndexInterior {
+    pub left_child_pointer: u32,
+    pub payload: DataRecord,
+}
+
+impl CellIndexInterior {
+    /// Parses a cell whose payload fits entirely on the page (no overflow).
+    pub fn parse(stream: &[u8]) -> Result<Self> {
+        let left_child_pointer = u32::from_be_bytes(stream[0..4].try_into()?);
+        let (payload_size, bytes_read) = decode_varint(&stream[4..]);
+        let payload_size: usize = payload_size.try_into()?;
+        let offset = 4 + bytes_read;
+        if stream.len() < offset + payload_size {
+            bail!("Cell payload of {payload_size} bytes does not fit on the page, use parse_with_overflow")
+        }
+
+        Ok(Self {
+            left_child_pointer,
+            payload: DataRecord::parse_index_key(&stream[offset..offset + payload_size]),
+        })
+    }
+
+    /// Parses a cell on a page with usable_size bytes, following the overflow
+    /// page chain through buffer_pool if the key spills.
+    pub fn parse_with_overflow(
+        stream: &[u8],
+        usable_size: usize,
+        buffer_pool: &mut BufferPool,
+    ) -> Result<Self> {
+        let left_child_pointer = u32::from_be_bytes(stream[0..4].try_into()?);
+        let (payload_size, bytes_read) = decode_varint(&stream[4..]);
+        let payload = read_payload(
+            &stream[4 + bytes_read..],
+            payload_size.try_into()?,
+            usable_size,
+            &PageType::InteriorIndex,
+            buffer_pool,
+        )?;
+
+        Ok(Self {
+            left_child_pointer,
+            payload: DataRecord::parse_index_key(&payload),
+        })
+    }
+
+    /// Returns the rowid of the table row this index entry points to.
+    pub fn rowid(&self) -> Option<i64> {
+        self.payload.values.last().and_then(|value| value.as_i64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use std::path::PathBuf;
+
+    use crate::model::cell_index_leaf::LeafIndexCell;
+    use crate::model::collation::Collation;
+    use crate::model::column_value::ColumnValue;
+    use crate::model::database::Database;
+    use crate::model::index_key::{KeyInfo, SortOrder};
+    use crate::model::page_id::PageId;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_cell_index_interior() {
+        // left child page 3, key ('a', rowid 9)
+        let cell_bytes: Vec<u8> = vec![0, 0, 0, 3, 5, 3, 15, 1, 97, 9];
+        let cell = CellIndexInterior::parse(cell_bytes.as_slice()).unwrap();
+        assert_eq!(cell.left_child_pointer, 3);
+        assert_eq!(cell.payload.values[0], ColumnValue::Text("a".to_owned()));
+        assert_eq!(cell.rowid(), Some(9));
+    }
+
+    #[test]
+    fn test_interior_keys_ordered_index_db() {
+        // index.db: idx_people_name_age on people(name collate nocase, age desc)
+        // is rooted at interior page 3.
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/index.db");
+        let mut db = Database::new(db_path.to_str().unwrap()).unwrap();
+        let usable_size = db.db_meta.db_header.usable_size();
+        let key_info = KeyInfo::new(
+            vec![SortOrder::Asc, SortOrder::Desc],
+            vec![Collation::NoCase, Collation::Binary],
+        );
+
+        let root = db.buffer_pool.get_page(PageId::new(3)).unwrap();
+        let mut root = root.borrow_mut();
+        assert!(root.page_header.is_index_interior());
+
+        let mut keys = vec![];
+        for index in 0..root.get_number_of_cells() as usize {
+            let cell_ptr = root.get_cell_ptr(index);
+            let cell = CellIndexInterior::parse_with_overflow(
+                &root.data[cell_ptr..],
+                usable_size,
+                &mut db.buffer_pool,
+            )
+            .unwrap();
+            assert!(cell.rowid().is_some());
+
+            // every key of the left child is less than the divider key
+            let child = db
+                .buffer_pool
+                .get_page(PageId::new(cell.left_child_pointer))
+                .unwrap();
+            let mut child = child.borrow_mut();
+            for child_index in 0..child.get_number_of_cells() as usize {
+                let child_ptr = child.get_cell_ptr(child_index);
+                let child_cell = LeafIndexCell::parse(&child.data[child_ptr..]).unwrap();
+                assert_eq!(
+                    key_info.compare(&child_cell.payload.values, &cell.payload.values),
+                    Ordering::Less
+                );
+            }
+            keys.push(cell.payload);
+        }
+
+        // divider keys are sorted following the index sort orders and collations
+        for pair in keys.windows(2) {
+            assert_eq!(
+                key_info.compare(&pair[0].values, &pair[1].values),
+                Ordering::Less
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use anyhow::{bail, Result};
+
+use crate::access::buffer_pool::BufferPool;
+use crate::model::data_record::DataRecord;
+use crate::model::page_header::PageType;
+use crate::model::payload::read_payload;
+use crate::varint::decode_varint;
+
+/*
+LeafIndexCell (header 0x0a) has format (in order of appearance)
+    -A varint which is the total number of bytes of key payload, including any overflow
+    -The initial portion of the payload that does not spill to overflow pages.
+    -A 4-byte big-endian integer page number for the first page of
+    the overflow page list - omitted if all payload fits on the b-tree page.
+
+The payload is a record of the indexed columns followed by the rowid of the table row.
+ */
+#[derive(Debug)]
+pub struct LeafIndexCell {
+    pub payload: DataRecord,
+}
+
+impl LeafIndexCell {
+    /// Parses a cell whose payload fits entirely on the page (no overflow).
+    pub fn parse(stream: &[u8]) -> Result<Self> {
+        let (payload_size, offset) = decode_varint(stream);
+        let payload_size: usize = payload_size.try_into()?;
+        if stream.len() < offset + payload_size {
+            bail!("Cell payload of {payload_size} bytes does not fit on the page, use parse_with_overflow")
+        }
+        let payload = DataRecord::parse_index_key(&stream[offset..offset + payload_size]);
+
+        Ok(Self { payload })
+    }
+
+    /// Parses a cell on a page with usable_size bytes, following the overflow
+    /// page chain through buffer_pool if the key spills.
+    pub fn parse_with_overflow(
+        stream: &[u8],
+        usable_size: usize,
+        buffer_pool: &mut BufferPool,
+    ) -> Result<Self> {
+        let (payload_size, offset) = decode_varint(stream);
+        let payload = read_payload(
+            &stream[offset..],
+            payload_size.try_into()?,
+            usable_size,
+            &PageType::LeafIndex,
+            buffer_pool,
+        )?;
+
+        Ok(Self {
+            payload: DataRecord::parse_index_key(&payload),
+        })
+    }
+
+    /// Returns the rowid of the table row this index entry points to.
+    pub fn rowid(&self) -> Option<i64> {
+        self.payload.values.last().and_then(|value| value.as_i64())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::model::column_value::ColumnValue;
+    use crate::model::database::Database;
+    use crate::model::page_id::PageId;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_leaf_index_cell() {
+        // index on t1(a) with key (177, rowid 2)
+        let cell_bytes: Vec<u8> = vec![6, 3, 2, 1, 0, 177, 2];
+        let cell = LeafIndexCell::parse(cell_bytes.as_slice()).unwrap();
+        assert_eq!(cell.payload.values.len(), 2);
+        assert_eq!(cell.payload.values[0], ColumnValue::Int16([0, 177]));
+        assert_eq!(cell.rowid(), Some(2));
+        assert_eq!(cell.payload.rowid, Some(2));
+    }
+
+    #[test]
+    fn test_parse_leaf_index_cells_index_db() {
+        // index.db: people(id integer primary key, name text, age int) with 200 rows,
+        // idx_people_name_age on people(name collate nocase, age desc) rooted at page 3.
+        // page 4 is the left-most leaf of the index.
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/index.db");
+        let mut db = Database::new(db_path.to_str().unwrap()).unwrap();
+        let usable_size = db.db_meta.db_header.usable_size();
+        let page = db.buffer_pool.get_page(PageId::new(4)).unwrap();
+        let mut page = page.borrow_mut();
+        assert!(page.page_header.is_index_leaf());
+
+        let cell_ptr = page.get_cell_ptr(0);
+        let cell = LeafIndexCell::parse_with_overflow(
+            &page.data[cell_ptr..],
+            usable_size,
+            &mut db.buffer_pool,
+        )
+        .unwrap();
+        // name, age and rowid
+        assert_eq!(cell.payload.values.len(), 3);
+        assert_eq!(
+            cell.payload.values[0],
+            ColumnValue::Text("Alice0".to_owned())
+        );
+        assert!(cell.rowid().is_some());
+    }
+}
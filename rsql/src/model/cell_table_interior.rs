@@ -5,6 +5,7 @@ use crate::varint::decode_varint;
 /// `CellTableInterior` represents a Table B-Tree Interior Cell (header 0x05).
 /// - 4-byte big-endian page number which is the left child pointer,
 /// - rowid - a varint which is the integer key.
+///
 /// Interior pages of table b-trees have no payload and so there is never any payload to spill.
 /// https://www.sqlite.org/fileformat.html#b_tree_pages
 #[derive(Debug)]
@@ -1,6 +1,10 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 
+use crate::access::buffer_pool::BufferPool;
+use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
+use crate::model::page_header::PageType;
+use crate::model::payload::read_payload;
 use crate::varint::decode_varint;
 
 /*
@@ -18,8 +22,55 @@ pub struct LeafTableCell {
 }
 
 impl LeafTableCell {
-    // not handling payload overflow yet (payload bigger than 1 page)
+    /// Parses a cell whose payload fits entirely on the page (no overflow).
     pub fn parse(stream: &[u8]) -> Result<Self> {
+        let (payload_size, rowid, offset) = Self::parse_header(stream)?;
+        if stream.len() < offset + payload_size {
+            bail!("Cell payload of {payload_size} bytes does not fit on the page, use parse_with_overflow")
+        }
+        let payload = DataRecord::parse_from(rowid, &stream[offset..offset + payload_size]);
+
+        Ok(Self { rowid, payload })
+    }
+
+    /// Parses a cell on a page with usable_size bytes. If the payload is too large
+    /// for the page, the overflow page chain is followed through buffer_pool
+    /// to rebuild the whole record.
+    pub fn parse_with_overflow(
+        stream: &[u8],
+        usable_size: usize,
+        buffer_pool: &mut BufferPool,
+    ) -> Result<Self> {
+        let (payload_size, rowid, offset) = Self::parse_header(stream)?;
+        let payload = read_payload(
+            &stream[offset..],
+            payload_size,
+            usable_size,
+            &PageType::LeafTable,
+            buffer_pool,
+        )?;
+        let payload = DataRecord::parse_from(rowid, &payload);
+
+        Ok(Self { rowid, payload })
+    }
+
+    /// Returns the record of the table row.
+    ///
+    /// rowid_alias: index of the INTEGER PRIMARY KEY column of the table. This column
+    /// is stored as NULL in the record, its value is the rowid of the cell.
+    pub fn into_record(self, rowid_alias: Option<usize>) -> DataRecord {
+        let mut record = self.payload;
+        if let Some(index) = rowid_alias {
+            if record.values.len() <= index {
+                record.values.resize(index + 1, ColumnValue::Null);
+            }
+            record.values[index] = ColumnValue::int64(self.rowid);
+        }
+        record
+    }
+
+    /// Returns (payload_size, rowid, bytes_read) from the cell header.
+    fn parse_header(stream: &[u8]) -> Result<(usize, i64, usize)> {
         let mut offset = 0;
         // payload size first
         let (payload_size, bytes_read) = decode_varint(stream);
@@ -28,18 +79,19 @@ impl LeafTableCell {
         // then rowid
         let (rowid, bytes_read) = decode_varint(&stream[offset..]);
         offset += bytes_read;
-        // then payload with Record format, not handling overflow
-        let payload =
-            DataRecord::parse_from(rowid.unsigned_abs(), &stream[offset..offset + payload_size]);
 
-        Ok(Self { rowid, payload })
+        Ok((payload_size, rowid, offset))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use crate::model::cell_table_leaf::LeafTableCell;
     use crate::model::column_value::ColumnValue;
+    use crate::model::database::Database;
+    use crate::model::page_id::PageId;
     use log::info;
 
     #[test]
@@ -78,6 +130,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_into_record_rowid_alias() {
+        // apples(id integer primary key autoincrement, name text, color text), rowid 2
+        let cell_bytes: Vec<u8> = vec![11, 2, 4, 0, 21, 19, 70, 117, 106, 105, 82, 101, 100];
+        let cell = LeafTableCell::parse(cell_bytes.as_slice()).unwrap();
+        let record = cell.into_record(Some(0));
+        assert_eq!(record.values[0], ColumnValue::int64(2));
+        assert_eq!(record.values[1], ColumnValue::Text("Fuji".to_owned()));
+
+        // without an alias the record is left as stored
+        let cell = LeafTableCell::parse(cell_bytes.as_slice()).unwrap();
+        assert_eq!(cell.into_record(None).values[0], ColumnValue::Null);
+    }
+
+    #[test]
+    fn test_parse_leaf_table_cell_with_overflow() {
+        // overflow.db has page size 512 and table docs(id integer primary key, body text, data blob)
+        // row 2 has a 1508-byte json body and a 768-byte blob spilling to overflow pages.
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/overflow.db");
+        let mut db = Database::new(db_path.to_str().unwrap()).unwrap();
+        let usable_size = db.db_meta.db_header.usable_size();
+        let page = db.buffer_pool.get_page(PageId::new(2)).unwrap();
+        let mut page = page.borrow_mut();
+
+        let mut cells = vec![];
+        for index in 0..page.get_number_of_cells() as usize {
+            let cell_ptr = page.get_cell_ptr(index);
+            let cell = LeafTableCell::parse_with_overflow(
+                &page.data[cell_ptr..],
+                usable_size,
+                &mut db.buffer_pool,
+            )
+            .unwrap();
+            cells.push(cell);
+        }
+
+        assert_eq!(cells.len(), 3);
+        let big = &cells[1];
+        assert_eq!(big.rowid, 2);
+        let body = String::try_from(big.payload.value_at_index(1)).unwrap();
+        assert_eq!(body.len(), 1508);
+        assert!(body.starts_with("{\"k\":\"abcdefghij"));
+        assert!(body.ends_with("abcdefghij\"}"));
+        let blob: Vec<u8> = (0..=255u8).cycle().take(768).collect();
+        assert_eq!(*big.payload.value_at_index(2), ColumnValue::Blob(blob));
+        // the row after the spilled one is not affected
+        assert_eq!(cells[2].rowid, 3);
+        assert_eq!(
+            *cells[2].payload.value_at_index(1),
+            ColumnValue::Text("tail".to_owned())
+        );
+
+        // parsing without following the chain reports an error instead of reading garbage
+        let cell_ptr = page.get_cell_ptr(1);
+        assert!(LeafTableCell::parse(&page.data[cell_ptr..]).is_err());
+    }
+
     #[test]
     fn test_parse_sqlite_schema_sample_db() {
         let cell_bytes: Vec<u8> = vec![
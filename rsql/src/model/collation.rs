@@ -0,0 +1,61 @@
+use std::cmp::Ordering;
+
+use anyhow::{bail, Result};
+
+/// Collating sequences built into SQLite, used to compare TEXT values.
+/// https://www.sqlite.org/datatype3.html#collating_sequences
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Collation {
+    /// Compares string data using memcmp(), regardless of text encoding.
+    #[default]
+    Binary,
+    /// Like binary, except the 26 upper case characters of ASCII are folded
+    /// to their lower case equivalents before the comparison is performed.
+    NoCase,
+    /// Like binary, except that trailing space characters are ignored.
+    RTrim,
+}
+
+impl Collation {
+    /// Looks up a collation by name, case-insensitive like in SQLite.
+    pub fn from_name(name: &str) -> Result<Self> {
+        Ok(match name.to_ascii_uppercase().as_str() {
+            "BINARY" => Collation::Binary,
+            "NOCASE" => Collation::NoCase,
+            "RTRIM" => Collation::RTrim,
+            _ => bail!("no such collation sequence: {name}"),
+        })
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        match self {
+            Collation::Binary => a.as_bytes().cmp(b.as_bytes()),
+            Collation::NoCase => a
+                .bytes()
+                .map(|byte| byte.to_ascii_lowercase())
+                .cmp(b.bytes().map(|byte| byte.to_ascii_lowercase())),
+            Collation::RTrim => a.trim_end_matches(' ').cmp(b.trim_end_matches(' ')),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collation_compare() {
+        assert_eq!(Collation::Binary.compare("B", "a"), Ordering::Less);
+        assert_eq!(Collation::NoCase.compare("B", "a"), Ordering::Greater);
+        assert_eq!(Collation::NoCase.compare("Apple", "aPPLE"), Ordering::Equal);
+        assert_eq!(Collation::RTrim.compare("abc  ", "abc"), Ordering::Equal);
+        assert_eq!(Collation::Binary.compare("abc  ", "abc"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_collation_from_name() {
+        assert_eq!(Collation::from_name("nocase").unwrap(), Collation::NoCase);
+        assert_eq!(Collation::from_name("RTRIM").unwrap(), Collation::RTrim);
+        assert!(Collation::from_name("unicode").is_err());
+    }
+}
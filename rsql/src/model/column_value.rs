@@ -13,8 +13,12 @@
 /// Any column in an SQLite version 3 database, except an INTEGER PRIMARY KEY column,
 /// may be used to store a value of any storage class.
 use anyhow::{bail, Result};
+use std::cmp::Ordering;
 use std::fmt;
 
+use crate::model::collation::Collation;
+use crate::model::conversion::format_real;
+
 // TODO optimize - copy for string and bytes values are costly, ok for the rest (int, float,...)
 #[derive(Debug, PartialEq, Clone)]
 pub enum ColumnValue {
@@ -46,10 +50,36 @@ impl ColumnValue {
     pub fn int32(int: i32) -> ColumnValue {
         ColumnValue::Int32(int.to_be_bytes())
     }
+    pub fn int64(int: i64) -> ColumnValue {
+        ColumnValue::Int64(int.to_be_bytes())
+    }
     pub fn int8(int: i8) -> ColumnValue {
         ColumnValue::Int8(int.to_be_bytes())
     }
 
+    /// Integer value with the smallest serial type holding int, like sqlite3 writes
+    /// it in a record. Schema format 4 has the serial types 8 and 9 for 0 and 1.
+    pub fn integer(int: i64, schema_format: u32) -> ColumnValue {
+        if schema_format >= 4 && (int == 0 || int == 1) {
+            return if int == 0 {
+                ColumnValue::Zero
+            } else {
+                ColumnValue::One
+            };
+        }
+        let bytes = int.to_be_bytes();
+        // magnitude of a negative value is the one of its complement
+        let magnitude = if int < 0 { !int } else { int };
+        match magnitude {
+            0..=0x7f => ColumnValue::Int8([bytes[7]]),
+            0x80..=0x7fff => ColumnValue::Int16([bytes[6], bytes[7]]),
+            0x8000..=0x7f_ffff => ColumnValue::Int24([bytes[5], bytes[6], bytes[7]]),
+            0x80_0000..=0x7fff_ffff => ColumnValue::Int32(bytes[4..].try_into().unwrap()),
+            0x8000_0000..=0x7fff_ffff_ffff => ColumnValue::Int48(bytes[2..].try_into().unwrap()),
+            _ => ColumnValue::Int64(bytes),
+        }
+    }
+
     /// Parses column value from bytes. Returns Result<col_value, value_size>
     /// https://www.sqlite.org/fileformat.html#record_format
     pub fn parse(serial_type: i64, stream: &[u8]) -> Result<(ColumnValue, usize)> {
@@ -83,6 +113,117 @@ impl ColumnValue {
             n => panic!("Invalid serial type: {}", n),
         })
     }
+
+    /// Serial type of the value in a record, the inverse of parse.
+    pub fn serial_type(&self) -> i64 {
+        match self {
+            ColumnValue::Null => 0,
+            ColumnValue::Int8(_) => 1,
+            ColumnValue::Int16(_) => 2,
+            ColumnValue::Int24(_) => 3,
+            ColumnValue::Int32(_) => 4,
+            ColumnValue::Int48(_) => 5,
+            ColumnValue::Int64(_) => 6,
+            ColumnValue::Float64(_) => 7,
+            ColumnValue::Zero => 8,
+            ColumnValue::One => 9,
+            ColumnValue::Blob(bytes) => bytes.len() as i64 * 2 + 12,
+            ColumnValue::Text(text) => text.len() as i64 * 2 + 13,
+        }
+    }
+
+    /// Bytes of the value in the content of a record, empty for NULL, 0 and 1.
+    pub fn content(&self) -> &[u8] {
+        match self {
+            ColumnValue::Null | ColumnValue::Zero | ColumnValue::One => &[],
+            ColumnValue::Int8(arr) => arr,
+            ColumnValue::Int16(arr) => arr,
+            ColumnValue::Int24(arr) => arr,
+            ColumnValue::Int32(arr) => arr,
+            ColumnValue::Int48(arr) => arr,
+            ColumnValue::Int64(arr) | ColumnValue::Float64(arr) => arr,
+            ColumnValue::Blob(bytes) => bytes,
+            ColumnValue::Text(text) => text.as_bytes(),
+        }
+    }
+
+    /// Returns the value of an INTEGER storage class value.
+    pub fn as_i64(&self) -> Option<i64> {
+        Some(match self {
+            ColumnValue::Int8(arr) => i8::from_be_bytes(*arr).into(),
+            ColumnValue::Int16(arr) => i16::from_be_bytes(*arr).into(),
+            // sign-extend 24 and 48-bit big-endian integers
+            ColumnValue::Int24(arr) => i32::from_be_bytes([arr[0], arr[1], arr[2], 0]) as i64 >> 8,
+            ColumnValue::Int32(arr) => i32::from_be_bytes(*arr).into(),
+            ColumnValue::Int48(arr) => {
+                i64::from_be_bytes([arr[0], arr[1], arr[2], arr[3], arr[4], arr[5], 0, 0]) >> 16
+            }
+            ColumnValue::Int64(arr) => i64::from_be_bytes(*arr),
+            ColumnValue::Zero => 0,
+            ColumnValue::One => 1,
+            _ => return None,
+        })
+    }
+
+    /// Returns the value of a REAL storage class value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            ColumnValue::Float64(arr) => Some(f64::from_be_bytes(*arr)),
+            _ => None,
+        }
+    }
+
+    /// Compares two values following SQLite sort order
+    /// https://www.sqlite.org/datatype3.html#sort_order
+    ///   - NULL values are less than any other value.
+    ///   - INTEGER and REAL values are less than TEXT or BLOB values,
+    ///     they are compared numerically.
+    ///   - TEXT values are less than BLOB values, they are compared
+    ///     with the collating sequence.
+    ///   - BLOB values are compared with memcmp().
+    pub fn compare(&self, other: &ColumnValue, collation: &Collation) -> Ordering {
+        match (self, other) {
+            (ColumnValue::Text(a), ColumnValue::Text(b)) => collation.compare(a, b),
+            (ColumnValue::Blob(a), ColumnValue::Blob(b)) => a.cmp(b),
+            (a, b) if a.sort_class() != b.sort_class() => a.sort_class().cmp(&b.sort_class()),
+            (ColumnValue::Null, ColumnValue::Null) => Ordering::Equal,
+            (a, b) => match (a.as_i64(), b.as_i64()) {
+                (Some(a), Some(b)) => a.cmp(&b),
+                (Some(a), None) => compare_int_real(a, b.as_f64().unwrap_or_default()),
+                (None, Some(b)) => compare_int_real(b, a.as_f64().unwrap_or_default()).reverse(),
+                (None, None) => a
+                    .as_f64()
+                    .unwrap_or_default()
+                    .total_cmp(&b.as_f64().unwrap_or_default()),
+            },
+        }
+    }
+
+    // Rank of the storage class in SQLite sort order: NULL < numeric < TEXT < BLOB.
+    fn sort_class(&self) -> u8 {
+        match self {
+            ColumnValue::Null => 0,
+            ColumnValue::Text(_) => 2,
+            ColumnValue::Blob(_) => 3,
+            _ => 1,
+        }
+    }
+}
+
+/// Compares an integer with a real without losing precision on large integers.
+/// Equivalent to sqlite3IntFloatCompare.
+fn compare_int_real(int: i64, real: f64) -> Ordering {
+    if real < -9223372036854775808.0 {
+        return Ordering::Greater;
+    }
+    if real >= 9223372036854775808.0 {
+        return Ordering::Less;
+    }
+    let truncated = real as i64;
+    match int.cmp(&truncated) {
+        Ordering::Equal => (int as f64).partial_cmp(&real).unwrap_or(Ordering::Equal),
+        ordering => ordering,
+    }
 }
 
 impl fmt::Display for ColumnValue {
@@ -103,7 +244,8 @@ impl fmt::Display for ColumnValue {
                 )
             }
             ColumnValue::Int64(arr) => write!(f, "{}", i64::from_be_bytes(*arr)),
-            ColumnValue::Float64(arr) => write!(f, "{}", f64::from_be_bytes(*arr)),
+            // like sqlite3, reals are shown with their text conversion
+            ColumnValue::Float64(arr) => write!(f, "{}", format_real(f64::from_be_bytes(*arr))),
             ColumnValue::Zero => write!(f, "0"),
             ColumnValue::One => write!(f, "1"),
             ColumnValue::Text(s) => write!(f, "{}", s),
@@ -195,6 +337,57 @@ mod tests {
         assert_eq!(s, "hello".to_owned());
     }
 
+    #[test]
+    fn test_serial_type_and_content_round_trip() {
+        for value in [
+            ColumnValue::Null,
+            ColumnValue::One,
+            ColumnValue::int32(-5),
+            ColumnValue::Int48([0, 0, 1, 0, 0, 0]),
+            ColumnValue::real(2.5),
+            ColumnValue::Blob(vec![0, 1]),
+            ColumnValue::Text("hello".to_owned()),
+        ] {
+            let (parsed, size) = ColumnValue::parse(value.serial_type(), value.content()).unwrap();
+            assert_eq!(parsed, value);
+            assert_eq!(size, value.content().len());
+        }
+    }
+
+    #[test]
+    fn test_integer_smallest_serial_type() {
+        let serial_types = |schema_format| {
+            [
+                0,
+                1,
+                -1,
+                127,
+                -128,
+                128,
+                -32769,
+                8388607,
+                -8388609,
+                1 << 31,
+                1 << 47,
+            ]
+            .map(|int| ColumnValue::integer(int, schema_format).serial_type())
+        };
+        assert_eq!(serial_types(4), [8, 9, 1, 1, 1, 2, 3, 3, 4, 5, 6]);
+        assert_eq!(serial_types(1), [1, 1, 1, 1, 1, 2, 3, 3, 4, 5, 6]);
+        for int in [
+            0,
+            -1,
+            200,
+            -8388608,
+            i32::MIN.into(),
+            1 << 40,
+            i64::MIN,
+            i64::MAX,
+        ] {
+            assert_eq!(ColumnValue::integer(int, 4).as_i64(), Some(int));
+        }
+    }
+
     #[test]
     fn test_i32_try_from_col_value() {
         let col_value = ColumnValue::int32(2_000_000_000);
@@ -204,6 +397,51 @@ mod tests {
         assert_eq!(i32::try_from(&col_value).unwrap(), 120);
     }
 
+    #[test]
+    fn test_as_i64() {
+        assert_eq!(ColumnValue::Int8([0xFF]).as_i64(), Some(-1));
+        assert_eq!(ColumnValue::Int24([0xFF, 0xFF, 0xFE]).as_i64(), Some(-2));
+        assert_eq!(ColumnValue::Int24([0x00, 0x01, 0x00]).as_i64(), Some(256));
+        assert_eq!(
+            ColumnValue::Int48([0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFD]).as_i64(),
+            Some(-3)
+        );
+        assert_eq!(ColumnValue::One.as_i64(), Some(1));
+        assert_eq!(ColumnValue::Float64(1.0f64.to_be_bytes()).as_i64(), None);
+        assert_eq!(ColumnValue::Text("1".to_owned()).as_i64(), None);
+    }
+
+    #[test]
+    fn test_compare_sort_order() {
+        let binary = Collation::Binary;
+        let null = ColumnValue::Null;
+        let int = ColumnValue::int8(5);
+        let real = ColumnValue::Float64(5.5f64.to_be_bytes());
+        let text = ColumnValue::Text("a".to_owned());
+        let blob = ColumnValue::Blob(vec![0]);
+
+        assert_eq!(null.compare(&int, &binary), Ordering::Less);
+        assert_eq!(int.compare(&real, &binary), Ordering::Less);
+        assert_eq!(real.compare(&int, &binary), Ordering::Greater);
+        assert_eq!(real.compare(&text, &binary), Ordering::Less);
+        assert_eq!(text.compare(&blob, &binary), Ordering::Less);
+        assert_eq!(null.compare(&ColumnValue::Null, &binary), Ordering::Equal);
+        // integer and real are compared numerically
+        assert_eq!(
+            ColumnValue::One.compare(&ColumnValue::Float64(1.0f64.to_be_bytes()), &binary),
+            Ordering::Equal
+        );
+        assert_eq!(
+            ColumnValue::Int64(i64::MAX.to_be_bytes())
+                .compare(&ColumnValue::Float64(9.2e18f64.to_be_bytes()), &binary),
+            Ordering::Greater
+        );
+        // text is compared with the collation
+        let upper = ColumnValue::Text("B".to_owned());
+        assert_eq!(upper.compare(&text, &binary), Ordering::Less);
+        assert_eq!(upper.compare(&text, &Collation::NoCase), Ordering::Greater);
+    }
+
     #[test]
     fn test_display_null() {
         let value = ColumnValue::Null;
@@ -249,7 +487,16 @@ mod tests {
     #[test]
     fn test_display_float64() {
         let value = ColumnValue::Float64([0x40, 0x09, 0x21, 0xFB, 0x54, 0x44, 0x2E, 0x8F]);
-        assert_eq!(value.to_string(), "3.1415926535899596");
+        // 15 significant digits like sqlite3
+        assert_eq!(value.to_string(), "3.14159265358996");
+    }
+
+    #[test]
+    fn test_display_float64_integral() {
+        assert_eq!(ColumnValue::real(66.0).to_string(), "66.0");
+        assert_eq!(ColumnValue::real(-2.0).to_string(), "-2.0");
+        assert_eq!(ColumnValue::real(1e20).to_string(), "1.0e+20");
+        assert_eq!(ColumnValue::real(0.1 + 0.2).to_string(), "0.3");
     }
 
     #[test]
@@ -0,0 +1,418 @@
+/// Conversions between the storage classes of values, as SQLite does them
+/// in expressions, comparisons and CAST.
+/// https://www.sqlite.org/datatype3.html#type_conversions_prior_to_comparison
+/// https://www.sqlite.org/lang_expr.html#castexpr
+use crate::model::affinity::Affinity;
+use crate::model::column_value::ColumnValue;
+
+/// A numeric value: operand or result of arithmetic operators.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    Real(f64),
+}
+
+impl Number {
+    pub fn as_f64(&self) -> f64 {
+        match *self {
+            Number::Integer(int) => int as f64,
+            Number::Real(real) => real,
+        }
+    }
+
+    /// Converts a real without fractional part to an integer (NUMERIC affinity).
+    fn integral(self) -> Number {
+        match self {
+            // i64::MAX as f64 rounds up to 2^63 which does not fit in i64
+            Number::Real(real)
+                if real.fract() == 0.0 && real >= i64::MIN as f64 && real < i64::MAX as f64 =>
+            {
+                Number::Integer(real as i64)
+            }
+            number => number,
+        }
+    }
+}
+
+impl From<Number> for ColumnValue {
+    fn from(number: Number) -> Self {
+        match number {
+            Number::Integer(int) => ColumnValue::int64(int),
+            Number::Real(real) => ColumnValue::real(real),
+        }
+    }
+}
+
+impl ColumnValue {
+    pub fn real(real: f64) -> ColumnValue {
+        ColumnValue::Float64(real.to_be_bytes())
+    }
+
+    /// Booleans are integers in SQLite: 1 for true and 0 for false.
+    pub fn from_bool(value: bool) -> ColumnValue {
+        if value {
+            ColumnValue::One
+        } else {
+            ColumnValue::Zero
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        *self == ColumnValue::Null
+    }
+
+    /// Returns the numeric value used by arithmetic operators, None for NULL.
+    /// TEXT and BLOB are converted from their longest numeric prefix, 0 if there is none:
+    /// '12abc' + 1 is 13.
+    pub fn to_number(&self) -> Option<Number> {
+        match self {
+            ColumnValue::Null => None,
+            ColumnValue::Float64(_) => self.as_f64().map(Number::Real),
+            ColumnValue::Text(text) => Some(number_prefix(text)),
+            ColumnValue::Blob(bytes) => Some(number_prefix(&String::from_utf8_lossy(bytes))),
+            int => int.as_i64().map(Number::Integer),
+        }
+    }
+
+    /// Truth value of the value in a boolean context (WHERE, AND, OR, NOT, CASE WHEN):
+    /// a value is true if its numeric value is not zero, NULL is unknown (None).
+    pub fn is_true(&self) -> Option<bool> {
+        self.to_number().map(|number| number.as_f64() != 0.0)
+    }
+
+    /// Returns the text representation of the value, None for NULL.
+    pub fn to_text(&self) -> Option<String> {
+        match self {
+            ColumnValue::Null => None,
+            ColumnValue::Text(text) => Some(text.clone()),
+            ColumnValue::Blob(bytes) => Some(String::from_utf8_lossy(bytes).to_string()),
+            ColumnValue::Float64(_) => self.as_f64().map(format_real),
+            int => int.as_i64().map(|int| int.to_string()),
+        }
+    }
+
+    /// Applies the affinity to the value before a comparison:
+    /// - INTEGER, REAL and NUMERIC: TEXT that is a well-formed number is converted to it.
+    /// - TEXT: INTEGER and REAL values are converted to TEXT.
+    /// - BLOB: no conversion.
+    pub fn apply_affinity(self, affinity: Affinity) -> ColumnValue {
+        match (affinity, &self) {
+            (Affinity::Integer | Affinity::Numeric, ColumnValue::Text(text)) => {
+                match parse_number(text) {
+                    Some(number) => number.integral().into(),
+                    None => self,
+                }
+            }
+            (Affinity::Real, ColumnValue::Text(text)) => match parse_number(text) {
+                Some(number) => ColumnValue::real(number.as_f64()),
+                None => self,
+            },
+            (Affinity::Text, ColumnValue::Text(_) | ColumnValue::Blob(_) | ColumnValue::Null) => {
+                self
+            }
+            (Affinity::Text, _) => ColumnValue::Text(self.to_text().unwrap_or_default()),
+            _ => self,
+        }
+    }
+
+    /// Applies the affinity of a column to a value stored in it: like apply_affinity,
+    /// and a REAL without fractional part is stored as an INTEGER in an INTEGER or
+    /// NUMERIC column.
+    /// https://www.sqlite.org/datatype3.html#type_affinity
+    pub fn store_affinity(self, affinity: Affinity) -> ColumnValue {
+        match (affinity, self.apply_affinity(affinity)) {
+            (Affinity::Integer | Affinity::Numeric, value @ ColumnValue::Float64(_)) => {
+                match value.to_number() {
+                    Some(number) => number.integral().into(),
+                    None => value,
+                }
+            }
+            (_, value) => value,
+        }
+    }
+
+    /// Applies the affinity of a column to a value read from it: the reals without
+    /// fractional part of a REAL column may be stored as integers, they are read as reals.
+    pub fn load_affinity(self, affinity: Affinity) -> ColumnValue {
+        match (affinity, self.as_i64()) {
+            (Affinity::Real, Some(int)) => ColumnValue::real(int as f64),
+            _ => self,
+        }
+    }
+
+    /// CAST(value AS type), the type is given by its affinity. NULL stays NULL.
+    pub fn cast(&self, affinity: Affinity) -> ColumnValue {
+        if self.is_null() {
+            return ColumnValue::Null;
+        }
+        match affinity {
+            Affinity::Integer => ColumnValue::int64(match self {
+                ColumnValue::Text(_) | ColumnValue::Blob(_) => {
+                    integer_prefix(&self.to_text().unwrap_or_default())
+                }
+                // reals are truncated toward zero, saturating at the bounds of i64
+                value => match value.to_number() {
+                    Some(Number::Real(real)) => real as i64,
+                    Some(Number::Integer(int)) => int,
+                    None => 0,
+                },
+            }),
+            Affinity::Real => ColumnValue::real(self.to_number().map_or(0.0, |n| n.as_f64())),
+            Affinity::Numeric => match self {
+                ColumnValue::Text(_) | ColumnValue::Blob(_) => self
+                    .to_number()
+                    .map_or(ColumnValue::Null, |n| n.integral().into()),
+                value => value.clone(),
+            },
+            Affinity::Text => ColumnValue::Text(self.to_text().unwrap_or_default()),
+            Affinity::Blob => match self {
+                ColumnValue::Blob(_) => self.clone(),
+                value => ColumnValue::Blob(value.to_text().unwrap_or_default().into_bytes()),
+            },
+        }
+    }
+}
+
+/// Formats a real like SQLite does when converting it to text ("%!.15g"):
+/// 15 significant digits, always with a decimal point.
+pub fn format_real(real: f64) -> String {
+    if real.is_nan() {
+        return "NaN".to_owned();
+    }
+    if real.is_infinite() {
+        return if real > 0.0 { "Inf" } else { "-Inf" }.to_owned();
+    }
+    let scientific = format!("{real:.14e}");
+    let (mantissa, exponent) = scientific.split_once('e').unwrap_or((&scientific, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or_default();
+    if real != 0.0 && !(-4..15).contains(&exponent) {
+        let mantissa = with_decimal_point(mantissa.trim_end_matches('0'));
+        let sign = if exponent < 0 { '-' } else { '+' };
+        return format!("{mantissa}e{sign}{:02}", exponent.abs());
+    }
+    let decimals = (14 - exponent).max(0) as usize;
+    let fixed = format!("{real:.decimals$}");
+    with_decimal_point(if fixed.contains('.') {
+        fixed.trim_end_matches('0')
+    } else {
+        &fixed
+    })
+}
+
+fn with_decimal_point(number: &str) -> String {
+    match number.strip_suffix('.') {
+        Some(integer) => format!("{integer}.0"),
+        None if !number.contains('.') => format!("{number}.0"),
+        None => number.to_owned(),
+    }
+}
+
+/// Parses text that is a well-formed number, ignoring leading and trailing spaces.
+pub fn parse_number(text: &str) -> Option<Number> {
+    let (number, len) = scan_number(text)?;
+    text[len..].trim().is_empty().then_some(number)
+}
+
+/// Parses the longest numeric prefix of text, 0 if there is none.
+pub fn number_prefix(text: &str) -> Number {
+    scan_number(text).map_or(Number::Integer(0), |(number, _)| number)
+}
+
+// Scans [spaces][+|-]digits[.digits][(e|E)[+|-]digits] at the start of text.
+// Returns the number and the length of the scanned text, None without digits.
+fn scan_number(text: &str) -> Option<(Number, usize)> {
+    let bytes = text.as_bytes();
+    let count_digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+
+    let start = text.len() - text.trim_start().len();
+    let mut end = start;
+    if matches!(bytes.get(end), Some(b'+' | b'-')) {
+        end += 1;
+    }
+    let integer_digits = count_digits(end);
+    end += integer_digits;
+    let mut is_integer = true;
+    if bytes.get(end) == Some(&b'.') {
+        let fraction_digits = count_digits(end + 1);
+        if integer_digits == 0 && fraction_digits == 0 {
+            return None;
+        }
+        end += 1 + fraction_digits;
+        is_integer = false;
+    } else if integer_digits == 0 {
+        return None;
+    }
+    let mut has_exponent = false;
+    if matches!(bytes.get(end), Some(b'e' | b'E')) {
+        let mut exponent_end = end + 1;
+        if matches!(bytes.get(exponent_end), Some(b'+' | b'-')) {
+            exponent_end += 1;
+        }
+        let exponent_digits = count_digits(exponent_end);
+        if exponent_digits > 0 {
+            end = exponent_end + exponent_digits;
+            has_exponent = true;
+        }
+    }
+
+    let literal = &text[start..end];
+    let number = match literal.parse::<i64>() {
+        Ok(int) if is_integer && !has_exponent => Number::Integer(int),
+        // integers too large for i64 are reals
+        _ => Number::Real(literal.parse::<f64>().ok()?),
+    };
+    Some((number, end))
+}
+
+// Parses the integer prefix of text for CAST AS INTEGER: '3.9' is 3, '1e3' is 1.
+// Integers too large for i64 saturate.
+fn integer_prefix(text: &str) -> i64 {
+    let text = text.trim_start();
+    let (negative, digits) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let digits = &digits[..digits.bytes().take_while(u8::is_ascii_digit).count()];
+    if digits.is_empty() {
+        return 0;
+    }
+    let magnitude = format!("{}{digits}", if negative { "-" } else { "" });
+    magnitude
+        .parse()
+        .unwrap_or(if negative { i64::MIN } else { i64::MAX })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_number() {
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        assert_eq!(text("12abc").to_number(), Some(Number::Integer(12)));
+        assert_eq!(text(" -3.5e2x").to_number(), Some(Number::Real(-350.0)));
+        assert_eq!(text("abc").to_number(), Some(Number::Integer(0)));
+        assert_eq!(text(".5").to_number(), Some(Number::Real(0.5)));
+        assert_eq!(text("1e").to_number(), Some(Number::Integer(1)));
+        assert_eq!(
+            text("9223372036854775808").to_number(),
+            Some(Number::Real(9223372036854775808.0))
+        );
+        assert_eq!(ColumnValue::Null.to_number(), None);
+        assert_eq!(ColumnValue::Zero.is_true(), Some(false));
+        assert_eq!(text("0.1").is_true(), Some(true));
+    }
+
+    #[test]
+    fn test_apply_affinity() {
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        assert_eq!(
+            text(" 42 ").apply_affinity(Affinity::Integer),
+            ColumnValue::int64(42)
+        );
+        assert_eq!(
+            text("3.0").apply_affinity(Affinity::Numeric),
+            ColumnValue::int64(3)
+        );
+        assert_eq!(text("42x").apply_affinity(Affinity::Numeric), text("42x"));
+        assert_eq!(
+            ColumnValue::int8(7).apply_affinity(Affinity::Text),
+            text("7")
+        );
+        assert_eq!(
+            ColumnValue::int8(7).apply_affinity(Affinity::Blob),
+            ColumnValue::int8(7)
+        );
+    }
+
+    #[test]
+    fn test_store_affinity() {
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        assert_eq!(
+            ColumnValue::real(2.0).store_affinity(Affinity::Integer),
+            ColumnValue::int64(2)
+        );
+        assert_eq!(
+            text("1e3").store_affinity(Affinity::Numeric),
+            ColumnValue::int64(1000)
+        );
+        assert_eq!(
+            ColumnValue::real(2.5).store_affinity(Affinity::Integer),
+            ColumnValue::real(2.5)
+        );
+        assert_eq!(
+            ColumnValue::real(2.0).store_affinity(Affinity::Real),
+            ColumnValue::real(2.0)
+        );
+        assert_eq!(
+            ColumnValue::real(2.0).store_affinity(Affinity::Text),
+            text("2.0")
+        );
+    }
+
+    #[test]
+    fn test_load_affinity() {
+        assert_eq!(
+            ColumnValue::int8(4).load_affinity(Affinity::Real),
+            ColumnValue::real(4.0)
+        );
+        assert_eq!(
+            ColumnValue::int8(4).load_affinity(Affinity::Numeric),
+            ColumnValue::int8(4)
+        );
+        let text = ColumnValue::Text("4".to_owned());
+        assert_eq!(text.clone().load_affinity(Affinity::Real), text);
+    }
+
+    #[test]
+    fn test_cast() {
+        // results of sqlite3 3.40
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        assert_eq!(text("1e3").cast(Affinity::Integer), ColumnValue::int64(1));
+        assert_eq!(
+            text("  12 ").cast(Affinity::Integer),
+            ColumnValue::int64(12)
+        );
+        assert_eq!(
+            ColumnValue::real(1e20).cast(Affinity::Integer),
+            ColumnValue::int64(i64::MAX)
+        );
+        assert_eq!(
+            ColumnValue::real(-2.7).cast(Affinity::Integer),
+            ColumnValue::int64(-2)
+        );
+        assert_eq!(text("3.9x").cast(Affinity::Numeric), ColumnValue::real(3.9));
+        assert_eq!(
+            text("1e3").cast(Affinity::Numeric),
+            ColumnValue::int64(1000)
+        );
+        assert_eq!(ColumnValue::real(4.0).cast(Affinity::Text), text("4.0"));
+        assert_eq!(
+            ColumnValue::Blob(b"12".to_vec()).cast(Affinity::Integer),
+            ColumnValue::int64(12)
+        );
+        assert_eq!(
+            ColumnValue::One.cast(Affinity::Blob),
+            ColumnValue::Blob(b"1".to_vec())
+        );
+        assert_eq!(ColumnValue::Null.cast(Affinity::Text), ColumnValue::Null);
+    }
+
+    #[test]
+    fn test_format_real() {
+        assert_eq!(format_real(100.0), "100.0");
+        assert_eq!(format_real(0.1), "0.1");
+        assert_eq!(format_real(10.0 / 3.0), "3.33333333333333");
+        assert_eq!(format_real(1.5e15), "1.5e+15");
+        assert_eq!(format_real(1e20), "1.0e+20");
+        assert_eq!(format_real(1.25e-5), "1.25e-05");
+        assert_eq!(format_real(-0.5), "-0.5");
+        assert_eq!(format_real(0.0), "0.0");
+    }
+}
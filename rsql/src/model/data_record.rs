@@ -1,12 +1,14 @@
+use std::borrow::Cow;
+
 use crate::model::column_value::ColumnValue;
-use crate::varint::decode_varint;
+use crate::varint::{decode_varint, encode_varint, varint_len};
 
 /// DataRecord needs a lifetime parameter 'a to tell the compiler that Vec values
 /// has the same lifetime a as owning struct DataRecord
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DataRecord {
     pub values: Vec<ColumnValue>,
-    pub rowid: Option<u64>,
+    pub rowid: Option<i64>,
 }
 
 impl DataRecord {
@@ -15,8 +17,8 @@ impl DataRecord {
     /// # Arguments
     ///
     /// * `payload` - a shared slice of bytes (u8) because we don't need to modify it.
-    ///     A shared slice allows multiple reader access, but no writer.
-    pub fn parse_from(rowid: u64, payload: &[u8]) -> DataRecord {
+    ///   A shared slice allows multiple reader access, but no writer.
+    pub fn parse_from(rowid: i64, payload: &[u8]) -> DataRecord {
         let (header_size, mut header_offset) = decode_varint(payload);
         let mut content_offset = header_size as usize;
         let mut col_values = vec![];
@@ -34,6 +36,52 @@ impl DataRecord {
         }
     }
 
+    /// parse_index_key parses the key record of an index B-Tree cell: the indexed
+    /// column values followed by the rowid of the table row, which is also set as
+    /// the rowid of the returned record.
+    pub fn parse_index_key(payload: &[u8]) -> DataRecord {
+        let mut record = DataRecord::parse_from(0, payload);
+        record.rowid = record.values.last().and_then(ColumnValue::as_i64);
+        record
+    }
+
+    /// serialize writes the record format read by parse_from: the header size, the
+    /// serial type of each value then the content of each value.
+    /// https://www.sqlite.org/fileformat.html#record_format
+    ///
+    /// Integers are written with their smallest serial type, including the types 8 and
+    /// 9 for 0 and 1 when schema_format (from the database header) is 4, so the payload
+    /// is the one written by sqlite3 for the same values. The rowid is not part of it.
+    pub fn serialize(&self, schema_format: u32) -> Vec<u8> {
+        let values: Vec<Cow<ColumnValue>> = self
+            .values
+            .iter()
+            .map(|value| match value.as_i64() {
+                Some(int) => Cow::Owned(ColumnValue::integer(int, schema_format)),
+                None => Cow::Borrowed(value),
+            })
+            .collect();
+        let serial_types: Vec<i64> = values.iter().map(|value| value.serial_type()).collect();
+        // the header size counts its own varint, which is longer for larger headers
+        let types_size: usize = serial_types.iter().map(|&t| varint_len(t)).sum();
+        let mut size_len = 1;
+        while varint_len((types_size + size_len) as i64) > size_len {
+            size_len += 1;
+        }
+        let header_size = types_size + size_len;
+
+        let content_size: usize = values.iter().map(|value| value.content().len()).sum();
+        let mut payload = Vec::with_capacity(header_size + content_size);
+        payload.extend(encode_varint(header_size as i64));
+        for serial_type in serial_types {
+            payload.extend(encode_varint(serial_type));
+        }
+        for value in &values {
+            payload.extend_from_slice(value.content());
+        }
+        payload
+    }
+
     pub fn value_at_index(&self, index: usize) -> &ColumnValue {
         &self.values[index]
     }
@@ -64,6 +112,80 @@ fn test_parse_record() {
     assert_eq!(record.values[2], ColumnValue::Text("hello".to_owned()));
 }
 
+#[test]
+fn test_parse_index_key() {
+    // index on t1(c): key ('hello', rowid 7)
+    let payload = hex::decode("03170168656C6C6F07").unwrap();
+    let record = DataRecord::parse_index_key(&payload);
+    assert_eq!(record.rowid, Some(7));
+    assert_eq!(record.values[0], ColumnValue::Text("hello".to_owned()));
+    assert_eq!(record.values[1], ColumnValue::int8(7));
+}
+
+#[test]
+fn test_serialize_record() {
+    let record = DataRecord::parse_from(1, &hex::decode("0402001700B168656C6C6F").unwrap());
+    assert_eq!(
+        record.serialize(4),
+        hex::decode("0402001700B168656C6C6F").unwrap()
+    );
+    // integers take their smallest serial type whatever the variant
+    let record = DataRecord {
+        values: vec![
+            ColumnValue::int64(177),
+            ColumnValue::Null,
+            ColumnValue::int32(1),
+        ],
+        rowid: None,
+    };
+    assert_eq!(record.serialize(4), hex::decode("0402000900B1").unwrap());
+    assert_eq!(record.serialize(1), hex::decode("0402000100B101").unwrap());
+}
+
+#[test]
+fn test_serialize_record_like_sqlite3() {
+    // payloads written by sqlite3 3.40 (schema format 4) for
+    // CREATE TABLE t(a,b,c,d,e,f);
+    // INSERT INTO t VALUES(0, 1, -1, 127, 128, -129);
+    // INSERT INTO t VALUES(32767, 32768, -8388608, 8388608, 2147483648, 140737488355328);
+    // INSERT INTO t VALUES(NULL, 2.5, 'hi', x'00ff', 9223372036854775807, -9223372036854775808);
+    for payload in [
+        "07080901010202ff7f0080ff7f",
+        "070203030405067fff008000800000008000000000800000000000800000000000",
+        "070007111006064004000000000000686900ff7fffffffffffffff8000000000000000",
+    ] {
+        let payload = hex::decode(payload).unwrap();
+        let record = DataRecord::parse_from(1, &payload);
+        assert_eq!(record.values.len(), 6);
+        assert_eq!(record.serialize(4), payload);
+    }
+
+    // 127 columns: the header of 129 bytes has a size of 2 bytes
+    // INSERT INTO w(c0) VALUES(1);
+    let mut payload = hex::decode("810109").unwrap();
+    payload.extend([0; 126]);
+    let record = DataRecord::parse_from(1, &payload);
+    assert_eq!(record.values.len(), 127);
+    assert_eq!(record.serialize(4), payload);
+}
+
+#[test]
+fn test_serialize_parse_round_trip() {
+    let values = vec![
+        ColumnValue::integer(-70000, 4),
+        ColumnValue::real(-0.5),
+        ColumnValue::Text("é".repeat(100)),
+        ColumnValue::Blob(vec![7; 300]),
+        ColumnValue::Zero,
+        ColumnValue::Null,
+    ];
+    let record = DataRecord {
+        values,
+        rowid: Some(3),
+    };
+    assert_eq!(DataRecord::parse_from(3, &record.serialize(4)), record);
+}
+
 #[test]
 fn value_at_index() {
     let payload = hex::decode("0402001700B168656C6C6F").unwrap();
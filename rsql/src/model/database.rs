@@ -3,10 +3,13 @@ use std::fs::File;
 use std::io::Read;
 use std::rc::Rc;
 
-use anyhow::Result;
+use anyhow::{anyhow, bail, Result};
 
 use crate::access::buffer_pool::BufferPool;
+use crate::model::db_header::DbHeader;
 use crate::model::db_meta::DbMeta;
+use crate::model::page::Page;
+use crate::model::page_id::PageId;
 use crate::storage::default::DefaultDiskManager;
 
 const MAGIC_HEADER: [u8; 16] = *b"SQLite format 3\0";
@@ -25,26 +28,73 @@ impl Database {
     pub fn new(file_path: &str) -> Result<Self> {
         // To get page_size we need to parse the first 100 bytes before
         // constructing BufferPool and DiskManager as they need those info.
-        // Hence, DbMeta has the exception of access physical file directly,
+        // Hence, DbHeader has the exception of access physical file directly,
         // not through BufferPool.
         let mut file = File::open(file_path)?;
-        let mut buf: Vec<u8> = vec![];
-        // TODO read only what we need not the whole file into mem.
-        file.read_to_end(&mut buf)?;
-        let db_meta = DbMeta::parse(buf.as_slice())?;
+        let mut buf = [0u8; DbHeader::SIZE];
+        file.read_exact(&mut buf)?;
+        let db_header = DbHeader::parse(&buf)?;
 
-        let page_size = db_meta.db_header.page_size;
-        let disk_manager = DefaultDiskManager::new(file_path, page_size as usize)?;
+        let page_size = db_header.page_size();
+        let disk_manager = DefaultDiskManager::new(file_path, page_size)?;
         let shared_dm = Rc::new(RefCell::new(disk_manager));
         // TODO does Database need ref to DiskManager? why?
         //  If yes, how to have both database and buffer pool refs 1 obj DiskManager?
         // ownership of disk_manager is moved to BufferPool
         let buffer_pool = BufferPool::new(10, shared_dm.clone());
 
-        Ok(Database {
-            db_meta,
+        // Schema objects are read with a BtCursor which needs a shared Database,
+        // the cursor is dropped once the schema is read so the Database can be unwrapped.
+        let database = Rc::new(RefCell::new(Database {
+            db_meta: DbMeta::new(db_header),
             buffer_pool,
-        })
+        }));
+        let schema_objects = DbMeta::read_schema_objects(database.clone())?;
+        let mut database = Rc::try_unwrap(database)
+            .map_err(|_| anyhow!("database is still referenced after reading the schema"))?
+            .into_inner();
+        database.db_meta.schema_objects = schema_objects;
+
+        Ok(database)
+    }
+
+    /// Writes the pages modified by a statement to the database file, with the db
+    /// header on the first page: the file change counter is incremented, the
+    /// version-valid-for and sqlite version numbers are set as SQLite sets them and the
+    /// page count is the one of the pages allocated.
+    ///
+    /// There is no rollback journal yet: a crash while the pages are written can
+    /// leave a corrupted file.
+    pub fn commit(&mut self) -> Result<()> {
+        if !self.buffer_pool.has_dirty_pages() {
+            return Ok(());
+        }
+        let header = &mut self.db_meta.db_header;
+        if header.write_format != 1 {
+            // the pages of a database in WAL mode are written to the WAL file
+            bail!("cannot write a database in WAL mode")
+        }
+        header.file_change_counter = header.file_change_counter.wrapping_add(1);
+        header.version_valid_for = header.file_change_counter;
+        header.software_version = DbHeader::SQLITE_VERSION_NUMBER;
+        let first_page = self
+            .buffer_pool
+            .get_page(PageId::new(Page::PAGE_NUM_DB_ROOT))?;
+        header.write_to(&mut first_page.borrow_mut().data[..DbHeader::SIZE]);
+        self.buffer_pool.mark_dirty(&first_page);
+        self.buffer_pool.flush_dirty_pages()
+    }
+
+    /// Drops the pages modified by a statement which failed, the db header is read
+    /// again from the database file.
+    pub fn rollback(&mut self) -> Result<()> {
+        self.buffer_pool.discard_dirty_pages();
+        let first_page = self
+            .buffer_pool
+            .get_page(PageId::new(Page::PAGE_NUM_DB_ROOT))?;
+        let db_header = DbHeader::parse(&first_page.borrow().data[..DbHeader::SIZE])?;
+        self.db_meta.db_header = db_header;
+        Ok(())
     }
 }
 
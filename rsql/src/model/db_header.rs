@@ -60,6 +60,9 @@ impl DbHeader {
     // the offset of root page for sqlite_schema table
     // is after to db header (size = 100)
     pub const ROOT_PAGE_OFFSET: usize = DbHeader::SIZE;
+    /// The SQLITE_VERSION_NUMBER written with the version-valid-for number by the
+    /// writes of rsql, the version of SQLite whose file format rsql writes.
+    pub const SQLITE_VERSION_NUMBER: u32 = 3_051_002;
 
     pub fn parse(stream: &[u8]) -> Result<Self> {
         Ok(Self {
@@ -92,6 +95,51 @@ impl DbHeader {
             software_version: u32::from_be_bytes(stream[96..100].try_into()?),
         })
     }
+
+    /// Writes the header into the first 100 bytes of stream, the inverse of parse.
+    /// The header string and the reserved bytes 72..92 are left unchanged.
+    pub fn write_to(&self, stream: &mut [u8]) {
+        let mut put_u32 = |offset: usize, value: u32| {
+            stream[offset..offset + 4].copy_from_slice(&value.to_be_bytes())
+        };
+        put_u32(24, self.file_change_counter);
+        put_u32(28, self.db_page_count);
+        put_u32(32, self.first_freelist_page);
+        put_u32(36, self.freelist_page_count);
+        put_u32(40, self.schema_cookie);
+        put_u32(44, self.schema_format);
+        put_u32(48, self.default_cache_size);
+        put_u32(52, self.autovacuum_top_root);
+        put_u32(56, self.text_encoding as u32);
+        put_u32(60, self.user_version);
+        put_u32(64, self.incremental_vacuum);
+        put_u32(68, self.application_id);
+        put_u32(92, self.version_valid_for);
+        put_u32(96, self.software_version);
+        stream[16..18].copy_from_slice(&self.page_size.to_be_bytes());
+        stream[18] = self.write_format;
+        stream[19] = self.read_format;
+        stream[20] = self.reserved_bytes;
+        stream[21] = self.max_emb_payload_frac;
+        stream[22] = self.min_emb_payload_frac;
+        stream[23] = self.leaf_payload_frac;
+    }
+
+    /// Returns the page size in bytes. The value 1 stored in the header
+    /// represents a page size of 65536 which does not fit in 2 bytes.
+    pub fn page_size(&self) -> usize {
+        match self.page_size {
+            1 => 65536,
+            n => n as usize,
+        }
+    }
+
+    /// Returns the usable size of a page: page size minus the reserved
+    /// space at the end of each page (used by extensions, usually 0).
+    /// https://www.sqlite.org/fileformat.html#reserved_bytes_per_page
+    pub fn usable_size(&self) -> usize {
+        self.page_size() - self.reserved_bytes as usize
+    }
 }
 
 #[cfg(test)]
@@ -107,5 +155,23 @@ mod tests {
         assert_eq!(db_header.page_size, 4096);
         assert_eq!(db_header.text_encoding, Enc::Utf8);
         assert_eq!(db_header.db_page_count, 4);
+        assert_eq!(db_header.usable_size(), 4096);
+    }
+
+    #[test]
+    fn test_write_header() {
+        let db_bytes = db_bytes();
+        let mut db_header = DbHeader::parse(db_bytes.as_slice()).unwrap();
+        let mut stream = db_bytes[..DbHeader::SIZE].to_vec();
+        db_header.write_to(&mut stream);
+        assert_eq!(stream, &db_bytes[..DbHeader::SIZE]);
+
+        db_header.db_page_count = 5;
+        db_header.file_change_counter += 1;
+        db_header.write_to(&mut stream);
+        let written = DbHeader::parse(&stream).unwrap();
+        assert_eq!(written.db_page_count, 5);
+        assert_eq!(written.file_change_counter, db_header.file_change_counter);
+        assert_eq!(written.schema_cookie, db_header.schema_cookie);
     }
 }
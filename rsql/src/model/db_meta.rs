@@ -1,9 +1,14 @@
-use anyhow::Result;
+use std::cell::RefCell;
+use std::rc::Rc;
 
+use anyhow::{Context, Result};
+
+use crate::btree::bt_cursor::BtCursor;
 use crate::model::cell_table_leaf::LeafTableCell;
+use crate::model::database::Database;
 use crate::model::db_header::DbHeader;
 use crate::model::page::Page;
-use crate::model::schema::SchemaObject;
+use crate::model::schema::{IndexDef, SchemaObjDetail, SchemaObjType, SchemaObject};
 
 /// DbMeta holds meta information of the database
 /// - db header
@@ -16,40 +21,120 @@ pub struct DbMeta {
 }
 
 impl DbMeta {
-    pub fn parse(db: &[u8]) -> Result<Self> {
-        let db_header = DbHeader::parse(&db[..DbHeader::SIZE])?;
-        let page_size: usize = db_header.page_size.into();
-        let mut first_page = Page::parse_db_schema_page(db, page_size)?;
+    /// Creates DbMeta from the db header, without schema objects yet.
+    /// Schema objects are read through the buffer pool with read_schema_objects
+    /// once the Database is set up.
+    pub fn new(db_header: DbHeader) -> Self {
+        DbMeta {
+            db_header,
+            schema_objects: vec![],
+        }
+    }
 
-        let leaf_table_cells: Vec<LeafTableCell> = first_page
-            .cell_ptrs()
+    /// Reads the schema objects by walking the sqlite_schema table btree rooted at page 1.
+    ///
+    /// The schema table is a regular table btree: once it outgrows the first page,
+    /// page 1 becomes an interior page and the rows live on its leaf pages.
+    pub fn read_schema_objects(database: Rc<RefCell<Database>>) -> Result<Vec<SchemaObject>> {
+        let cursor = BtCursor::new(database, Page::PAGE_NUM_DB_ROOT)?;
+        let leaf_table_cells: Vec<LeafTableCell> = cursor.scan_page().collect::<Result<_>>()?;
+
+        let mut schema_objects = leaf_table_cells
             .iter()
-            // use &cell_ptr to borrow usize value
-            .map(|&cell_ptr| LeafTableCell::parse(&db[cell_ptr..]).unwrap())
-            .collect();
+            .map(|cell| {
+                SchemaObject::parse(cell)
+                    .with_context(|| format!("Failed to read sqlite_schema row {}", cell.rowid))
+            })
+            .collect::<Result<Vec<_>>>()?;
 
-        let schema_objects: Vec<SchemaObject> = leaf_table_cells
+        // the automatic indexes have no sql: their columns are the ones of the
+        // constraints of their table
+        let autoindex_defs: Vec<Option<IndexDef>> = schema_objects
             .iter()
-            .flat_map(SchemaObject::parse)
+            .map(|index| {
+                if index.obj_type != SchemaObjType::Index || !index.sql.is_empty() {
+                    return None;
+                }
+                let number: usize = index
+                    .name
+                    .strip_prefix(&format!("sqlite_autoindex_{}_", index.tbl_name))?
+                    .parse()
+                    .ok()?;
+                schema_objects.iter().find_map(|table| match &table.detail {
+                    SchemaObjDetail::Table(table_def) if table.name == index.tbl_name => {
+                        table_def.autoindexes.get(number.checked_sub(1)?).cloned()
+                    }
+                    _ => None,
+                })
+            })
             .collect();
-
-        Ok(DbMeta {
-            db_header,
-            schema_objects,
-        })
+        for (schema_obj, index_def) in schema_objects.iter_mut().zip(autoindex_defs) {
+            if let Some(index_def) = index_def {
+                schema_obj.detail = SchemaObjDetail::Index(index_def);
+            }
+        }
+        Ok(schema_objects)
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::PathBuf;
+
     use log::info;
 
-    use crate::model::db_meta::DbMeta;
-    use crate::test_utils::db_bytes;
+    use crate::model::database::Database;
+    use crate::model::schema::SchemaObjType;
 
     #[test]
     fn test_parse_db_meta() {
-        let db_meta = DbMeta::parse(db_bytes().as_slice()).unwrap();
-        info!("{db_meta:?}")
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sample.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+        info!("{:?}", db.db_meta);
+
+        let table_names: Vec<&str> = db
+            .db_meta
+            .schema_objects
+            .iter()
+            .map(|schema_obj| schema_obj.name.as_str())
+            .collect();
+        assert!(table_names.contains(&"apples"));
+        assert!(table_names.contains(&"oranges"));
+        // untyped DDL is not dropped
+        assert!(table_names.contains(&"sqlite_sequence"));
+    }
+
+    #[test]
+    fn test_read_schema_spanning_many_pages() {
+        // schema.db has page size 512 and 40 tables t00..t39, each with an index.
+        // The schema does not fit on page 1 which becomes an interior page.
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/schema.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+
+        let table_names: Vec<&str> = db
+            .db_meta
+            .schema_objects
+            .iter()
+            .filter(|schema_obj| schema_obj.obj_type == SchemaObjType::Table)
+            .map(|schema_obj| schema_obj.name.as_str())
+            .collect();
+        assert_eq!(table_names.len(), 40);
+        assert_eq!(table_names.first(), Some(&"t00"));
+        assert_eq!(table_names.last(), Some(&"t39"));
+        let last_table = db
+            .db_meta
+            .schema_objects
+            .iter()
+            .find(|schema_obj| schema_obj.name == "t39")
+            .unwrap();
+        assert_eq!(last_table.columns.len(), 3);
+
+        let index_count = db
+            .db_meta
+            .schema_objects
+            .iter()
+            .filter(|schema_obj| schema_obj.obj_type == SchemaObjType::Index)
+            .count();
+        assert_eq!(index_count, 40);
     }
 }
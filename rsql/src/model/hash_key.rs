@@ -0,0 +1,77 @@
+use crate::model::collation::Collation;
+use crate::model::column_value::ColumnValue;
+
+/// A value normalized for hashing: values that are equal for SQLite have the same key,
+/// e.g. the INTEGER 1 and the REAL 1.0, or 'a' and 'A' with the NOCASE collation.
+///
+/// Used by operators grouping equal values with a hash table: GROUP BY, DISTINCT, etc.
+/// Like in SQLite, NULL values are equal to each other for grouping.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HashKey {
+    Null,
+    Integer(i64),
+    // bits of the f64, reals with an integer value are Integer keys
+    Real(u64),
+    Text(String),
+    Blob(Vec<u8>),
+}
+
+impl HashKey {
+    pub fn new(value: &ColumnValue, collation: Collation) -> Self {
+        match value {
+            ColumnValue::Null => HashKey::Null,
+            ColumnValue::Float64(_) => {
+                let real = value.as_f64().unwrap_or_default();
+                // 2^63 (i64::MAX as f64) does not fit in i64
+                if real.fract() == 0.0 && real >= i64::MIN as f64 && real < i64::MAX as f64 {
+                    HashKey::Integer(real as i64)
+                } else {
+                    HashKey::Real(real.to_bits())
+                }
+            }
+            ColumnValue::Text(text) => HashKey::Text(match collation {
+                Collation::Binary => text.clone(),
+                Collation::NoCase => text.to_ascii_lowercase(),
+                Collation::RTrim => text.trim_end_matches(' ').to_owned(),
+            }),
+            ColumnValue::Blob(bytes) => HashKey::Blob(bytes.clone()),
+            int => HashKey::Integer(int.as_i64().unwrap_or_default()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_key_equal_values() {
+        let binary = Collation::Binary;
+        assert_eq!(
+            HashKey::new(&ColumnValue::One, binary),
+            HashKey::new(&ColumnValue::real(1.0), binary)
+        );
+        assert_eq!(
+            HashKey::new(&ColumnValue::int8(7), binary),
+            HashKey::new(&ColumnValue::int64(7), binary)
+        );
+        assert_ne!(
+            HashKey::new(&ColumnValue::real(1.5), binary),
+            HashKey::new(&ColumnValue::One, binary)
+        );
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        assert_ne!(
+            HashKey::new(&text("North"), binary),
+            HashKey::new(&text("north"), binary)
+        );
+        assert_eq!(
+            HashKey::new(&text("North"), Collation::NoCase),
+            HashKey::new(&text("north"), Collation::NoCase)
+        );
+        // TEXT '1' is not the INTEGER 1
+        assert_ne!(
+            HashKey::new(&text("1"), binary),
+            HashKey::new(&ColumnValue::One, binary)
+        );
+    }
+}
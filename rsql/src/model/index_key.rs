@@ -0,0 +1,105 @@
+use std::cmp::Ordering;
+
+use crate::model::collation::Collation;
+use crate::model::column_value::ColumnValue;
+
+/// Sort order of an index column, from `CREATE INDEX ... (col ASC|DESC)`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// KeyInfo describes how to compare the keys of an index B-Tree.
+/// Equivalent to sqlite `struct KeyInfo`: one sort order and one collation per
+/// indexed column.
+///
+/// Index keys are records made of the indexed columns followed by the rowid.
+/// Columns beyond the ones described here (e.g. the trailing rowid) are compared
+/// in ascending order with the binary collation.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyInfo {
+    pub sort_orders: Vec<SortOrder>,
+    pub collations: Vec<Collation>,
+}
+
+impl KeyInfo {
+    pub fn new(sort_orders: Vec<SortOrder>, collations: Vec<Collation>) -> Self {
+        KeyInfo {
+            sort_orders,
+            collations,
+        }
+    }
+
+    /// Compares two index keys field by field.
+    ///
+    /// Only the common prefix is compared: a key with fewer fields is equal to
+    /// a longer key it is a prefix of. This allows seeking an index with a subset
+    /// of its columns, like `sqlite3VdbeRecordCompare` with default_rc = 0.
+    pub fn compare(&self, a: &[ColumnValue], b: &[ColumnValue]) -> Ordering {
+        for (i, (value_a, value_b)) in a.iter().zip(b.iter()).enumerate() {
+            let collation = self.collations.get(i).copied().unwrap_or_default();
+            let ordering = match value_a.compare(value_b, &collation) {
+                Ordering::Equal => continue,
+                ordering => ordering,
+            };
+            return match self.sort_orders.get(i) {
+                Some(SortOrder::Desc) => ordering.reverse(),
+                _ => ordering,
+            };
+        }
+        Ordering::Equal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> ColumnValue {
+        ColumnValue::Text(s.to_owned())
+    }
+
+    #[test]
+    fn test_compare_asc_binary() {
+        let key_info = KeyInfo::default();
+        let a = vec![text("a"), ColumnValue::int8(1)];
+        let b = vec![text("b"), ColumnValue::int8(0)];
+        assert_eq!(key_info.compare(&a, &b), Ordering::Less);
+        assert_eq!(key_info.compare(&b, &a), Ordering::Greater);
+        assert_eq!(key_info.compare(&a, &a), Ordering::Equal);
+        // uppercase sorts before lowercase with binary collation
+        assert_eq!(key_info.compare(&[text("B")], &[text("a")]), Ordering::Less);
+        // nulls first
+        assert_eq!(
+            key_info.compare(&[ColumnValue::Null], &[ColumnValue::int8(-100)]),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_compare_desc_and_collation() {
+        // index on (name COLLATE NOCASE, age DESC)
+        let key_info = KeyInfo::new(
+            vec![SortOrder::Asc, SortOrder::Desc],
+            vec![Collation::NoCase, Collation::Binary],
+        );
+        let a = vec![text("Bob"), ColumnValue::int8(30), ColumnValue::int8(1)];
+        let b = vec![text("bob"), ColumnValue::int8(20), ColumnValue::int8(2)];
+        // names are equal with nocase, larger age comes first
+        assert_eq!(key_info.compare(&a, &b), Ordering::Less);
+        // same name and age: the trailing rowid is compared ascending
+        let c = vec![text("BOB"), ColumnValue::int8(30), ColumnValue::int8(3)];
+        assert_eq!(key_info.compare(&a, &c), Ordering::Less);
+    }
+
+    #[test]
+    fn test_compare_prefix() {
+        let key_info = KeyInfo::default();
+        let key = vec![text("a"), ColumnValue::int8(1), ColumnValue::int8(7)];
+        assert_eq!(key_info.compare(&[text("a")], &key), Ordering::Equal);
+        assert_eq!(key_info.compare(&key, &[text("b")]), Ordering::Less);
+        assert_eq!(key_info.compare(&[], &key), Ordering::Equal);
+    }
+}
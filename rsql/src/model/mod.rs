@@ -1,12 +1,21 @@
+pub mod affinity;
+pub mod cell_index_interior;
+pub mod cell_index_leaf;
 pub mod cell_table_interior;
 pub mod cell_table_leaf;
+pub mod collation;
 pub mod column_value;
+pub mod conversion;
 pub mod data_record;
 pub mod database;
 pub mod db_header;
 pub mod db_meta;
+pub mod hash_key;
+pub mod index_key;
+pub mod overflow_page;
 pub mod page;
 pub mod page_header;
 pub mod page_id;
+pub mod payload;
 pub mod schema;
 pub mod table;
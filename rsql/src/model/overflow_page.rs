@@ -0,0 +1,72 @@
+use anyhow::{bail, Result};
+
+use crate::model::page_id::PageId;
+
+/// A page of an overflow chain.
+/// https://www.sqlite.org/fileformat.html#cell_payload_overflow_pages
+///
+/// When the payload of a b-tree cell is too large for the b-tree page,
+/// the surplus is spilled onto overflow pages. Overflow pages form a linked list.
+/// - first 4 bytes: big-endian page number of the next page in the chain,
+///   or zero for the last page.
+/// - the remaining usable space (usable size - 4 bytes) holds payload content.
+///
+/// Overflow pages do not have a b-tree page header so they cannot be parsed as `Page`.
+#[derive(Debug)]
+pub struct OverflowPage {
+    pub page_id: PageId,
+    // None when this is the last page of the chain
+    pub next_page_number: Option<u32>,
+    pub data: Vec<u8>, // bytes of the page
+}
+
+impl OverflowPage {
+    pub const NEXT_PAGE_SIZE: usize = 4;
+
+    /// Parses an overflow page from the bytes of the whole page.
+    pub fn parse(page_number: u32, page: &[u8]) -> Result<Self> {
+        if page.len() < Self::NEXT_PAGE_SIZE {
+            bail!(
+                "Overflow page {page_number} is too small: {} bytes",
+                page.len()
+            )
+        }
+        let next_page_number = match u32::from_be_bytes(page[..4].try_into()?) {
+            0 => None,
+            n => Some(n),
+        };
+
+        Ok(Self {
+            page_id: PageId::new(page_number),
+            next_page_number,
+            data: page.to_owned(),
+        })
+    }
+
+    /// Returns the payload content stored on this page.
+    /// usable_size: page size minus reserved bytes at the end of each page.
+    pub fn content(&self, usable_size: usize) -> &[u8] {
+        &self.data[Self::NEXT_PAGE_SIZE..usable_size.min(self.data.len())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_overflow_page() {
+        let mut bytes = vec![0, 0, 0, 7];
+        bytes.extend_from_slice(b"payload");
+        bytes.extend_from_slice(&[0, 0]); // reserved bytes
+
+        let page = OverflowPage::parse(5, &bytes).unwrap();
+        assert_eq!(page.page_id.page_number, 5);
+        assert_eq!(page.next_page_number, Some(7));
+        assert_eq!(page.content(11), b"payload");
+
+        let page = OverflowPage::parse(7, &[0, 0, 0, 0, 1]).unwrap();
+        assert_eq!(page.next_page_number, None);
+        assert_eq!(page.content(5), &[1]);
+    }
+}
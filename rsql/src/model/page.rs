@@ -1,11 +1,13 @@
 use std::fmt::Debug;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use log::debug;
 
 use crate::model::db_header::DbHeader;
-use crate::model::page_header::PageHeader;
+use crate::model::page_header::{PageHeader, PageType};
 use crate::model::page_id::PageId;
+use crate::model::payload::local_payload_size;
+use crate::varint::decode_varint;
 
 /// A page in the SQLite database.
 /// https://www.sqlite.org/fileformat.html#pages
@@ -37,6 +39,28 @@ impl Debug for Page {
 impl Page {
     // page number in sqlite in 1-indexed (starts from 1, not 0)
     pub const PAGE_NUM_DB_ROOT: u32 = 1;
+    // space taken by a cell is at least 4 bytes, the size of a freeblock header
+    pub const MIN_CELL_SIZE: usize = 4;
+    // size of a cell pointer
+    pub const CELL_PTR_SIZE: usize = 2;
+
+    /// Creates an empty b-tree page of page_type, the content area ends at usable_size.
+    /// The first page keeps room for the db header which is not written here.
+    pub fn new(
+        page_number: u32,
+        page_type: PageType,
+        page_size: usize,
+        usable_size: usize,
+    ) -> Self {
+        let mut page = Page {
+            page_header: PageHeader::new(page_type, usable_size),
+            page_id: PageId::new(page_number),
+            data: vec![0; page_size],
+            cell_ptrs: Some(vec![]),
+        };
+        page.write_header();
+        page
+    }
 
     /// Create a dummy page usually for mocking and testing.
     pub fn dummy() -> Self {
@@ -49,30 +73,35 @@ impl Page {
     }
 
     /// page_number: SQLite 1-indexed page number starting with 1
+    /// db: bytes of the whole database file
     pub fn parse(page_number: u32, page_size: usize, db: &[u8]) -> Result<Self> {
-        // page number in sqlite is 1-indexed (starts from 1, not 0).
-        // >=2 is from the second page.
-        if page_number >= 2 {
-            let page_offset = usize::try_from(page_number - 1)? * page_size;
-
-            Ok(Page {
-                page_header: PageHeader::parse(&db[page_offset..])?,
-                page_id: PageId { page_number },
-                data: db[page_offset..page_offset + page_size].to_owned(),
-                cell_ptrs: None,
-            })
+        let page_offset = usize::try_from(page_number.saturating_sub(1))? * page_size;
+        match db.get(page_offset..page_offset + page_size) {
+            Some(data) => Self::from_bytes(page_number, data.to_owned()),
+            None => bail!("Page {page_number} is out of the database file"),
+        }
+    }
+
+    /// Parses a page from its bytes.
+    /// page_number: SQLite 1-indexed page number starting with 1
+    pub fn from_bytes(page_number: u32, data: Vec<u8>) -> Result<Self> {
+        // first page data starts after the DBHeader 100 bytes
+        // https://www.sqlite.org/fileformat.html#b_tree_pages
+        let header_offset = if page_number == Self::PAGE_NUM_DB_ROOT {
+            DbHeader::SIZE
         } else {
-            // first page data starts after the DBHeader 100 bytes
-            // https://www.sqlite.org/fileformat.html#b_tree_pages
-            let page_offset = DbHeader::SIZE;
+            0
+        };
+        let Some(header_bytes) = data.get(header_offset..) else {
+            bail!("Page {page_number} is too small: {} bytes", data.len())
+        };
 
-            Ok(Self {
-                page_header: PageHeader::parse(&db[page_offset..])?,
-                page_id: PageId { page_number: 1 },
-                data: db[..page_size].to_owned(),
-                cell_ptrs: None,
-            })
-        }
+        Ok(Page {
+            page_header: PageHeader::parse(header_bytes)?,
+            page_id: PageId { page_number },
+            data,
+            cell_ptrs: None,
+        })
     }
 
     // Parse the first page of db file which is also the dn schema page.
@@ -102,6 +131,373 @@ impl Page {
         self.cell_ptrs.as_ref().unwrap()
     }
 
+    /// Returns the bytes of the index-th cell, including the first overflow page number
+    /// of a payload that spills. usable_size: page size minus reserved bytes.
+    pub fn cell(&mut self, index: usize, usable_size: usize) -> &[u8] {
+        let cell_ptr = self.get_cell_ptr(index);
+        let cell_len = self.cell_len(cell_ptr, usable_size);
+        &self.data[cell_ptr..cell_ptr + cell_len]
+    }
+
+    /// Returns the bytes of all cells of the page, in order.
+    pub fn cells(&mut self, usable_size: usize) -> Vec<Vec<u8>> {
+        (0..self.get_number_of_cells() as usize)
+            .map(|index| self.cell(index, usable_size).to_vec())
+            .collect()
+    }
+
+    /// Number of bytes of the cell starting at cell_ptr, computed from its header like
+    /// sqlite cellSizePtr (without the minimum size of 4 bytes).
+    fn cell_len(&self, cell_ptr: usize, usable_size: usize) -> usize {
+        let cell = &self.data[cell_ptr..];
+        let page_type = self.page_header.page_type;
+        // bytes before the payload and total payload size
+        let (header_len, payload_size) = match page_type {
+            // 4-byte left child pointer then the rowid, no payload
+            PageType::InteriorTable => return 4 + decode_varint(&cell[4..]).1,
+            PageType::LeafTable => {
+                let (payload_size, bytes_read) = decode_varint(cell);
+                let (_, rowid_bytes) = decode_varint(&cell[bytes_read..]);
+                (bytes_read + rowid_bytes, payload_size)
+            }
+            PageType::LeafIndex => {
+                let (payload_size, bytes_read) = decode_varint(cell);
+                (bytes_read, payload_size)
+            }
+            PageType::InteriorIndex => {
+                let (payload_size, bytes_read) = decode_varint(&cell[4..]);
+                (4 + bytes_read, payload_size)
+            }
+        };
+        let payload_size = payload_size as usize;
+        let local_size = local_payload_size(payload_size, usable_size, &page_type);
+        // the first overflow page number follows the local part of a spilled payload
+        let overflow_ptr = if local_size < payload_size { 4 } else { 0 };
+        header_len + local_size + overflow_ptr
+    }
+
+    /// Space taken on a page by cells: their content and cell pointers.
+    pub fn cells_space<'a>(cells: impl IntoIterator<Item = &'a Vec<u8>>) -> usize {
+        cells
+            .into_iter()
+            .map(|cell| cell.len().max(Self::MIN_CELL_SIZE) + Self::CELL_PTR_SIZE)
+            .sum()
+    }
+
+    /// Space for the cells of a page of page_type: the usable size minus the headers.
+    pub fn capacity(page_number: u32, page_type: PageType, usable_size: usize) -> usize {
+        let header_size = PageHeader::new(page_type, usable_size).size();
+        let db_header_size = if page_number == Self::PAGE_NUM_DB_ROOT {
+            DbHeader::SIZE
+        } else {
+            0
+        };
+        usable_size - db_header_size - header_size
+    }
+
+    /// Rewrites the page with the given cells, in order: the page header, the cell
+    /// pointers then the cell content packed at the end of the usable space, with no
+    /// freeblock or fragment. The page type can change, e.g. a leaf root becoming an
+    /// interior page when the b-tree grows. right_child is the right-most child
+    /// pointer of an interior page.
+    ///
+    /// Equivalent to sqlite rebuildPage.
+    pub fn rebuild(
+        &mut self,
+        page_type: PageType,
+        cells: &[Vec<u8>],
+        right_child: Option<u32>,
+        usable_size: usize,
+    ) -> Result<()> {
+        let page_number = self.page_id.page_number;
+        if Self::cells_space(cells) > Self::capacity(page_number, page_type, usable_size) {
+            bail!("{} cells do not fit on page {page_number}", cells.len())
+        }
+        let mut header = PageHeader::new(page_type, usable_size);
+        if header.is_interior() {
+            header.right_child_page_number = right_child;
+        }
+        header.number_of_cells = cells.len() as u16;
+        let header_offset = self.header_offset();
+        let mut cell_ptr_offset = header_offset + header.size();
+        let mut content_start = usable_size;
+        let mut cell_ptrs = Vec::with_capacity(cells.len());
+        for cell in cells {
+            content_start -= cell.len().max(Self::MIN_CELL_SIZE);
+            self.data[content_start..content_start + cell.len()].copy_from_slice(cell);
+            self.data[cell_ptr_offset..cell_ptr_offset + 2]
+                .copy_from_slice(&(content_start as u16).to_be_bytes());
+            cell_ptr_offset += Self::CELL_PTR_SIZE;
+            cell_ptrs.push(content_start);
+        }
+        // the unallocated space between the cell pointers and the content is zeroed
+        self.data[cell_ptr_offset..content_start].fill(0);
+        header.content_start_offset = content_start as u16;
+        self.page_header = header;
+        self.write_header();
+        self.cell_ptrs = Some(cell_ptrs);
+        Ok(())
+    }
+
+    /// Inserts cell as the index-th cell of the page: its content is written in a
+    /// freeblock large enough, or at the start of the cell content area, and its pointer
+    /// is inserted in the cell pointer array. The page is defragmented if the free space
+    /// is only large enough once the freeblocks and fragments are merged.
+    /// Fails if the cell does not fit on the page: the caller balances the b-tree.
+    ///
+    /// Equivalent to sqlite insertCell without overflow cells.
+    pub fn insert_cell(&mut self, index: usize, cell: &[u8], usable_size: usize) -> Result<()> {
+        let number_of_cells = self.get_number_of_cells() as usize;
+        if index > number_of_cells {
+            bail!(
+                "cannot insert cell {index} on page {} of {number_of_cells} cells",
+                self.page_id.page_number
+            )
+        }
+        let size = cell.len().max(Self::MIN_CELL_SIZE);
+        if self.free_space() < size + Self::CELL_PTR_SIZE {
+            bail!(
+                "cell of {} bytes does not fit on page {}",
+                cell.len(),
+                self.page_id.page_number
+            )
+        }
+        let cell_ptr = self.allocate_space(size, usable_size)?;
+        self.data[cell_ptr..cell_ptr + cell.len()].copy_from_slice(cell);
+
+        // the next cell pointers move up by one
+        let cell_ptrs_offset = self.header_offset() + self.page_header.size();
+        let ptr_offset = cell_ptrs_offset + Self::CELL_PTR_SIZE * index;
+        let ptrs_end = cell_ptrs_offset + Self::CELL_PTR_SIZE * number_of_cells;
+        self.data
+            .copy_within(ptr_offset..ptrs_end, ptr_offset + Self::CELL_PTR_SIZE);
+        self.write_u16(ptr_offset, cell_ptr);
+        if let Some(cell_ptrs) = self.cell_ptrs.as_mut() {
+            cell_ptrs.insert(index, cell_ptr);
+        }
+        self.page_header.number_of_cells += 1;
+        self.write_header();
+        Ok(())
+    }
+
+    /// Moves all cells to the end of the usable space, in the order of their pointers:
+    /// the free space becomes one unallocated area between the cell pointers and the
+    /// cell content, without freeblock or fragment.
+    ///
+    /// Equivalent to sqlite defragmentPage.
+    pub fn defragment(&mut self, usable_size: usize) -> Result<()> {
+        let cells = self.cells(usable_size);
+        let page_type = self.page_header.page_type;
+        let right_child = self.page_header.right_child_page_number;
+        self.rebuild(page_type, &cells, right_child, usable_size)
+    }
+
+    /// Writes page_header at the start of the page, after the db header on the first
+    /// page, once it has been changed.
+    pub fn write_header(&mut self) {
+        let header_offset = self.header_offset();
+        self.page_header.write_to(&mut self.data[header_offset..]);
+    }
+
+    // Returns the offset of size bytes for a new cell: the first freeblock large enough,
+    // otherwise the start of the cell content area, after a defragmentation if the
+    // unallocated space is too small. The pointer of the cell must fit too.
+    //
+    // Equivalent to sqlite allocateSpace.
+    fn allocate_space(&mut self, size: usize, usable_size: usize) -> Result<usize> {
+        let header = &self.page_header;
+        // end of the cell pointer array with the pointer of the new cell
+        let gap = self.header_offset()
+            + header.size()
+            + Self::CELL_PTR_SIZE * (header.number_of_cells as usize + 1);
+        if gap > self.content_start() {
+            bail!(
+                "corrupted cell pointers on page {}",
+                self.page_id.page_number
+            )
+        }
+        if header.first_free_block_start != 0 {
+            if let Some(start) = self.find_free_slot(size) {
+                return Ok(start);
+            }
+        }
+        if gap + size > self.content_start() {
+            self.defragment(usable_size)?;
+        }
+        let start = self.content_start() - size;
+        self.page_header.content_start_offset = start as u16;
+        Ok(start)
+    }
+
+    // Takes size bytes from the first freeblock large enough, at its end. A freeblock
+    // left with less than 4 bytes is removed, its remaining bytes become fragmented
+    // bytes, unless there are already too many of them. None if no freeblock is used.
+    //
+    // Equivalent to sqlite pageFindSlot.
+    fn find_free_slot(&mut self, size: usize) -> Option<usize> {
+        let mut previous = 0;
+        let mut block = self.page_header.first_free_block_start as usize;
+        while block != 0 {
+            let block_size = self.read_u16(block + 2);
+            let next = self.read_u16(block);
+            if block_size >= size {
+                let remaining = block_size - size;
+                if remaining >= Self::MIN_CELL_SIZE {
+                    self.write_u16(block + 2, remaining);
+                    return Some(block + remaining);
+                }
+                // the fragmented bytes of a page are at most 60
+                if self.page_header.fragmented_free_bytes > 57 {
+                    return None;
+                }
+                if previous == 0 {
+                    self.page_header.first_free_block_start = next as u16;
+                } else {
+                    self.write_u16(previous, next);
+                }
+                self.page_header.fragmented_free_bytes += remaining as u8;
+                return Some(block);
+            }
+            previous = block;
+            block = next;
+        }
+        None
+    }
+
+    /// Number of free bytes of the page: the unallocated space between the cell
+    /// pointers and the cell content area, the freeblocks and the fragmented bytes.
+    ///
+    /// Equivalent to sqlite `pPage->nFree` computed by btreeComputeFreeSpace.
+    pub fn free_space(&self) -> usize {
+        let header = &self.page_header;
+        let cell_ptrs_end = self.header_offset()
+            + header.size()
+            + Self::CELL_PTR_SIZE * header.number_of_cells as usize;
+        let mut free_space =
+            self.content_start() - cell_ptrs_end + header.fragmented_free_bytes as usize;
+        let mut freeblock = header.first_free_block_start as usize;
+        while freeblock != 0 {
+            free_space += self.read_u16(freeblock + 2);
+            freeblock = self.read_u16(freeblock);
+        }
+        free_space
+    }
+
+    /// Removes the index-th cell from the page: its pointer is removed from the cell
+    /// pointer array and its space becomes a freeblock. The overflow pages of the cell
+    /// are not freed. usable_size: page size minus reserved bytes.
+    ///
+    /// Equivalent to sqlite dropCell.
+    pub fn remove_cell(&mut self, index: usize, usable_size: usize) -> Result<()> {
+        let number_of_cells = self.get_number_of_cells() as usize;
+        if index >= number_of_cells {
+            bail!(
+                "no cell {index} on page {} of {number_of_cells} cells",
+                self.page_id.page_number
+            )
+        }
+        let cell_ptr = self.get_cell_ptr(index);
+        let cell_len = self
+            .cell_len(cell_ptr, usable_size)
+            .max(Self::MIN_CELL_SIZE);
+        self.free_block(cell_ptr, cell_len)?;
+
+        // the next cell pointers move down by one
+        let header_offset = self.header_offset();
+        let cell_ptrs_offset = header_offset + self.page_header.size();
+        let ptr_offset = cell_ptrs_offset + Self::CELL_PTR_SIZE * index;
+        let ptrs_end = cell_ptrs_offset + Self::CELL_PTR_SIZE * number_of_cells;
+        self.data
+            .copy_within(ptr_offset + Self::CELL_PTR_SIZE..ptrs_end, ptr_offset);
+        self.data[ptrs_end - Self::CELL_PTR_SIZE..ptrs_end].fill(0);
+        if let Some(cell_ptrs) = self.cell_ptrs.as_mut() {
+            cell_ptrs.remove(index);
+        }
+        let header = &mut self.page_header;
+        header.number_of_cells -= 1;
+        if header.number_of_cells == 0 {
+            // an empty page has no freeblock
+            header.first_free_block_start = 0;
+            header.fragmented_free_bytes = 0;
+            header.content_start_offset = usable_size as u16;
+        }
+        self.write_header();
+        Ok(())
+    }
+
+    // Adds the size bytes at start to the freeblock list, sorted by offset, merging
+    // them with the neighbour freeblocks and the fragments between them. The cell
+    // content area shrinks instead if the bytes are at its start.
+    //
+    // Equivalent to sqlite freeSpace.
+    fn free_block(&mut self, start: usize, size: usize) -> Result<()> {
+        let mut start = start;
+        let mut end = start + size;
+        // the freeblock before start, 0 if none, and the one after start
+        let mut previous = 0;
+        let mut next = self.page_header.first_free_block_start as usize;
+        while next != 0 && next < start {
+            previous = next;
+            next = self.read_u16(next);
+        }
+        let mut fragments = 0;
+        if next != 0 && end + 3 >= next {
+            // the fragment between the bytes and the next freeblock is merged
+            fragments += next - end;
+            end = next + self.read_u16(next + 2);
+            next = self.read_u16(next);
+        }
+        if previous != 0 {
+            let previous_end = previous + self.read_u16(previous + 2);
+            if previous_end + 3 >= start {
+                fragments += start - previous_end;
+                start = previous;
+            }
+        }
+        let header = &mut self.page_header;
+        let Some(fragmented) = header.fragmented_free_bytes.checked_sub(fragments as u8) else {
+            bail!("corrupted free space on page {}", self.page_id.page_number)
+        };
+        header.fragmented_free_bytes = fragmented;
+
+        if start <= self.content_start() {
+            // the content area starts after the freed bytes
+            if previous != 0 && previous != start {
+                bail!("corrupted free space on page {}", self.page_id.page_number)
+            }
+            self.page_header.first_free_block_start = next as u16;
+            self.page_header.content_start_offset = end as u16;
+        } else {
+            self.write_u16(start, next);
+            self.write_u16(start + 2, end - start);
+            if previous == 0 {
+                self.page_header.first_free_block_start = start as u16;
+            } else if previous != start {
+                self.write_u16(previous, start);
+            }
+        }
+        // freed bytes are zeroed, like sqlite secure_delete
+        let block_header = if start <= self.content_start() { 0 } else { 4 };
+        self.data[start + block_header..end].fill(0);
+        Ok(())
+    }
+
+    // Start of the cell content area, 0 in the header of an empty 64KiB page is 65536.
+    fn content_start(&self) -> usize {
+        match self.page_header.content_start_offset {
+            0 => 65536,
+            offset => offset as usize,
+        }
+    }
+
+    fn read_u16(&self, offset: usize) -> usize {
+        u16::from_be_bytes([self.data[offset], self.data[offset + 1]]) as usize
+    }
+
+    fn write_u16(&mut self, offset: usize, value: usize) {
+        self.data[offset..offset + 2].copy_from_slice(&(value as u16).to_be_bytes());
+    }
+
     /// Return whether page is a leaf page (table or index)
     pub fn is_leaf(&self) -> bool {
         self.page_header.is_leaf()
@@ -112,16 +508,20 @@ impl Page {
         self.page_header.is_interior()
     }
 
+    /// Offset of the page header: the first page starts with the db header.
+    pub fn header_offset(&self) -> usize {
+        if self.is_db_schema_page() {
+            DbHeader::SIZE
+        } else {
+            0
+        }
+    }
+
     fn parse_cell_ptrs(&mut self) {
         // must offset extra Db header size if it's the first page
         // first page: DbHeader | PageHeader | CellPointers | ...
         // other page: PageHeader | CellPointers | ...
-        let cell_ptrs_offset = self.page_header.size()
-            + if self.is_db_schema_page() {
-                DbHeader::SIZE
-            } else {
-                0
-            };
+        let cell_ptrs_offset = self.page_header.size() + self.header_offset();
         let num_cells: usize = self.page_header.number_of_cells.into();
 
         debug!("cell ptrs offset {cell_ptrs_offset}, num {num_cells}");
@@ -160,6 +560,7 @@ impl Page {
 mod tests {
     use crate::model::page_header::PageType;
     use crate::test_utils::db_bytes;
+    use crate::varint::encode_varint;
 
     use super::*;
 
@@ -190,6 +591,209 @@ mod tests {
         assert_eq!(page.get_cell_ptr(3), 4001);
     }
 
+    #[test]
+    fn test_cells_of_table_leaf_page() {
+        let db = db_bytes();
+        let mut page = Page::parse(2, SAMPLE_DB_PAGE_SIZE, db.as_slice()).unwrap();
+        let cells = page.cells(SAMPLE_DB_PAGE_SIZE);
+        assert_eq!(cells.len(), 4);
+        // rowid 2: [Null, Text("Fuji"), Text("Red")], see LeafTableCell tests
+        assert_eq!(
+            cells[1],
+            vec![11, 2, 4, 0, 21, 19, 70, 117, 106, 105, 82, 101, 100]
+        );
+        // cells are contiguous: the first cell ends at the end of the page
+        assert_eq!(4067 + cells[0].len(), SAMPLE_DB_PAGE_SIZE);
+    }
+
+    #[test]
+    fn test_rebuild_page() {
+        let db = db_bytes();
+        let mut page = Page::parse(2, SAMPLE_DB_PAGE_SIZE, db.as_slice()).unwrap();
+        let mut cells = page.cells(SAMPLE_DB_PAGE_SIZE);
+        // a cell with a 1-byte payload takes 4 bytes
+        cells.insert(0, vec![1, 1, 1]);
+        cells.remove(2);
+        page.rebuild(PageType::LeafTable, &cells, None, SAMPLE_DB_PAGE_SIZE)
+            .unwrap();
+
+        let mut parsed = Page::from_bytes(2, page.data.clone()).unwrap();
+        assert_eq!(parsed.page_header, page.page_header);
+        assert_eq!(parsed.get_number_of_cells(), 4);
+        assert_eq!(parsed.cells(SAMPLE_DB_PAGE_SIZE), cells);
+        assert_eq!(parsed.get_cell_ptr(0), 4092);
+        assert_eq!(
+            parsed.page_header.content_start_offset as usize,
+            SAMPLE_DB_PAGE_SIZE - (Page::cells_space(&cells) - 2 * cells.len())
+        );
+
+        // an interior page keeps the right child pointer in its header
+        let cells = vec![vec![0, 0, 0, 3, 10], vec![0, 0, 0, 4, 20]];
+        page.rebuild(
+            PageType::InteriorTable,
+            &cells,
+            Some(5),
+            SAMPLE_DB_PAGE_SIZE,
+        )
+        .unwrap();
+        let mut parsed = Page::from_bytes(2, page.data.clone()).unwrap();
+        assert_eq!(parsed.page_header.right_child_page_number, Some(5));
+        assert_eq!(parsed.cells(SAMPLE_DB_PAGE_SIZE), cells);
+
+        let too_many = vec![vec![0; 1000]; 5];
+        assert!(page
+            .rebuild(PageType::LeafIndex, &too_many, None, SAMPLE_DB_PAGE_SIZE)
+            .is_err());
+    }
+
+    #[test]
+    fn test_remove_cell() {
+        let db = db_bytes();
+        let mut page = Page::parse(2, SAMPLE_DB_PAGE_SIZE, db.as_slice()).unwrap();
+        let cells = page.cells(SAMPLE_DB_PAGE_SIZE);
+        // cells [4067, 4054, 4029, 4001] of 29, 13, 25 and 28 bytes
+        assert_eq!(page.free_space(), 4001 - 8 - 4 * 2);
+
+        // the cell becomes a freeblock
+        page.remove_cell(1, SAMPLE_DB_PAGE_SIZE).unwrap();
+        assert_eq!(page.page_header.first_free_block_start, 4054);
+        assert_eq!(page.free_space(), 4001 - 8 - 3 * 2 + 13);
+        // 2 bytes of the freeblock are a fragment before the next cell
+        page.data[4056..4058].copy_from_slice(&11u16.to_be_bytes());
+        page.page_header.fragmented_free_bytes = 2;
+
+        // the next cell, the fragment and the freeblock are merged
+        page.remove_cell(0, SAMPLE_DB_PAGE_SIZE).unwrap();
+        let mut parsed = Page::from_bytes(2, page.data.clone()).unwrap();
+        assert_eq!(parsed.page_header, page.page_header);
+        assert_eq!(parsed.page_header.fragmented_free_bytes, 0);
+        assert_eq!(parsed.page_header.first_free_block_start, 4054);
+        assert_eq!(parsed.read_u16(4054), 0);
+        assert_eq!(parsed.read_u16(4056), 42);
+        assert_eq!(parsed.cells(SAMPLE_DB_PAGE_SIZE), cells[2..].to_vec());
+
+        // a cell at the start of the content area extends the unallocated space
+        page.remove_cell(1, SAMPLE_DB_PAGE_SIZE).unwrap();
+        assert_eq!(page.page_header.content_start_offset, 4029);
+        assert_eq!(page.page_header.first_free_block_start, 4054);
+        assert_eq!(page.free_space(), 4029 - 8 - 2 + 42);
+        assert_eq!(page.cells(SAMPLE_DB_PAGE_SIZE), vec![cells[2].clone()]);
+
+        // an empty page has no freeblock
+        page.remove_cell(0, SAMPLE_DB_PAGE_SIZE).unwrap();
+        assert_eq!(page.page_header, PageHeader::new(PageType::LeafTable, 4096));
+        assert_eq!(page.free_space(), 4096 - 8);
+        assert!(page.remove_cell(0, SAMPLE_DB_PAGE_SIZE).is_err());
+    }
+
+    // A cell of a leaf page: the payload size and, on a table page, the rowid, then
+    // the payload of len bytes.
+    fn leaf_cell(rowid: Option<u8>, len: usize, byte: u8) -> Vec<u8> {
+        let mut cell = encode_varint(len as i64);
+        cell.extend(rowid);
+        cell.extend(vec![byte; len]);
+        cell
+    }
+
+    #[test]
+    fn test_insert_cell() {
+        let db = db_bytes();
+        let mut page = Page::parse(2, SAMPLE_DB_PAGE_SIZE, db.as_slice()).unwrap();
+        let cells = page.cells(SAMPLE_DB_PAGE_SIZE);
+        // cells [4067, 4054, 4029, 4001] of 29, 13, 25 and 28 bytes
+
+        // without freeblock, the cell is written at the start of the content area
+        let cell_10 = leaf_cell(Some(10), 8, 1);
+        page.insert_cell(0, &cell_10, SAMPLE_DB_PAGE_SIZE).unwrap();
+        assert_eq!(*page.cell_ptrs(), vec![3991, 4067, 4054, 4029, 4001]);
+        assert_eq!(page.page_header.content_start_offset, 3991);
+        assert_eq!(page.free_space(), 3991 - 8 - 5 * 2);
+
+        // a freeblock left with less than 4 bytes becomes fragmented bytes
+        page.remove_cell(2, SAMPLE_DB_PAGE_SIZE).unwrap();
+        assert_eq!(page.page_header.first_free_block_start, 4054);
+        let cell_11 = leaf_cell(Some(11), 9, 2);
+        page.insert_cell(1, &cell_11, SAMPLE_DB_PAGE_SIZE).unwrap();
+        assert_eq!(page.page_header.first_free_block_start, 0);
+        assert_eq!(page.page_header.fragmented_free_bytes, 2);
+        assert_eq!(page.get_cell_ptr(1), 4054);
+
+        // the end of a larger freeblock is used
+        page.remove_cell(3, SAMPLE_DB_PAGE_SIZE).unwrap();
+        let cell_5 = leaf_cell(Some(12), 3, 3);
+        page.insert_cell(4, &cell_5, SAMPLE_DB_PAGE_SIZE).unwrap();
+        assert_eq!(page.page_header.first_free_block_start, 4029);
+        assert_eq!(page.read_u16(4031), 20);
+        assert_eq!(page.get_cell_ptr(4), 4049);
+
+        let expected = vec![cell_10, cell_11, cells[0].clone(), cells[3].clone(), cell_5];
+        let mut parsed = Page::from_bytes(2, page.data.clone()).unwrap();
+        assert_eq!(parsed.page_header, page.page_header);
+        assert_eq!(parsed.cells(SAMPLE_DB_PAGE_SIZE), expected);
+
+        let too_large = leaf_cell(Some(13), page.free_space(), 4);
+        assert!(page
+            .insert_cell(0, &too_large, SAMPLE_DB_PAGE_SIZE)
+            .is_err());
+        assert!(page
+            .insert_cell(6, &leaf_cell(Some(14), 2, 5), SAMPLE_DB_PAGE_SIZE)
+            .is_err());
+    }
+
+    #[test]
+    fn test_defragment() {
+        let mut page = Page::new(2, PageType::LeafIndex, 1024, 1024);
+        for i in 0..4 {
+            page.insert_cell(i, &leaf_cell(None, 198, i as u8), 1024)
+                .unwrap();
+        }
+        assert_eq!(page.page_header.content_start_offset, 224);
+        // 2 freeblocks of 200 bytes, not adjacent
+        page.remove_cell(0, 1024).unwrap();
+        page.remove_cell(1, 1024).unwrap();
+        assert_eq!(page.free_space(), 224 - 8 - 2 * 2 + 400);
+
+        // neither a freeblock nor the unallocated space is large enough
+        let large_cell = leaf_cell(None, 228, 9);
+        page.insert_cell(1, &large_cell, 1024).unwrap();
+        assert_eq!(page.page_header.first_free_block_start, 0);
+        assert_eq!(page.page_header.content_start_offset, 1024 - 630);
+        assert_eq!(
+            page.cells(1024),
+            vec![
+                leaf_cell(None, 198, 1),
+                large_cell.clone(),
+                leaf_cell(None, 198, 3)
+            ]
+        );
+
+        // the freeblock of the first cell is merged into the unallocated space
+        page.remove_cell(0, 1024).unwrap();
+        assert_ne!(page.page_header.first_free_block_start, 0);
+        let free_space = page.free_space();
+        page.defragment(1024).unwrap();
+        assert_eq!(page.free_space(), free_space);
+        assert_eq!(page.page_header.first_free_block_start, 0);
+        assert_eq!(*page.cell_ptrs(), vec![794, 594]);
+        let mut parsed = Page::from_bytes(2, page.data.clone()).unwrap();
+        assert_eq!(parsed.page_header, page.page_header);
+        assert_eq!(
+            parsed.cells(1024),
+            vec![large_cell, leaf_cell(None, 198, 3)]
+        );
+    }
+
+    #[test]
+    fn test_new_first_page() {
+        let page = Page::new(1, PageType::LeafTable, 512, 512);
+        assert_eq!(page.data.len(), 512);
+        assert_eq!(page.data[100], 13);
+        assert_eq!(Page::capacity(1, PageType::LeafTable, 512), 512 - 108);
+        assert_eq!(Page::capacity(2, PageType::InteriorIndex, 512), 500);
+        let parsed = Page::from_bytes(1, page.data).unwrap();
+        assert_eq!(parsed.page_header.content_start_offset, 512);
+    }
+
     #[test]
     fn test_parse_first_page() {
         let db = db_bytes();
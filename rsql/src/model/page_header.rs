@@ -1,6 +1,6 @@
 use anyhow::{bail, Result};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PageHeader {
     pub page_type: PageType,
     pub first_free_block_start: u16,
@@ -46,6 +46,35 @@ impl PageHeader {
         })
     }
 
+    /// Creates the header of an empty page of page_type: the cell content area
+    /// starts at content_start_offset, the end of the usable space of the page.
+    pub fn new(page_type: PageType, content_start_offset: usize) -> Self {
+        Self {
+            page_type,
+            first_free_block_start: 0,
+            number_of_cells: 0,
+            // 65536 for an empty page of 64KiB is stored as 0
+            content_start_offset: content_start_offset as u16,
+            fragmented_free_bytes: 0,
+            right_child_page_number: match page_type {
+                PageType::InteriorTable | PageType::InteriorIndex => Some(0),
+                _ => None,
+            },
+        }
+    }
+
+    /// Writes the header at the start of stream, the inverse of parse.
+    pub fn write_to(&self, stream: &mut [u8]) {
+        stream[0] = self.page_type as u8;
+        stream[1..3].copy_from_slice(&self.first_free_block_start.to_be_bytes());
+        stream[3..5].copy_from_slice(&self.number_of_cells.to_be_bytes());
+        stream[5..7].copy_from_slice(&self.content_start_offset.to_be_bytes());
+        stream[7] = self.fragmented_free_bytes;
+        if let Some(right_child_page_number) = self.right_child_page_number {
+            stream[8..12].copy_from_slice(&right_child_page_number.to_be_bytes());
+        }
+    }
+
     // not sure why this fn is qualified with  const
     // https://doc.rust-lang.org/std/keyword.const.html#compile-time-evaluable-functions
     pub const fn is_leaf(&self) -> bool {
@@ -84,10 +113,43 @@ impl PageHeader {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PageType {
     InteriorIndex = 2,
     InteriorTable = 5,
     LeafIndex = 10,
     LeafTable = 13,
 }
+
+impl PageType {
+    /// Type of the interior pages of the b-tree of a page of this type.
+    pub const fn interior(self) -> PageType {
+        match self {
+            PageType::InteriorIndex | PageType::LeafIndex => PageType::InteriorIndex,
+            PageType::InteriorTable | PageType::LeafTable => PageType::InteriorTable,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_and_parse_header() {
+        let mut header = PageHeader::new(PageType::InteriorTable, 4096);
+        header.number_of_cells = 2;
+        header.content_start_offset = 4000;
+        header.right_child_page_number = Some(7);
+        let mut stream = [0; 12];
+        header.write_to(&mut stream);
+        assert_eq!(stream, [5, 0, 0, 0, 2, 15, 160, 0, 0, 0, 0, 7]);
+        assert_eq!(PageHeader::parse(&stream).unwrap(), header);
+
+        let header = PageHeader::new(PageType::LeafIndex, 512);
+        let mut stream = [0xff; 12];
+        header.write_to(&mut stream);
+        assert_eq!(&stream[..8], [10, 0, 0, 0, 0, 2, 0, 0]);
+        assert_eq!(PageHeader::parse(&stream).unwrap(), header);
+    }
+}
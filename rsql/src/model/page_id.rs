@@ -1,6 +1,6 @@
 /// A page number starting with 1 uniquely identifies a page
 /// because a sqlite database is a single file.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct PageId {
     pub page_number: u32,
 }
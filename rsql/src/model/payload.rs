@@ -0,0 +1,132 @@
+use anyhow::{bail, Result};
+
+use crate::access::buffer_pool::BufferPool;
+use crate::model::page_header::PageType;
+use crate::model::page_id::PageId;
+
+/*
+Cell payload spilling rules https://www.sqlite.org/fileformat.html#b_tree_pages
+    U: usable size of a database page (page size - reserved bytes)
+    P: payload size
+    X: max payload that can be stored directly on the b-tree page
+        U-35 for table leaf pages, ((U-12)*64/255)-23 for index pages
+    M: min payload stored on the b-tree page when spilling
+        ((U-12)*32/255)-23
+    K: M+((P-M)%(U-4))
+
+    - P <= X: the entire payload is stored on the b-tree page.
+    - P > X and K <= X: the first K bytes are stored on the b-tree page,
+    the remaining P-K bytes are stored on overflow pages.
+    - P > X and K > X: the first M bytes are stored on the b-tree page,
+    the remaining P-M bytes are stored on overflow pages.
+ */
+
+/// Returns the max payload X that can be stored on a b-tree page of page_type.
+pub fn max_local(usable_size: usize, page_type: &PageType) -> usize {
+    match page_type {
+        PageType::LeafTable | PageType::InteriorTable => usable_size - 35,
+        PageType::LeafIndex | PageType::InteriorIndex => ((usable_size - 12) * 64 / 255) - 23,
+    }
+}
+
+/// Returns the min payload M that is stored on a b-tree page when the payload spills.
+pub fn min_local(usable_size: usize) -> usize {
+    ((usable_size - 12) * 32 / 255) - 23
+}
+
+/// Returns the number of payload bytes stored on the b-tree page for a cell
+/// with payload_size bytes of payload. The rest is stored on overflow pages.
+pub fn local_payload_size(payload_size: usize, usable_size: usize, page_type: &PageType) -> usize {
+    let max_local = max_local(usable_size, page_type);
+    if payload_size <= max_local {
+        return payload_size;
+    }
+    let min_local = min_local(usable_size);
+    let surplus = min_local + ((payload_size - min_local) % (usable_size - 4));
+    if surplus <= max_local {
+        surplus
+    } else {
+        min_local
+    }
+}
+
+/// Reads the payload of a cell whose content starts at the beginning of stream.
+///
+/// stream: cell bytes right after the cell header varints (payload size, rowid).
+/// If the payload spills, the local part is followed by the 4-byte page number
+/// of the first overflow page, the chain is read through buffer_pool.
+pub fn read_payload(
+    stream: &[u8],
+    payload_size: usize,
+    usable_size: usize,
+    page_type: &PageType,
+    buffer_pool: &mut BufferPool,
+) -> Result<Vec<u8>> {
+    let local_size = local_payload_size(payload_size, usable_size, page_type);
+    if stream.len() < local_size {
+        bail!(
+            "Cell payload is truncated: expected {local_size} local bytes, got {}",
+            stream.len()
+        )
+    }
+    let mut payload = Vec::with_capacity(payload_size);
+    payload.extend_from_slice(&stream[..local_size]);
+    if local_size == payload_size {
+        return Ok(payload);
+    }
+
+    let first_overflow_page = match stream.get(local_size..local_size + 4) {
+        Some(bytes) => u32::from_be_bytes(bytes.try_into()?),
+        None => bail!("Cell is missing its first overflow page number"),
+    };
+    let mut next_page = Some(first_overflow_page);
+    while payload.len() < payload_size {
+        let page_number = match next_page {
+            Some(page_number) if page_number != 0 => page_number,
+            _ => bail!(
+                "Overflow chain ended early: read {} of {payload_size} payload bytes",
+                payload.len()
+            ),
+        };
+        let overflow_page = buffer_pool.get_overflow_page(PageId::new(page_number))?;
+        let content = overflow_page.content(usable_size);
+        let remaining = payload_size - payload.len();
+        payload.extend_from_slice(&content[..remaining.min(content.len())]);
+        next_page = overflow_page.next_page_number;
+    }
+
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_payload_size_table_leaf() {
+        // usable size 4096: X = 4061, M = 489
+        assert_eq!(max_local(4096, &PageType::LeafTable), 4061);
+        assert_eq!(min_local(4096), 489);
+        // fits on the page
+        assert_eq!(local_payload_size(100, 4096, &PageType::LeafTable), 100);
+        assert_eq!(local_payload_size(4061, 4096, &PageType::LeafTable), 4061);
+        // K = 489 + ((5000 - 489) % 4092) = 908 <= X
+        assert_eq!(local_payload_size(5000, 4096, &PageType::LeafTable), 908);
+        // K = 489 + ((8000 - 489) % 4092) = 3908 <= X
+        assert_eq!(local_payload_size(8000, 4096, &PageType::LeafTable), 3908);
+        // K = 489 + ((4070 - 489) % 4092) = 4070 > X -> M
+        assert_eq!(local_payload_size(4070, 4096, &PageType::LeafTable), 489);
+    }
+
+    #[test]
+    fn test_local_payload_size_index() {
+        // usable size 4096: X = 1002, M = 489
+        assert_eq!(max_local(4096, &PageType::LeafIndex), 1002);
+        assert_eq!(local_payload_size(1002, 4096, &PageType::LeafIndex), 1002);
+        // K = 489 + ((1500 - 489) % 4092) = 1500 > X -> M
+        assert_eq!(
+            local_payload_size(1500, 4096, &PageType::InteriorIndex),
+            489
+        );
+    }
+}
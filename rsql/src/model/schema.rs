@@ -1,8 +1,15 @@
-use anyhow::Result;
-use arrow_schema::Field;
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow_schema::{DataType, Field};
 
 use crate::model::cell_table_leaf::LeafTableCell;
-use crate::sql::parsing::parse_columns_from_ddl;
+use crate::model::collation::Collation;
+use crate::model::column_value::ColumnValue;
+use crate::model::index_key::SortOrder;
+use crate::sql::parsing::{
+    parse_index_from_ddl, parse_table_from_ddl, parse_trigger_from_ddl, parse_view_from_ddl,
+};
 
 /// Schema Table https://www.sqlite.org/schematab.html
 /// https://www.sqlite.org/fileformat.html#storage_of_the_sql_database_schema
@@ -22,7 +29,7 @@ use crate::sql::parsing::parse_columns_from_ddl;
 /// for the sqlite_schema table itself.
 /// The sqlite_schema table contains entries for internal schema objects
 /// in addition to application- and programmer-defined objects.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum SchemaObjType {
     Table,
     Index,
@@ -30,6 +37,127 @@ pub enum SchemaObjType {
     Trigger,
 }
 
+impl SchemaObjType {
+    /// Returns the object type from the `type` column of sqlite_schema.
+    pub fn from_name(name: &str) -> Result<Self> {
+        Ok(match name {
+            "table" => SchemaObjType::Table,
+            "index" => SchemaObjType::Index,
+            "view" => SchemaObjType::View,
+            "trigger" => SchemaObjType::Trigger,
+            _ => bail!("Unknown schema object type: {name}"),
+        })
+    }
+}
+
+/// Keys of the metadata of the table column fields, see `parse_columns_from_ddl`.
+/// Affinity of the column: TEXT, NUMERIC, INTEGER, REAL or BLOB.
+pub const FIELD_AFFINITY: &str = "affinity";
+/// Declared type as written in the DDL, empty if the column has no type.
+pub const FIELD_DECLARED_TYPE: &str = "declared_type";
+/// SQL text of the DEFAULT value, absent if the column has no default.
+pub const FIELD_DEFAULT: &str = "default";
+/// Sort order (ASC or DESC) of a PRIMARY KEY column, absent for other columns.
+pub const FIELD_PRIMARY_KEY: &str = "primary_key";
+/// "true" for an INTEGER PRIMARY KEY AUTOINCREMENT column.
+pub const FIELD_AUTOINCREMENT: &str = "autoincrement";
+/// Collation name in uppercase, absent if the column uses the default BINARY.
+pub const FIELD_COLLATION: &str = "collation";
+/// "true" for a column with a UNIQUE constraint.
+pub const FIELD_UNIQUE: &str = "unique";
+/// "true" for the hidden columns `rowid`, `oid` and `_rowid_` of a table, see
+/// `SchemaObject::rowid_columns`.
+pub const FIELD_HIDDEN: &str = "hidden";
+
+/// Names of the rowid of a table, unless a column has the name.
+pub const ROWID_NAMES: [&str; 3] = ["rowid", "oid", "_rowid_"];
+
+/// Definition of a table parsed from `CREATE TABLE`, besides its columns.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TableDef {
+    // index of the INTEGER PRIMARY KEY column, an alias for the rowid.
+    // Its value is the rowid of the row and it is stored as NULL in the record.
+    pub rowid_alias: Option<usize>,
+    pub without_rowid: bool,
+    // Automatic indexes `sqlite_autoindex_<table>_<n>` of the UNIQUE constraints and
+    // of a PRIMARY KEY that is not the rowid, the n-th one is at index n - 1. The first
+    // one of a WITHOUT ROWID table may be its PRIMARY KEY, the key of the table btree.
+    pub autoindexes: Vec<IndexDef>,
+}
+
+/// Definition of an index parsed from `CREATE [UNIQUE] INDEX`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexDef {
+    pub unique: bool,
+    pub columns: Vec<IndexedColumn>,
+    // expression of a partial index `WHERE ...`
+    pub where_clause: Option<String>,
+}
+
+impl IndexDef {
+    /// Definition of an index sqlite creates for a UNIQUE or PRIMARY KEY constraint,
+    /// named `sqlite_autoindex_<table>_<n>`. These indexes have no sql so their
+    /// columns are not known from the schema row: they are the ones of the n-th
+    /// constraint of the table (see `TableDef::autoindexes`), set once the table is read.
+    pub fn autoindex() -> Self {
+        IndexDef {
+            unique: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// A column of an index: a table column or an expression on the table columns.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedColumn {
+    // column name, or the sql text of the expression
+    pub name: String,
+    pub expression: bool,
+    // None when the index uses the collation of the table column
+    pub collation: Option<Collation>,
+    pub sort_order: SortOrder,
+}
+
+/// Definition of a view parsed from `CREATE VIEW`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ViewDef {
+    // column names given after the view name, empty if not given
+    pub columns: Vec<String>,
+    // sql text of the SELECT statement
+    pub select: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerTiming {
+    Before,
+    After,
+    InsteadOf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TriggerEvent {
+    Delete,
+    Insert,
+    // columns of `UPDATE OF col, ...`, empty for any column
+    Update { columns: Vec<String> },
+}
+
+/// Definition of a trigger parsed from `CREATE TRIGGER`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerDef {
+    pub timing: TriggerTiming,
+    pub event: TriggerEvent,
+}
+
+/// Details of a schema object specific to its type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SchemaObjDetail {
+    Table(TableDef),
+    Index(IndexDef),
+    View(ViewDef),
+    Trigger(TriggerDef),
+}
+
 #[derive(Debug)]
 pub struct SchemaObject {
     pub obj_type: SchemaObjType,
@@ -37,36 +165,103 @@ pub struct SchemaObject {
     pub tbl_name: String,
     // The maximum page number is 4,294,967,294 (2^32 - 2): use u32 not i32
     // https://www.sqlite.org/fileformat.html#pages
+    // Zero for views and triggers which do not have a btree.
     pub rootpage: u32,
+    // Empty for the automatic indexes of UNIQUE and PRIMARY KEY constraints
+    // whose sql is NULL.
     pub sql: String,
+    // columns of a table, empty for other types
     pub columns: Vec<Field>,
+    pub detail: SchemaObjDetail,
 }
 
 impl SchemaObject {
     pub fn parse(cell: &LeafTableCell) -> Result<Self> {
-        let obj_type = SchemaObjType::Table; // TODO fix hard-coded table here, include other types
+        let obj_type =
+            SchemaObjType::from_name(&String::try_from(cell.payload.value_at_index(0))?)?;
         let name = String::try_from(cell.payload.value_at_index(1))?;
         let tbl_name = String::try_from(cell.payload.value_at_index(2))?;
-        let rootpage = i32::try_from(cell.payload.value_at_index(3))?;
-        let sql = String::try_from(cell.payload.value_at_index(4))?;
-        let columns = parse_columns_from_ddl(&sql)?;
+        let rootpage = cell
+            .payload
+            .value_at_index(3)
+            .as_i64()
+            .ok_or_else(|| anyhow!("rootpage of {name} is not an integer"))?;
+        let sql = match cell.payload.value_at_index(4) {
+            ColumnValue::Null => String::new(),
+            value => String::try_from(value)?,
+        };
+
+        let (columns, detail) = Self::parse_sql(&obj_type, &sql)
+            .with_context(|| format!("Failed to parse the sql of {name}: {sql}"))?;
 
         Ok(Self {
             obj_type,
             name,
             tbl_name,
-            rootpage: rootpage as u32,
+            rootpage: u32::try_from(rootpage)?,
             sql,
             columns,
+            detail,
+        })
+    }
+
+    /// Returns the index of the rowid alias column of a table.
+    pub fn rowid_alias(&self) -> Option<usize> {
+        match &self.detail {
+            SchemaObjDetail::Table(table_def) => table_def.rowid_alias,
+            _ => None,
+        }
+    }
+
+    /// Returns the hidden columns whose value is the rowid of a table: `rowid`, `oid`
+    /// and `_rowid_`, except the names of its columns. None for a WITHOUT ROWID table.
+    /// They come after the columns of the table and `SELECT *` does not return them.
+    pub fn rowid_columns(&self) -> Vec<Field> {
+        match &self.detail {
+            SchemaObjDetail::Table(table_def) if !table_def.without_rowid => ROWID_NAMES
+                .iter()
+                .filter(|name| {
+                    !self
+                        .columns
+                        .iter()
+                        .any(|column| column.name().eq_ignore_ascii_case(name))
+                })
+                .map(|name| {
+                    Field::new(*name, DataType::Int64, false).with_metadata(HashMap::from([(
+                        FIELD_HIDDEN.to_owned(),
+                        "true".to_owned(),
+                    )]))
+                })
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    fn parse_sql(obj_type: &SchemaObjType, sql: &str) -> Result<(Vec<Field>, SchemaObjDetail)> {
+        Ok(match obj_type {
+            SchemaObjType::Table => {
+                let (columns, table_def) = parse_table_from_ddl(sql)?;
+                (columns, SchemaObjDetail::Table(table_def))
+            }
+            SchemaObjType::Index if sql.is_empty() => {
+                (vec![], SchemaObjDetail::Index(IndexDef::autoindex()))
+            }
+            SchemaObjType::Index => (vec![], SchemaObjDetail::Index(parse_index_from_ddl(sql)?)),
+            SchemaObjType::View => (vec![], SchemaObjDetail::View(parse_view_from_ddl(sql)?)),
+            SchemaObjType::Trigger => (
+                vec![],
+                SchemaObjDetail::Trigger(parse_trigger_from_ddl(sql)?),
+            ),
         })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use arrow_schema::DataType;
+    use std::path::PathBuf;
 
-    use crate::model::column_value::ColumnValue;
+    use crate::model::data_record::DataRecord;
+    use crate::model::database::Database;
 
     use super::*;
 
@@ -111,12 +306,125 @@ mod tests {
         assert_eq!(schema_obj.columns.len(), 3);
 
         assert_eq!(schema_obj.columns[0].name(), "id");
-        assert_eq!(schema_obj.columns[0].data_type(), &DataType::Int32);
+        assert_eq!(schema_obj.columns[0].data_type(), &DataType::Int64);
 
         assert_eq!(schema_obj.columns[1].name(), "name");
         assert_eq!(schema_obj.columns[1].data_type(), &DataType::Utf8);
 
         assert_eq!(schema_obj.columns[2].name(), "description");
         assert_eq!(schema_obj.columns[2].data_type(), &DataType::Utf8);
+        assert_eq!(schema_obj.rowid_alias(), Some(0));
+        let rowid_columns = schema_obj.rowid_columns();
+        let names: Vec<&String> = rowid_columns.iter().map(|field| field.name()).collect();
+        assert_eq!(names, ["rowid", "oid", "_rowid_"]);
+        assert_eq!(rowid_columns[0].metadata()[FIELD_HIDDEN], "true");
+    }
+
+    #[test]
+    fn test_parse_schema_objects_of_all_types() {
+        // objects.db: table users with a UNIQUE column, two indexes, a view and two triggers
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/objects.db");
+        let db = Database::new(db_path.to_str().unwrap()).unwrap();
+        let find = |name: &str| {
+            db.db_meta
+                .schema_objects
+                .iter()
+                .find(|schema_obj| schema_obj.name == name)
+                .unwrap()
+        };
+
+        let users = find("users");
+        assert_eq!(users.obj_type, SchemaObjType::Table);
+        assert_eq!(users.columns.len(), 3);
+
+        let autoindex = find("sqlite_autoindex_users_1");
+        assert_eq!(autoindex.obj_type, SchemaObjType::Index);
+        assert_eq!(autoindex.tbl_name, "users");
+        assert!(autoindex.rootpage > 0);
+        assert_eq!(autoindex.sql, "");
+        // the index of the email column, from the table definition
+        match &autoindex.detail {
+            SchemaObjDetail::Index(index_def) => {
+                assert!(index_def.unique);
+                assert_eq!(index_def.columns.len(), 1);
+                assert_eq!(index_def.columns[0].name, "email");
+            }
+            detail => panic!("unexpected detail {detail:?}"),
+        }
+
+        let index = find("idx_users_name");
+        assert_eq!(index.obj_type, SchemaObjType::Index);
+        match &index.detail {
+            SchemaObjDetail::Index(index_def) => {
+                assert!(index_def.unique);
+                assert_eq!(index_def.columns.len(), 2);
+                assert_eq!(index_def.columns[0].name, "name");
+                assert_eq!(index_def.columns[0].collation, Some(Collation::NoCase));
+                assert_eq!(index_def.columns[0].sort_order, SortOrder::Desc);
+                assert_eq!(index_def.columns[1].name, "id");
+            }
+            detail => panic!("expected an index, got {detail:?}"),
+        }
+
+        let partial_index = find("idx_users_email");
+        match &partial_index.detail {
+            SchemaObjDetail::Index(index_def) => {
+                assert!(!index_def.unique);
+                assert!(index_def.columns[0].expression);
+                assert_eq!(index_def.where_clause.as_deref(), Some("email is not null"));
+            }
+            detail => panic!("expected an index, got {detail:?}"),
+        }
+
+        let view = find("v_users");
+        assert_eq!(view.obj_type, SchemaObjType::View);
+        assert_eq!(view.rootpage, 0);
+        assert_eq!(
+            view.detail,
+            SchemaObjDetail::View(ViewDef {
+                columns: vec![],
+                select: "select id, name from users where id > 1".to_owned(),
+            })
+        );
+
+        let trigger = find("trg_users_update");
+        assert_eq!(trigger.obj_type, SchemaObjType::Trigger);
+        assert_eq!(trigger.tbl_name, "users");
+        assert_eq!(
+            trigger.detail,
+            SchemaObjDetail::Trigger(TriggerDef {
+                timing: TriggerTiming::After,
+                event: TriggerEvent::Update {
+                    columns: vec!["name".to_owned(), "email".to_owned()]
+                },
+            })
+        );
+        assert_eq!(
+            find("trg_users_delete").detail,
+            SchemaObjDetail::Trigger(TriggerDef {
+                timing: TriggerTiming::Before,
+                event: TriggerEvent::Delete,
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_schema_object_error() {
+        // a row of sqlite_schema with an unknown type
+        let record = DataRecord {
+            values: vec![
+                ColumnValue::Text("tabel".to_owned()),
+                ColumnValue::Text("t".to_owned()),
+                ColumnValue::Text("t".to_owned()),
+                ColumnValue::int8(2),
+                ColumnValue::Text("CREATE TABLE t(a)".to_owned()),
+            ],
+            rowid: None,
+        };
+        let cell = LeafTableCell {
+            rowid: 1,
+            payload: record,
+        };
+        assert!(SchemaObject::parse(&cell).is_err());
     }
 }
@@ -0,0 +1,76 @@
+use std::sync::Arc;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::binary::compare;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
+/// expr [NOT] BETWEEN low AND high, equivalent to expr >= low AND expr <= high
+/// (negated: expr < low OR expr > high) where expr is evaluated once.
+/// https://www.sqlite.org/lang_expr.html#between
+#[derive(Debug)]
+pub struct PhysicalBetween {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+    pub(crate) low: Arc<dyn PhysicalExpr>,
+    pub(crate) high: Arc<dyn PhysicalExpr>,
+    pub(crate) negated: bool,
+}
+
+impl PhysicalExpr for PhysicalBetween {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        let value = self.expr.evaluate(record);
+        let low = self.low.evaluate(record);
+        let high = self.high.evaluate(record);
+        let above_low = compare(&*self.expr, value.clone(), &*self.low, low).map(|o| o.is_ge());
+        let below_high = compare(&*self.expr, value, &*self.high, high).map(|o| o.is_le());
+
+        // three-valued AND of both bounds
+        let between = match (above_low, below_high) {
+            (Some(false), _) | (_, Some(false)) => Some(false),
+            (Some(true), Some(true)) => Some(true),
+            _ => None,
+        };
+        match between {
+            Some(between) => ColumnValue::from_bool(between != self.negated),
+            None => ColumnValue::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::expression::literal::PhysicalLiteral;
+
+    #[test]
+    fn test_between() {
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        let lit = |value| Arc::new(PhysicalLiteral { value }) as Arc<dyn PhysicalExpr>;
+        let between = |value, low, high, negated| {
+            PhysicalBetween {
+                expr: lit(value),
+                low: lit(low),
+                high: lit(high),
+                negated,
+            }
+            .evaluate(&record)
+        };
+        let int = ColumnValue::int64;
+
+        assert_eq!(between(int(2), int(1), int(3), false), ColumnValue::One);
+        assert_eq!(between(int(3), int(1), int(3), false), ColumnValue::One);
+        assert_eq!(between(int(4), int(1), int(3), true), ColumnValue::One);
+        // results of sqlite3: 1 between null and 0 is false
+        assert_eq!(
+            between(int(1), ColumnValue::Null, int(0), false),
+            ColumnValue::Zero
+        );
+        assert_eq!(
+            between(int(1), ColumnValue::Null, int(2), false),
+            ColumnValue::Null
+        );
+    }
+}
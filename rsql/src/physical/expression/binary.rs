@@ -0,0 +1,367 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use arrow_array::{ArrayRef, RecordBatch};
+use datafusion_expr::Operator;
+
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
+use crate::model::column_value::ColumnValue;
+use crate::model::conversion::Number;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::kernels;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::batch::{array_value, values_array};
+
+/// Binary operator expression: arithmetic (+ - * / %), comparison (= <> < <= > >=),
+/// logical (AND OR), concatenation (||) and IS [NOT] DISTINCT FROM.
+/// https://www.sqlite.org/lang_expr.html#operators_and_parse_affecting_attributes
+///
+/// Operators follow SQLite semantics: booleans are the integers 1 and 0, NULL operands
+/// give NULL except for AND/OR (three-valued logic) and IS [NOT] DISTINCT FROM.
+#[derive(Debug)]
+pub struct PhysicalBinary {
+    left: Arc<dyn PhysicalExpr>,
+    op: Operator,
+    right: Arc<dyn PhysicalExpr>,
+}
+
+impl PhysicalBinary {
+    pub fn try_new(
+        left: Arc<dyn PhysicalExpr>,
+        op: Operator,
+        right: Arc<dyn PhysicalExpr>,
+    ) -> Result<Self> {
+        match op {
+            Operator::Plus
+            | Operator::Minus
+            | Operator::Multiply
+            | Operator::Divide
+            | Operator::Modulo
+            | Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq
+            | Operator::And
+            | Operator::Or
+            | Operator::StringConcat
+            | Operator::IsDistinctFrom
+            | Operator::IsNotDistinctFrom => Ok(PhysicalBinary { left, op, right }),
+            _ => bail!("unsupported operator {op}"),
+        }
+    }
+}
+
+impl PhysicalBinary {
+    // Applies the operator to the value of the left operand and the one of the right
+    // operand returned by right. AND and OR do not evaluate the right operand when the
+    // left one decides.
+    fn apply(&self, left: ColumnValue, right: impl FnOnce() -> ColumnValue) -> ColumnValue {
+        match (self.op, left.is_true()) {
+            (Operator::And, Some(false)) => return ColumnValue::Zero,
+            (Operator::Or, Some(true)) => return ColumnValue::One,
+            _ => {}
+        }
+        let right = right();
+
+        match self.op {
+            Operator::And => match right.is_true() {
+                Some(false) => ColumnValue::Zero,
+                Some(true) if !left.is_null() => ColumnValue::One,
+                _ => ColumnValue::Null,
+            },
+            Operator::Or => match right.is_true() {
+                Some(true) => ColumnValue::One,
+                Some(false) if !left.is_null() => ColumnValue::Zero,
+                _ => ColumnValue::Null,
+            },
+            Operator::StringConcat => match (left.to_text(), right.to_text()) {
+                (Some(left), Some(right)) => ColumnValue::Text(left + &right),
+                _ => ColumnValue::Null,
+            },
+            Operator::IsDistinctFrom | Operator::IsNotDistinctFrom => {
+                let equal = match (left.is_null(), right.is_null()) {
+                    (true, true) => true,
+                    (false, false) => {
+                        compare(&*self.left, left, &*self.right, right) == Some(Ordering::Equal)
+                    }
+                    _ => false,
+                };
+                ColumnValue::from_bool(equal == (self.op == Operator::IsNotDistinctFrom))
+            }
+            Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq => match compare(&*self.left, left, &*self.right, right) {
+                Some(ordering) => ColumnValue::from_bool(match self.op {
+                    Operator::Eq => ordering.is_eq(),
+                    Operator::NotEq => ordering.is_ne(),
+                    Operator::Lt => ordering.is_lt(),
+                    Operator::LtEq => ordering.is_le(),
+                    Operator::Gt => ordering.is_gt(),
+                    _ => ordering.is_ge(),
+                }),
+                None => ColumnValue::Null,
+            },
+            op => match (left.to_number(), right.to_number()) {
+                (Some(left), Some(right)) => arithmetic(op, left, right),
+                _ => ColumnValue::Null,
+            },
+        }
+    }
+}
+
+impl PhysicalExpr for PhysicalBinary {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        self.apply(self.left.evaluate(record), || self.right.evaluate(record))
+    }
+
+    // The operands are evaluated on the batch, then the operator is applied to their
+    // arrays by a kernel, or row by row to their values when no kernel applies.
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let left = self.left.evaluate_batch(batch)?;
+        let right = self.right.evaluate_batch(batch)?;
+        let result = match self.op {
+            Operator::And | Operator::Or => kernels::logical(self.op, &left, &right)?,
+            Operator::Eq
+            | Operator::NotEq
+            | Operator::Lt
+            | Operator::LtEq
+            | Operator::Gt
+            | Operator::GtEq
+            | Operator::IsDistinctFrom
+            | Operator::IsNotDistinctFrom => {
+                let (left_affinity, right_affinity) =
+                    comparison_affinities(&*self.left, &*self.right);
+                kernels::compare(
+                    self.op,
+                    (&left, left_affinity),
+                    (&right, right_affinity),
+                    comparison_collation(&*self.left, &*self.right),
+                )?
+            }
+            Operator::StringConcat => None,
+            _ => kernels::arithmetic(self.op, &left, &right)?,
+        };
+        if let Some(result) = result {
+            return Ok(result);
+        }
+        let values = (0..batch.num_rows())
+            .map(|row| {
+                let right = array_value(&right, row)?;
+                Ok(self.apply(array_value(&left, row)?, || right))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        values_array(&values)
+    }
+}
+
+/// Compares the values of two expressions, None if one of them is NULL.
+///
+/// The values are converted by comparison_affinities and TEXT values are compared
+/// with comparison_collation.
+/// https://www.sqlite.org/datatype3.html#type_conversions_prior_to_comparison
+pub fn compare(
+    left_expr: &dyn PhysicalExpr,
+    left: ColumnValue,
+    right_expr: &dyn PhysicalExpr,
+    right: ColumnValue,
+) -> Option<Ordering> {
+    if left.is_null() || right.is_null() {
+        return None;
+    }
+    let (left_affinity, right_affinity) = comparison_affinities(left_expr, right_expr);
+    let left = match left_affinity {
+        Some(affinity) => left.apply_affinity(affinity),
+        None => left,
+    };
+    let right = match right_affinity {
+        Some(affinity) => right.apply_affinity(affinity),
+        None => right,
+    };
+    let collation = comparison_collation(left_expr, right_expr);
+    Some(left.compare(&right, &collation))
+}
+
+/// Affinities applied to the left and right operands of a comparison, the affinity
+/// of an operand is applied to the other one:
+/// - INTEGER, REAL or NUMERIC against TEXT, BLOB or no affinity: NUMERIC is applied.
+/// - TEXT against no affinity: TEXT is applied.
+pub fn comparison_affinities(
+    left_expr: &dyn PhysicalExpr,
+    right_expr: &dyn PhysicalExpr,
+) -> (Option<Affinity>, Option<Affinity>) {
+    let is_numeric = |affinity: Option<Affinity>| {
+        matches!(
+            affinity,
+            Some(Affinity::Integer | Affinity::Real | Affinity::Numeric)
+        )
+    };
+    // BLOB affinity was named NONE, it does not convert values
+    let has_none = |affinity: Option<Affinity>| matches!(affinity, None | Some(Affinity::Blob));
+    let (left_affinity, right_affinity) = (left_expr.affinity(), right_expr.affinity());
+
+    if is_numeric(left_affinity) && !is_numeric(right_affinity) {
+        (None, Some(Affinity::Numeric))
+    } else if is_numeric(right_affinity) && !is_numeric(left_affinity) {
+        (Some(Affinity::Numeric), None)
+    } else if left_affinity == Some(Affinity::Text) && has_none(right_affinity) {
+        (None, Some(Affinity::Text))
+    } else if right_affinity == Some(Affinity::Text) && has_none(left_affinity) {
+        (Some(Affinity::Text), None)
+    } else {
+        (None, None)
+    }
+}
+
+/// Collation of a comparison: the one of the left operand, else of the right one.
+pub fn comparison_collation(
+    left_expr: &dyn PhysicalExpr,
+    right_expr: &dyn PhysicalExpr,
+) -> Collation {
+    left_expr
+        .collation()
+        .or(right_expr.collation())
+        .unwrap_or_default()
+}
+
+// Integer arithmetic overflowing i64 is done on reals, division and modulo by zero are NULL.
+fn arithmetic(op: Operator, left: Number, right: Number) -> ColumnValue {
+    if let (Number::Integer(l), Number::Integer(r)) = (left, right) {
+        let result = match op {
+            Operator::Plus => l.checked_add(r),
+            Operator::Minus => l.checked_sub(r),
+            Operator::Multiply => l.checked_mul(r),
+            Operator::Divide if r == 0 => return ColumnValue::Null,
+            Operator::Divide => l.checked_div(r),
+            Operator::Modulo if r == 0 => return ColumnValue::Null,
+            // i64::MIN % -1 overflows
+            _ => Some(l.checked_rem(r).unwrap_or(0)),
+        };
+        if let Some(result) = result {
+            return ColumnValue::int64(result);
+        }
+    }
+    let (l, r) = (left.as_f64(), right.as_f64());
+    ColumnValue::real(match op {
+        Operator::Plus => l + r,
+        Operator::Minus => l - r,
+        Operator::Multiply => l * r,
+        Operator::Divide if r == 0.0 => return ColumnValue::Null,
+        Operator::Divide => l / r,
+        // the remainder of reals is the one of their integer parts
+        _ => match (l as i64, r as i64) {
+            (_, 0) => return ColumnValue::Null,
+            (l, r) => l.checked_rem(r).unwrap_or(0) as f64,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+    use crate::physical::expression::literal::PhysicalLiteral;
+
+    fn lit(value: ColumnValue) -> Arc<dyn PhysicalExpr> {
+        Arc::new(PhysicalLiteral { value })
+    }
+
+    fn eval(left: ColumnValue, op: Operator, right: ColumnValue) -> ColumnValue {
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        PhysicalBinary::try_new(lit(left), op, lit(right))
+            .unwrap()
+            .evaluate(&record)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let int = ColumnValue::int64;
+        let real = ColumnValue::real;
+        assert_eq!(eval(int(7), Operator::Divide, int(2)), int(3));
+        assert_eq!(eval(int(7), Operator::Divide, int(0)), ColumnValue::Null);
+        assert_eq!(eval(int(-7), Operator::Modulo, int(3)), int(-1));
+        assert_eq!(eval(real(5.5), Operator::Modulo, int(2)), real(1.0));
+        assert_eq!(eval(int(2), Operator::Multiply, real(2.0)), real(4.0));
+        assert_eq!(
+            eval(int(i64::MAX), Operator::Plus, int(1)),
+            real(9223372036854775808.0)
+        );
+        assert_eq!(
+            eval(
+                ColumnValue::Text("12abc".to_owned()),
+                Operator::Plus,
+                int(1)
+            ),
+            int(13)
+        );
+        assert_eq!(
+            eval(ColumnValue::Null, Operator::Minus, int(1)),
+            ColumnValue::Null
+        );
+        assert_eq!(
+            eval(int(1), Operator::StringConcat, real(2.5)),
+            ColumnValue::Text("12.5".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_three_valued_logic() {
+        let (null, t, f) = (ColumnValue::Null, ColumnValue::One, ColumnValue::Zero);
+        assert_eq!(eval(null.clone(), Operator::And, f.clone()), f);
+        assert_eq!(eval(null.clone(), Operator::And, t.clone()), null);
+        assert_eq!(eval(t.clone(), Operator::And, t.clone()), t);
+        assert_eq!(eval(null.clone(), Operator::Or, t.clone()), t);
+        assert_eq!(eval(f.clone(), Operator::Or, null.clone()), null);
+        assert_eq!(eval(f.clone(), Operator::Or, f.clone()), f);
+        assert_eq!(eval(null.clone(), Operator::Eq, null.clone()), null);
+        assert_eq!(eval(null.clone(), Operator::IsNotDistinctFrom, null), t);
+        assert_eq!(eval(t.clone(), Operator::IsDistinctFrom, f), t);
+    }
+
+    #[test]
+    fn test_comparison_affinity() {
+        let record = DataRecord {
+            values: vec![
+                ColumnValue::int64(10),
+                ColumnValue::Text("10".to_owned()),
+                ColumnValue::Text("abc".to_owned()),
+            ],
+            rowid: None,
+        };
+        let column = |col_index, affinity| {
+            Arc::new(PhysicalColByIndex {
+                col_index,
+                affinity: Some(affinity),
+                collation: None,
+            }) as Arc<dyn PhysicalExpr>
+        };
+        let text = |text: &str| lit(ColumnValue::Text(text.to_owned()));
+        let int = |int| lit(ColumnValue::int64(int));
+        let eq = |left, right| {
+            PhysicalBinary::try_new(left, Operator::Eq, right)
+                .unwrap()
+                .evaluate(&record)
+        };
+
+        // INTEGER column = '10': NUMERIC affinity is applied to the literal
+        assert_eq!(
+            eq(column(0, Affinity::Integer), text("10")),
+            ColumnValue::One
+        );
+        // TEXT column = 10: TEXT affinity is applied to the literal
+        assert_eq!(eq(column(1, Affinity::Text), int(10)), ColumnValue::One);
+        // literals have no affinity: INTEGER is less than TEXT
+        assert_eq!(eq(int(10), text("10")), ColumnValue::Zero);
+        let lt = PhysicalBinary::try_new(int(10), Operator::Lt, column(2, Affinity::Text));
+        assert_eq!(lt.unwrap().evaluate(&record), ColumnValue::One);
+    }
+}
@@ -0,0 +1,92 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::binary::compare;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
+/// CASE [base] WHEN when THEN then ... [ELSE else] END
+/// https://www.sqlite.org/lang_expr.html#the_case_expression
+///
+/// With a base expression, the result is the THEN of the first WHEN equal to the base
+/// (NULL equals nothing). Without, the THEN of the first true WHEN.
+/// NULL if no WHEN matches and there is no ELSE.
+#[derive(Debug)]
+pub struct PhysicalCase {
+    pub(crate) base: Option<Arc<dyn PhysicalExpr>>,
+    pub(crate) when_then: Vec<(Arc<dyn PhysicalExpr>, Arc<dyn PhysicalExpr>)>,
+    pub(crate) else_expr: Option<Arc<dyn PhysicalExpr>>,
+}
+
+impl PhysicalExpr for PhysicalCase {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        let base_value = self.base.as_ref().map(|base| base.evaluate(record));
+        for (when, then) in &self.when_then {
+            let when_value = when.evaluate(record);
+            let matched = match (&self.base, &base_value) {
+                (Some(base), Some(base_value)) => {
+                    compare(&**base, base_value.clone(), &**when, when_value)
+                        == Some(Ordering::Equal)
+                }
+                _ => when_value.is_true() == Some(true),
+            };
+            if matched {
+                return then.evaluate(record);
+            }
+        }
+        match &self.else_expr {
+            Some(else_expr) => else_expr.evaluate(record),
+            None => ColumnValue::Null,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::expression::literal::PhysicalLiteral;
+
+    #[test]
+    fn test_case() {
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        let lit = |value| Arc::new(PhysicalLiteral { value }) as Arc<dyn PhysicalExpr>;
+        let text = |text: &str| lit(ColumnValue::Text(text.to_owned()));
+        let int = |int| lit(ColumnValue::int64(int));
+
+        let searched = PhysicalCase {
+            base: None,
+            when_then: vec![
+                (lit(ColumnValue::Null), text("null")),
+                (int(0), text("zero")),
+                (int(2), text("two")),
+            ],
+            else_expr: None,
+        };
+        assert_eq!(
+            searched.evaluate(&record),
+            ColumnValue::Text("two".to_owned())
+        );
+
+        let simple = |base| PhysicalCase {
+            base: Some(base),
+            when_then: vec![
+                (lit(ColumnValue::Null), text("null")),
+                (int(1), text("one")),
+            ],
+            else_expr: Some(text("other")),
+        };
+        assert_eq!(
+            simple(int(1)).evaluate(&record),
+            ColumnValue::Text("one".to_owned())
+        );
+        // NULL does not match WHEN NULL
+        assert_eq!(
+            simple(lit(ColumnValue::Null)).evaluate(&record),
+            ColumnValue::Text("other".to_owned())
+        );
+    }
+}
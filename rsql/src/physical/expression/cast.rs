@@ -1,15 +1,38 @@
+use std::sync::Arc;
+
+use crate::model::affinity::Affinity;
 use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
 use crate::physical::expression::physical_expr::PhysicalExpr;
 
+/// CAST(expr AS type), the type name determines the affinity of the conversion
+/// like for a column declared with it.
+/// https://www.sqlite.org/lang_expr.html#castexpr
 #[derive(Debug)]
-struct PhysicalCast;
+pub struct PhysicalCast {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+    pub(crate) affinity: Affinity,
+}
 
 impl PhysicalExpr for PhysicalCast {
-    fn evaluate(&self, _record: &DataRecord) -> ColumnValue {
-        todo!()
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        self.expr.evaluate(record).cast(self.affinity)
+    }
+
+    fn affinity(&self) -> Option<Affinity> {
+        Some(self.affinity)
     }
 }
 
 #[test]
-fn cast_float_to_int() {}
+fn cast_float_to_int() {
+    let record = DataRecord {
+        values: vec![ColumnValue::real(3.9)],
+        rowid: None,
+    };
+    let cast = PhysicalCast {
+        expr: Arc::new(crate::physical::expression::col_by_index::PhysicalColByIndex::new(0)),
+        affinity: Affinity::Integer,
+    };
+    assert_eq!(cast.evaluate(&record), ColumnValue::int64(3));
+}
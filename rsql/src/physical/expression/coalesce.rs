@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
+/// coalesce(X, Y, ...) and ifnull(X, Y): the first argument that is not NULL, NULL if
+/// they all are. The arguments after it are not evaluated.
+/// https://www.sqlite.org/lang_corefunc.html#coalesce
+#[derive(Debug)]
+pub struct PhysicalCoalesce {
+    pub(crate) args: Vec<Arc<dyn PhysicalExpr>>,
+}
+
+impl PhysicalExpr for PhysicalCoalesce {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        self.args
+            .iter()
+            .map(|arg| arg.evaluate(record))
+            .find(|value| !value.is_null())
+            .unwrap_or(ColumnValue::Null)
+    }
+}
@@ -1,11 +1,28 @@
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
 use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
 use crate::physical::expression::physical_expr::PhysicalExpr;
+use anyhow::Result;
+use arrow_array::{ArrayRef, RecordBatch};
 
 #[derive(Debug)]
 pub struct PhysicalColByIndex {
     // index of the column in the record values
     pub(crate) col_index: usize,
+    // affinity and collation of the table column, None for computed columns
+    pub(crate) affinity: Option<Affinity>,
+    pub(crate) collation: Option<Collation>,
+}
+
+impl PhysicalColByIndex {
+    pub fn new(col_index: usize) -> Self {
+        PhysicalColByIndex {
+            col_index,
+            affinity: None,
+            collation: None,
+        }
+    }
 }
 
 impl PhysicalExpr for PhysicalColByIndex {
@@ -29,6 +46,19 @@ impl PhysicalExpr for PhysicalColByIndex {
             col => col.clone(),
         }
     }
+
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        // arrays are reference counted, the column is shared, not copied
+        Ok(batch.column(self.col_index).clone())
+    }
+
+    fn affinity(&self) -> Option<Affinity> {
+        self.affinity
+    }
+
+    fn collation(&self) -> Option<Collation> {
+        self.collation
+    }
 }
 
 #[test]
@@ -43,17 +73,17 @@ fn test_col_index() {
     };
 
     assert_eq!(
-        PhysicalColByIndex { col_index: 1 }.evaluate(&data_record),
+        PhysicalColByIndex::new(1).evaluate(&data_record),
         ColumnValue::Text("Light Green".to_owned())
     );
 
     assert_eq!(
-        PhysicalColByIndex { col_index: 0 }.evaluate(&data_record),
+        PhysicalColByIndex::new(0).evaluate(&data_record),
         ColumnValue::Text("Granny Smith".to_owned())
     );
 
     assert_eq!(
-        PhysicalColByIndex { col_index: 2 }.evaluate(&data_record),
+        PhysicalColByIndex::new(2).evaluate(&data_record),
         ColumnValue::Text("3".to_owned())
     );
 }
@@ -0,0 +1,37 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_array::{ArrayRef, RecordBatch};
+
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
+/// expr COLLATE name: the value of expr compared with the collation name.
+/// The COLLATE operator keeps the affinity of expr.
+/// https://www.sqlite.org/lang_expr.html#collateop
+#[derive(Debug)]
+pub struct PhysicalCollate {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+    pub(crate) collation: Collation,
+}
+
+impl PhysicalExpr for PhysicalCollate {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        self.expr.evaluate(record)
+    }
+
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        self.expr.evaluate_batch(batch)
+    }
+
+    fn affinity(&self) -> Option<Affinity> {
+        self.expr.affinity()
+    }
+
+    fn collation(&self) -> Option<Collation> {
+        Some(self.collation)
+    }
+}
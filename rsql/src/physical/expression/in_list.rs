@@ -0,0 +1,92 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::binary::compare;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
+/// expr [NOT] IN (list)
+/// https://www.sqlite.org/lang_expr.html#the_in_and_not_in_operators
+///
+/// True if expr equals a value of the list. Otherwise, NULL if expr or a value of
+/// the list is NULL, else false.
+#[derive(Debug)]
+pub struct PhysicalInList {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+    pub(crate) list: Vec<Arc<dyn PhysicalExpr>>,
+    pub(crate) negated: bool,
+}
+
+impl PhysicalExpr for PhysicalInList {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        let value = self.expr.evaluate(record);
+        if value.is_null() {
+            return ColumnValue::Null;
+        }
+        let mut has_null = false;
+        for item in &self.list {
+            let item_value = item.evaluate(record);
+            has_null |= item_value.is_null();
+            let ordering = compare(&*self.expr, value.clone(), &**item, item_value);
+            if ordering == Some(Ordering::Equal) {
+                return ColumnValue::from_bool(!self.negated);
+            }
+        }
+        if has_null {
+            ColumnValue::Null
+        } else {
+            ColumnValue::from_bool(self.negated)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::expression::literal::PhysicalLiteral;
+
+    #[test]
+    fn test_in_list() {
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        let lit = |value| Arc::new(PhysicalLiteral { value }) as Arc<dyn PhysicalExpr>;
+        let in_list = |value, list: Vec<ColumnValue>, negated| {
+            PhysicalInList {
+                expr: lit(value),
+                list: list.into_iter().map(lit).collect(),
+                negated,
+            }
+            .evaluate(&record)
+        };
+        let int = ColumnValue::int64;
+
+        assert_eq!(
+            in_list(int(3), vec![int(1), int(3)], false),
+            ColumnValue::One
+        );
+        assert_eq!(
+            in_list(int(3), vec![int(1), int(2)], false),
+            ColumnValue::Zero
+        );
+        assert_eq!(
+            in_list(int(3), vec![int(1), int(2)], true),
+            ColumnValue::One
+        );
+        // results of sqlite3: 3 in (1, null) and 3 not in (1, null) are NULL
+        assert_eq!(
+            in_list(int(3), vec![int(1), ColumnValue::Null], false),
+            ColumnValue::Null
+        );
+        assert_eq!(
+            in_list(int(3), vec![int(1), ColumnValue::Null], true),
+            ColumnValue::Null
+        );
+        assert_eq!(
+            in_list(ColumnValue::Null, vec![int(1)], false),
+            ColumnValue::Null
+        );
+    }
+}
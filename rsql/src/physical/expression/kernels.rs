@@ -0,0 +1,373 @@
+//! Arrow kernels evaluating operators on the arrays of a batch at once.
+//!
+//! A kernel is used when Arrow computes what SQLite does for the data types of the
+//! arrays: integers, reals, TEXT compared with the BINARY collation and BLOB. The
+//! functions return None otherwise, e.g. for unions of mixed storage classes, TEXT
+//! operands of arithmetic or an integer overflowing to a real, and the operator is
+//! evaluated row by row. Booleans are the integers 1 and 0 like in SQLite.
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_arith::boolean::{and_kleene, is_not_null, is_null, not, or_kleene};
+use arrow_arith::numeric::{add, div, mul, neg, neg_wrapping, rem, sub};
+use arrow_array::cast::AsArray;
+use arrow_array::types::{Float64Type, Int64Type};
+use arrow_array::{Array, ArrayRef, BooleanArray, NullArray};
+use arrow_cast::cast;
+use arrow_ord::cmp;
+use arrow_schema::DataType;
+use arrow_select::filter::prep_null_mask_filter;
+use arrow_select::nullif::nullif;
+use datafusion_expr::Operator;
+
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
+use crate::physical::plan::batch::array_value;
+
+/// Comparison `= <> < <= > >=` or IS [NOT] DISTINCT FROM of two arrays. The affinities
+/// are the ones applied to the operands (see binary::comparison_affinities): the
+/// kernels only compare arrays whose values they do not convert.
+pub fn compare(
+    op: Operator,
+    (left, left_affinity): (&ArrayRef, Option<Affinity>),
+    (right, right_affinity): (&ArrayRef, Option<Affinity>),
+    collation: Collation,
+) -> Result<Option<ArrayRef>> {
+    if !keeps_values(left_affinity, left.data_type())
+        || !keeps_values(right_affinity, right.data_type())
+    {
+        return Ok(None);
+    }
+    let distinct = matches!(op, Operator::IsDistinctFrom | Operator::IsNotDistinctFrom);
+    let (left, right) = match (left.data_type(), right.data_type()) {
+        (DataType::Null, _) | (_, DataType::Null) if !distinct => {
+            return Ok(Some(nulls(left.len())))
+        }
+        (DataType::Int64, DataType::Int64) | (DataType::Binary, DataType::Binary) => {
+            (left.clone(), right.clone())
+        }
+        (DataType::Utf8, DataType::Utf8) if collation == Collation::Binary => {
+            (left.clone(), right.clone())
+        }
+        // -0.0 and 0.0 are equal reals, they are not in the total order of the kernels
+        (DataType::Float64, DataType::Float64) => (positive_zeros(left), positive_zeros(right)),
+        _ => return Ok(None),
+    };
+    let result = match op {
+        Operator::Eq => cmp::eq(&left, &right)?,
+        Operator::NotEq => cmp::neq(&left, &right)?,
+        Operator::Lt => cmp::lt(&left, &right)?,
+        Operator::LtEq => cmp::lt_eq(&left, &right)?,
+        Operator::Gt => cmp::gt(&left, &right)?,
+        Operator::GtEq => cmp::gt_eq(&left, &right)?,
+        Operator::IsDistinctFrom => cmp::distinct(&left, &right)?,
+        Operator::IsNotDistinctFrom => cmp::not_distinct(&left, &right)?,
+        _ => return Ok(None),
+    };
+    integers(&result).map(Some)
+}
+
+/// Arithmetic operator `+ - * / %` on two arrays of numbers. Integers stay integers
+/// unless the operation overflows (the result is a real), integers and reals are
+/// reals. A division or a modulo by zero is NULL.
+pub fn arithmetic(op: Operator, left: &ArrayRef, right: &ArrayRef) -> Result<Option<ArrayRef>> {
+    let result = match (left.data_type(), right.data_type()) {
+        (DataType::Null, _) | (_, DataType::Null) => nulls(left.len()),
+        // the kernels fail on overflows and divisions by zero
+        (DataType::Int64, DataType::Int64) => {
+            let result = match op {
+                Operator::Plus => add(left, right),
+                Operator::Minus => sub(left, right),
+                Operator::Multiply => mul(left, right),
+                Operator::Divide => div(left, right),
+                Operator::Modulo => rem(left, right),
+                _ => return Ok(None),
+            };
+            return Ok(result.ok());
+        }
+        (DataType::Int64 | DataType::Float64, DataType::Int64 | DataType::Float64) => {
+            let left = cast(left, &DataType::Float64)?;
+            let right = cast(right, &DataType::Float64)?;
+            match op {
+                Operator::Plus => add(&left, &right)?,
+                Operator::Minus => sub(&left, &right)?,
+                Operator::Multiply => mul(&left, &right)?,
+                Operator::Divide => {
+                    let zeros =
+                        BooleanArray::from_unary(right.as_primitive::<Float64Type>(), |r| r == 0.0);
+                    nullif(&div(&left, &right)?, &zeros)?
+                }
+                // the remainder of reals is the one of their integer parts
+                _ => return Ok(None),
+            }
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(result))
+}
+
+/// AND or OR of the truth values of two arrays of numbers, with the three-valued logic
+/// of NULL.
+pub fn logical(op: Operator, left: &ArrayRef, right: &ArrayRef) -> Result<Option<ArrayRef>> {
+    let (Some(left), Some(right)) = (truth_values(left), truth_values(right)) else {
+        return Ok(None);
+    };
+    let result = match op {
+        Operator::And => and_kleene(&left, &right)?,
+        Operator::Or => or_kleene(&left, &right)?,
+        _ => return Ok(None),
+    };
+    integers(&result).map(Some)
+}
+
+/// NOT of the truth values of an array of numbers, NULL stays NULL.
+pub fn negation(array: &ArrayRef) -> Result<Option<ArrayRef>> {
+    truth_values(array)
+        .map(|truth| integers(&not(&truth)?))
+        .transpose()
+}
+
+/// -x of an array of numbers, the negation of the smallest integer is a real.
+pub fn negative(array: &ArrayRef) -> Result<Option<ArrayRef>> {
+    Ok(match array.data_type() {
+        DataType::Int64 => neg(array).ok(),
+        DataType::Float64 => Some(neg_wrapping(array)?),
+        DataType::Null => Some(array.clone()),
+        _ => None,
+    })
+}
+
+/// x IS NULL, or x IS NOT NULL when negated, of an array. Never NULL.
+pub fn null_test(array: &ArrayRef, negated: bool) -> Result<Option<ArrayRef>> {
+    // a union has no validity buffer, its NULL values are in its children
+    if let DataType::Union(..) = array.data_type() {
+        return Ok(None);
+    }
+    let result = match negated {
+        true => is_not_null(array)?,
+        false => is_null(array)?,
+    };
+    integers(&result).map(Some)
+}
+
+/// Whether the value of each row of an array is true, false for NULL: the mask of the
+/// rows kept by a filter. The values that are not numbers are converted row by row.
+pub fn is_true(array: &ArrayRef) -> Result<BooleanArray> {
+    match truth_values(array) {
+        Some(truth) if truth.null_count() > 0 => Ok(prep_null_mask_filter(&truth)),
+        Some(truth) => Ok(truth),
+        None => {
+            let mask = (0..array.len())
+                .map(|row| Ok(array_value(array, row)?.is_true() == Some(true)))
+                .collect::<Result<Vec<bool>>>()?;
+            Ok(BooleanArray::from(mask))
+        }
+    }
+}
+
+// Truth values of an array of numbers: a number is true if it is not zero, NULL is
+// unknown. None for TEXT, BLOB and mixed storage classes.
+fn truth_values(array: &ArrayRef) -> Option<BooleanArray> {
+    match array.data_type() {
+        DataType::Int64 => Some(BooleanArray::from_unary(
+            array.as_primitive::<Int64Type>(),
+            |value| value != 0,
+        )),
+        DataType::Float64 => Some(BooleanArray::from_unary(
+            array.as_primitive::<Float64Type>(),
+            |value| value != 0.0,
+        )),
+        DataType::Null => Some(BooleanArray::new_null(array.len())),
+        _ => None,
+    }
+}
+
+// Whether applying affinity to an array of data_type keeps its values: the NUMERIC
+// affinities convert TEXT, the TEXT affinity converts numbers.
+fn keeps_values(affinity: Option<Affinity>, data_type: &DataType) -> bool {
+    match affinity {
+        None | Some(Affinity::Blob) => true,
+        Some(Affinity::Text) => !matches!(data_type, DataType::Int64 | DataType::Float64),
+        Some(_) => *data_type != DataType::Utf8,
+    }
+}
+
+fn positive_zeros(array: &ArrayRef) -> ArrayRef {
+    Arc::new(
+        array
+            .as_primitive::<Float64Type>()
+            .unary::<_, Float64Type>(|value| value + 0.0),
+    )
+}
+
+fn integers(booleans: &BooleanArray) -> Result<ArrayRef> {
+    Ok(cast(booleans, &DataType::Int64)?)
+}
+
+fn nulls(len: usize) -> ArrayRef {
+    Arc::new(NullArray::new(len))
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_array::{Float64Array, Int64Array, StringArray};
+
+    use super::*;
+    use crate::model::column_value::ColumnValue;
+
+    fn values(array: Option<ArrayRef>) -> Vec<ColumnValue> {
+        let array = array.unwrap();
+        (0..array.len())
+            .map(|row| array_value(&array, row).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_compare() {
+        let ints: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), None, Some(3)]));
+        let others: ArrayRef = Arc::new(Int64Array::from(vec![Some(1), Some(2), Some(2)]));
+        let int = ColumnValue::int64;
+        assert_eq!(
+            values(
+                compare(
+                    Operator::GtEq,
+                    (&ints, None),
+                    (&others, None),
+                    Collation::Binary
+                )
+                .unwrap()
+            ),
+            [int(1), ColumnValue::Null, int(1)]
+        );
+        assert_eq!(
+            values(
+                compare(
+                    Operator::IsDistinctFrom,
+                    (&ints, None),
+                    (&others, None),
+                    Collation::Binary
+                )
+                .unwrap()
+            ),
+            [int(0), int(1), int(1)]
+        );
+
+        let reals: ArrayRef = Arc::new(Float64Array::from(vec![-0.0]));
+        let zeros: ArrayRef = Arc::new(Float64Array::from(vec![0.0]));
+        assert_eq!(
+            values(
+                compare(
+                    Operator::Eq,
+                    (&reals, None),
+                    (&zeros, None),
+                    Collation::Binary
+                )
+                .unwrap()
+            ),
+            [int(1)]
+        );
+
+        // TEXT with NUMERIC affinity, NOCASE and mixed storage classes are compared
+        // row by row
+        let texts: ArrayRef = Arc::new(StringArray::from(vec!["1"]));
+        let numeric = Some(Affinity::Numeric);
+        assert!(compare(
+            Operator::Eq,
+            (&texts, numeric),
+            (&texts, None),
+            Collation::Binary
+        )
+        .unwrap()
+        .is_none());
+        assert!(compare(
+            Operator::Eq,
+            (&texts, None),
+            (&texts, None),
+            Collation::NoCase
+        )
+        .unwrap()
+        .is_none());
+        assert!(compare(
+            Operator::Eq,
+            (&ints, None),
+            (&reals, None),
+            Collation::Binary
+        )
+        .unwrap()
+        .is_none());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let ints: ArrayRef = Arc::new(Int64Array::from(vec![Some(7), None, Some(-7)]));
+        let divisors: ArrayRef = Arc::new(Int64Array::from(vec![2, 0, 3]));
+        let int = ColumnValue::int64;
+        assert_eq!(
+            values(arithmetic(Operator::Divide, &ints, &divisors).unwrap()),
+            [int(3), ColumnValue::Null, int(-2)]
+        );
+        assert_eq!(
+            values(arithmetic(Operator::Modulo, &ints, &divisors).unwrap()),
+            [int(1), ColumnValue::Null, int(-1)]
+        );
+        // overflows and divisions by zero are evaluated row by row
+        let max: ArrayRef = Arc::new(Int64Array::from(vec![i64::MAX]));
+        assert!(arithmetic(Operator::Plus, &max, &max).unwrap().is_none());
+        let zero: ArrayRef = Arc::new(Int64Array::from(vec![0]));
+        assert!(arithmetic(Operator::Divide, &max, &zero).unwrap().is_none());
+
+        let reals: ArrayRef = Arc::new(Float64Array::from(vec![1.0, 2.5, 3.0]));
+        assert_eq!(
+            values(arithmetic(Operator::Divide, &divisors, &reals).unwrap()),
+            [
+                ColumnValue::real(2.0),
+                ColumnValue::real(0.0),
+                ColumnValue::real(1.0)
+            ]
+        );
+        let real_zeros: ArrayRef = Arc::new(Float64Array::from(vec![0.0, -0.0, 1.0]));
+        assert_eq!(
+            values(arithmetic(Operator::Divide, &reals, &real_zeros).unwrap()),
+            [ColumnValue::Null, ColumnValue::Null, ColumnValue::real(3.0)]
+        );
+        let texts: ArrayRef = Arc::new(StringArray::from(vec!["1", "2", "3"]));
+        assert!(arithmetic(Operator::Plus, &texts, &ints).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_logical() {
+        let left: ArrayRef = Arc::new(Int64Array::from(vec![Some(0), None, Some(2), None]));
+        let right: ArrayRef = Arc::new(Float64Array::from(vec![None, Some(0.0), Some(0.5), None]));
+        let (null, one, zero) = (
+            ColumnValue::Null,
+            ColumnValue::int64(1),
+            ColumnValue::int64(0),
+        );
+        assert_eq!(
+            values(logical(Operator::And, &left, &right).unwrap()),
+            [zero.clone(), zero.clone(), one.clone(), null.clone()]
+        );
+        assert_eq!(
+            values(logical(Operator::Or, &left, &right).unwrap()),
+            [null.clone(), null.clone(), one.clone(), null.clone()]
+        );
+        assert_eq!(
+            values(negation(&left).unwrap()),
+            [one.clone(), null.clone(), zero.clone(), null]
+        );
+        assert_eq!(
+            values(null_test(&left, false).unwrap()),
+            [zero.clone(), one.clone(), zero, one]
+        );
+        assert_eq!(
+            is_true(&left).unwrap(),
+            BooleanArray::from(vec![false, false, true, false])
+        );
+        let texts: ArrayRef = Arc::new(StringArray::from(vec![Some("1"), Some("a"), None]));
+        assert!(logical(Operator::And, &texts, &texts).unwrap().is_none());
+        assert_eq!(
+            is_true(&texts).unwrap(),
+            BooleanArray::from(vec![true, false, false])
+        );
+    }
+}
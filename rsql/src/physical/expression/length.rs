@@ -0,0 +1,61 @@
+use std::sync::Arc;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
+/// length(X): the number of characters of a TEXT value before its first NUL character,
+/// the number of bytes of a BLOB and the number of characters of the text of a number.
+/// NULL for NULL.
+/// https://www.sqlite.org/lang_corefunc.html#length
+#[derive(Debug)]
+pub struct PhysicalLength {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+}
+
+impl PhysicalExpr for PhysicalLength {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        let length = match self.expr.evaluate(record) {
+            ColumnValue::Null => return ColumnValue::Null,
+            ColumnValue::Blob(bytes) => bytes.len(),
+            value => value
+                .to_text()
+                .unwrap_or_default()
+                .chars()
+                .take_while(|char| *char != '\0')
+                .count(),
+        };
+        ColumnValue::int64(length as i64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::expression::literal::PhysicalLiteral;
+
+    #[test]
+    fn test_length() {
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        let length = |value| {
+            PhysicalLength {
+                expr: Arc::new(PhysicalLiteral { value }),
+            }
+            .evaluate(&record)
+        };
+        assert_eq!(length(ColumnValue::Null), ColumnValue::Null);
+        assert_eq!(
+            length(ColumnValue::Text("héllo\0world".to_owned())),
+            ColumnValue::int64(5)
+        );
+        assert_eq!(
+            length(ColumnValue::Blob(vec![0, 1, 2])),
+            ColumnValue::int64(3)
+        );
+        assert_eq!(length(ColumnValue::real(1.0 / 3.0)), ColumnValue::int64(17));
+        assert_eq!(length(ColumnValue::int64(-120)), ColumnValue::int64(4));
+    }
+}
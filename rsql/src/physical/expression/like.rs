@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
+/// Pattern matching operators.
+/// https://www.sqlite.org/lang_expr.html#the_like_glob_regexp_match_and_extract_operators
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PatternKind {
+    /// % matches any sequence of characters, _ any single character. Case-insensitive
+    /// for ASCII characters. The escape character makes the next one match itself.
+    Like { escape: Option<char> },
+    /// Unix file globbing: * matches any sequence, ? any single character and [...]
+    /// a character class, e.g. [a-z] or [^0-9]. Case-sensitive.
+    Glob,
+}
+
+/// expr [NOT] LIKE pattern [ESCAPE char], expr [NOT] GLOB pattern.
+/// Also the functions like(pattern, expr[, escape]) and glob(pattern, expr).
+/// NULL if expr or pattern is NULL, operands are compared as text.
+#[derive(Debug)]
+pub struct PhysicalLike {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+    pub(crate) pattern: Arc<dyn PhysicalExpr>,
+    pub(crate) kind: PatternKind,
+    pub(crate) negated: bool,
+}
+
+impl PhysicalExpr for PhysicalLike {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        let text = self.expr.evaluate(record).to_text();
+        let pattern = self.pattern.evaluate(record).to_text();
+        let (Some(text), Some(pattern)) = (text, pattern) else {
+            return ColumnValue::Null;
+        };
+        let text: Vec<char> = text.chars().collect();
+        let pattern: Vec<char> = pattern.chars().collect();
+        let matched = match self.kind {
+            PatternKind::Like { escape } => like_match(&pattern, &text, escape),
+            PatternKind::Glob => glob_match(&pattern, &text),
+        };
+        ColumnValue::from_bool(matched != self.negated)
+    }
+}
+
+fn like_match(pattern: &[char], text: &[char], escape: Option<char>) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return text.is_empty();
+    };
+    let eq = |p: char, t: char| p.eq_ignore_ascii_case(&t);
+    match first {
+        _ if Some(first) == escape => match (rest.split_first(), text.split_first()) {
+            (Some((&p, rest)), Some((&t, text))) => eq(p, t) && like_match(rest, text, escape),
+            _ => false,
+        },
+        '%' => {
+            // consecutive % are the same as one
+            let rest = &rest[rest.iter().take_while(|&&p| p == '%').count()..];
+            (0..=text.len()).any(|skip| like_match(rest, &text[skip..], escape))
+        }
+        '_' => !text.is_empty() && like_match(rest, &text[1..], escape),
+        p => match text.split_first() {
+            Some((&t, text)) => eq(p, t) && like_match(rest, text, escape),
+            None => false,
+        },
+    }
+}
+
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let Some((&first, rest)) = pattern.split_first() else {
+        return text.is_empty();
+    };
+    match first {
+        '*' => {
+            let rest = &rest[rest.iter().take_while(|&&p| p == '*').count()..];
+            (0..=text.len()).any(|skip| glob_match(rest, &text[skip..]))
+        }
+        '?' => !text.is_empty() && glob_match(rest, &text[1..]),
+        '[' => match (text.split_first(), char_class_end(rest)) {
+            (Some((&t, text)), Some(end)) => {
+                char_class_matches(&rest[..end], t) && glob_match(&rest[end + 1..], text)
+            }
+            // a class without closing ] matches nothing
+            _ => false,
+        },
+        p => match text.split_first() {
+            Some((&t, text)) => p == t && glob_match(rest, text),
+            None => false,
+        },
+    }
+}
+
+// Index of the ] closing the character class, a ] right after [ or [^ is part of the class.
+fn char_class_end(class: &[char]) -> Option<usize> {
+    let start = match class {
+        ['^', ']', ..] => 2,
+        ['^', ..] | [']', ..] => 1,
+        _ => 0,
+    };
+    class[start..]
+        .iter()
+        .position(|&c| c == ']')
+        .map(|index| start + index)
+}
+
+fn char_class_matches(class: &[char], c: char) -> bool {
+    let (inverted, mut class) = match class.split_first() {
+        Some(('^', rest)) => (true, rest),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    while let Some((&first, rest)) = class.split_first() {
+        match rest {
+            // a range, - at the end of the class is itself
+            ['-', last, rest @ ..] => {
+                matched |= first <= c && c <= *last;
+                class = rest;
+            }
+            _ => {
+                matched |= first == c;
+                class = rest;
+            }
+        }
+    }
+    matched != inverted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chars(s: &str) -> Vec<char> {
+        s.chars().collect()
+    }
+
+    fn like(pattern: &str, text: &str, escape: Option<char>) -> bool {
+        like_match(&chars(pattern), &chars(text), escape)
+    }
+
+    fn glob(pattern: &str, text: &str) -> bool {
+        glob_match(&chars(pattern), &chars(text))
+    }
+
+    #[test]
+    fn test_like() {
+        assert!(like("a%", "Apple", None));
+        assert!(like("%PL%", "apple", None));
+        assert!(like("_pple", "apple", None));
+        assert!(!like("_pple", "pple", None));
+        assert!(like("%%%", "", None));
+        assert!(like("100\\%", "100%", Some('\\')));
+        assert!(!like("100\\%", "1000", Some('\\')));
+        assert!(like("a\\_c", "a_c", Some('\\')));
+        assert!(!like("a\\_c", "abc", Some('\\')));
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(glob("a*", "abc"));
+        assert!(!glob("a*", "Abc"));
+        assert!(glob("?b?", "abc"));
+        assert!(glob("[a-c]x", "bx"));
+        assert!(!glob("[^a-c]x", "bx"));
+        assert!(glob("[^a-c]x", "dx"));
+        assert!(glob("[]]", "]"));
+        assert!(glob("[a-]", "-"));
+        assert!(!glob("[abc", "a"));
+        assert!(glob("*.rs", "main.rs"));
+    }
+
+    #[test]
+    fn test_like_null() {
+        let lit = |value| Arc::new(crate::physical::expression::literal::PhysicalLiteral { value });
+        let like = PhysicalLike {
+            expr: lit(ColumnValue::Null),
+            pattern: lit(ColumnValue::Text("%".to_owned())),
+            kind: PatternKind::Like { escape: None },
+            negated: false,
+        };
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        assert_eq!(like.evaluate(&record), ColumnValue::Null);
+    }
+}
@@ -1,6 +1,13 @@
+use std::iter;
+
+use anyhow::Result;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::DataType;
+
 use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
 use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::batch::values_to_array;
 
 #[derive(Debug)]
 pub struct PhysicalLiteral {
@@ -16,4 +23,15 @@ impl PhysicalExpr for PhysicalLiteral {
             col => col.clone(),
         }
     }
+
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let data_type = match &self.value {
+            ColumnValue::Null => DataType::Null,
+            ColumnValue::Float64(_) => DataType::Float64,
+            ColumnValue::Text(_) => DataType::Utf8,
+            ColumnValue::Blob(_) => DataType::Binary,
+            _ => DataType::Int64,
+        };
+        values_to_array(&data_type, iter::repeat_n(&self.value, batch.num_rows()))
+    }
 }
@@ -1,4 +1,14 @@
+pub mod between;
+pub mod binary;
+pub mod case;
 pub mod cast;
+pub mod coalesce;
 pub mod col_by_index;
+pub mod collate;
+pub mod in_list;
+pub mod kernels;
+pub mod length;
+pub mod like;
 pub mod literal;
 pub mod physical_expr;
+pub mod unary;
@@ -1,9 +1,39 @@
+use anyhow::Result;
+use arrow_array::{ArrayRef, RecordBatch};
+
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
 use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
+use crate::physical::plan::batch::{batch_to_records, values_array};
 use std::fmt::Debug;
 
-pub trait PhysicalExpr: Debug {
+pub trait PhysicalExpr: Debug + Send + Sync {
     // returns ColumnValue not &ColumnValue because we want the value to be copied
     // so it can be owned by others, not owned by the initial record
     fn evaluate(&self, record: &DataRecord) -> ColumnValue;
+
+    // evaluates the expression on all rows of batch at once, returning one value per row.
+    // By default the expression is evaluated row by row, the operators evaluate their
+    // operands on the batch and apply Arrow kernels to their arrays (see kernels).
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let values: Vec<ColumnValue> = batch_to_records(batch)?
+            .iter()
+            .map(|record| self.evaluate(record))
+            .collect();
+        values_array(&values)
+    }
+
+    // affinity of the expression, applied to the other operand of a comparison.
+    // Only columns and CAST have one.
+    // https://www.sqlite.org/datatype3.html#affinity_of_expressions
+    fn affinity(&self) -> Option<Affinity> {
+        None
+    }
+
+    // collating sequence used when comparing the expression: the column collation
+    // or the one of a COLLATE operator.
+    fn collation(&self) -> Option<Collation> {
+        None
+    }
 }
@@ -0,0 +1,142 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_array::{ArrayRef, RecordBatch};
+
+use crate::model::column_value::ColumnValue;
+use crate::model::conversion::Number;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::kernels;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::batch::{array_value, values_array};
+
+/// NOT expr: NULL stays NULL (unknown).
+#[derive(Debug)]
+pub struct PhysicalNot {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+}
+
+impl PhysicalNot {
+    fn apply(value: ColumnValue) -> ColumnValue {
+        match value.is_true() {
+            Some(value) => ColumnValue::from_bool(!value),
+            None => ColumnValue::Null,
+        }
+    }
+}
+
+impl PhysicalExpr for PhysicalNot {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        Self::apply(self.expr.evaluate(record))
+    }
+
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let array = self.expr.evaluate_batch(batch)?;
+        match kernels::negation(&array)? {
+            Some(result) => Ok(result),
+            None => map_values(&array, Self::apply),
+        }
+    }
+}
+
+/// -expr, the operand is converted to a number like for arithmetic operators.
+#[derive(Debug)]
+pub struct PhysicalNegative {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+}
+
+impl PhysicalNegative {
+    fn apply(value: ColumnValue) -> ColumnValue {
+        match value.to_number() {
+            Some(Number::Integer(int)) => match int.checked_neg() {
+                Some(negated) => ColumnValue::int64(negated),
+                None => ColumnValue::real(-(int as f64)),
+            },
+            Some(Number::Real(real)) => ColumnValue::real(-real),
+            None => ColumnValue::Null,
+        }
+    }
+}
+
+impl PhysicalExpr for PhysicalNegative {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        Self::apply(self.expr.evaluate(record))
+    }
+
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let array = self.expr.evaluate_batch(batch)?;
+        match kernels::negative(&array)? {
+            Some(result) => Ok(result),
+            None => map_values(&array, Self::apply),
+        }
+    }
+}
+
+/// expr IS NULL, or expr IS NOT NULL when negated. Never NULL.
+#[derive(Debug)]
+pub struct PhysicalIsNull {
+    pub(crate) expr: Arc<dyn PhysicalExpr>,
+    pub(crate) negated: bool,
+}
+
+impl PhysicalExpr for PhysicalIsNull {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        ColumnValue::from_bool(self.expr.evaluate(record).is_null() != self.negated)
+    }
+
+    fn evaluate_batch(&self, batch: &RecordBatch) -> Result<ArrayRef> {
+        let array = self.expr.evaluate_batch(batch)?;
+        match kernels::null_test(&array, self.negated)? {
+            Some(result) => Ok(result),
+            None => map_values(&array, |value| {
+                ColumnValue::from_bool(value.is_null() != self.negated)
+            }),
+        }
+    }
+}
+
+// Applies f to each value of the array of an operand, when no kernel applies.
+fn map_values(array: &ArrayRef, f: impl Fn(ColumnValue) -> ColumnValue) -> Result<ArrayRef> {
+    let values = (0..array.len())
+        .map(|row| Ok(f(array_value(array, row)?)))
+        .collect::<Result<Vec<_>>>()?;
+    values_array(&values)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::expression::literal::PhysicalLiteral;
+
+    fn lit(value: ColumnValue) -> Arc<dyn PhysicalExpr> {
+        Arc::new(PhysicalLiteral { value })
+    }
+
+    #[test]
+    fn test_unary() {
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        let not = |value| PhysicalNot { expr: lit(value) }.evaluate(&record);
+        assert_eq!(not(ColumnValue::Null), ColumnValue::Null);
+        assert_eq!(not(ColumnValue::real(0.5)), ColumnValue::Zero);
+        assert_eq!(not(ColumnValue::Text("0".to_owned())), ColumnValue::One);
+
+        let negative = |value| PhysicalNegative { expr: lit(value) }.evaluate(&record);
+        assert_eq!(
+            negative(ColumnValue::Text("abc".to_owned())),
+            ColumnValue::int64(0)
+        );
+        assert_eq!(
+            negative(ColumnValue::int64(i64::MIN)),
+            ColumnValue::real(9223372036854775808.0)
+        );
+
+        let is_not_null = PhysicalIsNull {
+            expr: lit(ColumnValue::Null),
+            negated: true,
+        };
+        assert_eq!(is_not_null.evaluate(&record), ColumnValue::Zero);
+    }
+}
@@ -1,26 +1,81 @@
 use anyhow::bail;
-use datafusion_expr::LogicalPlan;
+use arrow_schema::{DataType, Field, Schema};
+use datafusion_common::{DFSchema, JoinType, ScalarValue};
+use datafusion_expr::expr::{Alias, Between, BinaryExpr, Cast, TryCast};
+use datafusion_expr::{
+    Aggregate, DmlStatement, Expr, Filter, LogicalPlan, Operator, Projection, Sort, TableScan,
+    WriteOp,
+};
+use datafusion_sql::planner::{
+    object_name_to_table_reference, ContextProvider, PlannerContext, SqlToRel,
+};
+use datafusion_sql::sqlparser::ast::{
+    Expr as SqlExpr, GroupByExpr, Ident, Query, Select, SelectItem, SetExpr, Statement, TableFactor,
+};
 use log::{error, info};
 use std::cell::RefCell;
+use std::ops::{Bound, RangeInclusive};
 use std::rc::Rc;
 use std::sync::Arc;
 
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
 use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
 use crate::model::database::Database;
+use crate::model::index_key::{KeyInfo, SortOrder};
+use crate::model::schema::{
+    IndexDef, IndexedColumn, SchemaObjDetail, SchemaObjType, FIELD_AFFINITY, FIELD_COLLATION,
+    FIELD_HIDDEN, ROWID_NAMES,
+};
+use crate::physical::expression::between::PhysicalBetween;
+use crate::physical::expression::binary::{
+    comparison_affinities, comparison_collation, PhysicalBinary,
+};
+use crate::physical::expression::case::PhysicalCase;
+use crate::physical::expression::cast::PhysicalCast;
+use crate::physical::expression::coalesce::PhysicalCoalesce;
 use crate::physical::expression::col_by_index::PhysicalColByIndex;
+use crate::physical::expression::collate::PhysicalCollate;
+use crate::physical::expression::in_list::PhysicalInList;
+use crate::physical::expression::length::PhysicalLength;
+use crate::physical::expression::like::{PatternKind, PhysicalLike};
 use crate::physical::expression::literal::PhysicalLiteral;
 use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::expression::unary::{PhysicalIsNull, PhysicalNegative, PhysicalNot};
+use crate::physical::plan::aggregate::{AggregateExpr, AggregateFunction, ExecAggregateHash};
 use crate::physical::plan::exec::Exec;
-use crate::physical::plan::exec_dummy::ExecDummy;
+use crate::physical::plan::exec_ddl::ExecDdl;
+use crate::physical::plan::exec_delete::ExecDelete;
+use crate::physical::plan::exec_distinct::ExecDistinct;
+use crate::physical::plan::exec_filter::ExecFilter;
+use crate::physical::plan::exec_insert::ExecInsert;
+use crate::physical::plan::exec_limit::ExecLimit;
 use crate::physical::plan::exec_projection::ExecProjection;
-use crate::physical::plan::join::ExecJoinHash;
-use crate::physical::plan::scan::ExecScan;
+use crate::physical::plan::exec_update::ExecUpdate;
+use crate::physical::plan::join::{
+    ExecJoinHash, ExecJoinIndexNestedLoop, ExecJoinNestedLoop, ExecJoinSortMerge, JoinLookup,
+    SortedInputs,
+};
+use crate::physical::plan::scan::{ExecIndexScan, ExecMemTable, ExecScan, IndexBounds};
+use crate::physical::plan::sort::{ExecSort, SortExpr, DEFAULT_SORT_MEMORY_LIMIT};
+use crate::sql::functions::DISTINCT_SUFFIX;
+use crate::sql::parsing::DdlStatement;
 
 pub struct PhysicalPlanner {
     pub database: Rc<RefCell<Database>>,
+    /// memory in bytes used by a sort before spilling records to temporary files
+    pub sort_memory_limit: usize,
 }
 
 impl PhysicalPlanner {
+    pub fn new(database: Rc<RefCell<Database>>) -> Self {
+        PhysicalPlanner {
+            database,
+            sort_memory_limit: DEFAULT_SORT_MEMORY_LIMIT,
+        }
+    }
+
     ///
     /// Box puts a type on heap instead of stack.
     /// What:
@@ -30,8 +85,10 @@ impl PhysicalPlanner {
     ///     use for types the compiler does not know the size. Example Exec trait here
     ///     can be many types so we don't know the size at compile time.
     ///
-    pub fn plan(&self, logical_plan: &LogicalPlan) -> Arc<dyn Exec> {
-        info!("executing logical plan \n{logical_plan:?}");
+    /// Each operator owns its children, the root returned here is opened and pulled
+    /// by the caller.
+    pub fn plan(&self, logical_plan: &LogicalPlan) -> anyhow::Result<Box<dyn Exec>> {
+        info!("planning logical plan \n{logical_plan:?}");
 
         match logical_plan {
             LogicalPlan::TableScan(table_scan) => {
@@ -39,84 +96,1623 @@ impl PhysicalPlanner {
                     "Scanning table {} projection {:?}",
                     table_scan.table_name, table_scan.projection
                 );
-                // TODO root page number should not be hardcoded but looked up in db meta
-                let table_page_number = 2; // hard-coded for sample.db, table apples
-
-                // TODO: Use Arc without send/sync is usually wrong, please double check here.
-                #[allow(clippy::arc_with_non_send_sync)]
-                Arc::new(ExecScan::new(
-                    table_scan.table_name.to_string(),
-                    table_page_number,
+                Ok(Box::new(ExecScan::try_new(
+                    table_scan.table_name.table().to_owned(),
+                    scan_projection(table_scan),
                     self.database.clone(),
-                ))
+                )?))
             }
             LogicalPlan::Projection(logical_proj) => {
-                let physical_expressions = logical_proj
-                    .expr
-                    .iter()
-                    .map(|logical_expr|
-                        // knowing that logical plan is Projection having only 1 input -> access idx 0
-                        create_physical_expr(logical_expr, logical_plan.inputs()[0],
-                        ).expect("cannot parse physical expr"))
-                    .collect();
                 // * to defer the smart ptr input: Arc<datafusion LogicalPlan>,
                 // then take a reference with &
-                let input_physical_plan = self.plan(&logical_proj.input);
+                let input_physical_plan = self.plan(&logical_proj.input)?;
+                self.plan_projection(logical_proj, input_physical_plan)
+            }
+
+            LogicalPlan::Filter(filter) => {
+                let predicate = create_physical_expr(&filter.predicate, filter.input.schema())?;
+                let input_physical_plan = match self.plan_filter_scan(filter, false)? {
+                    Some(scan) => scan,
+                    None => self.plan(&filter.input)?,
+                };
+                Ok(Box::new(ExecFilter::new(input_physical_plan, predicate)))
+            }
+
+            // HAVING is a Filter above the Aggregate
+            LogicalPlan::Aggregate(aggregate) => {
+                let group_exprs = aggregate
+                    .group_expr
+                    .iter()
+                    .map(|expr| match expr {
+                        Expr::GroupingSet(_) => bail!("unsupported grouping set {expr}"),
+                        _ => create_physical_expr(expr, aggregate.input.schema()),
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let aggregates = aggregate
+                    .aggr_expr
+                    .iter()
+                    .map(|expr| create_aggregate_expr(expr, aggregate.input.schema()))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                let input_physical_plan = match self.plan_rowid_min_max(aggregate)? {
+                    Some(input) => input,
+                    None => self.plan(&aggregate.input)?,
+                };
+                Ok(Box::new(ExecAggregateHash::new(
+                    input_physical_plan,
+                    group_exprs,
+                    aggregates,
+                    Arc::new(aggregate.schema.as_ref().into()),
+                )))
+            }
 
-                // TODO: Use Arc without send/sync is usually wrong, please double check here.
-                #[allow(clippy::arc_with_non_send_sync)]
-                Arc::new(ExecProjection::new(input_physical_plan, physical_expressions).unwrap())
+            LogicalPlan::Sort(sort) => self.plan_sort(sort, sort.fetch),
+
+            LogicalPlan::Limit(limit) => {
+                // ORDER BY ... LIMIT n OFFSET m only keeps the first n + m records while sorting
+                let sorted = match limit.fetch {
+                    Some(fetch) => {
+                        self.plan_fetch(&limit.input, limit.skip.saturating_add(fetch))?
+                    }
+                    None => None,
+                };
+                let input_physical_plan = match sorted {
+                    Some(sorted) => sorted,
+                    None => self.plan(&limit.input)?,
+                };
+                Ok(Box::new(ExecLimit::new(
+                    input_physical_plan,
+                    limit.skip,
+                    limit.fetch,
+                )))
+            }
+
+            LogicalPlan::Distinct(distinct) => {
+                let collations = distinct
+                    .input
+                    .schema()
+                    .fields()
+                    .iter()
+                    .map(|field| Ok(field_collation(field.field())?.unwrap_or_default()))
+                    .collect::<anyhow::Result<_>>()?;
+                let input_physical_plan = self.plan(&distinct.input)?;
+                Ok(Box::new(ExecDistinct::new(input_physical_plan, collations)))
             }
 
+            // `FROM t1 AS a` only renames the columns of the logical plan
+            LogicalPlan::SubqueryAlias(alias) => self.plan(&alias.input),
+
             LogicalPlan::Join(join) => {
                 // receiving logical plan, based on different criteria the most appropriate
                 // physical plan will be produced.
-                let left_physical = self.plan(&join.left);
-                let right_physical = self.plan(&join.right);
-                error!("Join on {:?}", join.on);
-                let join_on_physical = vec![];
-
-                // TODO: Use Arc without send/sync is usually wrong, please double check here.
-                #[allow(clippy::arc_with_non_send_sync)]
-                Arc::new(
-                    ExecJoinHash::try_new(
+                let left_schema = join.left.schema();
+                let right_schema = join.right.schema();
+                // ON a.id = b.id AND a.x > 1: datafusion keeps the whole condition in the
+                // filter, equalities between the two sides are the keys of the join
+                let mut keys: Vec<(&Expr, &Expr, bool)> = join
+                    .on
+                    .iter()
+                    .map(|(left, right)| (left, right, false))
+                    .collect();
+                let mut residual = vec![];
+                for predicate in join.filter.iter().flat_map(split_conjunction) {
+                    match join_key(predicate, left_schema, right_schema) {
+                        Some(key) => keys.push(key),
+                        None => residual.push(predicate.clone()),
+                    }
+                }
+                let on = keys
+                    .iter()
+                    .map(|&(left, right, swapped)| {
+                        let left = create_physical_expr(left, left_schema)?;
+                        let right = create_physical_expr(right, right_schema)?;
+                        if !swapped {
+                            return Ok((left, right));
+                        }
+                        // the collation of the left operand of `=` takes precedence
+                        let collation = comparison_collation(right.as_ref(), left.as_ref());
+                        let left: Arc<dyn PhysicalExpr> = Arc::new(PhysicalCollate {
+                            expr: left,
+                            collation,
+                        });
+                        Ok((left, right))
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                // the filter is evaluated on the left values followed by the right values
+                let filter = residual
+                    .into_iter()
+                    .reduce(Expr::and)
+                    .map(|filter| create_physical_expr(&filter, &left_schema.join(right_schema)?))
+                    .transpose()?;
+                let left_physical = self.plan(&join.left)?;
+                // theta joins like a.ts BETWEEN b.start AND b.end cannot be hashed
+                if on.is_empty() {
+                    return Ok(Box::new(ExecJoinNestedLoop::try_new(
                         left_physical,
-                        right_physical,
-                        join_on_physical,
+                        self.plan(&join.right)?,
+                        filter,
                         &join.join_type,
+                    )?));
+                }
+
+                if let [(left_key, right_key, _)] = keys.as_slice() {
+                    // scans joined on their INTEGER PRIMARY KEY are merged in rowid order
+                    if self.is_rowid_ordered(&join.left, left_key)
+                        && self.is_rowid_ordered(&join.right, right_key)
+                    {
+                        return Ok(Box::new(ExecJoinSortMerge::try_new(
+                            left_physical,
+                            self.plan(&join.right)?,
+                            on,
+                            filter,
+                            &join.join_type,
+                            SortedInputs {
+                                left: true,
+                                right: true,
+                            },
+                        )?));
+                    }
+
+                    // a right table with a key to search is not scanned
+                    if matches!(
+                        join.join_type,
+                        JoinType::Inner | JoinType::Left | JoinType::LeftSemi | JoinType::LeftAnti
+                    ) {
+                        let collation = comparison_collation(on[0].0.as_ref(), on[0].1.as_ref());
+                        if let Some((right_scan, lookup)) =
+                            self.plan_join_lookup(&join.right, right_key, collation)?
+                        {
+                            return Ok(Box::new(ExecJoinIndexNestedLoop::try_new(
+                                left_physical,
+                                right_scan,
+                                on[0].clone(),
+                                lookup,
+                                filter,
+                                &join.join_type,
+                            )?));
+                        }
+                    }
+                }
+
+                Ok(Box::new(ExecJoinHash::try_new(
+                    left_physical,
+                    self.plan(&join.right)?,
+                    on,
+                    filter,
+                    &join.join_type,
+                )?))
+            }
+
+            // FROM a, b: every pair of records, WHERE is a filter above the join
+            LogicalPlan::CrossJoin(cross_join) => {
+                let left_physical = self.plan(&cross_join.left)?;
+                let right_physical = self.plan(&cross_join.right)?;
+                Ok(Box::new(ExecJoinNestedLoop::try_new(
+                    left_physical,
+                    right_physical,
+                    None,
+                    &JoinType::Inner,
+                )?))
+            }
+
+            // VALUES (...), (...): rows of constant expressions
+            LogicalPlan::Values(values) => {
+                let schema = DFSchema::empty();
+                let empty = DataRecord {
+                    values: vec![],
+                    rowid: None,
+                };
+                let records = values
+                    .values
+                    .iter()
+                    .map(|row| {
+                        let values = row
+                            .iter()
+                            .map(|expr| Ok(create_physical_expr(expr, &schema)?.evaluate(&empty)))
+                            .collect::<anyhow::Result<_>>()?;
+                        Ok(DataRecord {
+                            values,
+                            rowid: None,
+                        })
+                    })
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                Ok(Box::new(ExecMemTable::new(
+                    &records,
+                    Arc::new(values.schema.as_ref().into()),
+                )))
+            }
+
+            // SELECT without FROM: one record without values, that the projection evaluates
+            // its expressions on
+            LogicalPlan::EmptyRelation(empty_relation) => {
+                let records = match empty_relation.produce_one_row {
+                    true => vec![DataRecord {
+                        values: vec![],
+                        rowid: None,
+                    }],
+                    false => vec![],
+                };
+                Ok(Box::new(ExecMemTable::new(
+                    &records,
+                    Arc::new(empty_relation.schema.as_ref().into()),
+                )))
+            }
+
+            // the input is the scan of the table under the WHERE filter, its records
+            // have the rowid of the rows to delete
+            LogicalPlan::Dml(dml) if dml.op == WriteOp::Delete => {
+                Ok(Box::new(ExecDelete::try_new(
+                    dml.table_name.table().to_owned(),
+                    self.plan(&dml.input)?,
+                    self.database.clone(),
+                )?))
+            }
+
+            _ => bail!("unsupported logical plan: {}", logical_plan.display()),
+        }
+    }
+
+    /// Plans a DDL statement, which has no logical plan (see `DdlStatement`).
+    pub fn plan_ddl(&self, statement: DdlStatement) -> Box<dyn Exec> {
+        Box::new(ExecDdl::new(statement, self.database.clone()))
+    }
+
+    // The scan of the table under filter reading only the rows its comparisons of columns
+    // with constants can select: a range of rowids or of index entries, in descending
+    // rowid order if descending. The scan may return more rows than the predicate
+    // selects, the filter is still applied to them.
+    // None if the input is not a table scan or no column is compared with a constant.
+    fn plan_filter_scan(
+        &self,
+        filter: &Filter,
+        descending: bool,
+    ) -> anyhow::Result<Option<Box<dyn Exec>>> {
+        let Some((table_scan, filter_scan)) = self.filter_scan(filter)? else {
+            return Ok(None);
+        };
+        let table = ExecScan::try_new(
+            table_scan.table_name.table().to_owned(),
+            scan_projection(table_scan),
+            self.database.clone(),
+        )?;
+        Ok(match filter_scan {
+            FilterScan::Rowids(rowids) => {
+                info!("Scanning table {} rowids {rowids:?}", table_scan.table_name);
+                Some(Box::new(
+                    table.with_rowid_range(rowids).with_descending(descending),
+                ))
+            }
+            // the index entries are not in rowid order
+            FilterScan::Index(..) if descending => None,
+            FilterScan::Index(index, bounds) => {
+                info!(
+                    "Scanning table {} with index {} {bounds:?}",
+                    table_scan.table_name, index.name
+                );
+                Some(Box::new(ExecIndexScan::new(
+                    table,
+                    index.name,
+                    index.root_page_number,
+                    index.key_info,
+                    bounds,
+                )))
+            }
+        })
+    }
+
+    // How the table scanned under filter is read: a point lookup of the INTEGER PRIMARY
+    // KEY is preferred, then an index with equalities, a rowid range and an index range.
+    fn filter_scan<'a>(
+        &self,
+        filter: &'a Filter,
+    ) -> anyhow::Result<Option<(&'a TableScan, FilterScan)>> {
+        let table_scan = match filter.input.as_ref() {
+            LogicalPlan::TableScan(table_scan) => table_scan,
+            LogicalPlan::SubqueryAlias(alias) => match alias.input.as_ref() {
+                LogicalPlan::TableScan(table_scan) => table_scan,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        if !table_scan.filters.is_empty() {
+            return Ok(None);
+        }
+        let comparisons = column_comparisons(&filter.predicate, filter.input.schema())?;
+        if comparisons.is_empty() {
+            return Ok(None);
+        }
+
+        let rowid_indices = self.rowid_columns(&filter.input);
+        let (mut low, mut high) = (i64::MIN, i64::MAX);
+        for comparison in &comparisons {
+            if !rowid_indices.contains(&comparison.column) {
+                continue;
+            }
+            if let Some(rowids) = comparison_rowids(comparison.op, &comparison.value) {
+                low = low.max(*rowids.start());
+                high = high.min(*rowids.end());
+            }
+        }
+        let rowids = ((low, high) != (i64::MIN, i64::MAX)).then_some(low..=high);
+        let index = self.filter_index(table_scan, &comparisons)?;
+
+        Ok(match (rowids, index) {
+            // one rowid, or none
+            (Some(rowids), _) if low >= high => Some(FilterScan::Rowids(rowids)),
+            (_, Some((index, bounds))) if !bounds.equal.is_empty() => {
+                Some(FilterScan::Index(index, bounds))
+            }
+            (Some(rowids), _) => Some(FilterScan::Rowids(rowids)),
+            (None, Some((index, bounds))) => Some(FilterScan::Index(index, bounds)),
+            (None, None) => None,
+        }
+        .map(|filter_scan| (table_scan, filter_scan)))
+    }
+
+    // The index of the table of table_scan whose first columns are compared with the
+    // most equalities, then with a range on the next column, with the collation of the
+    // index. None if no index column is compared.
+    fn filter_index(
+        &self,
+        table_scan: &TableScan,
+        comparisons: &[ColumnComparison],
+    ) -> anyhow::Result<Option<(SearchableIndex, IndexBounds)>> {
+        let table_name = table_scan.table_name.table();
+        let column_names: Vec<String> = {
+            let database = self.database.borrow();
+            if !database.db_meta.schema_objects.iter().any(|schema_obj| {
+                schema_obj.obj_type == SchemaObjType::Table && schema_obj.name == table_name
+            }) {
+                return Ok(None);
+            }
+            // name of the table column of each column of the scan, the hidden rowid
+            // columns are not indexed
+            let source_schema = table_scan.source.schema();
+            let column_name = |field: &Field| match is_hidden(field) {
+                true => String::new(),
+                false => field.name().clone(),
+            };
+            match &table_scan.projection {
+                Some(projection) => projection
+                    .iter()
+                    .map(|&index| column_name(source_schema.field(index)))
+                    .collect(),
+                None => source_schema
+                    .fields()
+                    .iter()
+                    .map(|field| column_name(field))
+                    .collect(),
+            }
+        };
+
+        let mut best: Option<((usize, bool), SearchableIndex, IndexBounds)> = None;
+        for index in self.searchable_indexes(table_name)? {
+            let mut bounds = IndexBounds {
+                equal: vec![],
+                low: Bound::Unbounded,
+                high: Bound::Unbounded,
+            };
+            for (i, indexed_column) in index.columns.iter().enumerate() {
+                if indexed_column.expression {
+                    break;
+                }
+                let (mut low, mut high, mut equal) = (Bound::Unbounded, Bound::Unbounded, None);
+                let on_column = comparisons.iter().filter(|comparison| {
+                    comparison.collation == index.key_info.collations[i]
+                        && column_names[comparison.column]
+                            .eq_ignore_ascii_case(&indexed_column.name)
+                });
+                for comparison in on_column {
+                    let value = comparison.value.clone();
+                    match comparison.op {
+                        Operator::Eq if equal.is_none() => equal = Some(value),
+                        Operator::Gt if low == Bound::Unbounded => low = Bound::Excluded(value),
+                        Operator::GtEq if low == Bound::Unbounded => low = Bound::Included(value),
+                        Operator::Lt if high == Bound::Unbounded => high = Bound::Excluded(value),
+                        Operator::LtEq if high == Bound::Unbounded => high = Bound::Included(value),
+                        _ => {}
+                    }
+                }
+                if let Some(value) = equal {
+                    bounds.equal.push(value);
+                    continue;
+                }
+                if (&low, &high) == (&Bound::Unbounded, &Bound::Unbounded) {
+                    break;
+                }
+                // NULL is never in a range and smaller than any value: it is first in
+                // ascending order, last in descending order
+                let descending = indexed_column.sort_order == SortOrder::Desc;
+                if descending {
+                    std::mem::swap(&mut low, &mut high);
+                }
+                match (descending, &low, &high) {
+                    (false, Bound::Unbounded, _) => low = Bound::Excluded(ColumnValue::Null),
+                    (true, _, Bound::Unbounded) => high = Bound::Excluded(ColumnValue::Null),
+                    _ => {}
+                }
+                (bounds.low, bounds.high) = (low, high);
+                break;
+            }
+            let score = (bounds.equal.len(), bounds.low != Bound::Unbounded);
+            if score > (0, false) && best.as_ref().is_none_or(|(best, ..)| score > *best) {
+                best = Some((score, index, bounds));
+            }
+        }
+        Ok(best.map(|(_, index, bounds)| (index, bounds)))
+    }
+
+    // The indexes of a table the planner can search: partial indexes are not used, they
+    // miss rows, and the columns of automatic indexes are not known.
+    fn searchable_indexes(&self, table_name: &str) -> anyhow::Result<Vec<SearchableIndex>> {
+        let database = self.database.borrow();
+        let schema_objects = &database.db_meta.schema_objects;
+        let Some(table) = schema_objects.iter().find(|schema_obj| {
+            schema_obj.obj_type == SchemaObjType::Table && schema_obj.name == table_name
+        }) else {
+            return Ok(vec![]);
+        };
+        let mut indexes = vec![];
+        for index in schema_objects.iter().filter(|schema_obj| {
+            schema_obj.obj_type == SchemaObjType::Index && schema_obj.tbl_name == table_name
+        }) {
+            let SchemaObjDetail::Index(index_def) = &index.detail else {
+                continue;
+            };
+            if index_def.where_clause.is_some() || index_def.columns.is_empty() {
+                continue;
+            }
+            indexes.push(SearchableIndex {
+                name: index.name.clone(),
+                root_page_number: index.rootpage,
+                columns: index_def.columns.clone(),
+                key_info: index_key_info(index_def, &table.columns)?,
+            });
+        }
+        Ok(indexes)
+    }
+
+    // The input of `min(rowid)` or `max(rowid)` without GROUP BY: only the first record
+    // of the input in ascending, or descending, rowid order is read.
+    // None for other aggregates or if the input is not in rowid order.
+    fn plan_rowid_min_max(&self, aggregate: &Aggregate) -> anyhow::Result<Option<Box<dyn Exec>>> {
+        let ([expr], []) = (
+            aggregate.aggr_expr.as_slice(),
+            aggregate.group_expr.as_slice(),
+        ) else {
+            return Ok(None);
+        };
+        let Expr::AggregateUDF(function) = expr.clone().unalias() else {
+            return Ok(None);
+        };
+        let ([arg], None, None) = (
+            function.args.as_slice(),
+            &function.filter,
+            &function.order_by,
+        ) else {
+            return Ok(None);
+        };
+        if !self.is_rowid_ordered(&aggregate.input, arg) {
+            return Ok(None);
+        }
+        let ordered = match function.fun.name.as_str() {
+            "min" => Some(self.plan(&aggregate.input)?),
+            "max" => self.plan_rowid_descending(&aggregate.input)?,
+            _ => None,
+        };
+        Ok(ordered.map(|input| Box::new(ExecLimit::new(input, 0, Some(1))) as Box<dyn Exec>))
+    }
+
+    // The records of a table scan in descending rowid order, walking the table B-Tree
+    // backward, possibly under filters, aliases and projections.
+    // None for other plans: e.g. a LIMIT keeps the first records in ascending order.
+    fn plan_rowid_descending(&self, plan: &LogicalPlan) -> anyhow::Result<Option<Box<dyn Exec>>> {
+        Ok(match plan {
+            LogicalPlan::TableScan(table_scan) => Some(Box::new(
+                ExecScan::try_new(
+                    table_scan.table_name.table().to_owned(),
+                    scan_projection(table_scan),
+                    self.database.clone(),
+                )?
+                .with_descending(true),
+            )),
+            LogicalPlan::SubqueryAlias(alias) => self.plan_rowid_descending(&alias.input)?,
+            LogicalPlan::Filter(filter) => {
+                let input = match self.plan_filter_scan(filter, true)? {
+                    Some(scan) => Some(scan),
+                    None => self.plan_rowid_descending(&filter.input)?,
+                };
+                match input {
+                    Some(input) => {
+                        let predicate =
+                            create_physical_expr(&filter.predicate, filter.input.schema())?;
+                        Some(Box::new(ExecFilter::new(input, predicate)))
+                    }
+                    None => None,
+                }
+            }
+            LogicalPlan::Projection(logical_proj) => {
+                match self.plan_rowid_descending(&logical_proj.input)? {
+                    Some(input) => Some(self.plan_projection(logical_proj, input)?),
+                    None => None,
+                }
+            }
+            _ => None,
+        })
+    }
+
+    // The right side of an index nested loop join: a table scan searched by its
+    // INTEGER PRIMARY KEY, or by an index whose first column is key with the collation
+    // of the comparison.
+    fn plan_join_lookup(
+        &self,
+        plan: &LogicalPlan,
+        key: &Expr,
+        collation: Collation,
+    ) -> anyhow::Result<Option<(ExecScan, JoinLookup)>> {
+        let table_scan = match plan {
+            LogicalPlan::TableScan(table_scan) => table_scan,
+            LogicalPlan::SubqueryAlias(alias) => match alias.input.as_ref() {
+                LogicalPlan::TableScan(table_scan) => table_scan,
+                _ => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let Expr::Column(column) = key else {
+            return Ok(None);
+        };
+        let Ok(index) = plan.schema().index_of_column(column) else {
+            return Ok(None);
+        };
+        if !table_scan.filters.is_empty() {
+            return Ok(None);
+        }
+        let column_index = match &table_scan.projection {
+            Some(projection) => projection[index],
+            None => index,
+        };
+        let table_name = table_scan.table_name.table();
+
+        let (is_rowid, column_name) = {
+            let database = self.database.borrow();
+            let Some(table) = database.db_meta.schema_objects.iter().find(|schema_obj| {
+                schema_obj.obj_type == SchemaObjType::Table && schema_obj.name == table_name
+            }) else {
+                return Ok(None);
+            };
+            // the hidden rowid columns come after the table columns
+            match table.columns.get(column_index) {
+                Some(column) => (
+                    table.rowid_alias() == Some(column_index),
+                    column.name().clone(),
+                ),
+                None => (true, String::new()),
+            }
+        };
+        let lookup = if is_rowid {
+            Some(JoinLookup::Rowid)
+        } else {
+            self.searchable_indexes(table_name)?
+                .into_iter()
+                .find(|index| {
+                    let first = &index.columns[0];
+                    !first.expression
+                        && first.name.eq_ignore_ascii_case(&column_name)
+                        && index.key_info.collations[0] == collation
+                })
+                .map(|index| JoinLookup::Index {
+                    name: index.name,
+                    root_page_number: index.root_page_number,
+                    key_info: index.key_info,
+                })
+        };
+        let Some(lookup) = lookup else {
+            return Ok(None);
+        };
+        let right_scan = ExecScan::try_new(
+            table_name.to_owned(),
+            scan_projection(table_scan),
+            self.database.clone(),
+        )?;
+        Ok(Some((right_scan, lookup)))
+    }
+
+    // Whether the records of plan are in ascending order of expr: a column whose values
+    // are the rowid of a table scan, returned in rowid order.
+    fn is_rowid_ordered(&self, plan: &LogicalPlan, expr: &Expr) -> bool {
+        let Expr::Column(column) = expr else {
+            return false;
+        };
+        let Ok(index) = plan.schema().index_of_column(column) else {
+            return false;
+        };
+        self.rowid_columns(plan).contains(&index)
+    }
+
+    // Indices of the columns of plan holding the rowid of the records of a table scan,
+    // the INTEGER PRIMARY KEY and the hidden rowid columns, if the records are still in
+    // rowid order.
+    fn rowid_columns(&self, plan: &LogicalPlan) -> Vec<usize> {
+        match plan {
+            LogicalPlan::TableScan(table_scan) => {
+                let database = self.database.borrow();
+                let rowid_alias = database
+                    .db_meta
+                    .schema_objects
+                    .iter()
+                    .find(|schema_obj| {
+                        schema_obj.obj_type == SchemaObjType::Table
+                            && schema_obj.name == table_scan.table_name.table()
+                    })
+                    .and_then(|table| table.rowid_alias());
+                let source_schema = table_scan.source.schema();
+                let is_rowid = |index: usize| {
+                    Some(index) == rowid_alias || is_hidden(source_schema.field(index))
+                };
+                match &table_scan.projection {
+                    Some(projection) => (0..projection.len())
+                        .filter(|&position| is_rowid(projection[position]))
+                        .collect(),
+                    None => (0..source_schema.fields().len())
+                        .filter(|&index| is_rowid(index))
+                        .collect(),
+                }
+            }
+            LogicalPlan::Filter(filter) => match self.filter_scan(filter) {
+                // an index scan returns the records in index order
+                Ok(Some((_, FilterScan::Index(..)))) => vec![],
+                _ => self.rowid_columns(&filter.input),
+            },
+            LogicalPlan::SubqueryAlias(alias) => self.rowid_columns(&alias.input),
+            LogicalPlan::Limit(limit) => self.rowid_columns(&limit.input),
+            LogicalPlan::Projection(projection) => {
+                let input_indices = self.rowid_columns(&projection.input);
+                let input_schema = projection.input.schema();
+                (0..projection.expr.len())
+                    .filter(
+                        |&position| match projection.expr[position].clone().unalias() {
+                            Expr::Column(column) => input_schema
+                                .index_of_column(&column)
+                                .is_ok_and(|index| input_indices.contains(&index)),
+                            _ => false,
+                        },
                     )
-                    .unwrap(),
-                )
+                    .collect()
             }
+            _ => vec![],
+        }
+    }
 
-            _ => {
-                error!("error executing plan {logical_plan:#?}");
-                // TODO make return type Result with error
-                Arc::new(ExecDummy {})
+    /// Plans `INSERT INTO table [(columns)] source` from the statement: datafusion would
+    /// cast the values to the arrow types of the columns and require one type per column
+    /// of a VALUES list, SQLite applies the affinity of the column to each value instead
+    /// (see ExecInsert). The rows of VALUES are evaluated expression by expression, another
+    /// source is planned as a query.
+    pub fn plan_insert<S: ContextProvider>(
+        &self,
+        statement: &Statement,
+        sql_to_rel: &SqlToRel<S>,
+    ) -> anyhow::Result<Box<dyn Exec>> {
+        let (table_name, columns, source) = match statement {
+            Statement::Insert {
+                or: None,
+                on: None,
+                returning: None,
+                table_name,
+                columns,
+                source,
+                ..
+            } => (table_name, columns, source),
+            _ => bail!("unsupported statement: {statement}"),
+        };
+        let table_name = object_name_to_table_reference(table_name.clone(), true)?;
+        let input_columns = columns.iter().map(|column| column.value.clone()).collect();
+        let input = match source.body.as_ref() {
+            SetExpr::Values(values)
+                if source.with.is_none()
+                    && source.order_by.is_empty()
+                    && source.limit.is_none()
+                    && source.offset.is_none() =>
+            {
+                self.plan_values(&values.rows, sql_to_rel)?
             }
+            _ => self.plan(&sql_to_rel.sql_statement_to_plan(Statement::Query(source.clone()))?)?,
+        };
+        Ok(Box::new(ExecInsert::try_new(
+            table_name.table().to_owned(),
+            input_columns,
+            input,
+            self.database.clone(),
+        )?))
+    }
+
+    // The rows of `VALUES (...), (...)` of an INSERT, whose values can have any storage
+    // class: the fields of the schema are untyped.
+    fn plan_values<S: ContextProvider>(
+        &self,
+        rows: &[Vec<SqlExpr>],
+        sql_to_rel: &SqlToRel<S>,
+    ) -> anyhow::Result<Box<dyn Exec>> {
+        let width = rows.first().map_or(0, Vec::len);
+        if rows.iter().any(|row| row.len() != width) {
+            bail!("all VALUES must have the same number of terms");
         }
+        let schema = DFSchema::empty();
+        let empty = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        let records = rows
+            .iter()
+            .map(|row| {
+                let values = row
+                    .iter()
+                    .map(|expr| {
+                        let expr = sql_to_rel.sql_to_expr(
+                            expr.clone(),
+                            &schema,
+                            &mut PlannerContext::new(),
+                        )?;
+                        Ok(create_physical_expr(&expr, &schema)?.evaluate(&empty))
+                    })
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(DataRecord {
+                    values,
+                    rowid: None,
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let fields: Vec<Field> = (1..=width)
+            .map(|index| Field::new(format!("column{index}"), DataType::Null, true))
+            .collect();
+        Ok(Box::new(ExecMemTable::new(
+            &records,
+            Arc::new(Schema::new(fields)),
+        )))
+    }
+
+    /// Plans `UPDATE table SET column = value, ... [WHERE ...]` from the statement, like
+    /// INSERT (see plan_insert): the input is `SELECT value AS column, ... FROM table
+    /// [WHERE ...]` with the new values of all columns of the table on the rows to update,
+    /// keeping their rowid. The values get the affinity of the columns in ExecUpdate.
+    pub fn plan_update<S: ContextProvider>(
+        &self,
+        statement: &Statement,
+        sql_to_rel: &SqlToRel<S>,
+    ) -> anyhow::Result<Box<dyn Exec>> {
+        let (table, assignments, selection) = match statement {
+            Statement::Update {
+                table,
+                assignments,
+                from: None,
+                selection,
+                returning: None,
+            } => (table, assignments, selection),
+            _ => bail!("unsupported statement: {statement}"),
+        };
+        let TableFactor::Table { name, .. } = &table.relation else {
+            bail!("cannot update {}", table.relation);
+        };
+        let table_name = object_name_to_table_reference(name.clone(), true)?;
+        let table_name = table_name.table().to_owned();
+        let columns = {
+            let db = self.database.borrow();
+            db.db_meta
+                .schema_objects
+                .iter()
+                .find(|obj| obj.obj_type == SchemaObjType::Table && obj.name == table_name)
+                .map(|obj| obj.columns.clone())
+                .ok_or_else(|| anyhow::anyhow!("no such table: {table_name}"))?
+        };
+
+        let mut values: Vec<Option<SqlExpr>> = vec![None; columns.len()];
+        for assignment in assignments {
+            let Some(column) = assignment.id.last() else {
+                bail!("no column to update");
+            };
+            match columns
+                .iter()
+                .position(|field| field.name().eq_ignore_ascii_case(&column.value))
+            {
+                Some(index) => values[index] = Some(assignment.value.clone()),
+                // the rowid changes with the INTEGER PRIMARY KEY
+                None if ROWID_NAMES
+                    .iter()
+                    .any(|name| column.value.eq_ignore_ascii_case(name)) =>
+                {
+                    bail!("cannot update {}: the rowid is not a column", column.value)
+                }
+                None => bail!("no such column: {}", column.value),
+            }
+        }
+        let projection = columns
+            .iter()
+            .zip(values)
+            .map(|(field, value)| {
+                let column = Ident::with_quote('"', field.name());
+                match value {
+                    Some(expr) => SelectItem::ExprWithAlias {
+                        expr,
+                        alias: column,
+                    },
+                    None => SelectItem::UnnamedExpr(SqlExpr::Identifier(column)),
+                }
+            })
+            .collect();
+        let select = Select {
+            distinct: None,
+            top: None,
+            projection,
+            into: None,
+            from: vec![table.clone()],
+            lateral_views: vec![],
+            selection: selection.clone(),
+            group_by: GroupByExpr::Expressions(vec![]),
+            cluster_by: vec![],
+            distribute_by: vec![],
+            sort_by: vec![],
+            having: None,
+            named_window: vec![],
+            qualify: None,
+        };
+        let query = Query {
+            with: None,
+            body: Box::new(SetExpr::Select(Box::new(select))),
+            order_by: vec![],
+            limit: None,
+            limit_by: vec![],
+            offset: None,
+            fetch: None,
+            locks: vec![],
+        };
+        let input = sql_to_rel.sql_statement_to_plan(Statement::Query(Box::new(query)))?;
+        Ok(Box::new(ExecUpdate::try_new(
+            table_name,
+            self.plan(&input)?,
+            self.database.clone(),
+        )?))
+    }
+
+    fn plan_projection(
+        &self,
+        logical_proj: &Projection,
+        input_physical_plan: Box<dyn Exec>,
+    ) -> anyhow::Result<Box<dyn Exec>> {
+        let physical_expressions = logical_proj
+            .expr
+            .iter()
+            .map(|logical_expr| create_physical_expr(logical_expr, logical_proj.input.schema()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Box::new(ExecProjection::new(
+            input_physical_plan,
+            physical_expressions,
+            Arc::new(logical_proj.schema.as_ref().into()),
+        )?))
+    }
+
+    // Plans the Sort under a LIMIT, possibly below projections, keeping only the first
+    // fetch records. None if there is no such Sort.
+    fn plan_fetch(
+        &self,
+        logical_plan: &LogicalPlan,
+        fetch: usize,
+    ) -> anyhow::Result<Option<Box<dyn Exec>>> {
+        Ok(match logical_plan {
+            LogicalPlan::Sort(sort) => {
+                let fetch = sort.fetch.map_or(fetch, |sort_fetch| sort_fetch.min(fetch));
+                Some(self.plan_sort(sort, Some(fetch))?)
+            }
+            LogicalPlan::Projection(logical_proj) => {
+                match self.plan_fetch(&logical_proj.input, fetch)? {
+                    Some(input) => Some(self.plan_projection(logical_proj, input)?),
+                    None => None,
+                }
+            }
+            _ => None,
+        })
+    }
+
+    fn plan_sort(&self, sort: &Sort, fetch: Option<usize>) -> anyhow::Result<Box<dyn Exec>> {
+        // ORDER BY the rowid of a table scan: its records are already in rowid order, or in
+        // descending order when the table B-Tree is walked backward. The rowid is unique,
+        // the other sort keys do not change the order.
+        if let Some(Expr::Sort(first)) = sort.expr.first() {
+            if self.is_rowid_ordered(&sort.input, &first.expr) {
+                let ordered = if first.asc {
+                    Some(self.plan(&sort.input)?)
+                } else {
+                    self.plan_rowid_descending(&sort.input)?
+                };
+                if let Some(ordered) = ordered {
+                    return Ok(match fetch {
+                        Some(fetch) => Box::new(ExecLimit::new(ordered, 0, Some(fetch))),
+                        None => ordered,
+                    });
+                }
+            }
+        }
+        let sort_exprs = sort
+            .expr
+            .iter()
+            .map(|expr| match expr {
+                Expr::Sort(sort_expr) => Ok(SortExpr {
+                    expr: create_physical_expr(&sort_expr.expr, sort.input.schema())?,
+                    descending: !sort_expr.asc,
+                    nulls_first: sort_expr.nulls_first,
+                }),
+                _ => bail!("cannot create sort expression from {expr}"),
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let input_physical_plan = self.plan(&sort.input)?;
+        Ok(Box::new(
+            ExecSort::new(
+                input_physical_plan,
+                sort_exprs,
+                fetch,
+                Arc::new(sort.input.schema().as_ref().into()),
+            )
+            .with_memory_limit(self.sort_memory_limit),
+        ))
     }
 }
 
 pub fn create_physical_expr(
-    logical_expr: &datafusion_expr::Expr,
-    input: &LogicalPlan,
+    logical_expr: &Expr,
+    input_schema: &DFSchema,
 ) -> anyhow::Result<Arc<dyn PhysicalExpr>> {
-    match logical_expr {
-        datafusion_expr::Expr::Column(col) => {
-            let schema = input.schema();
-            let col_index = schema.index_of_column(col)?;
-            Ok(Arc::new(PhysicalColByIndex { col_index }))
-        }
-        datafusion_expr::Expr::Literal(_) => {
-            let column_value = ColumnValue::One;
-            Ok(Arc::new(PhysicalLiteral {
-                value: column_value,
-            }))
+    let create = |expr: &Expr| create_physical_expr(expr, input_schema);
+    Ok(match logical_expr {
+        Expr::Alias(alias) => create(&alias.expr)?,
+        Expr::Column(col) => {
+            let col_index = input_schema.index_of_column(col)?;
+            // table columns carry their affinity and collation in the field metadata
+            let field = input_schema.field(col_index).field();
+            let affinity = field
+                .metadata()
+                .get(FIELD_AFFINITY)
+                .map(|name| Affinity::from_name(name))
+                .transpose()?;
+            // a table column without COLLATE still has the BINARY collation: it takes
+            // precedence over the one of the right operand of a comparison
+            let collation = match (field_collation(field)?, affinity) {
+                (Some(collation), _) => Some(collation),
+                (None, Some(_)) => Some(Collation::Binary),
+                (None, None) => None,
+            };
+            Arc::new(PhysicalColByIndex {
+                col_index,
+                affinity,
+                collation,
+            })
+        }
+        Expr::Literal(scalar) => Arc::new(PhysicalLiteral {
+            value: scalar_to_value(scalar)?,
+        }),
+        Expr::BinaryExpr(binary) => Arc::new(PhysicalBinary::try_new(
+            create(&binary.left)?,
+            binary.op,
+            create(&binary.right)?,
+        )?),
+        Expr::Not(expr) => Arc::new(PhysicalNot {
+            expr: create(expr)?,
+        }),
+        Expr::Negative(expr) => Arc::new(PhysicalNegative {
+            expr: create(expr)?,
+        }),
+        Expr::IsNull(expr) | Expr::IsNotNull(expr) => Arc::new(PhysicalIsNull {
+            expr: create(expr)?,
+            negated: matches!(logical_expr, Expr::IsNotNull(_)),
+        }),
+        Expr::InList(in_list) => Arc::new(PhysicalInList {
+            expr: create(&in_list.expr)?,
+            list: in_list
+                .list
+                .iter()
+                .map(create)
+                .collect::<anyhow::Result<_>>()?,
+            negated: in_list.negated,
+        }),
+        Expr::Between(between) => Arc::new(PhysicalBetween {
+            expr: create(&between.expr)?,
+            low: create(&between.low)?,
+            high: create(&between.high)?,
+            negated: between.negated,
+        }),
+        // LIKE is case-insensitive in SQLite, ILIKE is the same operator
+        Expr::Like(like) => Arc::new(PhysicalLike {
+            expr: create(&like.expr)?,
+            pattern: create(&like.pattern)?,
+            kind: PatternKind::Like {
+                escape: like.escape_char,
+            },
+            negated: like.negated,
+        }),
+        Expr::Case(case) => Arc::new(PhysicalCase {
+            base: case.expr.as_deref().map(create).transpose()?,
+            when_then: case
+                .when_then_expr
+                .iter()
+                .map(|(when, then)| Ok((create(when)?, create(then)?)))
+                .collect::<anyhow::Result<_>>()?,
+            else_expr: case.else_expr.as_deref().map(create).transpose()?,
+        }),
+        Expr::Cast(Cast { expr, data_type }) | Expr::TryCast(TryCast { expr, data_type }) => {
+            Arc::new(PhysicalCast {
+                expr: create(expr)?,
+                affinity: cast_affinity(data_type)?,
+            })
         }
+        Expr::ScalarUDF(udf) => create_function(udf.fun.name.as_str(), &udf.args, input_schema)?,
         _ => bail!("cannot create physical expr from {logical_expr}"),
+    })
+}
+
+// How the table scanned under a filter is read instead of a full scan.
+enum FilterScan {
+    Rowids(RangeInclusive<i64>),
+    Index(SearchableIndex, IndexBounds),
+}
+
+// An index the planner can search, see PhysicalPlanner::searchable_indexes.
+struct SearchableIndex {
+    name: String,
+    root_page_number: u32,
+    columns: Vec<IndexedColumn>,
+    key_info: KeyInfo,
+}
+
+// `column op value` in a filter: column is the index of a column of the input and
+// value a constant converted with the affinity of the comparison.
+struct ColumnComparison {
+    column: usize,
+    op: Operator,
+    value: ColumnValue,
+    collation: Collation,
+}
+
+// The comparisons of columns with constants joined by AND in predicate, the bounds of
+// `column BETWEEN low AND high` included. Comparisons with NULL are left out, they
+// select no rows.
+fn column_comparisons(
+    predicate: &Expr,
+    schema: &DFSchema,
+) -> anyhow::Result<Vec<ColumnComparison>> {
+    let is_constant = |expr: &Expr| expr.to_columns().is_ok_and(|columns| columns.is_empty());
+    let is_column = |expr: &Expr| matches!(expr, Expr::Column(_));
+    let mut comparisons = vec![];
+    for conjunct in split_conjunction(predicate) {
+        let sides = match conjunct {
+            Expr::BinaryExpr(BinaryExpr { left, op, right })
+                if is_column(left) && is_constant(right) =>
+            {
+                vec![(left, *op, right)]
+            }
+            Expr::BinaryExpr(BinaryExpr { left, op, right })
+                if is_column(right) && is_constant(left) =>
+            {
+                op.swap().map(|op| (right, op, left)).into_iter().collect()
+            }
+            Expr::Between(Between {
+                expr,
+                negated: false,
+                low,
+                high,
+            }) if is_column(expr) && is_constant(low) && is_constant(high) => {
+                vec![(expr, Operator::GtEq, low), (expr, Operator::LtEq, high)]
+            }
+            _ => vec![],
+        };
+        for (column, op, value) in sides {
+            let (
+                Expr::Column(logical_column),
+                Operator::Eq | Operator::Lt | Operator::LtEq | Operator::Gt | Operator::GtEq,
+            ) = (column.as_ref(), op)
+            else {
+                continue;
+            };
+            let column_index = schema.index_of_column(logical_column)?;
+            let column = create_physical_expr(column, schema)?;
+            let value = create_physical_expr(value, schema)?;
+            let (_, affinity) = comparison_affinities(column.as_ref(), value.as_ref());
+            let collation = comparison_collation(column.as_ref(), value.as_ref());
+            let constant = DataRecord {
+                values: vec![],
+                rowid: None,
+            };
+            let value = match affinity {
+                Some(affinity) => value.evaluate(&constant).apply_affinity(affinity),
+                None => value.evaluate(&constant),
+            };
+            if !value.is_null() {
+                comparisons.push(ColumnComparison {
+                    column: column_index,
+                    op,
+                    value,
+                    collation,
+                });
+            }
+        }
+    }
+    Ok(comparisons)
+}
+
+// a AND b AND c is [a, b, c]
+fn split_conjunction(expr: &Expr) -> Vec<&Expr> {
+    match expr {
+        Expr::BinaryExpr(BinaryExpr {
+            left,
+            op: Operator::And,
+            right,
+        }) => {
+            let mut predicates = split_conjunction(left);
+            predicates.extend(split_conjunction(right));
+            predicates
+        }
+        _ => vec![expr],
+    }
+}
+
+// Rowids r for which `r op value` is true. None if value is not a number: TEXT and BLOB
+// comparisons are left to the filter.
+fn comparison_rowids(op: Operator, value: &ColumnValue) -> Option<RangeInclusive<i64>> {
+    #[allow(clippy::reversed_empty_ranges)]
+    const EMPTY: RangeInclusive<i64> = 1..=0;
+    if let Some(int) = value.as_i64() {
+        return Some(match op {
+            Operator::Eq => int..=int,
+            Operator::Gt => int.checked_add(1).map_or(EMPTY, |low| low..=i64::MAX),
+            Operator::GtEq => int..=i64::MAX,
+            Operator::Lt => int.checked_sub(1).map_or(EMPTY, |high| i64::MIN..=high),
+            Operator::LtEq => i64::MIN..=int,
+            _ => return None,
+        });
+    }
+    // integers are compared with reals by their numeric value
+    let real = value.as_f64().filter(|real| !real.is_nan())?;
+    let (low, high) = match op {
+        Operator::Eq => (real.ceil(), real.floor()),
+        Operator::Gt => (real.floor() + 1.0, f64::INFINITY),
+        Operator::GtEq => (real.ceil(), f64::INFINITY),
+        Operator::Lt => (f64::NEG_INFINITY, real.ceil() - 1.0),
+        Operator::LtEq => (f64::NEG_INFINITY, real.floor()),
+        _ => return None,
+    };
+    // 2^63 is larger than any rowid, -2^63 is i64::MIN
+    if low > high || low >= 9223372036854775808.0 || high < -9223372036854775808.0 {
+        return Some(EMPTY);
+    }
+    // the casts saturate the unbounded ends
+    Some(low as i64..=high as i64)
+}
+
+// Sides of `left = right` when each one only has columns of one input, swapped if needed
+// with true.
+fn join_key<'a>(
+    predicate: &'a Expr,
+    left_schema: &DFSchema,
+    right_schema: &DFSchema,
+) -> Option<(&'a Expr, &'a Expr, bool)> {
+    let Expr::BinaryExpr(BinaryExpr {
+        left,
+        op: Operator::Eq,
+        right,
+    }) = predicate
+    else {
+        return None;
+    };
+    let only_columns_of = |expr: &Expr, schema: &DFSchema| match expr.to_columns() {
+        Ok(columns) => {
+            !columns.is_empty()
+                && columns
+                    .iter()
+                    .all(|col| schema.index_of_column(col).is_ok())
+        }
+        Err(_) => false,
+    };
+    if only_columns_of(left, left_schema) && only_columns_of(right, right_schema) {
+        Some((left, right, false))
+    } else if only_columns_of(left, right_schema) && only_columns_of(right, left_schema) {
+        Some((right, left, true))
+    } else {
+        None
+    }
+}
+
+// Collation of a column, kept in the metadata of its field.
+// How to compare the keys of an index: the collation of an indexed column is the one
+// of its COLLATE clause, or the one of the table column.
+pub(crate) fn index_key_info(
+    index_def: &IndexDef,
+    table_columns: &[Field],
+) -> anyhow::Result<KeyInfo> {
+    let mut sort_orders = vec![];
+    let mut collations = vec![];
+    for indexed_column in &index_def.columns {
+        let table_column = table_columns.iter().find(|field| {
+            !indexed_column.expression && field.name().eq_ignore_ascii_case(&indexed_column.name)
+        });
+        let collation = match (indexed_column.collation, table_column) {
+            (Some(collation), _) => Some(collation),
+            (None, Some(field)) => field_collation(field)?,
+            (None, None) => None,
+        };
+        sort_orders.push(indexed_column.sort_order);
+        collations.push(collation.unwrap_or_default());
+    }
+    Ok(KeyInfo::new(sort_orders, collations))
+}
+
+// The columns of the table read by a table scan. The table source has the hidden rowid
+// columns after the table columns when the statement names one of them (see
+// `SqliteContextProvider::with_rowid_columns`), ExecScan returns them if they are listed.
+fn scan_projection(table_scan: &TableScan) -> Option<Vec<usize>> {
+    let source_schema = table_scan.source.schema();
+    match &table_scan.projection {
+        Some(projection) => Some(projection.clone()),
+        None if source_schema.fields().iter().any(|field| is_hidden(field)) => {
+            Some((0..source_schema.fields().len()).collect())
+        }
+        None => None,
+    }
+}
+
+fn is_hidden(field: &Field) -> bool {
+    field.metadata().get(FIELD_HIDDEN) == Some(&"true".to_owned())
+}
+
+fn field_collation(field: &Field) -> anyhow::Result<Option<Collation>> {
+    field
+        .metadata()
+        .get(FIELD_COLLATION)
+        .map(|name| Collation::from_name(name))
+        .transpose()
+}
+
+/// Creates an aggregate function call: count is the datafusion built-in aggregate, the
+/// others are user-defined (see sql::functions).
+pub fn create_aggregate_expr(
+    logical_expr: &Expr,
+    input_schema: &DFSchema,
+) -> anyhow::Result<AggregateExpr> {
+    let (name, args, distinct, filter, order_by) = match logical_expr {
+        Expr::Alias(alias) => return create_aggregate_expr(&alias.expr, input_schema),
+        Expr::AggregateFunction(function) => (
+            function.fun.to_string(),
+            &function.args,
+            function.distinct,
+            &function.filter,
+            &function.order_by,
+        ),
+        Expr::AggregateUDF(udaf) => {
+            let name = udaf.fun.name.as_str();
+            let (name, distinct) = match name.strip_suffix(DISTINCT_SUFFIX) {
+                Some(name) => (name, true),
+                None => (name, false),
+            };
+            (
+                name.to_owned(),
+                &udaf.args,
+                distinct,
+                &udaf.filter,
+                &udaf.order_by,
+            )
+        }
+        _ => bail!("cannot create aggregate from {logical_expr}"),
+    };
+    if order_by.is_some() {
+        bail!("unsupported ORDER BY in aggregate {logical_expr}");
     }
+    AggregateExpr::try_new(
+        AggregateFunction::from_name(&name)?,
+        args.iter()
+            .map(|arg| match arg {
+                // count(*) counts rows: its argument is never NULL
+                Expr::Wildcard { .. } => Ok(Arc::new(PhysicalLiteral {
+                    value: ColumnValue::One,
+                }) as Arc<dyn PhysicalExpr>),
+                _ => create_physical_expr(arg, input_schema),
+            })
+            .collect::<anyhow::Result<_>>()?,
+        distinct,
+        filter
+            .as_deref()
+            .map(|filter| create_physical_expr(filter, input_schema))
+            .transpose()?,
+    )
+}
+
+// glob(X, Y) is `Y GLOB X`, like(X, Y[, Z]) is `Y LIKE X [ESCAPE Z]`,
+// collate(X, name) is `X COLLATE name` and arithmetic_operand(X) is X, an operand of an
+// arithmetic operator (see rewrite::sqlite_operators).
+// https://www.sqlite.org/lang_corefunc.html#glob
+fn create_function(
+    name: &str,
+    args: &[Expr],
+    input_schema: &DFSchema,
+) -> anyhow::Result<Arc<dyn PhysicalExpr>> {
+    match (name, args) {
+        ("arithmetic_operand", [expr]) => return create_physical_expr(expr, input_schema),
+        ("length", [expr]) => {
+            return Ok(Arc::new(PhysicalLength {
+                expr: create_physical_expr(expr, input_schema)?,
+            }))
+        }
+        ("coalesce", [_, _, ..]) | ("ifnull", [_, _]) => {
+            return Ok(Arc::new(PhysicalCoalesce {
+                args: args
+                    .iter()
+                    .map(|arg| create_physical_expr(arg, input_schema))
+                    .collect::<anyhow::Result<_>>()?,
+            }))
+        }
+        ("length" | "coalesce" | "ifnull", _) => {
+            bail!("wrong number of arguments to function {name}()")
+        }
+        _ => {}
+    }
+    if let ("collate", [expr, Expr::Literal(ScalarValue::Utf8(Some(collation)))]) = (name, args) {
+        return Ok(Arc::new(PhysicalCollate {
+            expr: create_physical_expr(expr, input_schema)?,
+            collation: Collation::from_name(collation)?,
+        }));
+    }
+    let kind = match (name, args) {
+        ("glob", [_, _]) => PatternKind::Glob,
+        ("like", [_, _]) => PatternKind::Like { escape: None },
+        ("like", [_, _, Expr::Literal(ScalarValue::Utf8(Some(escape)))]) => {
+            let mut chars = escape.chars();
+            match (chars.next(), chars.next()) {
+                (Some(escape), None) => PatternKind::Like {
+                    escape: Some(escape),
+                },
+                _ => bail!("ESCAPE expression must be a single character"),
+            }
+        }
+        _ => bail!("unsupported function {name} with {} arguments", args.len()),
+    };
+    Ok(Arc::new(PhysicalLike {
+        expr: create_physical_expr(&args[1], input_schema)?,
+        pattern: create_physical_expr(&args[0], input_schema)?,
+        kind,
+        negated: false,
+    }))
+}
+
+/// Converts a literal to a value of the SQLite storage class, booleans are integers.
+fn scalar_to_value(scalar: &ScalarValue) -> anyhow::Result<ColumnValue> {
+    if scalar.is_null() {
+        return Ok(ColumnValue::Null);
+    }
+    Ok(match scalar {
+        ScalarValue::Boolean(Some(value)) => ColumnValue::from_bool(*value),
+        ScalarValue::Int8(Some(int)) => ColumnValue::int64((*int).into()),
+        ScalarValue::Int16(Some(int)) => ColumnValue::int64((*int).into()),
+        ScalarValue::Int32(Some(int)) => ColumnValue::int64((*int).into()),
+        ScalarValue::Int64(Some(int)) => ColumnValue::int64(*int),
+        ScalarValue::UInt8(Some(int)) => ColumnValue::int64((*int).into()),
+        ScalarValue::UInt16(Some(int)) => ColumnValue::int64((*int).into()),
+        ScalarValue::UInt32(Some(int)) => ColumnValue::int64((*int).into()),
+        // integers larger than i64 are reals in SQLite
+        ScalarValue::UInt64(Some(int)) => match i64::try_from(*int) {
+            Ok(int) => ColumnValue::int64(int),
+            Err(_) => ColumnValue::real(*int as f64),
+        },
+        ScalarValue::Float32(Some(real)) => ColumnValue::real((*real).into()),
+        ScalarValue::Float64(Some(real)) => ColumnValue::real(*real),
+        ScalarValue::Utf8(Some(text)) | ScalarValue::LargeUtf8(Some(text)) => {
+            ColumnValue::Text(text.clone())
+        }
+        ScalarValue::Binary(Some(bytes)) | ScalarValue::LargeBinary(Some(bytes)) => {
+            ColumnValue::Blob(bytes.clone())
+        }
+        _ => bail!("unsupported literal {scalar}"),
+    })
+}
+
+/// Affinity of the type of CAST(expr AS type), datafusion parses the SQL type name
+/// into an arrow type: INTEGER is Int32, REAL is Float32, NUMERIC is Decimal128, etc.
+/// BOOLEAN has NUMERIC affinity like in SQLite.
+fn cast_affinity(data_type: &DataType) -> anyhow::Result<Affinity> {
+    Ok(match data_type {
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => Affinity::Integer,
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => Affinity::Real,
+        DataType::Decimal128(_, _) | DataType::Decimal256(_, _) | DataType::Boolean => {
+            Affinity::Numeric
+        }
+        DataType::Utf8 | DataType::LargeUtf8 => Affinity::Text,
+        DataType::Binary | DataType::LargeBinary => Affinity::Blob,
+        _ => bail!("unsupported CAST to {data_type}"),
+    })
 }
 
-#[test]
-fn test_create_case_expr() {}
+#[cfg(test)]
+mod tests {
+    use datafusion_expr::{col, lit, when};
+
+    use datafusion_sql::planner::SqlToRel;
+    use datafusion_sql::sqlparser::dialect::SQLiteDialect;
+    use datafusion_sql::sqlparser::parser::Parser;
+
+    use super::*;
+    use crate::model::data_record::DataRecord;
+    use crate::sql::context_provider::SqliteContextProvider;
+    use crate::sql::rewrite;
+
+    fn evaluate(expr: Expr) -> ColumnValue {
+        let record = DataRecord {
+            values: vec![],
+            rowid: None,
+        };
+        create_physical_expr(&expr, &DFSchema::empty())
+            .unwrap()
+            .evaluate(&record)
+    }
+
+    #[test]
+    fn test_create_case_expr() {
+        let case = when(lit(1).gt(lit(2)), lit("a"))
+            .when(lit(2).gt(lit(1)), lit("b"))
+            .otherwise(lit("c"))
+            .unwrap();
+        assert_eq!(evaluate(case), ColumnValue::Text("b".to_owned()));
+    }
+
+    #[test]
+    fn test_create_literal_expr() {
+        assert_eq!(evaluate(lit(42i32)), ColumnValue::int64(42));
+        assert_eq!(evaluate(lit(true)), ColumnValue::One);
+        assert_eq!(evaluate(lit(1.5)), ColumnValue::real(1.5));
+        assert_eq!(evaluate(lit(ScalarValue::Null)), ColumnValue::Null);
+        assert_eq!(
+            evaluate((lit(7) + lit(1)).between(lit(1), lit(8))),
+            ColumnValue::One
+        );
+        assert_eq!(
+            evaluate(Expr::Cast(Cast::new(Box::new(lit("3.9")), DataType::Int32))),
+            ColumnValue::int64(3)
+        );
+    }
+
+    #[test]
+    fn test_create_unknown_column() {
+        assert!(create_physical_expr(&col("a"), &DFSchema::empty()).is_err());
+    }
+
+    fn plan_sql(db_name: &str, sql: &str) -> String {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/resources")
+            .join(db_name);
+        let database = Database::new(path.to_str().unwrap()).unwrap();
+        let mut provider = SqliteContextProvider::new_for_db(&database);
+        let mut statement = Parser::parse_sql(&SQLiteDialect {}, sql).unwrap().remove(0);
+        if rewrite::sqlite_rowid_columns(&mut statement, &database) {
+            provider = provider.with_rowid_columns(&database, None);
+        }
+        let logical_plan = SqlToRel::new(&provider)
+            .sql_statement_to_plan(statement)
+            .unwrap();
+        let planner = PhysicalPlanner::new(Rc::new(RefCell::new(database)));
+        format!("{:?}", planner.plan(&logical_plan).unwrap())
+    }
+
+    #[test]
+    fn test_plan_join_algorithm() {
+        // both scans are in order of their INTEGER PRIMARY KEY
+        let merge = plan_sql(
+            "sales.db",
+            "SELECT a.id FROM sales a JOIN sales AS b ON b.id = a.id",
+        );
+        assert!(merge.contains("ExecJoinSortMerge"));
+        let hash = plan_sql(
+            "sales.db",
+            "SELECT a.id FROM sales a JOIN sales b ON a.id = b.quantity",
+        );
+        assert!(hash.contains("ExecJoinHash"));
+        let nested_loop = plan_sql(
+            "sales.db",
+            "SELECT a.id FROM sales a JOIN sales b ON a.id < b.id",
+        );
+        assert!(nested_loop.contains("ExecJoinNestedLoop"));
+
+        // customers is searched by its rowid, or by idx_customers_email
+        let rowid = plan_sql(
+            "orders.db",
+            "SELECT o.id FROM orders o JOIN customers c ON o.cust_id = c.id",
+        );
+        assert!(rowid.contains("ExecJoinIndexNestedLoop") && rowid.contains("Rowid"));
+        let hidden_rowid = plan_sql(
+            "orders.db",
+            "SELECT o.id FROM orders o JOIN customers c ON o.cust_id = c.rowid",
+        );
+        assert!(hidden_rowid.contains("ExecJoinIndexNestedLoop") && hidden_rowid.contains("Rowid"));
+        let index = plan_sql(
+            "orders.db",
+            "SELECT o.id FROM orders o LEFT JOIN customers c ON c.email = o.email",
+        );
+        assert!(index.contains("idx_customers_email"));
+        // BINARY collation of the left operand, the index is NOCASE
+        let hash = plan_sql(
+            "orders.db",
+            "SELECT o.id FROM orders o JOIN customers c ON o.email = c.email",
+        );
+        assert!(hash.contains("ExecJoinHash"));
+    }
+
+    #[test]
+    fn test_plan_rowid_range_scan() {
+        let point = plan_sql("orders.db", "SELECT name FROM customers WHERE id = 4521");
+        assert!(point.contains("rowids: 4521..=4521"));
+        let range = plan_sql(
+            "orders.db",
+            "SELECT c.name FROM customers c WHERE c.id BETWEEN 10 AND 20.5 AND 12 < c.id",
+        );
+        assert!(range.contains("rowids: 13..=20"));
+        // no bound on the rowid: full scan
+        let full = plan_sql(
+            "orders.db",
+            "SELECT name FROM customers WHERE id = 5 OR name = 'x'",
+        );
+        assert!(full.contains("rowids: -9223372036854775808..=9223372036854775807"));
+        // the hidden rowid columns
+        let hidden = plan_sql(
+            "orders.db",
+            "SELECT * FROM customers WHERE rowid >= 10 AND oid < 20 AND _rowid_ <> 12",
+        );
+        assert!(hidden.contains("rowids: 10..=19"));
+    }
+
+    #[test]
+    fn test_plan_rowid_order() {
+        let descending = plan_sql("orders.db", "SELECT name FROM customers ORDER BY id DESC");
+        assert!(descending.contains("descending: true") && !descending.contains("ExecSort"));
+        let ascending = plan_sql(
+            "orders.db",
+            "SELECT id FROM customers c ORDER BY c.id LIMIT 3",
+        );
+        assert!(!ascending.contains("ExecSort"));
+        // the last rowid below 100, found by a seek
+        let max = plan_sql("orders.db", "SELECT max(id) FROM customers WHERE id < 100");
+        assert!(max.contains("rowids: -9223372036854775808..=99, descending: true"));
+        // the subquery keeps the first 5 rows of the table
+        let sort = plan_sql(
+            "orders.db",
+            "SELECT id FROM (SELECT id FROM customers LIMIT 5) ORDER BY id DESC",
+        );
+        assert!(sort.contains("ExecSort"));
+    }
+
+    #[test]
+    fn test_plan_index_scan() {
+        let equal = plan_sql(
+            "orders.db",
+            "SELECT id FROM customers WHERE email = 'c7@example.com' AND id > 5",
+        );
+        assert!(equal.contains("ExecIndexScan") && equal.contains("idx_customers_email"));
+        let range = plan_sql(
+            "orders.db",
+            "SELECT c.id FROM customers c WHERE 'c9' > c.email",
+        );
+        assert!(range.contains("low: Excluded(Null), high: Excluded(Text(\"c9\"))"));
+        // the rowid point lookup is preferred
+        let rowid = plan_sql(
+            "orders.db",
+            "SELECT id FROM customers WHERE email = 'c7@example.com' AND id = 21",
+        );
+        assert!(rowid.contains("rowids: 21..=21") && !rowid.contains("ExecIndexScan"));
+        // the comparison is BINARY, the index is NOCASE
+        let full = plan_sql("index.db", "SELECT id FROM people WHERE name = 'bob1'");
+        assert!(!full.contains("ExecIndexScan"));
+        // the records of the index scan are sorted by rowid
+        let sort = plan_sql(
+            "orders.db",
+            "SELECT id FROM customers WHERE email > 'c5' ORDER BY id",
+        );
+        assert!(sort.contains("ExecSort"));
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::HashSet;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use arrow_array::{ArrayRef, BooleanArray, RecordBatch};
+
+use crate::model::collation::Collation;
+use crate::model::column_value::ColumnValue;
+use crate::model::conversion::{parse_number, Number};
+use crate::model::data_record::DataRecord;
+use crate::model::hash_key::HashKey;
+use crate::physical::expression::kernels;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::batch::array_value;
+
+/// Aggregate functions of SQLite.
+/// https://www.sqlite.org/lang_aggfunc.html
+///
+/// All of them ignore NULL values, count(*) counts rows because its argument is
+/// the literal 1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AggregateFunction {
+    /// count(X): number of non-NULL X, an integer.
+    Count,
+    /// sum(X): NULL without values, an integer if all values are integers
+    /// (an error if the sum overflows) else a real.
+    Sum,
+    /// total(X): like sum but always a real, 0.0 without values. Never overflows.
+    Total,
+    /// avg(X): real average of the values, NULL without values.
+    Avg,
+    /// min(X), max(X): smallest or largest value in SQLite sort order
+    /// with the collation of X, NULL without values.
+    Min,
+    Max,
+    /// group_concat(X[, separator]): concatenation of the values as text
+    /// separated by separator (',' by default), NULL without values.
+    GroupConcat,
+}
+
+impl AggregateFunction {
+    pub fn from_name(name: &str) -> Result<Self> {
+        Ok(match name.to_ascii_lowercase().as_str() {
+            "count" => AggregateFunction::Count,
+            "sum" => AggregateFunction::Sum,
+            "total" => AggregateFunction::Total,
+            "avg" => AggregateFunction::Avg,
+            "min" => AggregateFunction::Min,
+            "max" => AggregateFunction::Max,
+            "group_concat" => AggregateFunction::GroupConcat,
+            _ => bail!("unsupported aggregate function {name}"),
+        })
+    }
+}
+
+/// An aggregate function call of a query, e.g. `count(DISTINCT x) FILTER (WHERE y > 1)`.
+#[derive(Debug)]
+pub struct AggregateExpr {
+    pub function: AggregateFunction,
+    pub args: Vec<Arc<dyn PhysicalExpr>>,
+    /// only distinct values of the first argument are aggregated
+    pub distinct: bool,
+    /// only rows for which filter is true are aggregated
+    pub filter: Option<Arc<dyn PhysicalExpr>>,
+}
+
+impl AggregateExpr {
+    pub fn try_new(
+        function: AggregateFunction,
+        args: Vec<Arc<dyn PhysicalExpr>>,
+        distinct: bool,
+        filter: Option<Arc<dyn PhysicalExpr>>,
+    ) -> Result<Self> {
+        let arg_count_ok = match function {
+            AggregateFunction::GroupConcat if distinct => args.len() == 1,
+            AggregateFunction::GroupConcat => (1..=2).contains(&args.len()),
+            _ => args.len() == 1,
+        };
+        if !arg_count_ok {
+            bail!("wrong number of arguments to function {function:?}")
+        }
+        Ok(AggregateExpr {
+            function,
+            args,
+            distinct,
+            filter,
+        })
+    }
+
+    /// Creates the accumulator of one group.
+    pub fn create_accumulator(&self) -> Accumulator {
+        let state = match self.function {
+            AggregateFunction::Count => State::Count(0),
+            AggregateFunction::Sum | AggregateFunction::Total | AggregateFunction::Avg => {
+                State::Sum {
+                    count: 0,
+                    all_integers: true,
+                    integer_sum: Some(0),
+                    real_sum: 0.0,
+                }
+            }
+            AggregateFunction::Min | AggregateFunction::Max => State::Extreme(None),
+            AggregateFunction::GroupConcat => State::Concat(None),
+        };
+        Accumulator {
+            state,
+            seen: self.distinct.then(HashSet::new),
+        }
+    }
+
+    /// Adds the values of record to the accumulator of its group.
+    pub fn update(&self, accumulator: &mut Accumulator, record: &DataRecord) -> Result<()> {
+        if let Some(filter) = &self.filter {
+            if filter.evaluate(record).is_true() != Some(true) {
+                return Ok(());
+            }
+        }
+        let args = self.args.iter().map(|arg| arg.evaluate(record)).collect();
+        self.add(accumulator, args)
+    }
+
+    /// Evaluates the arguments and the filter on the rows of a batch at once.
+    pub fn evaluate_batch(&self, batch: &RecordBatch) -> Result<BatchArguments> {
+        let args = self
+            .args
+            .iter()
+            .map(|arg| arg.evaluate_batch(batch))
+            .collect::<Result<_>>()?;
+        let mask = match &self.filter {
+            Some(filter) => Some(kernels::is_true(&filter.evaluate_batch(batch)?)?),
+            None => None,
+        };
+        Ok(BatchArguments { args, mask })
+    }
+
+    /// Adds the values of the arguments of a row, the value then the separator of
+    /// group_concat, to the accumulator of its group.
+    pub fn add(&self, accumulator: &mut Accumulator, args: Vec<ColumnValue>) -> Result<()> {
+        let mut args = args.into_iter();
+        let value = args.next().unwrap_or(ColumnValue::Null);
+        if value.is_null() {
+            return Ok(());
+        }
+        let collation = self.args[0].collation().unwrap_or_default();
+        if let Some(seen) = &mut accumulator.seen {
+            if !seen.insert(HashKey::new(&value, collation)) {
+                return Ok(());
+            }
+        }
+
+        match &mut accumulator.state {
+            State::Count(count) => *count += 1,
+            State::Sum {
+                count,
+                all_integers,
+                integer_sum,
+                real_sum,
+            } => {
+                *count += 1;
+                // TEXT that is a well-formed integer is an integer (sqlite3_value_numeric_type),
+                // other TEXT and BLOB values make the sum a real
+                let integer = match &value {
+                    ColumnValue::Text(text) => match parse_number(text) {
+                        Some(Number::Integer(int)) => Some(int),
+                        _ => None,
+                    },
+                    value => value.as_i64(),
+                };
+                match integer {
+                    Some(int) => {
+                        *integer_sum = integer_sum.and_then(|sum| sum.checked_add(int));
+                        *real_sum += int as f64;
+                    }
+                    None => {
+                        *all_integers = false;
+                        *real_sum += value.to_number().map_or(0.0, |number| number.as_f64());
+                    }
+                }
+            }
+            State::Extreme(extreme) => {
+                let replace = match extreme {
+                    None => true,
+                    Some(current) => {
+                        let ordering = value.compare(current, &collation);
+                        match self.function {
+                            AggregateFunction::Min => ordering.is_lt(),
+                            _ => ordering.is_gt(),
+                        }
+                    }
+                };
+                if replace {
+                    *extreme = Some(value);
+                }
+            }
+            State::Concat(concat) => {
+                let text = value.to_text().unwrap_or_default();
+                match concat {
+                    None => *concat = Some(text),
+                    Some(concat) => {
+                        let separator = match args.next() {
+                            Some(separator) => separator.to_text(),
+                            None => Some(",".to_owned()),
+                        };
+                        concat.push_str(&separator.unwrap_or_default());
+                        concat.push_str(&text);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the result of the aggregate for the group of accumulator.
+    pub fn evaluate(&self, accumulator: &Accumulator) -> Result<ColumnValue> {
+        Ok(match &accumulator.state {
+            State::Count(count) => ColumnValue::int64(*count),
+            State::Sum {
+                count,
+                all_integers,
+                integer_sum,
+                real_sum,
+            } => match self.function {
+                AggregateFunction::Total => ColumnValue::real(*real_sum),
+                _ if *count == 0 => ColumnValue::Null,
+                AggregateFunction::Avg => ColumnValue::real(*real_sum / *count as f64),
+                _ if *all_integers => match integer_sum {
+                    Some(sum) => ColumnValue::int64(*sum),
+                    None => bail!("integer overflow"),
+                },
+                _ => ColumnValue::real(*real_sum),
+            },
+            State::Extreme(extreme) => extreme.clone().unwrap_or(ColumnValue::Null),
+            State::Concat(concat) => concat.clone().map_or(ColumnValue::Null, ColumnValue::Text),
+        })
+    }
+}
+
+/// The arguments of an aggregate evaluated on a batch, one array per argument.
+pub struct BatchArguments {
+    args: Vec<ArrayRef>,
+    // rows for which the filter is true
+    mask: Option<BooleanArray>,
+}
+
+impl BatchArguments {
+    /// Returns the values of the arguments at row, None if the filter discards the row.
+    pub fn row_values(&self, row: usize) -> Result<Option<Vec<ColumnValue>>> {
+        if self.mask.as_ref().is_some_and(|mask| !mask.value(row)) {
+            return Ok(None);
+        }
+        self.args
+            .iter()
+            .map(|arg| array_value(arg, row))
+            .collect::<Result<_>>()
+            .map(Some)
+    }
+}
+
+/// The state of an aggregate function for one group.
+#[derive(Debug)]
+pub struct Accumulator {
+    state: State,
+    // keys of the values already aggregated, for DISTINCT
+    seen: Option<HashSet<HashKey>>,
+}
+
+#[derive(Debug)]
+enum State {
+    Count(i64),
+    Sum {
+        count: i64,
+        // false once a value is not an INTEGER, the sum is then a real
+        all_integers: bool,
+        // None after an overflow
+        integer_sum: Option<i64>,
+        real_sum: f64,
+    },
+    Extreme(Option<ColumnValue>),
+    Concat(Option<String>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+
+    // aggregates the values of the first column of records
+    fn aggregate(
+        function: AggregateFunction,
+        distinct: bool,
+        values: &[ColumnValue],
+    ) -> Result<ColumnValue> {
+        let expr = AggregateExpr::try_new(
+            function,
+            vec![Arc::new(PhysicalColByIndex::new(0))],
+            distinct,
+            None,
+        )?;
+        let mut accumulator = expr.create_accumulator();
+        for value in values {
+            let record = DataRecord {
+                values: vec![value.clone()],
+                rowid: None,
+            };
+            expr.update(&mut accumulator, &record)?;
+        }
+        expr.evaluate(&accumulator)
+    }
+
+    #[test]
+    fn test_sum_total_avg() {
+        let int = ColumnValue::int64;
+        let values = [int(1), ColumnValue::Null, int(2), int(2)];
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &values).unwrap(),
+            int(5)
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, true, &values).unwrap(),
+            int(3)
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Total, false, &values).unwrap(),
+            ColumnValue::real(5.0)
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Avg, false, &values).unwrap(),
+            ColumnValue::real(5.0 / 3.0)
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Count, false, &values).unwrap(),
+            int(3)
+        );
+        assert_eq!(
+            aggregate(
+                AggregateFunction::Sum,
+                false,
+                &[int(1), ColumnValue::real(0.5)]
+            )
+            .unwrap(),
+            ColumnValue::real(1.5)
+        );
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let empty = [ColumnValue::Null];
+        assert_eq!(
+            aggregate(AggregateFunction::Count, false, &empty).unwrap(),
+            ColumnValue::int64(0)
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Total, false, &empty).unwrap(),
+            ColumnValue::real(0.0)
+        );
+        for function in [
+            AggregateFunction::Sum,
+            AggregateFunction::Avg,
+            AggregateFunction::Min,
+            AggregateFunction::GroupConcat,
+        ] {
+            assert_eq!(
+                aggregate(function, false, &empty).unwrap(),
+                ColumnValue::Null
+            );
+        }
+    }
+
+    #[test]
+    fn test_sum_integer_overflow() {
+        let values = [ColumnValue::int64(i64::MAX), ColumnValue::int64(1)];
+        let err = aggregate(AggregateFunction::Sum, false, &values).unwrap_err();
+        assert_eq!(err.to_string(), "integer overflow");
+        assert_eq!(
+            aggregate(AggregateFunction::Total, false, &values).unwrap(),
+            ColumnValue::real(i64::MAX as f64 + 1.0)
+        );
+    }
+
+    #[test]
+    fn test_sum_text() {
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        // well-formed integers are integers
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[text("3"), text(" 4 ")]).unwrap(),
+            ColumnValue::int64(7)
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Sum, false, &[text("3"), text("4abc")]).unwrap(),
+            ColumnValue::real(7.0)
+        );
+        assert_eq!(
+            aggregate(
+                AggregateFunction::Sum,
+                false,
+                &[text("1e2"), ColumnValue::int64(1)]
+            )
+            .unwrap(),
+            ColumnValue::real(101.0)
+        );
+        assert_eq!(
+            aggregate(
+                AggregateFunction::Sum,
+                false,
+                &[ColumnValue::Blob(b"3".to_vec())]
+            )
+            .unwrap(),
+            ColumnValue::real(3.0)
+        );
+    }
+
+    #[test]
+    fn test_min_max_group_concat() {
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        let values = [text("b"), ColumnValue::int64(3), text("a"), text("b")];
+        // numbers are less than texts
+        assert_eq!(
+            aggregate(AggregateFunction::Min, false, &values).unwrap(),
+            ColumnValue::int64(3)
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::Max, false, &values).unwrap(),
+            text("b")
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::GroupConcat, false, &values).unwrap(),
+            text("b,3,a,b")
+        );
+        assert_eq!(
+            aggregate(AggregateFunction::GroupConcat, true, &values).unwrap(),
+            text("b,3,a")
+        );
+    }
+
+    #[test]
+    fn test_wrong_argument_count() {
+        let arg = || Arc::new(PhysicalColByIndex::new(0)) as Arc<dyn PhysicalExpr>;
+        assert!(
+            AggregateExpr::try_new(AggregateFunction::Sum, vec![arg(), arg()], false, None)
+                .is_err()
+        );
+        assert!(AggregateExpr::try_new(
+            AggregateFunction::GroupConcat,
+            vec![arg(), arg()],
+            false,
+            None
+        )
+        .is_ok());
+    }
+}
@@ -0,0 +1,395 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::vec;
+
+use anyhow::{bail, Result};
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::model::hash_key::HashKey;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::aggregate::accumulator::{Accumulator, AggregateExpr};
+use crate::physical::plan::batch::{array_value, records_to_batch, BATCH_SIZE};
+use crate::physical::plan::exec::Exec;
+
+/// Hash aggregation: `SELECT a, count(*), sum(b) FROM t1 GROUP BY a`.
+///
+/// The first call to next or next_batch consumes the input and keeps one accumulator per
+/// aggregate for each group in a hash table keyed by the values of the GROUP BY
+/// expressions. Equal values (1 and 1.0, or different cases with NOCASE) are in the same
+/// group, NULLs are one group. next_batch pulls the input in batches: the GROUP BY
+/// expressions, the arguments and the filters of the aggregates are evaluated on each
+/// batch, then each row updates the accumulators of its group.
+///
+/// Records are the values of the GROUP BY expressions followed by the results of the
+/// aggregates. They are returned in the order of the GROUP BY values like SQLite does.
+/// Without GROUP BY, there is exactly one record even if the input is empty.
+/// HAVING is a filter on the records of this operator.
+#[derive(Debug)]
+pub struct ExecAggregateHash {
+    pub(crate) input: Box<dyn Exec>,
+    pub(crate) group_exprs: Vec<Arc<dyn PhysicalExpr>>,
+    pub(crate) aggregates: Vec<AggregateExpr>,
+    schema: SchemaRef,
+    open: bool,
+    // aggregated records, set by the first call to next or next_batch
+    output: Option<vec::IntoIter<DataRecord>>,
+}
+
+// values of the GROUP BY expressions of a group and the accumulators of its aggregates
+struct Group {
+    values: Vec<ColumnValue>,
+    accumulators: Vec<Accumulator>,
+}
+
+// the groups and their index by key
+struct Groups {
+    groups: Vec<Group>,
+    indices: HashMap<Vec<HashKey>, usize>,
+}
+
+impl ExecAggregateHash {
+    pub fn new(
+        input: Box<dyn Exec>,
+        group_exprs: Vec<Arc<dyn PhysicalExpr>>,
+        aggregates: Vec<AggregateExpr>,
+        schema: SchemaRef,
+    ) -> Self {
+        ExecAggregateHash {
+            input,
+            group_exprs,
+            aggregates,
+            schema,
+            open: false,
+            output: None,
+        }
+    }
+
+    fn new_group(&self, values: Vec<ColumnValue>) -> Group {
+        Group {
+            values,
+            accumulators: self
+                .aggregates
+                .iter()
+                .map(AggregateExpr::create_accumulator)
+                .collect(),
+        }
+    }
+
+    fn new_groups(&self) -> Groups {
+        let mut groups = Groups {
+            groups: vec![],
+            indices: HashMap::new(),
+        };
+        if self.group_exprs.is_empty() {
+            groups.groups.push(self.new_group(vec![]));
+            groups.indices.insert(vec![], 0);
+        }
+        groups
+    }
+
+    // The group of the values of the GROUP BY expressions, created for new values.
+    fn group<'a>(&self, groups: &'a mut Groups, values: Vec<ColumnValue>) -> &'a mut Group {
+        let key: Vec<HashKey> = values
+            .iter()
+            .zip(&self.group_exprs)
+            .map(|(value, expr)| HashKey::new(value, expr.collation().unwrap_or_default()))
+            .collect();
+        let index = match groups.indices.get(&key) {
+            Some(&index) => index,
+            None => {
+                groups.groups.push(self.new_group(values));
+                groups.indices.insert(key, groups.groups.len() - 1);
+                groups.groups.len() - 1
+            }
+        };
+        &mut groups.groups[index]
+    }
+
+    fn aggregate_records(&mut self) -> Result<Groups> {
+        let mut groups = self.new_groups();
+        while let Some(record) = self.input.next()? {
+            let values: Vec<ColumnValue> = self
+                .group_exprs
+                .iter()
+                .map(|expr| expr.evaluate(&record))
+                .collect();
+            let group = self.group(&mut groups, values);
+            for (aggregate, accumulator) in self.aggregates.iter().zip(&mut group.accumulators) {
+                aggregate.update(accumulator, &record)?;
+            }
+        }
+        Ok(groups)
+    }
+
+    fn aggregate_batches(&mut self) -> Result<Groups> {
+        let mut groups = self.new_groups();
+        while let Some(batch) = self.input.next_batch()? {
+            let group_arrays = self
+                .group_exprs
+                .iter()
+                .map(|expr| expr.evaluate_batch(&batch))
+                .collect::<Result<Vec<_>>>()?;
+            let arguments = self
+                .aggregates
+                .iter()
+                .map(|aggregate| aggregate.evaluate_batch(&batch))
+                .collect::<Result<Vec<_>>>()?;
+            for row in 0..batch.num_rows() {
+                let values = group_arrays
+                    .iter()
+                    .map(|array| array_value(array, row))
+                    .collect::<Result<Vec<_>>>()?;
+                let group = self.group(&mut groups, values);
+                for ((aggregate, arguments), accumulator) in self
+                    .aggregates
+                    .iter()
+                    .zip(&arguments)
+                    .zip(&mut group.accumulators)
+                {
+                    if let Some(args) = arguments.row_values(row)? {
+                        aggregate.add(accumulator, args)?;
+                    }
+                }
+            }
+        }
+        Ok(groups)
+    }
+
+    // The records of the groups in the order of their GROUP BY values.
+    fn group_records(&self, groups: Groups) -> Result<Vec<DataRecord>> {
+        let mut groups = groups.groups;
+        groups.sort_by(|a, b| {
+            a.values
+                .iter()
+                .zip(&b.values)
+                .zip(&self.group_exprs)
+                .map(|((a, b), expr)| a.compare(b, &expr.collation().unwrap_or_default()))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        groups
+            .into_iter()
+            .map(|group| {
+                let mut values = group.values;
+                for (aggregate, accumulator) in self.aggregates.iter().zip(&group.accumulators) {
+                    values.push(aggregate.evaluate(accumulator)?);
+                }
+                Ok(DataRecord {
+                    values,
+                    rowid: None,
+                })
+            })
+            .collect()
+    }
+
+    // The aggregated records, the input is aggregated by its records or its batches on
+    // the first call.
+    fn output(&mut self, batches: bool) -> Result<&mut vec::IntoIter<DataRecord>> {
+        if !self.open {
+            bail!("aggregate is not open")
+        }
+        let output = match self.output.take() {
+            Some(output) => output,
+            None => {
+                let groups = match batches {
+                    true => self.aggregate_batches()?,
+                    false => self.aggregate_records()?,
+                };
+                self.group_records(groups)?.into_iter()
+            }
+        };
+        Ok(self.output.insert(output))
+    }
+}
+
+impl Exec for ExecAggregateHash {
+    fn open(&mut self) -> Result<()> {
+        self.input.open()?;
+        self.output = None;
+        self.open = true;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        Ok(self.output(false)?.next())
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let records: Vec<DataRecord> = self.output(true)?.take(BATCH_SIZE).collect();
+        if records.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(records_to_batch(self.schema(), &records)?))
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.output = None;
+        self.open = false;
+        self.input.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion_expr::Operator;
+
+    use super::*;
+    use crate::model::collation::Collation;
+    use crate::physical::expression::binary::PhysicalBinary;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+    use crate::physical::expression::literal::PhysicalLiteral;
+    use crate::physical::plan::aggregate::AggregateFunction;
+    use crate::physical::plan::batch::batch_to_records;
+    use crate::physical::plan::scan::ExecMemTable;
+
+    // (region, quantity) records, region with the NOCASE collation
+    fn input() -> Box<dyn Exec> {
+        let schema = Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new("quantity", DataType::Int64, true),
+        ]);
+        let records: Vec<DataRecord> = [
+            (Some("north"), Some(10)),
+            (Some("south"), None),
+            (None, Some(1)),
+            (Some("North"), Some(5)),
+        ]
+        .into_iter()
+        .map(|(region, quantity)| DataRecord {
+            values: vec![
+                region.map_or(ColumnValue::Null, |region| {
+                    ColumnValue::Text(region.to_owned())
+                }),
+                quantity.map_or(ColumnValue::Null, ColumnValue::int64),
+            ],
+            rowid: None,
+        })
+        .collect();
+        Box::new(ExecMemTable::new(&records, Arc::new(schema)))
+    }
+
+    fn sum_quantity() -> AggregateExpr {
+        AggregateExpr::try_new(
+            AggregateFunction::Sum,
+            vec![Arc::new(PhysicalColByIndex::new(1))],
+            false,
+            None,
+        )
+        .unwrap()
+    }
+
+    fn collect(exec: &mut dyn Exec) -> Vec<Vec<ColumnValue>> {
+        exec.open().unwrap();
+        let mut rows = vec![];
+        while let Some(record) = exec.next().unwrap() {
+            rows.push(record.values);
+        }
+        exec.close().unwrap();
+        rows
+    }
+
+    #[test]
+    fn test_group_by_nocase() {
+        let region = PhysicalColByIndex {
+            col_index: 0,
+            affinity: None,
+            collation: Some(Collation::NoCase),
+        };
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new("sum", DataType::Int64, true),
+        ]));
+        let mut aggregate = ExecAggregateHash::new(
+            input(),
+            vec![Arc::new(region)],
+            vec![sum_quantity()],
+            schema,
+        );
+        let rows = collect(&mut aggregate);
+        // NULLs are one group sorted first, north and North are the same group
+        assert_eq!(
+            rows,
+            vec![
+                vec![ColumnValue::Null, ColumnValue::int64(1)],
+                vec![
+                    ColumnValue::Text("north".to_owned()),
+                    ColumnValue::int64(15)
+                ],
+                vec![ColumnValue::Text("south".to_owned()), ColumnValue::Null],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_batches() {
+        // count(quantity) FILTER (WHERE quantity > 5) GROUP BY region
+        let count_filtered = AggregateExpr::try_new(
+            AggregateFunction::Count,
+            vec![Arc::new(PhysicalColByIndex::new(1))],
+            false,
+            Some(Arc::new(
+                PhysicalBinary::try_new(
+                    Arc::new(PhysicalColByIndex::new(1)),
+                    Operator::Gt,
+                    Arc::new(PhysicalLiteral {
+                        value: ColumnValue::int64(5),
+                    }),
+                )
+                .unwrap(),
+            )),
+        )
+        .unwrap();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("region", DataType::Utf8, true),
+            Field::new("sum", DataType::Int64, true),
+            Field::new("count", DataType::Int64, true),
+        ]));
+        let mut aggregate = ExecAggregateHash::new(
+            input(),
+            vec![Arc::new(PhysicalColByIndex::new(0))],
+            vec![sum_quantity(), count_filtered],
+            schema,
+        );
+        aggregate.open().unwrap();
+        let batch = aggregate.next_batch().unwrap().unwrap();
+        assert!(aggregate.next_batch().unwrap().is_none());
+        aggregate.close().unwrap();
+        let rows: Vec<Vec<ColumnValue>> = batch_to_records(&batch)
+            .unwrap()
+            .into_iter()
+            .map(|record| record.values)
+            .collect();
+        // the batch holds the records of next
+        assert_eq!(rows, collect(&mut aggregate));
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        assert_eq!(
+            rows[1..3],
+            [
+                vec![text("North"), ColumnValue::int64(5), ColumnValue::int64(0)],
+                vec![text("north"), ColumnValue::int64(10), ColumnValue::int64(1)],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_group_by() {
+        let schema = Arc::new(Schema::new(vec![Field::new("sum", DataType::Int64, true)]));
+        let mut aggregate =
+            ExecAggregateHash::new(input(), vec![], vec![sum_quantity()], schema.clone());
+        assert_eq!(collect(&mut aggregate), vec![vec![ColumnValue::int64(16)]]);
+
+        // one record even without input
+        let empty = ExecMemTable::new(&[], schema.clone());
+        let mut aggregate =
+            ExecAggregateHash::new(Box::new(empty), vec![], vec![sum_quantity()], schema);
+        assert_eq!(collect(&mut aggregate), vec![vec![ColumnValue::Null]]);
+    }
+}
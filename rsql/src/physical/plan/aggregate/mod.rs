@@ -0,0 +1,5 @@
+pub mod accumulator;
+mod exec_aggregate_hash;
+
+pub use accumulator::{AggregateExpr, AggregateFunction};
+pub use exec_aggregate_hash::ExecAggregateHash;
@@ -0,0 +1,353 @@
+//! Conversion between records and Arrow RecordBatches for batch execution.
+//!
+//! SQLite is dynamically typed: a column can store values of any storage class while
+//! an Arrow array has one data type. An array of a batch has the data type of its
+//! column (see Affinity::data_type) when all its values have this storage class,
+//! else the data type of the storage class of its values, e.g. Utf8 for the TEXT of
+//! a column without a type, or a sparse union of the storage classes when they are
+//! mixed. The values are never converted: the batch schema can differ from the one
+//! of the operator in the data types.
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use arrow_array::{
+    Array, ArrayRef, BinaryArray, Float64Array, Int64Array, NullArray, RecordBatch,
+    RecordBatchOptions, StringArray, UnionArray,
+};
+use arrow_buffer::Buffer;
+use arrow_schema::{DataType, Field, Schema, SchemaRef, UnionFields, UnionMode};
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+
+/// Maximum number of rows of the batches built from records.
+pub const BATCH_SIZE: usize = 1024;
+
+/// Builds a RecordBatch from the values of records, column by column. The fields of
+/// the batch are the ones of schema with the data types of the arrays.
+pub fn records_to_batch(schema: SchemaRef, records: &[DataRecord]) -> Result<RecordBatch> {
+    let mut fields = vec![];
+    let mut columns = vec![];
+    for (index, field) in schema.fields().iter().enumerate() {
+        let values: Vec<&ColumnValue> = records
+            .iter()
+            .map(|record| record.values.get(index).unwrap_or(&ColumnValue::Null))
+            .collect();
+        let data_type = match values_data_type(values.iter().copied()) {
+            // NULL is valid in all arrays
+            DataType::Null => field.data_type().clone(),
+            data_type => data_type,
+        };
+        columns.push(values_to_array(&data_type, values.into_iter())?);
+        fields.push(field.as_ref().clone().with_data_type(data_type));
+    }
+    let schema = Schema::new_with_metadata(fields, schema.metadata().clone());
+    // records without values, e.g. the row of a SELECT without FROM
+    let options = RecordBatchOptions::new().with_row_count(Some(records.len()));
+    Ok(RecordBatch::try_new_with_options(
+        Arc::new(schema),
+        columns,
+        &options,
+    )?)
+}
+
+/// Splits a RecordBatch into its rows. The rowid of the records is unknown.
+pub fn batch_to_records(batch: &RecordBatch) -> Result<Vec<DataRecord>> {
+    (0..batch.num_rows())
+        .map(|row| {
+            let values = batch
+                .columns()
+                .iter()
+                .map(|column| array_value(column, row))
+                .collect::<Result<Vec<_>>>()?;
+            Ok(DataRecord {
+                values,
+                rowid: None,
+            })
+        })
+        .collect()
+}
+
+/// Builds an array of data_type from values.
+/// - Int64: INTEGER values.
+/// - Float64: INTEGER and REAL values.
+/// - Utf8: TEXT values, numbers are converted to their text like with TEXT affinity.
+/// - Binary: BLOB values.
+/// - Union: values of any storage class, in the child of their storage class.
+///
+/// NULL is valid in all arrays.
+pub fn values_to_array<'a>(
+    data_type: &DataType,
+    values: impl Iterator<Item = &'a ColumnValue>,
+) -> Result<ArrayRef> {
+    Ok(match data_type {
+        DataType::Int64 => Arc::new(
+            values
+                .map(|value| convert(value, data_type, ColumnValue::as_i64))
+                .collect::<Result<Int64Array>>()?,
+        ),
+        DataType::Float64 => Arc::new(
+            values
+                .map(|value| {
+                    convert(value, data_type, |value| {
+                        value.as_f64().or(value.as_i64().map(|int| int as f64))
+                    })
+                })
+                .collect::<Result<Float64Array>>()?,
+        ),
+        DataType::Utf8 => Arc::new(
+            values
+                .map(|value| {
+                    convert(value, data_type, |value| match value {
+                        ColumnValue::Text(text) => Some(text.clone()),
+                        ColumnValue::Blob(_) => None,
+                        number => Some(number.to_string()),
+                    })
+                })
+                .collect::<Result<StringArray>>()?,
+        ),
+        DataType::Binary => {
+            let values = values
+                .map(|value| {
+                    convert(value, data_type, |value| match value {
+                        ColumnValue::Blob(bytes) => Some(bytes.as_slice()),
+                        _ => None,
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            Arc::new(BinaryArray::from(values))
+        }
+        DataType::Union(union_fields, UnionMode::Sparse) => {
+            let values: Vec<&ColumnValue> = values.collect();
+            // NULL values are in the first child
+            let type_ids = values.iter().map(|value| {
+                let data_type = storage_class_data_type(value);
+                union_fields
+                    .iter()
+                    .find(|(_, field)| Some(field.data_type()) == data_type.as_ref())
+                    .map_or(0, |(type_id, _)| type_id)
+            });
+            let type_ids = Buffer::from_iter(type_ids.collect::<Vec<i8>>());
+            // a sparse union has children of the length of the array
+            let children = union_fields
+                .iter()
+                .map(|(_, field)| {
+                    let child_values: Vec<&ColumnValue> = values
+                        .iter()
+                        .map(|value| match storage_class_data_type(value) {
+                            Some(data_type) if data_type == *field.data_type() => *value,
+                            _ => &ColumnValue::Null,
+                        })
+                        .collect();
+                    let array = values_to_array(field.data_type(), child_values.into_iter())?;
+                    Ok((field.as_ref().clone(), array))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let type_id_list: Vec<i8> = union_fields.iter().map(|(type_id, _)| type_id).collect();
+            Arc::new(UnionArray::try_new(
+                &type_id_list,
+                type_ids,
+                None,
+                children,
+            )?)
+        }
+        DataType::Null => {
+            let len = values
+                .map(|value| convert(value, data_type, |_| None::<()>))
+                .collect::<Result<Vec<_>>>()?
+                .len();
+            Arc::new(NullArray::new(len))
+        }
+        _ => bail!("unsupported data type {data_type}"),
+    })
+}
+
+/// Builds an array holding values without converting them, see values_data_type.
+pub fn values_array(values: &[ColumnValue]) -> Result<ArrayRef> {
+    values_to_array(&values_data_type(values), values.iter())
+}
+
+/// Returns the data type of an array holding values without converting them: the data
+/// type of their storage class, a sparse union of the storage classes of the values
+/// when there are several, or Null if all values are NULL.
+pub fn values_data_type<'a>(values: impl IntoIterator<Item = &'a ColumnValue>) -> DataType {
+    let mut data_types: Vec<DataType> = vec![];
+    for data_type in values.into_iter().filter_map(storage_class_data_type) {
+        if !data_types.contains(&data_type) {
+            data_types.push(data_type);
+        }
+    }
+    match data_types.len() {
+        0 => DataType::Null,
+        1 => data_types.remove(0),
+        _ => {
+            let fields = data_types
+                .into_iter()
+                .map(|data_type| Field::new(data_type.to_string(), data_type, true));
+            DataType::Union(
+                UnionFields::new(0..fields.len() as i8, fields),
+                UnionMode::Sparse,
+            )
+        }
+    }
+}
+
+// Returns the data type of the storage class of a value, None for NULL.
+fn storage_class_data_type(value: &ColumnValue) -> Option<DataType> {
+    match value {
+        ColumnValue::Null => None,
+        ColumnValue::Float64(_) => Some(DataType::Float64),
+        ColumnValue::Text(_) => Some(DataType::Utf8),
+        ColumnValue::Blob(_) => Some(DataType::Binary),
+        _ => Some(DataType::Int64),
+    }
+}
+
+// Converts a non NULL value with f, None from f means the value does not fit data_type.
+fn convert<'a, T>(
+    value: &'a ColumnValue,
+    data_type: &DataType,
+    f: impl Fn(&'a ColumnValue) -> Option<T>,
+) -> Result<Option<T>> {
+    if *value == ColumnValue::Null {
+        return Ok(None);
+    }
+    match f(value) {
+        Some(converted) => Ok(Some(converted)),
+        None => bail!("cannot convert {value} to {data_type}"),
+    }
+}
+
+/// Returns the value at row of array.
+pub fn array_value(array: &ArrayRef, row: usize) -> Result<ColumnValue> {
+    // a union has no validity buffer, the NULL values are in its children
+    if let DataType::Union(..) = array.data_type() {
+        let union = downcast::<UnionArray>(array.as_any())?;
+        return array_value(union.child(union.type_id(row)), union.value_offset(row));
+    }
+    if array.is_null(row) || *array.data_type() == DataType::Null {
+        return Ok(ColumnValue::Null);
+    }
+    let any = array.as_any();
+    Ok(match array.data_type() {
+        DataType::Int64 => ColumnValue::int64(downcast::<Int64Array>(any)?.value(row)),
+        DataType::Float64 => {
+            ColumnValue::Float64(downcast::<Float64Array>(any)?.value(row).to_be_bytes())
+        }
+        DataType::Utf8 => ColumnValue::Text(downcast::<StringArray>(any)?.value(row).to_owned()),
+        DataType::Binary => ColumnValue::Blob(downcast::<BinaryArray>(any)?.value(row).to_vec()),
+        data_type => bail!("unsupported data type {data_type}"),
+    })
+}
+
+fn downcast<T: 'static>(any: &dyn std::any::Any) -> Result<&T> {
+    match any.downcast_ref::<T>() {
+        Some(array) => Ok(array),
+        None => bail!("array does not match its data type"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{Field, Schema};
+
+    use super::*;
+
+    #[test]
+    fn test_records_to_batch_and_back() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("price", DataType::Float64, true),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("data", DataType::Binary, true),
+        ]));
+        let records = vec![
+            DataRecord {
+                values: vec![
+                    ColumnValue::int64(1),
+                    ColumnValue::Float64(2.5f64.to_be_bytes()),
+                    ColumnValue::Text("a".to_owned()),
+                    ColumnValue::Blob(vec![1, 2]),
+                ],
+                rowid: Some(1),
+            },
+            DataRecord {
+                // short record: missing values are NULL
+                values: vec![ColumnValue::One, ColumnValue::int8(3)],
+                rowid: Some(2),
+            },
+        ];
+
+        let batch = records_to_batch(schema, &records).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let ids = batch.column(0).as_any().downcast_ref::<Int64Array>();
+        assert_eq!(ids.unwrap().values(), &[1, 1]);
+
+        // the integer of the REAL column is not converted: the array is a union
+        assert!(matches!(batch.column(1).data_type(), DataType::Union(..)));
+        assert_eq!(batch.schema().field(2).data_type(), &DataType::Utf8);
+
+        let rows = batch_to_records(&batch).unwrap();
+        assert_eq!(rows[0].values, records[0].values);
+        assert_eq!(
+            rows[1].values,
+            vec![
+                ColumnValue::int64(1),
+                ColumnValue::int64(3),
+                ColumnValue::Null,
+                ColumnValue::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn test_records_to_batch_dynamic_column() {
+        // a column without a type is Null in the schema
+        let schema = Arc::new(Schema::new(vec![Field::new("v", DataType::Null, true)]));
+        let values = [
+            ColumnValue::Text("apples".to_owned()),
+            ColumnValue::Null,
+            ColumnValue::int64(4),
+            ColumnValue::Blob(vec![1]),
+            ColumnValue::Float64(2.5f64.to_be_bytes()),
+        ];
+        let records: Vec<DataRecord> = values
+            .iter()
+            .map(|value| DataRecord {
+                values: vec![value.clone()],
+                rowid: None,
+            })
+            .collect();
+
+        let batch = records_to_batch(schema.clone(), &records[..2]).unwrap();
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Utf8);
+        let batch = records_to_batch(schema.clone(), &records[1..2]).unwrap();
+        assert_eq!(batch.schema().field(0).data_type(), &DataType::Null);
+        assert_eq!(
+            batch_to_records(&batch).unwrap()[0].values,
+            [ColumnValue::Null]
+        );
+
+        let batch = records_to_batch(schema, &records).unwrap();
+        let rows: Vec<ColumnValue> = batch_to_records(&batch)
+            .unwrap()
+            .into_iter()
+            .flat_map(|record| record.values)
+            .collect();
+        assert_eq!(rows, values);
+    }
+
+    #[test]
+    fn test_values_to_array_mismatched_storage_class() {
+        let text = [ColumnValue::Text("abc".to_owned())];
+        assert!(values_to_array(&DataType::Int64, text.iter()).is_err());
+        assert!(values_to_array(&DataType::Binary, text.iter()).is_err());
+
+        // numbers in TEXT columns are converted to text
+        let array = values_to_array(&DataType::Utf8, [ColumnValue::int64(42)].iter()).unwrap();
+        assert_eq!(
+            array_value(&array, 0).unwrap(),
+            ColumnValue::Text("42".to_owned())
+        );
+    }
+}
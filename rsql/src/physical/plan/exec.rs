@@ -1,16 +1,56 @@
+use anyhow::Result;
+use arrow_array::RecordBatch;
 use arrow_schema::SchemaRef;
 
 use crate::model::data_record::DataRecord;
+use crate::physical::plan::batch::{records_to_batch, BATCH_SIZE};
 use std::fmt::Debug;
 
 /// Represent node in Physical Plan Tree
+///
+/// Operators follow the iterator (Volcano) model: the consumer calls open once,
+/// then next until it returns None, then close. Each operator pulls records
+/// one at a time from its children, so a result is only materialized by the
+/// operators that need all their input (e.g. the build side of a hash join).
+///
+/// Records can also be pulled in batches of columns with next_batch. The scan,
+/// projection, filter, limit, hash aggregate and hash join override it to pass batches
+/// between them, their expressions being evaluated on the arrays of the batches. The
+/// other operators (sort, distinct, writes) pull their input with next and build their
+/// batches from their records.
+///
+/// Errors (I/O, corrupted pages, unsupported values, etc.) are returned to the caller.
 pub trait Exec: Debug {
-    // TODO use Iterator?
-    /// Returns a slide to provide a read view without ownership.
+    /// Prepares the operator to return records: opens its children and
+    /// initializes its state (cursor, hash table, etc.).
+    fn open(&mut self) -> Result<()>;
+
+    /// Returns the next record, or None when there are no more records.
     ///
-    /// execute can modify self, hence &mut. For example, recording
-    /// metrics in executing, changing self state.
-    fn execute(&mut self) -> &[DataRecord];
+    /// next can modify self, hence &mut. For example, advancing a cursor,
+    /// recording metrics in executing.
+    fn next(&mut self) -> Result<Option<DataRecord>>;
+
+    /// Returns the next batch of records as Arrow arrays, one per field of the schema,
+    /// or None when there are no more records. Batches are never empty. The data types
+    /// of the arrays are the ones of their values (see batch::records_to_batch).
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let mut records = vec![];
+        while records.len() < BATCH_SIZE {
+            match self.next()? {
+                Some(record) => records.push(record),
+                None => break,
+            }
+        }
+        if records.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(records_to_batch(self.schema(), &records)?))
+    }
+
+    /// Releases the resources held by the operator and closes its children.
+    /// The operator can be opened again to restart from the first record.
+    fn close(&mut self) -> Result<()>;
 
     // Get the schema for this Physical Plan. Currenyly using arrow Schema.
     // Let's see later when project grows if depending on arrow for this is a good idea.
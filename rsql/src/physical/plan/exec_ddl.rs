@@ -0,0 +1,454 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use arrow_schema::{Schema, SchemaRef};
+
+use crate::btree::bt_cursor::BtCursor;
+use crate::btree::bt_write::{create_btree, drop_btree};
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::model::database::Database;
+use crate::model::db_meta::DbMeta;
+use crate::model::page::Page;
+use crate::model::page_header::PageType;
+use crate::model::schema::{IndexDef, SchemaObjDetail, SchemaObjType, FIELD_AUTOINCREMENT};
+use crate::physical::physical_planner::index_key_info;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::table_writer::{commit_or_rollback, index_contains, index_entry};
+use crate::sql::parsing::{parse_index_from_ddl, parse_table_from_ddl, DdlStatement};
+
+// table of the largest rowid of the AUTOINCREMENT tables, created with the first one
+const SQLITE_SEQUENCE: &str = "sqlite_sequence";
+const SQLITE_SEQUENCE_SQL: &str = "CREATE TABLE sqlite_sequence(name,seq)";
+
+/// A schema object found by name.
+#[derive(Debug)]
+struct FoundObject {
+    obj_type: SchemaObjType,
+    // name as stored in sqlite_schema
+    name: String,
+    rootpage: u32,
+    sql: String,
+}
+
+/// Executes a DDL statement: `CREATE TABLE`, `CREATE INDEX`, `DROP TABLE` or
+/// `DROP INDEX`.
+///
+/// A created table or index gets a new root page and a row in sqlite_schema, a new
+/// index is filled with the rows of its table. A dropped table or index has every
+/// page of its btree freed and its row deleted from sqlite_schema, with the indexes
+/// and triggers of a dropped table. The schema cookie of the db header is incremented
+/// on each change, so sqlite reads the schema again.
+///
+/// Like inserts, the statement is executed when the plan is opened and the pages are
+/// written to the database file at the end or dropped on error. The schema objects of
+/// the database are then read again for the next statements. No record is returned.
+/// A created table gets the automatic indexes `sqlite_autoindex_<table>_<n>` of its
+/// UNIQUE constraints and of a PRIMARY KEY that is not the rowid.
+/// WITHOUT ROWID tables, CREATE TABLE ... AS, views, virtual tables, triggers, partial
+/// and expression indexes and auto-vacuum databases are not supported: they are
+/// rejected when created, so the file never holds objects rsql cannot read.
+#[derive(Debug)]
+pub struct ExecDdl {
+    statement: DdlStatement,
+    database: Rc<RefCell<Database>>,
+    schema: SchemaRef,
+}
+
+impl ExecDdl {
+    pub fn new(statement: DdlStatement, database: Rc<RefCell<Database>>) -> Self {
+        ExecDdl {
+            statement,
+            database,
+            schema: Arc::new(Schema::empty()),
+        }
+    }
+
+    fn execute(&self) -> Result<()> {
+        if self.database.borrow().db_meta.db_header.autovacuum_top_root != 0 {
+            bail!("cannot change the schema of an auto-vacuum database")
+        }
+        match &self.statement {
+            DdlStatement::CreateTable {
+                name,
+                if_not_exists,
+                sql,
+            } => self.create_table(name, *if_not_exists, sql),
+            DdlStatement::CreateIndex {
+                name,
+                table_name,
+                if_not_exists,
+                sql,
+            } => self.create_index(name, table_name, *if_not_exists, sql),
+            DdlStatement::DropTable { name, if_exists } => self.drop_table(name, *if_exists),
+            DdlStatement::DropIndex { name, if_exists } => self.drop_index(name, *if_exists),
+        }
+    }
+
+    fn create_table(&self, name: &str, if_not_exists: bool, sql: &str) -> Result<()> {
+        check_name(name)?;
+        match self.find_object(name, |obj_type| obj_type != SchemaObjType::Trigger) {
+            Some(obj) if obj.obj_type == SchemaObjType::Index => {
+                bail!("there is already an index named {name}")
+            }
+            Some(_) if if_not_exists => return Ok(()),
+            Some(obj) if obj.obj_type == SchemaObjType::View => {
+                bail!("view {name} already exists")
+            }
+            Some(_) => bail!("table {name} already exists"),
+            None => {}
+        }
+        let (columns, table_def) = parse_table_from_ddl(sql)?;
+        for (index, field) in columns.iter().enumerate() {
+            if columns[..index]
+                .iter()
+                .any(|other| other.name().eq_ignore_ascii_case(field.name()))
+            {
+                bail!("duplicate column name: {}", field.name())
+            }
+        }
+        let autoincrement = columns
+            .iter()
+            .position(|field| field.metadata().contains_key(FIELD_AUTOINCREMENT));
+        if autoincrement.is_some() && autoincrement != table_def.rowid_alias {
+            bail!("AUTOINCREMENT is only allowed on an INTEGER PRIMARY KEY")
+        }
+        // the rows of a WITHOUT ROWID table are in an index btree, which rsql can
+        // neither scan nor write
+        if table_def.without_rowid {
+            bail!("cannot create table {name}: WITHOUT ROWID tables are not supported")
+        }
+
+        let root_page_number = create_btree(&mut self.database.borrow_mut(), PageType::LeafTable)?;
+        self.insert_schema_row(
+            SchemaObjType::Table,
+            name,
+            name,
+            root_page_number,
+            Some(sql),
+        )?;
+        // the indexes of the constraints are created like CREATE INDEX on the new
+        // table, with a NULL sql
+        self.read_schema()?;
+        for (number, index_def) in table_def.autoindexes.iter().enumerate() {
+            let index_name = format!("sqlite_autoindex_{name}_{}", number + 1);
+            self.build_index(&index_name, name, index_def, None)?;
+        }
+        if autoincrement.is_some()
+            && self
+                .find_object(SQLITE_SEQUENCE, |obj_type| obj_type == SchemaObjType::Table)
+                .is_none()
+        {
+            let root_page_number =
+                create_btree(&mut self.database.borrow_mut(), PageType::LeafTable)?;
+            self.insert_schema_row(
+                SchemaObjType::Table,
+                SQLITE_SEQUENCE,
+                SQLITE_SEQUENCE,
+                root_page_number,
+                Some(SQLITE_SEQUENCE_SQL),
+            )?;
+        }
+        self.schema_changed();
+        Ok(())
+    }
+
+    fn create_index(
+        &self,
+        name: &str,
+        table_name: &str,
+        if_not_exists: bool,
+        sql: &str,
+    ) -> Result<()> {
+        check_name(name)?;
+        match self.find_object(name, |obj_type| obj_type != SchemaObjType::Trigger) {
+            Some(obj) if obj.obj_type == SchemaObjType::Index => {
+                if if_not_exists {
+                    return Ok(());
+                }
+                bail!("index {name} already exists")
+            }
+            Some(_) => bail!("there is already a table named {name}"),
+            None => {}
+        }
+        let index_def = parse_index_from_ddl(sql)?;
+        if index_def.where_clause.is_some() {
+            bail!("cannot create partial index {name}")
+        }
+        self.build_index(name, table_name, &index_def, Some(sql))
+    }
+
+    // Creates the index btree of index_def on the columns of table_name, its row in
+    // sqlite_schema and its entries of the rows of the table.
+    fn build_index(
+        &self,
+        name: &str,
+        table_name: &str,
+        index_def: &IndexDef,
+        sql: Option<&str>,
+    ) -> Result<()> {
+        let db = self.database.borrow();
+        let Some(table) = db.db_meta.schema_objects.iter().find(|obj| {
+            obj.obj_type == SchemaObjType::Table && obj.name.eq_ignore_ascii_case(table_name)
+        }) else {
+            bail!("no such table: {table_name}")
+        };
+        if is_internal(&table.name) {
+            bail!("table {} may not be indexed", table.name)
+        }
+        let SchemaObjDetail::Table(table_def) = &table.detail else {
+            bail!("no such table: {table_name}")
+        };
+        if table_def.without_rowid {
+            bail!("cannot create index {name} on WITHOUT ROWID table {table_name}")
+        }
+        let mut columns = vec![];
+        for indexed_column in &index_def.columns {
+            if indexed_column.expression {
+                bail!(
+                    "cannot create index {name} on expression {}",
+                    indexed_column.name
+                )
+            }
+            match table
+                .columns
+                .iter()
+                .position(|field| field.name().eq_ignore_ascii_case(&indexed_column.name))
+            {
+                Some(position) => columns.push(position),
+                None => bail!("no such column: {}", indexed_column.name),
+            }
+        }
+        let key_info = index_key_info(index_def, &table.columns)?;
+        let table_page_number = table.rootpage;
+        let table_name = table.name.clone();
+        let table_columns = table.columns.clone();
+        let rowid_alias = table_def.rowid_alias;
+        drop(db);
+
+        let root_page_number = create_btree(&mut self.database.borrow_mut(), PageType::LeafIndex)?;
+        self.insert_schema_row(
+            SchemaObjType::Index,
+            name,
+            &table_name,
+            root_page_number,
+            sql,
+        )?;
+        // the entries of the existing rows, in rowid order
+        for cell in BtCursor::new(self.database.clone(), table_page_number)?.scan_page() {
+            let cell = cell?;
+            let rowid = cell.rowid;
+            let mut values = cell.into_record(rowid_alias).values;
+            // rows written before `ALTER TABLE ADD COLUMN` have fewer values than columns
+            values.resize(table_columns.len(), ColumnValue::Null);
+            let key: Vec<ColumnValue> = columns.iter().map(|&i| values[i].clone()).collect();
+            // NULLs are distinct in a UNIQUE index
+            if index_def.unique
+                && !key.iter().any(ColumnValue::is_null)
+                && index_contains(&self.database, root_page_number, &key, &key_info)?
+            {
+                let names: Vec<String> = columns
+                    .iter()
+                    .map(|&i| format!("{table_name}.{}", table_columns[i].name()))
+                    .collect();
+                bail!("UNIQUE constraint failed: {}", names.join(", "))
+            }
+            BtCursor::new(self.database.clone(), root_page_number)?
+                .index_insert(&index_entry(key, rowid), &key_info)?;
+        }
+        self.schema_changed();
+        Ok(())
+    }
+
+    fn drop_table(&self, name: &str, if_exists: bool) -> Result<()> {
+        let table = match self.find_object(name, |obj_type| {
+            obj_type == SchemaObjType::Table || obj_type == SchemaObjType::View
+        }) {
+            Some(obj) if obj.obj_type == SchemaObjType::View => {
+                bail!("use DROP VIEW to delete view {name}")
+            }
+            Some(obj) => obj,
+            None if if_exists => return Ok(()),
+            None => bail!("no such table: {name}"),
+        };
+        if is_internal(&table.name) {
+            bail!("table {} may not be dropped", table.name)
+        }
+
+        // the table and its indexes, its triggers have no btree
+        let root_page_numbers: Vec<u32> = self
+            .database
+            .borrow()
+            .db_meta
+            .schema_objects
+            .iter()
+            .filter(|obj| obj.tbl_name.eq_ignore_ascii_case(&table.name) && obj.rootpage > 0)
+            .map(|obj| obj.rootpage)
+            .collect();
+        for root_page_number in root_page_numbers {
+            drop_btree(&mut self.database.borrow_mut(), root_page_number)?;
+        }
+        // the rows of the table, its indexes and triggers: (type, name, tbl_name, ...)
+        self.delete_rows(Page::PAGE_NUM_DB_ROOT, |record| {
+            text_eq(&record.values[2], &table.name)
+        })?;
+        if let Some(sequence) =
+            self.find_object(SQLITE_SEQUENCE, |obj_type| obj_type == SchemaObjType::Table)
+        {
+            // the row of the table in sqlite_sequence: (name, seq)
+            self.delete_rows(sequence.rootpage, |record| {
+                text_eq(&record.values[0], &table.name)
+            })?;
+        }
+        self.schema_changed();
+        Ok(())
+    }
+
+    fn drop_index(&self, name: &str, if_exists: bool) -> Result<()> {
+        let index = match self.find_object(name, |obj_type| obj_type == SchemaObjType::Index) {
+            Some(obj) => obj,
+            None if if_exists => return Ok(()),
+            None => bail!("no such index: {name}"),
+        };
+        if index.sql.is_empty() {
+            bail!("index associated with UNIQUE or PRIMARY KEY constraint cannot be dropped")
+        }
+        drop_btree(&mut self.database.borrow_mut(), index.rootpage)?;
+        self.delete_rows(Page::PAGE_NUM_DB_ROOT, |record| {
+            text_eq(&record.values[0], "index") && text_eq(&record.values[1], &index.name)
+        })?;
+        self.schema_changed();
+        Ok(())
+    }
+
+    // The schema object named name, ignoring case, whose type matches.
+    fn find_object(
+        &self,
+        name: &str,
+        type_matches: impl Fn(SchemaObjType) -> bool,
+    ) -> Option<FoundObject> {
+        self.database
+            .borrow()
+            .db_meta
+            .schema_objects
+            .iter()
+            .find(|obj| type_matches(obj.obj_type) && obj.name.eq_ignore_ascii_case(name))
+            .map(|obj| FoundObject {
+                obj_type: obj.obj_type,
+                name: obj.name.clone(),
+                rootpage: obj.rootpage,
+                sql: obj.sql.clone(),
+            })
+    }
+
+    // Appends a row (type, name, tbl_name, rootpage, sql) to sqlite_schema, the sql of
+    // an automatic index is NULL.
+    fn insert_schema_row(
+        &self,
+        obj_type: SchemaObjType,
+        name: &str,
+        tbl_name: &str,
+        rootpage: u32,
+        sql: Option<&str>,
+    ) -> Result<()> {
+        let obj_type = match obj_type {
+            SchemaObjType::Table => "table",
+            SchemaObjType::Index => "index",
+            SchemaObjType::View => "view",
+            SchemaObjType::Trigger => "trigger",
+        };
+        let mut cursor = BtCursor::new(self.database.clone(), Page::PAGE_NUM_DB_ROOT)?;
+        cursor.move_to_last()?;
+        let rowid = if cursor.is_eof() {
+            1
+        } else {
+            cursor.table_leaf_cell()?.rowid + 1
+        };
+        let record = DataRecord {
+            values: vec![
+                ColumnValue::Text(obj_type.to_owned()),
+                ColumnValue::Text(name.to_owned()),
+                ColumnValue::Text(tbl_name.to_owned()),
+                ColumnValue::int64(i64::from(rootpage)),
+                sql.map_or(ColumnValue::Null, |sql| ColumnValue::Text(sql.to_owned())),
+            ],
+            rowid: None,
+        };
+        cursor.table_insert(rowid, &record)
+    }
+
+    // Deletes the rows of the table rooted at root_page_number matching the predicate.
+    fn delete_rows(
+        &self,
+        root_page_number: u32,
+        predicate: impl Fn(&DataRecord) -> bool,
+    ) -> Result<()> {
+        let mut rowids = vec![];
+        for cell in BtCursor::new(self.database.clone(), root_page_number)?.scan_page() {
+            let cell = cell?;
+            if predicate(&cell.payload) {
+                rowids.push(cell.rowid);
+            }
+        }
+        let mut cursor = BtCursor::new(self.database.clone(), root_page_number)?;
+        for rowid in rowids {
+            cursor.table_delete(rowid)?;
+        }
+        Ok(())
+    }
+
+    // Reads the schema objects again, with the changes of the statement.
+    fn read_schema(&self) -> Result<()> {
+        let schema_objects = DbMeta::read_schema_objects(self.database.clone())?;
+        self.database.borrow_mut().db_meta.schema_objects = schema_objects;
+        Ok(())
+    }
+
+    // sqlite reads the schema again when the schema cookie has changed.
+    fn schema_changed(&self) {
+        let header = &mut self.database.borrow_mut().db_meta.db_header;
+        header.schema_cookie = header.schema_cookie.wrapping_add(1);
+    }
+}
+
+// Names starting with sqlite_ are reserved for the internal schema objects.
+fn is_internal(name: &str) -> bool {
+    name.len() >= 7 && name[..7].eq_ignore_ascii_case("sqlite_")
+}
+
+fn check_name(name: &str) -> Result<()> {
+    if is_internal(name) {
+        bail!("object name reserved for internal use: {name}")
+    }
+    Ok(())
+}
+
+// True if value is the text, ignoring case like the names of schema objects.
+fn text_eq(value: &ColumnValue, text: &str) -> bool {
+    matches!(value, ColumnValue::Text(value) if value.eq_ignore_ascii_case(text))
+}
+
+impl Exec for ExecDdl {
+    fn open(&mut self) -> Result<()> {
+        let result = self.execute();
+        // the schema is read again even on error, the statement may have read its changes
+        let committed = commit_or_rollback(&self.database, result);
+        // the statements planned next see the new schema
+        self.read_schema()?;
+        committed
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,81 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow_schema::{Schema, SchemaRef};
+
+use crate::btree::bt_cursor::BtCursor;
+use crate::model::data_record::DataRecord;
+use crate::model::database::Database;
+use crate::model::schema::TriggerEvent;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::table_writer::{commit_or_rollback, read_all, TableWriter};
+
+/// Deletes the rows of a table returned by its input, a scan of the table possibly
+/// under a filter: `DELETE FROM t WHERE ...`. The rows are found by the rowid of the
+/// input records.
+///
+/// Each row is deleted from the table btree and its key from each index btree, pages
+/// less than a third full are merged with their siblings and emptied pages are added
+/// to the freelist, like the overflow pages of the deleted rows.
+///
+/// The input is read before the first delete. The rows are deleted when the plan is
+/// opened: all or none, the pages are written to the database file at the end or
+/// dropped on error. No record is returned.
+#[derive(Debug)]
+pub struct ExecDelete {
+    pub table_name: String,
+    input: Box<dyn Exec>,
+    database: Rc<RefCell<Database>>,
+    writer: TableWriter,
+    schema: SchemaRef,
+}
+
+impl ExecDelete {
+    pub fn try_new(
+        table_name: String,
+        input: Box<dyn Exec>,
+        database: Rc<RefCell<Database>>,
+    ) -> Result<Self> {
+        let writer = TableWriter::try_new(&table_name, TriggerEvent::Delete, database.clone())?;
+        Ok(ExecDelete {
+            table_name,
+            input,
+            database,
+            writer,
+            schema: Arc::new(Schema::empty()),
+        })
+    }
+
+    fn delete_all(&self, records: Vec<DataRecord>) -> Result<()> {
+        let mut cursor = BtCursor::new(self.database.clone(), self.writer.table_page_number)?;
+        for record in records {
+            let rowid = record
+                .rowid
+                .ok_or_else(|| anyhow!("cannot delete from {}: no rowid", self.table_name))?;
+            self.writer.delete_row(&mut cursor, rowid)?;
+        }
+        Ok(())
+    }
+}
+
+impl Exec for ExecDelete {
+    fn open(&mut self) -> Result<()> {
+        let records = read_all(self.input.as_mut())?;
+        let result = self.delete_all(records);
+        commit_or_rollback(&self.database, result)
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
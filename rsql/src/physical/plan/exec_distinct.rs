@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+
+use anyhow::Result;
+use arrow_schema::SchemaRef;
+
+use crate::model::collation::Collation;
+use crate::model::data_record::DataRecord;
+use crate::model::hash_key::HashKey;
+use crate::physical::plan::exec::Exec;
+
+/// Distinct returns the records of its input not equal to a previous one:
+/// `SELECT DISTINCT a, b FROM t1`.
+///
+/// Records are compared like SQLite does for DISTINCT: NULLs are equal to each other,
+/// 1 and 1.0 are equal, and texts are compared with the collation of their column.
+/// Records are streamed in input order, only the keys of the returned ones are kept.
+#[derive(Debug)]
+pub struct ExecDistinct {
+    pub(crate) input: Box<dyn Exec>,
+    /// collation of each column
+    pub(crate) collations: Vec<Collation>,
+    seen: HashSet<Vec<HashKey>>,
+}
+
+impl ExecDistinct {
+    pub fn new(input: Box<dyn Exec>, collations: Vec<Collation>) -> Self {
+        ExecDistinct {
+            input,
+            collations,
+            seen: HashSet::new(),
+        }
+    }
+}
+
+impl Exec for ExecDistinct {
+    fn open(&mut self) -> Result<()> {
+        self.seen.clear();
+        self.input.open()
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        while let Some(record) = self.input.next()? {
+            let key = record
+                .values
+                .iter()
+                .enumerate()
+                .map(|(index, value)| {
+                    let collation = self.collations.get(index).copied().unwrap_or_default();
+                    HashKey::new(value, collation)
+                })
+                .collect();
+            if self.seen.insert(key) {
+                return Ok(Some(record));
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.seen.clear();
+        self.input.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::model::column_value::ColumnValue;
+    use crate::physical::plan::scan::ExecMemTable;
+
+    #[test]
+    fn test_distinct_sqlite_equality() {
+        let text = |text: &str| ColumnValue::Text(text.to_owned());
+        let rows = vec![
+            vec![ColumnValue::One, text("a")],
+            vec![ColumnValue::real(1.0), text("A")],
+            vec![ColumnValue::int64(1), text("a")],
+            vec![ColumnValue::Null, ColumnValue::Null],
+            vec![ColumnValue::Null, ColumnValue::Null],
+            vec![text("1"), text("a")],
+        ];
+        let records: Vec<DataRecord> = rows
+            .into_iter()
+            .map(|values| DataRecord {
+                values,
+                rowid: None,
+            })
+            .collect();
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("a", DataType::Int64, true),
+            Field::new("b", DataType::Utf8, true),
+        ]));
+        let distinct_count = |collations: Vec<Collation>| {
+            let input = ExecMemTable::new(&records, schema.clone());
+            let mut distinct = ExecDistinct::new(Box::new(input), collations);
+            distinct.open().unwrap();
+            let mut count = 0;
+            while distinct.next().unwrap().is_some() {
+                count += 1;
+            }
+            distinct.close().unwrap();
+            count
+        };
+        assert_eq!(
+            distinct_count(vec![Collation::Binary, Collation::Binary]),
+            4
+        );
+        assert_eq!(
+            distinct_count(vec![Collation::Binary, Collation::NoCase]),
+            3
+        );
+    }
+}
@@ -0,0 +1,122 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+use arrow_select::filter::filter_record_batch;
+
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::kernels;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::exec::Exec;
+
+/// Filter returns the records of its input for which the predicate is true,
+/// the WHERE clause of `SELECT * FROM t1 WHERE a > 1`.
+///
+/// A record is kept only if the predicate is true: records for which it is
+/// false or NULL (unknown) are discarded.
+#[derive(Debug)]
+pub struct ExecFilter {
+    pub(crate) input: Box<dyn Exec>,
+    pub(crate) predicate: Arc<dyn PhysicalExpr>,
+}
+
+impl ExecFilter {
+    pub fn new(input: Box<dyn Exec>, predicate: Arc<dyn PhysicalExpr>) -> Self {
+        ExecFilter { input, predicate }
+    }
+}
+
+impl Exec for ExecFilter {
+    fn open(&mut self) -> Result<()> {
+        self.input.open()
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        while let Some(record) = self.input.next()? {
+            if self.predicate.evaluate(&record).is_true() == Some(true) {
+                return Ok(Some(record));
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        while let Some(batch) = self.input.next_batch()? {
+            let predicate = self.predicate.evaluate_batch(&batch)?;
+            let filtered = filter_record_batch(&batch, &kernels::is_true(&predicate)?)?;
+            // batches are never empty
+            if filtered.num_rows() > 0 {
+                return Ok(Some(filtered));
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.input.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion_expr::Operator;
+
+    use super::*;
+    use crate::model::column_value::ColumnValue;
+    use crate::physical::expression::binary::PhysicalBinary;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+    use crate::physical::expression::literal::PhysicalLiteral;
+    use crate::physical::plan::scan::ExecMemTable;
+
+    fn filter_gt(value: i64) -> ExecFilter {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int64, true)]);
+        let records: Vec<DataRecord> = [Some(1), None, Some(3), Some(4)]
+            .into_iter()
+            .map(|a| DataRecord {
+                values: vec![a.map_or(ColumnValue::Null, ColumnValue::int64)],
+                rowid: None,
+            })
+            .collect();
+        let input = ExecMemTable::new(&records, Arc::new(schema));
+        let predicate = PhysicalBinary::try_new(
+            Arc::new(PhysicalColByIndex::new(0)),
+            Operator::Gt,
+            Arc::new(PhysicalLiteral {
+                value: ColumnValue::int64(value),
+            }),
+        )
+        .unwrap();
+        ExecFilter::new(Box::new(input), Arc::new(predicate))
+    }
+
+    #[test]
+    fn test_filter_discards_false_and_null() {
+        let mut filter = filter_gt(1);
+        filter.open().unwrap();
+        let mut values = vec![];
+        while let Some(record) = filter.next().unwrap() {
+            values.push(record.values[0].clone());
+        }
+        filter.close().unwrap();
+        assert_eq!(values, vec![ColumnValue::int64(3), ColumnValue::int64(4)]);
+    }
+
+    #[test]
+    fn test_filter_batch() {
+        let mut filter = filter_gt(3);
+        filter.open().unwrap();
+        let batch = filter.next_batch().unwrap().unwrap();
+        assert_eq!(batch.num_rows(), 1);
+        assert!(filter.next_batch().unwrap().is_none());
+
+        let mut filter = filter_gt(4);
+        filter.open().unwrap();
+        assert!(filter.next_batch().unwrap().is_none());
+    }
+}
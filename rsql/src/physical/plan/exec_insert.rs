@@ -0,0 +1,277 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use arrow_schema::{Schema, SchemaRef};
+use datafusion_common::DFSchema;
+use datafusion_sql::planner::{PlannerContext, SqlToRel};
+use datafusion_sql::sqlparser::dialect::AnsiDialect;
+use datafusion_sql::sqlparser::parser::Parser;
+
+use crate::btree::bt_cursor::BtCursor;
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::model::database::Database;
+use crate::model::schema::{SchemaObjType, TriggerEvent, FIELD_DEFAULT};
+use crate::physical::physical_planner::create_physical_expr;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::table_writer::{commit_or_rollback, read_all, TableWriter};
+use crate::sql::context_provider::SqliteContextProvider;
+
+// table of the largest rowid of the AUTOINCREMENT tables: (name, seq)
+const SQLITE_SEQUENCE: &str = "sqlite_sequence";
+
+/// Value of a column of an inserted row.
+#[derive(Debug)]
+enum ColumnSource {
+    /// the value at the index of the input record
+    Input(usize),
+    /// the DEFAULT value, or NULL, of a column not listed in the statement
+    Default(ColumnValue),
+}
+
+/// Inserts the records of its input into a table: `INSERT INTO t VALUES (...)` or
+/// `INSERT INTO t SELECT ...`. The records have the values of the columns listed
+/// in the statement, the other columns get their DEFAULT value or NULL.
+///
+/// Each row is written by a TableWriter: it gets the affinity of the columns, the
+/// NOT NULL and UNIQUE constraints are checked, then the row is inserted in the table
+/// btree and its key in each index btree. Without INTEGER PRIMARY KEY value, the rowid
+/// is the largest one plus 1.
+///
+/// The input is read before the first insert, like a SELECT of the same table into
+/// a temporary table. The rows are inserted when the plan is opened: all or none, the
+/// pages are written to the database file at the end or dropped on error.
+/// No record is returned.
+#[derive(Debug)]
+pub struct ExecInsert {
+    pub table_name: String,
+    input: Box<dyn Exec>,
+    database: Rc<RefCell<Database>>,
+    writer: TableWriter,
+    // value of each column of the table
+    column_sources: Vec<ColumnSource>,
+    schema: SchemaRef,
+}
+
+impl ExecInsert {
+    /// Creates the insert into table_name of the records of input whose fields are
+    /// the table columns named input_columns, or all the columns if it is empty.
+    pub fn try_new(
+        table_name: String,
+        input_columns: Vec<String>,
+        input: Box<dyn Exec>,
+        database: Rc<RefCell<Database>>,
+    ) -> Result<Self> {
+        let writer = TableWriter::try_new(&table_name, TriggerEvent::Insert, database.clone())?;
+        let input_columns = check_input_columns(&writer, input_columns, &input.schema())?;
+        let db = database.borrow();
+        let mut column_sources = vec![];
+        for field in &writer.columns {
+            let position = input_columns
+                .iter()
+                .position(|name| name.eq_ignore_ascii_case(field.name()));
+            column_sources.push(match position {
+                Some(index) => ColumnSource::Input(index),
+                None => ColumnSource::Default(match field.metadata().get(FIELD_DEFAULT) {
+                    Some(default) => default_value(default, &db)?,
+                    None => ColumnValue::Null,
+                }),
+            });
+        }
+        drop(db);
+
+        Ok(ExecInsert {
+            table_name,
+            input,
+            database,
+            writer,
+            column_sources,
+            schema: Arc::new(Schema::empty()),
+        })
+    }
+
+    fn insert_all(&mut self, records: Vec<DataRecord>) -> Result<()> {
+        let mut cursor = BtCursor::new(self.database.clone(), self.writer.table_page_number)?;
+        let sequence = if self.writer.autoincrement {
+            self.read_sequence()?
+        } else {
+            None
+        };
+        let mut seq = sequence.map_or(0, |(_, seq)| seq);
+        for record in records {
+            let rowid = self.insert(&mut cursor, record, seq)?;
+            seq = seq.max(rowid);
+        }
+        if self.writer.autoincrement && Some(seq) != sequence.map(|(_, seq)| seq) {
+            self.write_sequence(sequence.map(|(rowid, _)| rowid), seq)?;
+        }
+        Ok(())
+    }
+
+    // Inserts a row with the values of the input record, returns its rowid. seq: the
+    // largest rowid of an AUTOINCREMENT table.
+    fn insert(&self, cursor: &mut BtCursor, record: DataRecord, seq: i64) -> Result<i64> {
+        let values = self
+            .column_sources
+            .iter()
+            .map(|source| match source {
+                ColumnSource::Input(index) => record.values[*index].clone(),
+                ColumnSource::Default(default) => default.clone(),
+            })
+            .collect();
+        let values = self.writer.apply_affinity(values)?;
+        let rowid = match self.writer.alias_rowid(&values)? {
+            Some(rowid) => rowid,
+            None => self.new_rowid(cursor, seq)?,
+        };
+        self.writer.insert_row(cursor, values, rowid)?;
+        Ok(rowid)
+    }
+
+    // The largest rowid of the table plus 1. An AUTOINCREMENT table does not reuse the
+    // rowids of deleted rows: the rowid is also larger than seq.
+    //
+    // sqlite picks a random unused rowid when the largest one is i64::MAX, unless
+    // the table is AUTOINCREMENT.
+    fn new_rowid(&self, cursor: &mut BtCursor, seq: i64) -> Result<i64> {
+        cursor.move_to_last()?;
+        let max_rowid = if cursor.is_eof() {
+            0
+        } else {
+            cursor.table_leaf_cell()?.rowid
+        };
+        let max_rowid = if self.writer.autoincrement {
+            max_rowid.max(seq)
+        } else {
+            max_rowid
+        };
+        match max_rowid.checked_add(1) {
+            Some(rowid) => Ok(rowid),
+            None => bail!("database or disk is full"),
+        }
+    }
+
+    // The row of the table in sqlite_sequence: its rowid and seq.
+    fn read_sequence(&self) -> Result<Option<(i64, i64)>> {
+        let cursor = BtCursor::new(self.database.clone(), self.sequence_page_number()?)?;
+        for cell in cursor.scan_page() {
+            let cell = cell?;
+            if cell.payload.value_at_index(0) == &ColumnValue::Text(self.table_name.clone()) {
+                let seq = cell.payload.value_at_index(1).as_i64().unwrap_or(0);
+                return Ok(Some((cell.rowid, seq)));
+            }
+        }
+        Ok(None)
+    }
+
+    // Sets the seq of the table in sqlite_sequence, replacing its row at rowid or
+    // adding a row if it has none.
+    fn write_sequence(&self, rowid: Option<i64>, seq: i64) -> Result<()> {
+        let mut cursor = BtCursor::new(self.database.clone(), self.sequence_page_number()?)?;
+        let rowid = match rowid {
+            Some(rowid) => rowid,
+            None => {
+                cursor.move_to_last()?;
+                if cursor.is_eof() {
+                    1
+                } else {
+                    cursor.table_leaf_cell()?.rowid + 1
+                }
+            }
+        };
+        let record = DataRecord {
+            values: vec![
+                ColumnValue::Text(self.table_name.clone()),
+                ColumnValue::int64(seq),
+            ],
+            rowid: None,
+        };
+        cursor.table_insert(rowid, &record)
+    }
+
+    fn sequence_page_number(&self) -> Result<u32> {
+        self.database
+            .borrow()
+            .db_meta
+            .schema_objects
+            .iter()
+            .find(|obj| obj.obj_type == SchemaObjType::Table && obj.name == SQLITE_SEQUENCE)
+            .map(|obj| obj.rootpage)
+            .ok_or_else(|| anyhow!("no such table: {SQLITE_SEQUENCE}"))
+    }
+}
+
+// The names of the columns of the input fields: all the columns of the table if none is
+// listed. There is one input field per column.
+fn check_input_columns(
+    writer: &TableWriter,
+    input_columns: Vec<String>,
+    input_schema: &Schema,
+) -> Result<Vec<String>> {
+    let table_name = &writer.table_name;
+    let value_count = input_schema.fields().len();
+    if input_columns.is_empty() {
+        if value_count != writer.columns.len() {
+            bail!(
+                "table {table_name} has {} columns but {value_count} values were supplied",
+                writer.columns.len()
+            );
+        }
+        return Ok(writer
+            .columns
+            .iter()
+            .map(|field| field.name().clone())
+            .collect());
+    }
+    for name in &input_columns {
+        if !writer
+            .columns
+            .iter()
+            .any(|field| field.name().eq_ignore_ascii_case(name))
+        {
+            bail!("table {table_name} has no column named {name}");
+        }
+    }
+    if value_count != input_columns.len() {
+        bail!("{value_count} values for {} columns", input_columns.len());
+    }
+    Ok(input_columns)
+}
+
+// Evaluates the SQL text of the DEFAULT value of a column.
+fn default_value(sql: &str, database: &Database) -> Result<ColumnValue> {
+    let expr = Parser::new(&AnsiDialect {})
+        .try_with_sql(sql)?
+        .parse_expr()?;
+    let provider = SqliteContextProvider::new_for_db(database);
+    let schema = DFSchema::empty();
+    let logical_expr =
+        SqlToRel::new(&provider).sql_to_expr(expr, &schema, &mut PlannerContext::new())?;
+    let record = DataRecord {
+        values: vec![],
+        rowid: None,
+    };
+    Ok(create_physical_expr(&logical_expr, &schema)?.evaluate(&record))
+}
+
+impl Exec for ExecInsert {
+    fn open(&mut self) -> Result<()> {
+        let records = read_all(self.input.as_mut())?;
+        let result = self.insert_all(records);
+        commit_or_rollback(&self.database, result)
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::Result;
+use arrow_array::RecordBatch;
+use arrow_schema::SchemaRef;
+
+use crate::model::data_record::DataRecord;
+use crate::physical::plan::exec::Exec;
+
+/// Limit skips the first skip records of its input and returns at most fetch of the
+/// following ones: `SELECT * FROM t1 LIMIT fetch OFFSET skip`.
+///
+/// Once fetch records are returned, the input is not pulled anymore: a scan below
+/// only reads the pages of the returned records.
+#[derive(Debug)]
+pub struct ExecLimit {
+    pub(crate) input: Box<dyn Exec>,
+    pub(crate) skip: usize,
+    /// no limit if None
+    pub(crate) fetch: Option<usize>,
+    // records skipped and returned since open
+    skipped: usize,
+    returned: usize,
+}
+
+impl ExecLimit {
+    pub fn new(input: Box<dyn Exec>, skip: usize, fetch: Option<usize>) -> Self {
+        ExecLimit {
+            input,
+            skip,
+            fetch,
+            skipped: 0,
+            returned: 0,
+        }
+    }
+
+    fn remaining(&self) -> usize {
+        self.fetch
+            .map_or(usize::MAX, |fetch| fetch.saturating_sub(self.returned))
+    }
+}
+
+impl Exec for ExecLimit {
+    fn open(&mut self) -> Result<()> {
+        self.skipped = 0;
+        self.returned = 0;
+        self.input.open()
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        if self.remaining() == 0 {
+            return Ok(None);
+        }
+        while self.skipped < self.skip {
+            if self.input.next()?.is_none() {
+                return Ok(None);
+            }
+            self.skipped += 1;
+        }
+        let record = self.input.next()?;
+        if record.is_some() {
+            self.returned += 1;
+        }
+        Ok(record)
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        while self.remaining() > 0 {
+            let Some(batch) = self.input.next_batch()? else {
+                return Ok(None);
+            };
+            let skip = (self.skip - self.skipped).min(batch.num_rows());
+            self.skipped += skip;
+            let length = (batch.num_rows() - skip).min(self.remaining());
+            // batches are never empty
+            if length > 0 {
+                self.returned += length;
+                return Ok(Some(batch.slice(skip, length)));
+            }
+        }
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.input.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.input.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use anyhow::bail;
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::model::column_value::ColumnValue;
+
+    // returns the integers 0..count, fails if it is pulled after max_pulls records
+    #[derive(Debug)]
+    struct Counter {
+        count: i64,
+        max_pulls: i64,
+        pulls: i64,
+    }
+
+    impl Exec for Counter {
+        fn open(&mut self) -> Result<()> {
+            self.pulls = 0;
+            Ok(())
+        }
+
+        fn next(&mut self) -> Result<Option<DataRecord>> {
+            if self.pulls == self.max_pulls {
+                bail!("input pulled after the limit")
+            }
+            self.pulls += 1;
+            Ok((self.pulls <= self.count).then(|| DataRecord {
+                values: vec![ColumnValue::int64(self.pulls - 1)],
+                rowid: None,
+            }))
+        }
+
+        fn close(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn schema(&self) -> SchemaRef {
+            Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, false)]))
+        }
+    }
+
+    fn limit(count: i64, max_pulls: i64, skip: usize, fetch: Option<usize>) -> ExecLimit {
+        let input = Counter {
+            count,
+            max_pulls,
+            pulls: 0,
+        };
+        ExecLimit::new(Box::new(input), skip, fetch)
+    }
+
+    fn collect(mut limit: ExecLimit) -> Vec<i64> {
+        limit.open().unwrap();
+        let mut values = vec![];
+        while let Some(record) = limit.next().unwrap() {
+            values.push(record.values[0].as_i64().unwrap());
+        }
+        limit.close().unwrap();
+        values
+    }
+
+    #[test]
+    fn test_limit_stops_pulling_input() {
+        assert_eq!(collect(limit(100, 3, 0, Some(3))), vec![0, 1, 2]);
+        assert_eq!(collect(limit(100, 5, 2, Some(3))), vec![2, 3, 4]);
+        assert_eq!(collect(limit(100, 0, 2, Some(0))), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn test_limit_offset_past_input() {
+        assert_eq!(collect(limit(3, 10, 2, None)), vec![2]);
+        assert_eq!(collect(limit(3, 10, 5, Some(2))), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn test_limit_batch() {
+        // batches of 1024 records are sliced
+        let mut limit = limit(3000, 4000, 1000, Some(1500));
+        limit.open().unwrap();
+        let mut sizes = vec![];
+        while let Some(batch) = limit.next_batch().unwrap() {
+            sizes.push(batch.num_rows());
+        }
+        assert_eq!(sizes, vec![24, 1024, 452]);
+    }
+}
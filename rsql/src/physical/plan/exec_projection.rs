@@ -1,6 +1,8 @@
 use std::sync::Arc;
 
 use anyhow::Result;
+use arrow_array::{Array, RecordBatch};
+use arrow_schema::{Field, Schema, SchemaRef};
 
 use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
@@ -20,25 +22,29 @@ use crate::physical::plan::exec::Exec;
 pub struct ExecProjection {
     /// Physical plan input into this Exec for example
     /// SourceScan, CsvScan, SqliteTableScan, etc.
-    pub(crate) input: Arc<dyn Exec>,
+    pub(crate) input: Box<dyn Exec>,
     /// expressions to be projected on the returned row
     pub(crate) expressions: Vec<Arc<dyn PhysicalExpr>>,
-    result: Vec<DataRecord>,
+    /// schema of the projected records, one field per expression
+    schema: SchemaRef,
 }
 
 impl ExecProjection {
-    pub fn new(input: Arc<dyn Exec>, expressions: Vec<Arc<dyn PhysicalExpr>>) -> Result<Self> {
+    pub fn new(
+        input: Box<dyn Exec>,
+        expressions: Vec<Arc<dyn PhysicalExpr>>,
+        schema: SchemaRef,
+    ) -> Result<Self> {
         Ok(Self {
             input,
             expressions,
-            result: vec![],
+            schema,
         })
     }
 
-    // TODO project by column name
-    fn project(&self, record: &DataRecord) -> DataRecord {
+    fn project(expressions: &[Arc<dyn PhysicalExpr>], record: &DataRecord) -> DataRecord {
         let mut values: Vec<ColumnValue> = vec![];
-        for expr in &self.expressions {
+        for expr in expressions {
             values.push(expr.evaluate(record));
         }
         DataRecord {
@@ -49,23 +55,49 @@ impl ExecProjection {
 }
 
 impl Exec for ExecProjection {
-    fn execute(&mut self) -> &[DataRecord] {
-        // why use into_iter() here instead of iter()?
-        // https://www.becomebetterprogrammer.com/rust-iter-vs-iter_mut-vs-into_iter/
-        // into_iter() yields any of T (moved value), &T or &mut T depending on the usage
-        // seems like we need a moved value here, not sure why we need yet.
-        // self.result = *(self.input)
-        //     .execute()
-        //     .into_iter()
-        //     .map(|record| self.project(&record))
-        //     .collect();
+    fn open(&mut self) -> Result<()> {
+        self.input.open()
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        Ok(self
+            .input
+            .next()?
+            .map(|record| Self::project(&self.expressions, &record)))
+    }
 
-        // &self.result
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let Some(batch) = self.input.next_batch()? else {
+            return Ok(None);
+        };
+        let columns = self
+            .expressions
+            .iter()
+            .map(|expr| expr.evaluate_batch(&batch))
+            .collect::<Result<Vec<_>>>()?;
+        // the type of computed columns is the one of their values, e.g. comparisons
+        // are Int64 (0 or 1) and not Boolean like in the logical plan.
+        let fields: Vec<Field> = self
+            .schema
+            .fields()
+            .iter()
+            .zip(&columns)
+            .map(|(field, column)| {
+                Field::new(field.name(), column.data_type().clone(), true)
+                    .with_metadata(field.metadata().clone())
+            })
+            .collect();
+        Ok(Some(RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            columns,
+        )?))
+    }
 
-        todo!()
+    fn close(&mut self) -> Result<()> {
+        self.input.close()
     }
 
-    fn schema(&self) -> arrow_schema::SchemaRef {
-        todo!()
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
     }
 }
@@ -0,0 +1,93 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use anyhow::{anyhow, bail, Result};
+use arrow_schema::{Schema, SchemaRef};
+
+use crate::btree::bt_cursor::BtCursor;
+use crate::model::data_record::DataRecord;
+use crate::model::database::Database;
+use crate::model::schema::TriggerEvent;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::table_writer::{commit_or_rollback, read_all, TableWriter};
+
+/// Updates the rows of a table: `UPDATE t SET ... WHERE ...`. The input returns the new
+/// values of all columns of each updated row, with the rowid of the row.
+///
+/// Each row is deleted then inserted again with its new values, which get the affinity
+/// of the columns and are checked against the NOT NULL and UNIQUE constraints like
+/// an inserted row. The rowid changes with the INTEGER PRIMARY KEY value, which cannot
+/// be NULL. Rows are updated in the order of the input, a UNIQUE constraint fails if
+/// a new value is the one of a row not updated yet.
+///
+/// The input is read before the first update. The rows are updated when the plan is
+/// opened: all or none, the pages are written to the database file at the end or
+/// dropped on error. No record is returned.
+#[derive(Debug)]
+pub struct ExecUpdate {
+    pub table_name: String,
+    input: Box<dyn Exec>,
+    database: Rc<RefCell<Database>>,
+    writer: TableWriter,
+    schema: SchemaRef,
+}
+
+impl ExecUpdate {
+    pub fn try_new(
+        table_name: String,
+        input: Box<dyn Exec>,
+        database: Rc<RefCell<Database>>,
+    ) -> Result<Self> {
+        let writer = TableWriter::try_new(
+            &table_name,
+            TriggerEvent::Update { columns: vec![] },
+            database.clone(),
+        )?;
+        Ok(ExecUpdate {
+            table_name,
+            input,
+            database,
+            writer,
+            schema: Arc::new(Schema::empty()),
+        })
+    }
+
+    fn update_all(&self, records: Vec<DataRecord>) -> Result<()> {
+        let mut cursor = BtCursor::new(self.database.clone(), self.writer.table_page_number)?;
+        for record in records {
+            let rowid = record
+                .rowid
+                .ok_or_else(|| anyhow!("cannot update {}: no rowid", self.table_name))?;
+            let values = self.writer.apply_affinity(record.values)?;
+            let new_rowid = match (self.writer.rowid_alias, self.writer.alias_rowid(&values)?) {
+                (None, _) => rowid,
+                (Some(_), Some(new_rowid)) => new_rowid,
+                (Some(_), None) => bail!("datatype mismatch"),
+            };
+            self.writer.delete_row(&mut cursor, rowid)?;
+            self.writer.insert_row(&mut cursor, values, new_rowid)?;
+        }
+        Ok(())
+    }
+}
+
+impl Exec for ExecUpdate {
+    fn open(&mut self) -> Result<()> {
+        let records = read_all(self.input.as_mut())?;
+        let result = self.update_all(records);
+        commit_or_rollback(&self.database, result)
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
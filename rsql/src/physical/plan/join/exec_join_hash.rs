@@ -1,31 +1,63 @@
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 
-use anyhow::{bail, Ok, Result};
+use anyhow::{bail, Result};
+use arrow_array::{ArrayRef, RecordBatch};
 use arrow_schema::SchemaRef;
 use datafusion_common::JoinType;
-use datafusion_physical_plan::expressions::Column;
-use datafusion_physical_plan::joins::utils::{build_join_schema, JoinOn};
+use datafusion_physical_plan::joins::utils::build_join_schema;
 
+use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
+use crate::model::hash_key::HashKey;
+use crate::physical::expression::binary::{comparison_affinities, comparison_collation};
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::batch::{array_value, batch_to_records, records_to_batch, BATCH_SIZE};
 use crate::physical::plan::exec::Exec;
+use crate::physical::plan::join::JoinOn;
 
 /// Hash Join Physical Plan for equi joins
+///
+/// The first call to next or next_batch reads the left input into a hash table keyed by
+/// the values of the left expressions of on, then each record of the right input probes
+/// it. next_batch pulls both inputs in batches: the expressions of on are evaluated on
+/// each batch, and the joined records are returned in batches.
+/// Keys are equal like with the = operator: NULL keys never match, affinities
+/// and collations are applied like for a comparison.
+///
+/// Records of the left input that are not matched (Left, Full, LeftAnti) or matched
+/// (LeftSemi) are returned once the right input is exhausted.
 #[derive(Debug)]
 pub struct ExecJoinHash {
     /// left (build) side which gets hashed
-    pub left: Arc<dyn Exec>,
+    pub left: Box<dyn Exec>,
     /// right (probe) side which are filtered by hash table
-    pub right: Arc<dyn Exec>,
-    /// Set of equijoin columns from the relations: (left_col, right_col)
-    pub on: Vec<(Column, Column)>,
+    pub right: Box<dyn Exec>,
+    /// Set of equijoin expressions from the relations: (left_expr, right_expr)
+    pub on: JoinOn,
+    /// Residual condition evaluated on the left values followed by the right values
+    /// of records with equal keys, e.g. `a.x > b.y` in `ON a.id = b.id AND a.x > b.y`
+    pub filter: Option<Arc<dyn PhysicalExpr>>,
     /// the type of join: OUTER, INNER, etc.
     pub join_type: JoinType,
 
-    // TODO output schema for the join
-    // do we need a separate schema field if using the volcano processing
-    // model row by row? How does Spark do it?
     /// Schema once the join is applied
     schema: SchemaRef,
+    open: bool,
+    // hash table of the left input, set by the first call to next or next_batch
+    table: Option<HashTable>,
+    // joined records not returned yet
+    pending: VecDeque<DataRecord>,
+    // whether the left records not matched or matched are returned
+    finished: bool,
+}
+
+#[derive(Debug)]
+struct HashTable {
+    records: Vec<DataRecord>,
+    indices: HashMap<Vec<HashKey>, Vec<usize>>,
+    // whether each left record matched a right one
+    matched: Vec<bool>,
 }
 
 impl ExecJoinHash {
@@ -33,11 +65,11 @@ impl ExecJoinHash {
     /// taking ownership of it (JoinType) for a couple of reasons:
     ///
     /// 1. Efficiency: Taking a reference is more efficient than taking ownership
-    /// if the function does not need to consume or modify the value.
-    /// This is because taking a reference does not involve copying or moving the value.
+    ///    if the function does not need to consume or modify the value.
+    ///    This is because taking a reference does not involve copying or moving the value.
     ///
     /// 2. Flexibility: By taking a reference, the try_new function allows the caller
-    /// to continue using the JoinType value after the call.
+    ///    to continue using the JoinType value after the call.
     ///
     /// Then why used ownership (JoinType) in the struct?
     ///     1. Lifetime: if reference is used, lifetime of join_type must be ensured
@@ -47,14 +79,24 @@ impl ExecJoinHash {
     ///     3. Copying is cheap for JoinType enum -> simplify the code by not using
     ///     reference.
     pub fn try_new(
-        left: Arc<dyn Exec>,
-        right: Arc<dyn Exec>,
+        left: Box<dyn Exec>,
+        right: Box<dyn Exec>,
         on: JoinOn,
+        filter: Option<Arc<dyn PhysicalExpr>>,
         join_type: &JoinType, // using reference as we read only
     ) -> Result<Self> {
         if on.is_empty() {
             bail!("On constraints in ExecJoinHash should be non-empty")
         }
+        match join_type {
+            JoinType::Inner
+            | JoinType::Left
+            | JoinType::Right
+            | JoinType::Full
+            | JoinType::LeftSemi
+            | JoinType::LeftAnti => {}
+            _ => bail!("unsupported join type {join_type} in ExecJoinHash"),
+        }
 
         let left_schema = left.schema();
         let right_schema = right.schema();
@@ -64,16 +106,237 @@ impl ExecJoinHash {
             left,
             right,
             on,
+            filter,
             // dereferences the join_type reference (*join_type) to copy value into struct
             join_type: *join_type,
             schema: Arc::new(schema),
+            open: false,
+            table: None,
+            pending: VecDeque::new(),
+            finished: false,
+        })
+    }
+
+    // Values of the expressions of one side of on for a record.
+    fn key_values(&self, record: &DataRecord, left_side: bool) -> Vec<ColumnValue> {
+        self.on
+            .iter()
+            .map(|(left_expr, right_expr)| match left_side {
+                true => left_expr.evaluate(record),
+                false => right_expr.evaluate(record),
+            })
+            .collect()
+    }
+
+    // Arrays of the expressions of one side of on for the rows of a batch.
+    fn key_arrays(&self, batch: &RecordBatch, left_side: bool) -> Result<Vec<ArrayRef>> {
+        self.on
+            .iter()
+            .map(|(left_expr, right_expr)| match left_side {
+                true => left_expr.evaluate_batch(batch),
+                false => right_expr.evaluate_batch(batch),
+            })
+            .collect()
+    }
+
+    // Key of the values of the expressions of one side, None if a value is NULL: it
+    // matches nothing.
+    fn key(&self, values: Vec<ColumnValue>, left_side: bool) -> Option<Vec<HashKey>> {
+        self.on
+            .iter()
+            .zip(values)
+            .map(|((left_expr, right_expr), value)| {
+                if value.is_null() {
+                    return None;
+                }
+                let (left_affinity, right_affinity) =
+                    comparison_affinities(left_expr.as_ref(), right_expr.as_ref());
+                let affinity = if left_side {
+                    left_affinity
+                } else {
+                    right_affinity
+                };
+                let value = match affinity {
+                    Some(affinity) => value.apply_affinity(affinity),
+                    None => value,
+                };
+                let collation = comparison_collation(left_expr.as_ref(), right_expr.as_ref());
+                Some(HashKey::new(&value, collation))
+            })
+            .collect()
+    }
+
+    // Reads the left input by its records or its batches.
+    fn build(&mut self, batches: bool) -> Result<HashTable> {
+        let mut keyed_records = vec![];
+        if batches {
+            while let Some(batch) = self.left.next_batch()? {
+                let arrays = self.key_arrays(&batch, true)?;
+                for (row, record) in batch_to_records(&batch)?.into_iter().enumerate() {
+                    let key = self.key(row_values(&arrays, row)?, true);
+                    keyed_records.push((key, record));
+                }
+            }
+        } else {
+            while let Some(record) = self.left.next()? {
+                let key = self.key(self.key_values(&record, true), true);
+                keyed_records.push((key, record));
+            }
+        }
+        let mut records = vec![];
+        let mut indices: HashMap<Vec<HashKey>, Vec<usize>> = HashMap::new();
+        for (key, record) in keyed_records {
+            if let Some(key) = key {
+                indices.entry(key).or_default().push(records.len());
+            }
+            records.push(record);
+        }
+        Ok(HashTable {
+            matched: vec![false; records.len()],
+            records,
+            indices,
         })
     }
+
+    // Builds the hash table on the first call.
+    fn build_table(&mut self, batches: bool) -> Result<()> {
+        if !self.open {
+            bail!("hash join is not open")
+        }
+        if self.table.is_none() {
+            self.table = Some(self.build(batches)?);
+        }
+        Ok(())
+    }
+
+    // Joins a right record with the left records of the same key passing the filter.
+    fn probe(&mut self, right: DataRecord, key: Option<Vec<HashKey>>) {
+        let Some(table) = self.table.as_mut() else {
+            return;
+        };
+        let candidates = key.and_then(|key| table.indices.get(&key));
+        let mut right_matched = false;
+        for &index in candidates.into_iter().flatten() {
+            let joined = join_values(&table.records[index].values, &right.values);
+            if let Some(filter) = &self.filter {
+                if filter.evaluate(&joined).is_true() != Some(true) {
+                    continue;
+                }
+            }
+            right_matched = true;
+            table.matched[index] = true;
+            if matches!(
+                self.join_type,
+                JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full
+            ) {
+                self.pending.push_back(joined);
+            }
+        }
+        if !right_matched && matches!(self.join_type, JoinType::Right | JoinType::Full) {
+            let left_width = self.left.schema().fields().len();
+            let nulls = vec![ColumnValue::Null; left_width];
+            self.pending.push_back(join_values(&nulls, &right.values));
+        }
+    }
+
+    // Left records returned after the probe: not matched ones for Left, Full and LeftAnti,
+    // matched ones for LeftSemi.
+    fn finish(&mut self) {
+        let Some(table) = self.table.as_ref() else {
+            return;
+        };
+        let right_width = self.right.schema().fields().len();
+        let nulls = vec![ColumnValue::Null; right_width];
+        for (record, &matched) in table.records.iter().zip(&table.matched) {
+            match (self.join_type, matched) {
+                (JoinType::Left | JoinType::Full, false) => {
+                    self.pending.push_back(join_values(&record.values, &nulls))
+                }
+                (JoinType::LeftSemi, true) | (JoinType::LeftAnti, false) => {
+                    self.pending.push_back(record.clone())
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// Values of the arrays at row.
+fn row_values(arrays: &[ArrayRef], row: usize) -> Result<Vec<ColumnValue>> {
+    arrays.iter().map(|array| array_value(array, row)).collect()
+}
+
+fn join_values(left: &[ColumnValue], right: &[ColumnValue]) -> DataRecord {
+    DataRecord {
+        values: left.iter().chain(right).cloned().collect(),
+        rowid: None,
+    }
 }
 
 impl Exec for ExecJoinHash {
-    fn execute(&mut self) -> &[DataRecord] {
-        todo!()
+    fn open(&mut self) -> Result<()> {
+        self.left.open()?;
+        self.right.open()?;
+        self.pending.clear();
+        self.finished = false;
+        self.table = None;
+        self.open = true;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        self.build_table(false)?;
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Ok(Some(record));
+            }
+            if self.finished {
+                return Ok(None);
+            }
+            match self.right.next()? {
+                Some(right) => {
+                    let key = self.key(self.key_values(&right, false), false);
+                    self.probe(right, key);
+                }
+                None => {
+                    self.finish();
+                    self.finished = true;
+                }
+            }
+        }
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        self.build_table(true)?;
+        while self.pending.len() < BATCH_SIZE && !self.finished {
+            match self.right.next_batch()? {
+                Some(batch) => {
+                    let arrays = self.key_arrays(&batch, false)?;
+                    for (row, right) in batch_to_records(&batch)?.into_iter().enumerate() {
+                        let key = self.key(row_values(&arrays, row)?, false);
+                        self.probe(right, key);
+                    }
+                }
+                None => {
+                    self.finish();
+                    self.finished = true;
+                }
+            }
+        }
+        let len = self.pending.len().min(BATCH_SIZE);
+        if len == 0 {
+            return Ok(None);
+        }
+        let records: Vec<DataRecord> = self.pending.drain(..len).collect();
+        Ok(Some(records_to_batch(self.schema(), &records)?))
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        self.table = None;
+        self.pending.clear();
+        self.left.close()?;
+        self.right.close()
     }
 
     fn schema(&self) -> SchemaRef {
@@ -85,8 +348,10 @@ impl Exec for ExecJoinHash {
 mod tests {
     use anyhow::Result;
     use arrow_schema::{DataType, Field, Schema};
-    use log::info;
+    use datafusion_expr::Operator;
 
+    use crate::physical::expression::binary::PhysicalBinary;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
     use crate::physical::plan::scan::ExecMemTable;
 
     use super::*;
@@ -95,39 +360,181 @@ mod tests {
         a: (&str, &Vec<i32>),
         b: (&str, &Vec<i32>),
         c: (&str, &Vec<i32>),
-    ) -> Arc<dyn Exec> {
+    ) -> Box<dyn Exec> {
         let schema = Schema::new(vec![
             Field::new(a.0, DataType::Int32, false),
             Field::new(b.0, DataType::Int32, false),
             Field::new(c.0, DataType::Int32, false),
         ]);
-        let records = vec![];
+        let records: Vec<DataRecord> = (0..a.1.len())
+            .map(|row| DataRecord {
+                values: vec![
+                    ColumnValue::int32(a.1[row]),
+                    ColumnValue::int32(b.1[row]),
+                    ColumnValue::int32(c.1[row]),
+                ],
+                rowid: None,
+            })
+            .collect();
+
+        Box::new(ExecMemTable::new(&records, Arc::new(schema)))
+    }
 
-        Arc::new(ExecMemTable::new(&records, Arc::new(schema)))
+    fn col(col_index: usize) -> Arc<dyn PhysicalExpr> {
+        Arc::new(PhysicalColByIndex::new(col_index))
+    }
+
+    // left (a1, b1, c1) and right (a2, b2, c2) joined on a1 = a2
+    fn join(join_type: JoinType, filter: Option<Arc<dyn PhysicalExpr>>) -> Result<Vec<Vec<i32>>> {
+        join_records(join_type, filter, false)
+    }
+
+    fn join_records(
+        join_type: JoinType,
+        filter: Option<Arc<dyn PhysicalExpr>>,
+        batches: bool,
+    ) -> Result<Vec<Vec<i32>>> {
+        let left = build_table(
+            ("a1", &vec![1, 2, 3, 3]),
+            ("b1", &vec![4, 5, 6, 7]),
+            ("c1", &vec![7, 8, 9, 9]),
+        );
+        let right = build_table(
+            ("a2", &vec![10, 3, 1, 3]),
+            ("b2", &vec![20, 6, 4, 8]),
+            ("c2", &vec![70, 90, 70, 90]),
+        );
+        let on: JoinOn = vec![(col(0), col(0))];
+        let mut hash_join = ExecJoinHash::try_new(left, right, on, filter, &join_type)?;
+        hash_join.open()?;
+        let mut records = vec![];
+        if batches {
+            while let Some(batch) = hash_join.next_batch()? {
+                records.extend(batch_to_records(&batch)?);
+            }
+        } else {
+            while let Some(record) = hash_join.next()? {
+                records.push(record);
+            }
+        }
+        let mut rows = vec![];
+        for record in records {
+            let row = record
+                .values
+                .iter()
+                // -1 for NULL
+                .map(|value| value.as_i64().map_or(-1, |int| int as i32))
+                .collect();
+            rows.push(row);
+        }
+        hash_join.close()?;
+        Ok(rows)
     }
 
     #[test]
     fn test_join_on_1_column_pair() -> Result<()> {
-        // joining left and right on a1 = a2
-        let left_physical = build_table(
-            ("a1", &vec![10, 1, 1]),
-            ("b1", &vec![20, 1, 1]),
-            ("c1", &vec![30, 1, 1]), // not matched in join
+        assert_eq!(
+            join(JoinType::Inner, None)?,
+            vec![
+                vec![3, 6, 9, 3, 6, 90],
+                vec![3, 7, 9, 3, 6, 90],
+                vec![1, 4, 7, 1, 4, 70],
+                vec![3, 6, 9, 3, 8, 90],
+                vec![3, 7, 9, 3, 8, 90],
+            ]
         );
-        let right_physical = build_table(
-            ("a2", &vec![10, 2, 2]),
-            ("b2", &vec![20, 2, 2]),
-            ("c2", &vec![40, 2, 2]), // not matched in join
-        );
-        let on: Vec<(Column, Column)> = vec![(
-            Column::new_with_schema("a1", &left_physical.schema())?,
-            Column::new_with_schema("a2", &right_physical.schema())?,
-        )];
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_batches() -> Result<()> {
+        let filter: Arc<dyn PhysicalExpr> =
+            Arc::new(PhysicalBinary::try_new(col(1), Operator::Eq, col(4))?);
+        for join_type in [
+            JoinType::Inner,
+            JoinType::Left,
+            JoinType::Right,
+            JoinType::Full,
+            JoinType::LeftSemi,
+            JoinType::LeftAnti,
+        ] {
+            for filter in [None, Some(filter.clone())] {
+                assert_eq!(
+                    join_records(join_type, filter.clone(), true)?,
+                    join_records(join_type, filter, false)?
+                );
+            }
+        }
+        Ok(())
+    }
 
-        let hash_join = ExecJoinHash::try_new(left_physical, right_physical, on, &JoinType::Inner)?;
+    #[test]
+    fn test_join_outer() -> Result<()> {
+        let left = join(JoinType::Left, None)?;
+        assert_eq!(left.len(), 6);
+        assert_eq!(left[5], vec![2, 5, 8, -1, -1, -1]);
 
-        info!("{:?}", hash_join.schema());
+        let right = join(JoinType::Right, None)?;
+        assert_eq!(right.len(), 6);
+        assert_eq!(right[0], vec![-1, -1, -1, 10, 20, 70]);
+
+        let full = join(JoinType::Full, None)?;
+        assert_eq!(full.len(), 7);
+        assert_eq!(full[0], vec![-1, -1, -1, 10, 20, 70]);
+        assert_eq!(full[6], vec![2, 5, 8, -1, -1, -1]);
+        Ok(())
+    }
 
+    #[test]
+    fn test_join_semi_anti() -> Result<()> {
+        assert_eq!(
+            join(JoinType::LeftSemi, None)?,
+            vec![vec![1, 4, 7], vec![3, 6, 9], vec![3, 7, 9]]
+        );
+        assert_eq!(join(JoinType::LeftAnti, None)?, vec![vec![2, 5, 8]]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_residual_filter() -> Result<()> {
+        // ON a1 = a2 AND b1 = b2, filter columns are left ones followed by right ones
+        let filter = Arc::new(PhysicalBinary::try_new(col(1), Operator::Eq, col(4))?);
+        assert_eq!(
+            join(JoinType::Inner, Some(filter.clone()))?,
+            vec![vec![3, 6, 9, 3, 6, 90], vec![1, 4, 7, 1, 4, 70]]
+        );
+        // left records matched by the keys but not the filter are not matched
+        assert_eq!(
+            join(JoinType::LeftAnti, Some(filter))?,
+            vec![vec![2, 5, 8], vec![3, 7, 9]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_join_null_keys_never_match() -> Result<()> {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int64, true)]));
+        let records: Vec<DataRecord> = [ColumnValue::Null, ColumnValue::One]
+            .into_iter()
+            .map(|value| DataRecord {
+                values: vec![value],
+                rowid: None,
+            })
+            .collect();
+        let table = || Box::new(ExecMemTable::new(&records, schema.clone()));
+        let mut hash_join = ExecJoinHash::try_new(
+            table(),
+            table(),
+            vec![(col(0), col(0))],
+            None,
+            &JoinType::Inner,
+        )?;
+        hash_join.open()?;
+        assert_eq!(
+            hash_join.next()?.map(|record| record.values),
+            Some(vec![ColumnValue::One, ColumnValue::One])
+        );
+        assert!(hash_join.next()?.is_none());
         Ok(())
     }
 }
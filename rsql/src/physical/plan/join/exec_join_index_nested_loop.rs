@@ -0,0 +1,368 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use arrow_schema::SchemaRef;
+use datafusion_common::JoinType;
+use datafusion_physical_plan::joins::utils::build_join_schema;
+
+use crate::btree::bt_cursor::BtCursor;
+use crate::model::affinity::Affinity;
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::model::index_key::KeyInfo;
+use crate::physical::expression::binary::comparison_affinities;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::scan::ExecScan;
+
+/// How the right table of an index nested loop join is searched for the key of
+/// a left record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoinLookup {
+    /// the right key is the INTEGER PRIMARY KEY, the rowid of the table B-Tree
+    Rowid,
+    /// the right key is the first column of an index, whose entries give the rowids
+    Index {
+        name: String,
+        root_page_number: u32,
+        key_info: KeyInfo,
+    },
+}
+
+/// Index nested loop join: for each left (outer) record, the right table is searched
+/// with a B-Tree seek instead of being scanned, e.g. for
+/// `orders JOIN customers ON orders.cust_id = customers.id` each order reads
+/// O(depth) pages of customers. This is how SQLite runs most joins.
+///
+/// The right key is compared like `left = right` in SQLite: the left key value gets
+/// the comparison affinity, the collation of the index must be the one of the comparison.
+///
+/// Only the join types returning right records with a match are supported:
+/// Inner, Left, LeftSemi and LeftAnti.
+#[derive(Debug)]
+pub struct ExecJoinIndexNestedLoop {
+    pub left: Box<dyn Exec>,
+    /// right table, only read by seeks
+    pub right: ExecScan,
+    /// left key and right key, a column of the right table
+    pub on: (Arc<dyn PhysicalExpr>, Arc<dyn PhysicalExpr>),
+    pub lookup: JoinLookup,
+    /// filter on the left values followed by the right values of the records found
+    pub filter: Option<Arc<dyn PhysicalExpr>>,
+    pub join_type: JoinType,
+    schema: SchemaRef,
+    // affinity applied to the left key value before searching
+    key_affinity: Option<Affinity>,
+    // cursor of an index lookup, set when open
+    index_cursor: Option<BtCursor>,
+    pending: VecDeque<DataRecord>,
+    open: bool,
+}
+
+impl ExecJoinIndexNestedLoop {
+    pub fn try_new(
+        left: Box<dyn Exec>,
+        right: ExecScan,
+        on: (Arc<dyn PhysicalExpr>, Arc<dyn PhysicalExpr>),
+        lookup: JoinLookup,
+        filter: Option<Arc<dyn PhysicalExpr>>,
+        join_type: &JoinType,
+    ) -> Result<Self> {
+        match join_type {
+            JoinType::Inner | JoinType::Left | JoinType::LeftSemi | JoinType::LeftAnti => {}
+            _ => bail!("unsupported join type {join_type} in ExecJoinIndexNestedLoop"),
+        }
+        let (schema, _) = build_join_schema(&left.schema(), &right.schema(), join_type);
+        let (key_affinity, _) = comparison_affinities(on.0.as_ref(), on.1.as_ref());
+        Ok(ExecJoinIndexNestedLoop {
+            left,
+            right,
+            on,
+            lookup,
+            filter,
+            join_type: *join_type,
+            schema: Arc::new(schema),
+            key_affinity,
+            index_cursor: None,
+            pending: VecDeque::new(),
+            open: false,
+        })
+    }
+
+    // Right records whose key is equal to the key of the left record.
+    fn search(&mut self, left: &DataRecord) -> Result<Vec<DataRecord>> {
+        let value = self.on.0.evaluate(left);
+        // NULL is equal to nothing
+        if value.is_null() {
+            return Ok(vec![]);
+        }
+        let value = match self.key_affinity {
+            Some(affinity) => value.apply_affinity(affinity),
+            None => value,
+        };
+        let rowids = match &self.lookup {
+            JoinLookup::Rowid => rowid_value(&value).into_iter().collect(),
+            JoinLookup::Index { key_info, .. } => {
+                let Some(cursor) = self.index_cursor.as_mut() else {
+                    bail!("index nested loop join is not open")
+                };
+                let key = [value];
+                let mut rowids = vec![];
+                cursor.index_move_to(&key, key_info)?;
+                while !cursor.is_eof() {
+                    let entry = cursor.index_key()?;
+                    if key_info.compare(&key, &entry.values).is_ne() {
+                        break;
+                    }
+                    rowids.extend(entry.values.last().and_then(ColumnValue::as_i64));
+                    match cursor.move_to_next() {
+                        Err(_) if cursor.is_eof() => break,
+                        result => result?,
+                    }
+                }
+                rowids
+            }
+        };
+        let mut records = vec![];
+        for rowid in rowids {
+            records.extend(self.right.seek_rowid(rowid)?);
+        }
+        Ok(records)
+    }
+
+    fn probe(&mut self, left: DataRecord) -> Result<()> {
+        let mut matched = false;
+        for right in self.search(&left)? {
+            let joined = join_values(&left.values, &right.values);
+            if let Some(filter) = &self.filter {
+                if filter.evaluate(&joined).is_true() != Some(true) {
+                    continue;
+                }
+            }
+            matched = true;
+            if matches!(self.join_type, JoinType::Inner | JoinType::Left) {
+                self.pending.push_back(joined);
+            }
+        }
+        match (self.join_type, matched) {
+            (JoinType::Left, false) => {
+                let nulls = vec![ColumnValue::Null; self.right.schema().fields().len()];
+                self.pending.push_back(join_values(&left.values, &nulls));
+            }
+            (JoinType::LeftSemi, true) | (JoinType::LeftAnti, false) => {
+                self.pending.push_back(left)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+// The rowid equal to a value: an integer, or a real with an integer value like 2.0.
+fn rowid_value(value: &ColumnValue) -> Option<i64> {
+    match (value.as_i64(), value.as_f64()) {
+        (Some(int), _) => Some(int),
+        (None, Some(real))
+            if real.fract() == 0.0 && real >= i64::MIN as f64 && real < i64::MAX as f64 =>
+        {
+            Some(real as i64)
+        }
+        _ => None,
+    }
+}
+
+fn join_values(left: &[ColumnValue], right: &[ColumnValue]) -> DataRecord {
+    DataRecord {
+        values: left.iter().chain(right).cloned().collect(),
+        rowid: None,
+    }
+}
+
+impl Exec for ExecJoinIndexNestedLoop {
+    fn open(&mut self) -> Result<()> {
+        self.left.open()?;
+        if let JoinLookup::Index {
+            root_page_number, ..
+        } = &self.lookup
+        {
+            self.index_cursor = Some(BtCursor::new(
+                self.right.database.clone(),
+                *root_page_number,
+            )?);
+        }
+        self.pending.clear();
+        self.open = true;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        if !self.open {
+            bail!("index nested loop join is not open")
+        }
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Ok(Some(record));
+            }
+            match self.left.next()? {
+                Some(left) => self.probe(left)?,
+                None => return Ok(None),
+            }
+        }
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        self.index_cursor = None;
+        self.pending.clear();
+        self.left.close()?;
+        self.right.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::model::collation::Collation;
+    use crate::model::database::Database;
+    use crate::model::index_key::SortOrder;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+    use crate::physical::plan::scan::ExecMemTable;
+
+    // orders.db: customers(id integer primary key, name text, email text collate nocase)
+    // with ids 3, 6, ..., 6000 and idx_customers_email on email
+    fn customers() -> ExecScan {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/orders.db");
+        let database = Database::new(path.to_str().unwrap()).unwrap();
+        ExecScan::try_new(
+            "customers".to_owned(),
+            Some(vec![0, 2]),
+            Rc::new(RefCell::new(database)),
+        )
+        .unwrap()
+    }
+
+    fn keys(values: Vec<ColumnValue>) -> Box<dyn Exec> {
+        let schema = Schema::new(vec![Field::new("key", DataType::Utf8, true)]);
+        let records: Vec<DataRecord> = values
+            .into_iter()
+            .map(|value| DataRecord {
+                values: vec![value],
+                rowid: None,
+            })
+            .collect();
+        Box::new(ExecMemTable::new(&records, Arc::new(schema)))
+    }
+
+    fn collect(mut join: ExecJoinIndexNestedLoop) -> Vec<Vec<ColumnValue>> {
+        join.open().unwrap();
+        let mut rows = vec![];
+        while let Some(record) = join.next().unwrap() {
+            rows.push(record.values);
+        }
+        join.close().unwrap();
+        rows
+    }
+
+    fn text(text: &str) -> ColumnValue {
+        ColumnValue::Text(text.to_owned())
+    }
+
+    #[test]
+    fn test_index_nested_loop_rowid() {
+        let right = customers();
+        let right_key = Arc::new(PhysicalColByIndex::new(0));
+        let join = ExecJoinIndexNestedLoop::try_new(
+            keys(vec![
+                ColumnValue::int64(6),
+                ColumnValue::real(9.0),
+                ColumnValue::int64(7),
+                ColumnValue::Null,
+            ]),
+            right,
+            (Arc::new(PhysicalColByIndex::new(0)), right_key),
+            JoinLookup::Rowid,
+            None,
+            &JoinType::Left,
+        )
+        .unwrap();
+        assert_eq!(
+            collect(join),
+            vec![
+                vec![
+                    ColumnValue::int64(6),
+                    ColumnValue::int64(6),
+                    text("c2@example.com")
+                ],
+                vec![
+                    ColumnValue::real(9.0),
+                    ColumnValue::int64(9),
+                    text("c3@example.com")
+                ],
+                vec![ColumnValue::int64(7), ColumnValue::Null, ColumnValue::Null],
+                vec![ColumnValue::Null, ColumnValue::Null, ColumnValue::Null],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_index_nested_loop_index() {
+        let lookup = JoinLookup::Index {
+            name: "idx_customers_email".to_owned(),
+            root_page_number: 3,
+            key_info: KeyInfo::new(vec![SortOrder::Asc], vec![Collation::NoCase]),
+        };
+        let join = ExecJoinIndexNestedLoop::try_new(
+            keys(vec![
+                text("C7@EXAMPLE.COM"),
+                text("nobody"),
+                text("c1499@example.com"),
+            ]),
+            customers(),
+            (
+                Arc::new(PhysicalColByIndex::new(0)),
+                Arc::new(PhysicalColByIndex::new(2)),
+            ),
+            lookup.clone(),
+            None,
+            &JoinType::Inner,
+        )
+        .unwrap();
+        let ids: Vec<ColumnValue> = collect(join)
+            .into_iter()
+            .map(|row| row[1].clone())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                ColumnValue::int64(21),
+                ColumnValue::int64(4521),
+                ColumnValue::int64(4497)
+            ]
+        );
+
+        let anti = ExecJoinIndexNestedLoop::try_new(
+            keys(vec![text("C7@EXAMPLE.COM"), text("nobody")]),
+            customers(),
+            (
+                Arc::new(PhysicalColByIndex::new(0)),
+                Arc::new(PhysicalColByIndex::new(2)),
+            ),
+            lookup,
+            None,
+            &JoinType::LeftAnti,
+        )
+        .unwrap();
+        assert_eq!(collect(anti), vec![vec![text("nobody")]]);
+    }
+}
@@ -1,43 +1,246 @@
+use std::collections::VecDeque;
 use std::sync::Arc;
 
-use anyhow::{Ok, Result, bail};
+use anyhow::{bail, Result};
 use arrow_schema::SchemaRef;
 use datafusion_common::JoinType;
-use datafusion_physical_plan::joins::utils::{build_join_schema, JoinOn};
-use datafusion_physical_plan::expressions::Column;
+use datafusion_physical_plan::joins::utils::build_join_schema;
 
+use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
 use crate::physical::expression::physical_expr::PhysicalExpr;
 use crate::physical::plan::exec::Exec;
 
+/// Number of records of the right input kept in memory by a nested loop join.
+pub const DEFAULT_JOIN_BLOCK_SIZE: usize = 1024;
+
 /// Nested loop join physical plan.
-/// Note that NestedLoop does not have on join condition as HashJoin.
+/// Note that NestedLoop does not have on join condition as HashJoin: any filter
+/// on the left values followed by the right values is evaluated on every pair of records,
+/// e.g. `a.ts BETWEEN b.start AND b.end`. Without filter, it is a cross join.
+///
+/// Block nested loop: the right (inner) input is read in blocks of block_size records,
+/// the left (outer) input is scanned again for each block. So the left input is
+/// opened once per block and the right one is read once.
+///
+/// Left records not matched (Left, Full, LeftAnti) or matched (LeftSemi) are returned
+/// during the scan of the last block, right records not matched (Right, Full) after
+/// the scan of their block.
 #[derive(Debug)]
 pub struct ExecJoinNestedLoop {
-    /// left (build) side which gets hashed
-    pub left: Arc<dyn Exec>,
-    /// right (probe) side which are filtered by hash table
-    pub right: Arc<dyn Exec>,
+    /// left (outer) side scanned for each block of the right side
+    pub left: Box<dyn Exec>,
+    /// right (inner) side read in blocks
+    pub right: Box<dyn Exec>,
+    /// join condition, None for a cross join
+    pub filter: Option<Arc<dyn PhysicalExpr>>,
     /// the type of join: OUTER, INNER, etc.
     pub join_type: JoinType,
     /// Schema once the join is applied
     schema: SchemaRef,
+    block_size: usize,
+    state: Option<State>,
 }
 
-impl ExecJoinNestedLoop {
+#[derive(Debug)]
+struct State {
+    // records of the right input in the current block and whether they matched
+    block: Vec<DataRecord>,
+    block_matched: Vec<bool>,
+    // first right record of the next block, read to know if the block is the last one
+    next_right: Option<DataRecord>,
+    last_block: bool,
+    // whether the left input is scanned for the current block
+    scanning: bool,
+    // position of the next left record in the scan, and whether each one matched
+    left_position: usize,
+    left_matched: Vec<bool>,
+    // joined records not returned yet
+    pending: VecDeque<DataRecord>,
+    done: bool,
+}
 
+impl ExecJoinNestedLoop {
     pub fn try_new(
-        left: Arc<dyn Exec>, 
-        right: Arc<dyn Exec>,
+        left: Box<dyn Exec>,
+        right: Box<dyn Exec>,
+        filter: Option<Arc<dyn PhysicalExpr>>,
         join_type: &JoinType, // using reference as we read only
     ) -> Result<Self> {
-        todo!()
+        match join_type {
+            JoinType::Inner
+            | JoinType::Left
+            | JoinType::Right
+            | JoinType::Full
+            | JoinType::LeftSemi
+            | JoinType::LeftAnti => {}
+            _ => bail!("unsupported join type {join_type} in ExecJoinNestedLoop"),
+        }
+        let (schema, _) = build_join_schema(&left.schema(), &right.schema(), join_type);
+        Ok(ExecJoinNestedLoop {
+            left,
+            right,
+            filter,
+            join_type: *join_type,
+            schema: Arc::new(schema),
+            block_size: DEFAULT_JOIN_BLOCK_SIZE,
+            state: None,
+        })
+    }
+
+    /// Sets the number of right records kept in memory.
+    pub fn with_block_size(mut self, block_size: usize) -> Self {
+        self.block_size = block_size.max(1);
+        self
+    }
+
+    // Reads the next block of the right input and opens the scan of the left input.
+    // Returns false when the right input is exhausted, the first block can be empty.
+    fn next_block(&mut self, state: &mut State, first: bool) -> Result<bool> {
+        state.block.clear();
+        if let Some(record) = state.next_right.take() {
+            state.block.push(record);
+        }
+        while state.block.len() < self.block_size {
+            match self.right.next()? {
+                Some(record) => state.block.push(record),
+                None => break,
+            }
+        }
+        if state.block.is_empty() && !first {
+            return Ok(false);
+        }
+        state.next_right = self.right.next()?;
+        state.last_block = state.next_right.is_none();
+        state.block_matched = vec![false; state.block.len()];
+
+        if !first {
+            self.left.close()?;
+        }
+        self.left.open()?;
+        state.scanning = true;
+        state.left_position = 0;
+        Ok(true)
+    }
+
+    // Joins a left record with the records of the block.
+    fn join_left(&self, state: &mut State, left: DataRecord) {
+        let position = state.left_position;
+        state.left_position += 1;
+        if state.left_matched.len() <= position {
+            state.left_matched.push(false);
+        }
+        let emit_pairs = matches!(
+            self.join_type,
+            JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full
+        );
+        for (index, right) in state.block.iter().enumerate() {
+            let joined = join_values(&left.values, &right.values);
+            if let Some(filter) = &self.filter {
+                if filter.evaluate(&joined).is_true() != Some(true) {
+                    continue;
+                }
+            }
+            state.left_matched[position] = true;
+            state.block_matched[index] = true;
+            if emit_pairs {
+                state.pending.push_back(joined);
+            }
+        }
+
+        if !state.last_block {
+            return;
+        }
+        match (self.join_type, state.left_matched[position]) {
+            (JoinType::Left | JoinType::Full, false) => {
+                let nulls = vec![ColumnValue::Null; self.right.schema().fields().len()];
+                state.pending.push_back(join_values(&left.values, &nulls));
+            }
+            (JoinType::LeftSemi, true) | (JoinType::LeftAnti, false) => {
+                state.pending.push_back(left)
+            }
+            _ => {}
+        }
+    }
+
+    // Right records of the block not matched by any left record.
+    fn finish_block(&self, state: &mut State) {
+        if !matches!(self.join_type, JoinType::Right | JoinType::Full) {
+            return;
+        }
+        let nulls = vec![ColumnValue::Null; self.left.schema().fields().len()];
+        for (right, &matched) in state.block.iter().zip(&state.block_matched) {
+            if !matched {
+                state.pending.push_back(join_values(&nulls, &right.values));
+            }
+        }
+    }
+}
+
+fn join_values(left: &[ColumnValue], right: &[ColumnValue]) -> DataRecord {
+    DataRecord {
+        values: left.iter().chain(right).cloned().collect(),
+        rowid: None,
     }
 }
 
 impl Exec for ExecJoinNestedLoop {
-    fn execute(&mut self) -> &[DataRecord] {
-        todo!()
+    fn open(&mut self) -> Result<()> {
+        self.right.open()?;
+        let mut state = State {
+            block: vec![],
+            block_matched: vec![],
+            next_right: None,
+            last_block: false,
+            scanning: false,
+            left_position: 0,
+            left_matched: vec![],
+            pending: VecDeque::new(),
+            done: false,
+        };
+        self.next_block(&mut state, true)?;
+        self.state = Some(state);
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        // the state is taken out of self to call methods borrowing self
+        let Some(mut state) = self.state.take() else {
+            bail!("nested loop join is not open")
+        };
+        let result = loop {
+            if let Some(record) = state.pending.pop_front() {
+                break Ok(Some(record));
+            }
+            if state.done {
+                break Ok(None);
+            }
+            if state.scanning {
+                match self.left.next() {
+                    Ok(Some(left)) => self.join_left(&mut state, left),
+                    Ok(None) => {
+                        state.scanning = false;
+                        self.finish_block(&mut state);
+                        state.done = state.last_block;
+                    }
+                    Err(err) => break Err(err),
+                }
+            } else {
+                match self.next_block(&mut state, false) {
+                    Ok(true) => {}
+                    Ok(false) => state.done = true,
+                    Err(err) => break Err(err),
+                }
+            }
+        };
+        self.state = Some(state);
+        result
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.state = None;
+        self.left.close()?;
+        self.right.close()
     }
 
     fn schema(&self) -> SchemaRef {
@@ -47,14 +250,149 @@ impl Exec for ExecJoinNestedLoop {
 
 #[cfg(test)]
 mod tests {
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion_expr::Operator;
+
     use super::*;
+    use crate::physical::expression::between::PhysicalBetween;
+    use crate::physical::expression::binary::PhysicalBinary;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+    use crate::physical::plan::scan::ExecMemTable;
+
+    fn table(name: &str, values: &[i64]) -> Box<dyn Exec> {
+        let schema = Schema::new(vec![Field::new(name, DataType::Int64, true)]);
+        let records: Vec<DataRecord> = values
+            .iter()
+            .map(|&value| DataRecord {
+                values: vec![ColumnValue::int64(value)],
+                rowid: None,
+            })
+            .collect();
+        Box::new(ExecMemTable::new(&records, Arc::new(schema)))
+    }
+
+    fn col(col_index: usize) -> Arc<dyn PhysicalExpr> {
+        Arc::new(PhysicalColByIndex::new(col_index))
+    }
+
+    // left a in [1, 3, 5] and right b in [2, 3, 4, 0] joined on a < b
+    fn join(join_type: JoinType, block_size: usize) -> Vec<Vec<i64>> {
+        let filter = PhysicalBinary::try_new(col(0), Operator::Lt, col(1)).unwrap();
+        let mut join = ExecJoinNestedLoop::try_new(
+            table("a", &[1, 3, 5]),
+            table("b", &[2, 3, 4, 0]),
+            Some(Arc::new(filter)),
+            &join_type,
+        )
+        .unwrap()
+        .with_block_size(block_size);
+        collect(&mut join)
+    }
 
-    fn build_table() -> Arc<dyn Exec> {
-        todo!()
+    fn collect(join: &mut ExecJoinNestedLoop) -> Vec<Vec<i64>> {
+        join.open().unwrap();
+        let mut rows = vec![];
+        while let Some(record) = join.next().unwrap() {
+            // -1 for NULL
+            rows.push(
+                record
+                    .values
+                    .iter()
+                    .map(|value| value.as_i64().unwrap_or(-1))
+                    .collect(),
+            );
+        }
+        join.close().unwrap();
+        rows.sort();
+        rows
     }
 
     #[test]
-    fn test_join_on_1_column_pair() {
+    fn test_nested_loop_inner_any_block_size() {
+        let expected = vec![vec![1, 2], vec![1, 3], vec![1, 4], vec![3, 4]];
+        for block_size in [1, 2, 3, 100] {
+            assert_eq!(join(JoinType::Inner, block_size), expected);
+        }
+    }
 
+    #[test]
+    fn test_nested_loop_outer() {
+        for block_size in [1, 3, 100] {
+            assert_eq!(
+                join(JoinType::Left, block_size),
+                vec![vec![1, 2], vec![1, 3], vec![1, 4], vec![3, 4], vec![5, -1]]
+            );
+            assert_eq!(
+                join(JoinType::Full, block_size),
+                vec![
+                    vec![-1, 0],
+                    vec![1, 2],
+                    vec![1, 3],
+                    vec![1, 4],
+                    vec![3, 4],
+                    vec![5, -1]
+                ]
+            );
+            assert_eq!(join(JoinType::Right, block_size).len(), 5);
+            assert_eq!(join(JoinType::LeftSemi, block_size), vec![vec![1], vec![3]]);
+            assert_eq!(join(JoinType::LeftAnti, block_size), vec![vec![5]]);
+        }
+    }
+
+    #[test]
+    fn test_nested_loop_cross_and_empty() {
+        let mut cross = ExecJoinNestedLoop::try_new(
+            table("a", &[1, 2]),
+            table("b", &[3, 4]),
+            None,
+            &JoinType::Inner,
+        )
+        .unwrap();
+        assert_eq!(
+            collect(&mut cross),
+            vec![vec![1, 3], vec![1, 4], vec![2, 3], vec![2, 4]]
+        );
+        // an empty right input still returns the left records of a left join
+        let mut left = ExecJoinNestedLoop::try_new(
+            table("a", &[1, 2]),
+            table("b", &[]),
+            None,
+            &JoinType::Left,
+        )
+        .unwrap();
+        assert_eq!(collect(&mut left), vec![vec![1, -1], vec![2, -1]]);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_nested_loop_theta_between() {
+        // ts BETWEEN start AND end, right columns are start and end
+        let schema = Schema::new(vec![
+            Field::new("start", DataType::Int64, false),
+            Field::new("end", DataType::Int64, false),
+        ]);
+        let ranges: Vec<DataRecord> = [(0, 10), (5, 15)]
+            .into_iter()
+            .map(|(start, end)| DataRecord {
+                values: vec![ColumnValue::int64(start), ColumnValue::int64(end)],
+                rowid: None,
+            })
+            .collect();
+        let between = PhysicalBetween {
+            expr: col(0),
+            low: col(1),
+            high: col(2),
+            negated: false,
+        };
+        let mut join = ExecJoinNestedLoop::try_new(
+            table("ts", &[3, 7, 20]),
+            Box::new(ExecMemTable::new(&ranges, Arc::new(schema))),
+            Some(Arc::new(between)),
+            &JoinType::Inner,
+        )
+        .unwrap();
+        assert_eq!(
+            collect(&mut join),
+            vec![vec![3, 0, 10], vec![7, 0, 10], vec![7, 5, 15]]
+        );
+    }
+}
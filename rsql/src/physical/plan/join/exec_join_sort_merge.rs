@@ -0,0 +1,518 @@
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use anyhow::{bail, Result};
+use arrow_schema::SchemaRef;
+use datafusion_common::JoinType;
+use datafusion_physical_plan::joins::utils::build_join_schema;
+
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::binary::{comparison_affinities, comparison_collation};
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::join::JoinOn;
+use crate::physical::plan::sort::{ExecSort, SortExpr};
+
+/// Whether the inputs of a sort-merge join are already in ascending order of their
+/// join keys, e.g. a table scan joined on its INTEGER PRIMARY KEY. Unsorted inputs
+/// are sorted by ExecSort.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SortedInputs {
+    pub left: bool,
+    pub right: bool,
+}
+
+/// Sort-merge join: both inputs are read in ascending order of their join keys and
+/// merged like two sorted lists, no hash table is built.
+///
+/// Records with equal keys on both sides form two groups joined pair by pair with the
+/// filter, so duplicate keys on both sides are supported. A record whose key has a NULL
+/// matches nothing.
+///
+/// Keys are compared like `left = right` in SQLite: with the comparison affinities and
+/// collation of each pair of expressions. Unsorted inputs are sorted the same way.
+#[derive(Debug)]
+pub struct ExecJoinSortMerge {
+    pub left: Box<dyn Exec>,
+    pub right: Box<dyn Exec>,
+    /// equijoin keys with the affinity and collation of the comparison
+    left_keys: Vec<Arc<MergeKey>>,
+    right_keys: Vec<Arc<MergeKey>>,
+    /// filter on the left values followed by the right values of records with equal keys
+    pub filter: Option<Arc<dyn PhysicalExpr>>,
+    pub join_type: JoinType,
+    schema: SchemaRef,
+    // next record of each input, None when the input is exhausted
+    left_head: Option<KeyedRecord>,
+    right_head: Option<KeyedRecord>,
+    pending: VecDeque<DataRecord>,
+    open: bool,
+}
+
+/// A join key expression of one side whose value is converted with the affinity of the
+/// comparison, and compared with its collation.
+#[derive(Debug)]
+struct MergeKey {
+    expr: Arc<dyn PhysicalExpr>,
+    affinity: Option<Affinity>,
+    collation: Collation,
+}
+
+impl PhysicalExpr for MergeKey {
+    fn evaluate(&self, record: &DataRecord) -> ColumnValue {
+        let value = self.expr.evaluate(record);
+        match self.affinity {
+            Some(affinity) if !value.is_null() => value.apply_affinity(affinity),
+            _ => value,
+        }
+    }
+
+    fn collation(&self) -> Option<Collation> {
+        Some(self.collation)
+    }
+}
+
+#[derive(Debug)]
+struct KeyedRecord {
+    key: Vec<ColumnValue>,
+    record: DataRecord,
+}
+
+impl KeyedRecord {
+    fn has_null(&self) -> bool {
+        self.key.iter().any(ColumnValue::is_null)
+    }
+}
+
+impl ExecJoinSortMerge {
+    pub fn try_new(
+        left: Box<dyn Exec>,
+        right: Box<dyn Exec>,
+        on: JoinOn,
+        filter: Option<Arc<dyn PhysicalExpr>>,
+        join_type: &JoinType,
+        sorted: SortedInputs,
+    ) -> Result<Self> {
+        match join_type {
+            JoinType::Inner
+            | JoinType::Left
+            | JoinType::Right
+            | JoinType::Full
+            | JoinType::LeftSemi
+            | JoinType::LeftAnti => {}
+            _ => bail!("unsupported join type {join_type} in ExecJoinSortMerge"),
+        }
+        if on.is_empty() {
+            bail!("sort-merge join without equality")
+        }
+        let (schema, _) = build_join_schema(&left.schema(), &right.schema(), join_type);
+
+        let mut left_keys = vec![];
+        let mut right_keys = vec![];
+        for (left_expr, right_expr) in on {
+            let (left_affinity, right_affinity) =
+                comparison_affinities(left_expr.as_ref(), right_expr.as_ref());
+            let collation = comparison_collation(left_expr.as_ref(), right_expr.as_ref());
+            left_keys.push(Arc::new(MergeKey {
+                expr: left_expr,
+                affinity: left_affinity,
+                collation,
+            }));
+            right_keys.push(Arc::new(MergeKey {
+                expr: right_expr,
+                affinity: right_affinity,
+                collation,
+            }));
+        }
+        let left = if sorted.left {
+            left
+        } else {
+            sort_input(left, &left_keys)
+        };
+        let right = if sorted.right {
+            right
+        } else {
+            sort_input(right, &right_keys)
+        };
+
+        Ok(ExecJoinSortMerge {
+            left,
+            right,
+            left_keys,
+            right_keys,
+            filter,
+            join_type: *join_type,
+            schema: Arc::new(schema),
+            left_head: None,
+            right_head: None,
+            pending: VecDeque::new(),
+            open: false,
+        })
+    }
+
+    fn read_left(&mut self) -> Result<Option<KeyedRecord>> {
+        read_keyed(self.left.as_mut(), &self.left_keys)
+    }
+
+    fn read_right(&mut self) -> Result<Option<KeyedRecord>> {
+        read_keyed(self.right.as_mut(), &self.right_keys)
+    }
+
+    fn compare(&self, left: &[ColumnValue], right: &[ColumnValue]) -> Ordering {
+        self.left_keys
+            .iter()
+            .zip(left.iter().zip(right))
+            .map(
+                |(key, (left, right))| match (left.is_null(), right.is_null()) {
+                    // NULLs are first in the sorted inputs
+                    (true, true) => Ordering::Equal,
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => left.compare(right, &key.collation),
+                },
+            )
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
+
+    // Moves the merge forward: the smallest head, or both groups of equal keys.
+    fn step(&mut self) -> Result<()> {
+        let ordering = match (&self.left_head, &self.right_head) {
+            (None, None) => return Ok(()),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(left), Some(right)) => match self.compare(&left.key, &right.key) {
+                // NULL keys compare equal in the sort order but do not match
+                Ordering::Equal if left.has_null() => Ordering::Less,
+                ordering => ordering,
+            },
+        };
+        match ordering {
+            Ordering::Less => {
+                if let Some(left) = self.left_head.take() {
+                    self.left_unmatched(left.record);
+                }
+                self.left_head = self.read_left()?;
+            }
+            Ordering::Greater => {
+                if let Some(right) = self.right_head.take() {
+                    self.right_unmatched(right.record);
+                }
+                self.right_head = self.read_right()?;
+            }
+            Ordering::Equal => {
+                let left_group = self.left_group()?;
+                let right_group = self.right_group()?;
+                self.join_groups(left_group, right_group);
+            }
+        }
+        Ok(())
+    }
+
+    // Head of the left input and the following records with the same key.
+    fn left_group(&mut self) -> Result<Vec<KeyedRecord>> {
+        let mut group: Vec<KeyedRecord> = self.left_head.take().into_iter().collect();
+        while let Some(next) = self.read_left()? {
+            if self.compare(&group[0].key, &next.key).is_ne() {
+                self.left_head = Some(next);
+                break;
+            }
+            group.push(next);
+        }
+        Ok(group)
+    }
+
+    fn right_group(&mut self) -> Result<Vec<KeyedRecord>> {
+        let mut group: Vec<KeyedRecord> = self.right_head.take().into_iter().collect();
+        while let Some(next) = self.read_right()? {
+            if self.compare(&group[0].key, &next.key).is_ne() {
+                self.right_head = Some(next);
+                break;
+            }
+            group.push(next);
+        }
+        Ok(group)
+    }
+
+    // Nested loop on two groups of records with equal keys.
+    fn join_groups(&mut self, left_group: Vec<KeyedRecord>, right_group: Vec<KeyedRecord>) {
+        let emit_pairs = matches!(
+            self.join_type,
+            JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full
+        );
+        let mut right_matched = vec![false; right_group.len()];
+        for left in left_group {
+            let mut left_matched = false;
+            for (index, right) in right_group.iter().enumerate() {
+                let joined = join_values(&left.record.values, &right.record.values);
+                if let Some(filter) = &self.filter {
+                    if filter.evaluate(&joined).is_true() != Some(true) {
+                        continue;
+                    }
+                }
+                left_matched = true;
+                right_matched[index] = true;
+                if emit_pairs {
+                    self.pending.push_back(joined);
+                }
+            }
+            if left_matched {
+                if self.join_type == JoinType::LeftSemi {
+                    self.pending.push_back(left.record);
+                }
+            } else {
+                self.left_unmatched(left.record);
+            }
+        }
+        for (right, matched) in right_group.into_iter().zip(right_matched) {
+            if !matched {
+                self.right_unmatched(right.record);
+            }
+        }
+    }
+
+    fn left_unmatched(&mut self, left: DataRecord) {
+        match self.join_type {
+            JoinType::Left | JoinType::Full => {
+                let nulls = vec![ColumnValue::Null; self.right.schema().fields().len()];
+                self.pending.push_back(join_values(&left.values, &nulls));
+            }
+            JoinType::LeftAnti => self.pending.push_back(left),
+            _ => {}
+        }
+    }
+
+    fn right_unmatched(&mut self, right: DataRecord) {
+        if matches!(self.join_type, JoinType::Right | JoinType::Full) {
+            let nulls = vec![ColumnValue::Null; self.left.schema().fields().len()];
+            self.pending.push_back(join_values(&nulls, &right.values));
+        }
+    }
+}
+
+// Sorts an input in ascending order of its keys, NULLs first.
+fn sort_input(input: Box<dyn Exec>, keys: &[Arc<MergeKey>]) -> Box<dyn Exec> {
+    let sort_exprs = keys
+        .iter()
+        .map(|key| SortExpr {
+            expr: key.clone(),
+            descending: false,
+            nulls_first: true,
+        })
+        .collect();
+    let schema = input.schema();
+    Box::new(ExecSort::new(input, sort_exprs, None, schema))
+}
+
+fn read_keyed(input: &mut dyn Exec, keys: &[Arc<MergeKey>]) -> Result<Option<KeyedRecord>> {
+    Ok(input.next()?.map(|record| KeyedRecord {
+        key: keys.iter().map(|key| key.evaluate(&record)).collect(),
+        record,
+    }))
+}
+
+fn join_values(left: &[ColumnValue], right: &[ColumnValue]) -> DataRecord {
+    DataRecord {
+        values: left.iter().chain(right).cloned().collect(),
+        rowid: None,
+    }
+}
+
+impl Exec for ExecJoinSortMerge {
+    fn open(&mut self) -> Result<()> {
+        self.left.open()?;
+        self.right.open()?;
+        self.pending.clear();
+        self.left_head = self.read_left()?;
+        self.right_head = self.read_right()?;
+        self.open = true;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        if !self.open {
+            bail!("sort-merge join is not open")
+        }
+        loop {
+            if let Some(record) = self.pending.pop_front() {
+                return Ok(Some(record));
+            }
+            if self.left_head.is_none() && self.right_head.is_none() {
+                return Ok(None);
+            }
+            self.step()?;
+        }
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.open = false;
+        self.left_head = None;
+        self.right_head = None;
+        self.pending.clear();
+        self.left.close()?;
+        self.right.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{DataType, Field, Schema};
+    use datafusion_expr::Operator;
+
+    use super::*;
+    use crate::physical::expression::binary::PhysicalBinary;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+    use crate::physical::plan::scan::ExecMemTable;
+
+    // records (key, id), NULL keys given as None
+    fn table(rows: &[(Option<i64>, i64)]) -> Box<dyn Exec> {
+        let schema = Schema::new(vec![
+            Field::new("key", DataType::Int64, true),
+            Field::new("id", DataType::Int64, false),
+        ]);
+        let records: Vec<DataRecord> = rows
+            .iter()
+            .map(|&(key, id)| DataRecord {
+                values: vec![
+                    key.map_or(ColumnValue::Null, ColumnValue::int64),
+                    ColumnValue::int64(id),
+                ],
+                rowid: None,
+            })
+            .collect();
+        Box::new(ExecMemTable::new(&records, Arc::new(schema)))
+    }
+
+    fn col(col_index: usize) -> Arc<dyn PhysicalExpr> {
+        Arc::new(PhysicalColByIndex::new(col_index))
+    }
+
+    fn on_key() -> JoinOn {
+        vec![(col(0), col(0))]
+    }
+
+    // ids of the left and right records of each joined record, -1 for NULL
+    fn collect(mut join: ExecJoinSortMerge) -> Vec<Vec<i64>> {
+        let width = join.schema().fields().len();
+        join.open().unwrap();
+        let mut rows = vec![];
+        while let Some(record) = join.next().unwrap() {
+            rows.push(
+                (1..width)
+                    .step_by(2)
+                    .map(|index| record.values[index].as_i64().unwrap_or(-1))
+                    .collect(),
+            );
+        }
+        join.close().unwrap();
+        rows
+    }
+
+    fn sorted_join(join_type: JoinType) -> Vec<Vec<i64>> {
+        let join = ExecJoinSortMerge::try_new(
+            table(&[
+                (None, 10),
+                (Some(1), 11),
+                (Some(2), 12),
+                (Some(2), 13),
+                (Some(4), 14),
+            ]),
+            table(&[
+                (None, 20),
+                (Some(2), 21),
+                (Some(2), 22),
+                (Some(3), 23),
+                (Some(4), 24),
+            ]),
+            on_key(),
+            None,
+            &join_type,
+            SortedInputs {
+                left: true,
+                right: true,
+            },
+        )
+        .unwrap();
+        collect(join)
+    }
+
+    #[test]
+    fn test_sort_merge_duplicate_keys() {
+        assert_eq!(
+            sorted_join(JoinType::Inner),
+            vec![
+                vec![12, 21],
+                vec![12, 22],
+                vec![13, 21],
+                vec![13, 22],
+                vec![14, 24]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_merge_outer() {
+        assert_eq!(
+            sorted_join(JoinType::Full),
+            vec![
+                vec![10, -1],
+                vec![-1, 20],
+                vec![11, -1],
+                vec![12, 21],
+                vec![12, 22],
+                vec![13, 21],
+                vec![13, 22],
+                vec![-1, 23],
+                vec![14, 24]
+            ]
+        );
+        assert_eq!(
+            sorted_join(JoinType::Left),
+            vec![
+                vec![10, -1],
+                vec![11, -1],
+                vec![12, 21],
+                vec![12, 22],
+                vec![13, 21],
+                vec![13, 22],
+                vec![14, 24]
+            ]
+        );
+        assert_eq!(sorted_join(JoinType::Right).len(), 7);
+        assert_eq!(
+            sorted_join(JoinType::LeftSemi),
+            vec![vec![12], vec![13], vec![14]]
+        );
+        assert_eq!(sorted_join(JoinType::LeftAnti), vec![vec![10], vec![11]]);
+    }
+
+    #[test]
+    fn test_sort_merge_sorts_inputs() {
+        // the left input is not sorted, the filter is evaluated on groups of equal keys
+        let filter = PhysicalBinary::try_new(col(1), Operator::Lt, col(3)).unwrap();
+        let join = ExecJoinSortMerge::try_new(
+            table(&[(Some(4), 34), (Some(2), 32), (None, 30), (Some(2), 22)]),
+            table(&[(Some(2), 21), (Some(2), 33), (Some(4), 24)]),
+            on_key(),
+            Some(Arc::new(filter)),
+            &JoinType::Left,
+            SortedInputs {
+                left: false,
+                right: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            collect(join),
+            vec![vec![30, -1], vec![32, 33], vec![22, 33], vec![34, -1]]
+        );
+    }
+}
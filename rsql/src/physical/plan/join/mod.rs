@@ -1,3 +1,17 @@
+use std::sync::Arc;
+
+use crate::physical::expression::physical_expr::PhysicalExpr;
+
 pub mod exec_join_hash;
+pub mod exec_join_index_nested_loop;
+pub mod exec_join_nested_loop;
+pub mod exec_join_sort_merge;
 
 pub use exec_join_hash::ExecJoinHash;
+pub use exec_join_index_nested_loop::{ExecJoinIndexNestedLoop, JoinLookup};
+pub use exec_join_nested_loop::ExecJoinNestedLoop;
+pub use exec_join_sort_merge::{ExecJoinSortMerge, SortedInputs};
+
+/// Equijoin keys of a join: pairs of expressions on the left and right inputs
+/// whose values must be equal.
+pub type JoinOn = Vec<(Arc<dyn PhysicalExpr>, Arc<dyn PhysicalExpr>)>;
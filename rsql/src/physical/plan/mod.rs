@@ -1,5 +1,15 @@
+pub mod aggregate;
+pub mod batch;
 pub mod exec;
-pub mod exec_dummy;
+pub mod exec_ddl;
+pub mod exec_delete;
+pub mod exec_distinct;
+pub mod exec_filter;
+pub mod exec_insert;
+pub mod exec_limit;
 pub mod exec_projection;
+pub mod exec_update;
 pub mod join;
 pub mod scan;
+pub mod sort;
+pub mod table_writer;
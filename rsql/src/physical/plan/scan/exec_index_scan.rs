@@ -0,0 +1,259 @@
+use std::cmp::Ordering;
+use std::ops::Bound;
+
+use anyhow::{bail, Result};
+use arrow_schema::SchemaRef;
+
+use crate::btree::bt_cursor::BtCursor;
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::model::index_key::KeyInfo;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::scan::ExecScan;
+
+/// Entries of an index read by an index scan: the entries starting with the values
+/// of equal, whose next field is between low and high in the order of the index keys.
+///
+/// `WHERE name = 'bob' AND age > 20` on an index of (name, age) is equal ['bob'] and
+/// low Excluded(20).
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexBounds {
+    pub equal: Vec<ColumnValue>,
+    pub low: Bound<ColumnValue>,
+    pub high: Bound<ColumnValue>,
+}
+
+impl IndexBounds {
+    // key prefix made of the equal values followed by value
+    fn key(&self, value: &ColumnValue) -> Vec<ColumnValue> {
+        let mut key = self.equal.clone();
+        key.push(value.clone());
+        key
+    }
+}
+
+/// Index range scan: seeks the first entry of the index within bounds and reads the
+/// entries in index order until the last one, fetching the record of each entry rowid
+/// from the table B-Tree. Only O(depth) pages are read per record found instead of
+/// all the pages of the table.
+///
+/// Like `ExecScan`, this is the scan of the table: the records have the columns of
+/// the projection of table. They are in index order, not in rowid order.
+#[derive(Debug)]
+pub struct ExecIndexScan {
+    /// the records are fetched with ExecScan::seek_rowid
+    pub table: ExecScan,
+    pub index_name: String,
+    pub root_page_number: u32,
+    pub key_info: KeyInfo,
+    pub bounds: IndexBounds,
+    // cursor on the next index entry, set when open
+    cursor: Option<BtCursor>,
+    // set when the cursor moved past the last entry within bounds
+    done: bool,
+}
+
+impl ExecIndexScan {
+    pub fn new(
+        table: ExecScan,
+        index_name: String,
+        root_page_number: u32,
+        key_info: KeyInfo,
+        bounds: IndexBounds,
+    ) -> Self {
+        ExecIndexScan {
+            table,
+            index_name,
+            root_page_number,
+            key_info,
+            bounds,
+            cursor: None,
+            done: false,
+        }
+    }
+
+    // Whether entry is past the last entry within bounds.
+    fn is_past_high(&self, entry: &[ColumnValue]) -> bool {
+        match &self.bounds.high {
+            Bound::Included(value) => {
+                self.key_info.compare(&self.bounds.key(value), entry) == Ordering::Less
+            }
+            Bound::Excluded(value) => {
+                self.key_info.compare(&self.bounds.key(value), entry) != Ordering::Greater
+            }
+            Bound::Unbounded => self.key_info.compare(&self.bounds.equal, entry).is_ne(),
+        }
+    }
+}
+
+// Moves the cursor to the next index entry, stopping at eof.
+fn advance(cursor: &mut BtCursor) -> Result<()> {
+    match cursor.move_to_next() {
+        Err(_) if cursor.is_eof() => Ok(()),
+        result => result,
+    }
+}
+
+impl Exec for ExecIndexScan {
+    fn open(&mut self) -> Result<()> {
+        let mut cursor = BtCursor::new(self.table.database.clone(), self.root_page_number)?;
+        match &self.bounds.low {
+            Bound::Included(value) | Bound::Excluded(value) => {
+                let key = self.bounds.key(value);
+                cursor.index_move_to(&key, &self.key_info)?;
+                if let Bound::Excluded(_) = self.bounds.low {
+                    while !cursor.is_eof()
+                        && self
+                            .key_info
+                            .compare(&key, &cursor.index_key()?.values)
+                            .is_eq()
+                    {
+                        advance(&mut cursor)?;
+                    }
+                }
+            }
+            Bound::Unbounded if self.bounds.equal.is_empty() => cursor.move_to_first()?,
+            Bound::Unbounded => cursor.index_move_to(&self.bounds.equal, &self.key_info)?,
+        }
+        self.cursor = Some(cursor);
+        self.done = false;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        let entry = match self.cursor.as_mut() {
+            Some(cursor) if !self.done && !cursor.is_eof() => {
+                let entry = cursor.index_key()?;
+                advance(cursor)?;
+                entry
+            }
+            Some(_) => return Ok(None),
+            None => bail!("scan of index {} is not open", self.index_name),
+        };
+        if self.is_past_high(&entry.values) {
+            // the following entries are past high too
+            self.done = true;
+            return Ok(None);
+        }
+        // the last field of an index entry is the rowid of the table row
+        let Some(rowid) = entry.values.last().and_then(ColumnValue::as_i64) else {
+            bail!("index {} entry without rowid", self.index_name)
+        };
+        match self.table.seek_rowid(rowid)? {
+            Some(record) => Ok(Some(record)),
+            None => bail!(
+                "index {} entry for missing rowid {rowid} of table {}",
+                self.index_name,
+                self.table.table_name
+            ),
+        }
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.cursor = None;
+        self.table.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.table.schema()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::model::collation::Collation;
+    use crate::model::database::Database;
+    use crate::model::index_key::SortOrder;
+
+    fn scan_ids(
+        db_name: &str,
+        table_name: &str,
+        root_page_number: u32,
+        key_info: KeyInfo,
+        bounds: IndexBounds,
+    ) -> Vec<ColumnValue> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/resources")
+            .join(db_name);
+        let database = Rc::new(RefCell::new(Database::new(path.to_str().unwrap()).unwrap()));
+        let table = ExecScan::try_new(table_name.to_owned(), Some(vec![0]), database).unwrap();
+        let mut scan = ExecIndexScan::new(
+            table,
+            "index".to_owned(),
+            root_page_number,
+            key_info,
+            bounds,
+        );
+        scan.open().unwrap();
+        let mut ids = vec![];
+        while let Some(record) = scan.next().unwrap() {
+            ids.push(record.values[0].clone());
+        }
+        scan.close().unwrap();
+        ids
+    }
+
+    fn text(text: &str) -> ColumnValue {
+        ColumnValue::Text(text.to_owned())
+    }
+
+    #[test]
+    fn test_index_scan_email() {
+        // orders.db: idx_customers_email on customers(email COLLATE NOCASE), root page 3
+        let key_info = KeyInfo::new(vec![SortOrder::Asc], vec![Collation::NoCase]);
+        let scan = |bounds| scan_ids("orders.db", "customers", 3, key_info.clone(), bounds);
+        let equal = scan(IndexBounds {
+            equal: vec![text("C7@EXAMPLE.COM")],
+            low: Bound::Unbounded,
+            high: Bound::Unbounded,
+        });
+        assert_eq!(equal, [21, 4521].map(ColumnValue::int64).to_vec());
+
+        let range = scan(IndexBounds {
+            equal: vec![],
+            low: Bound::Excluded(text("c998")),
+            high: Bound::Excluded(text("c999@z")),
+        });
+        assert_eq!(range, [2994, 2997].map(ColumnValue::int64).to_vec());
+        let first = scan(IndexBounds {
+            equal: vec![],
+            low: Bound::Unbounded,
+            high: Bound::Included(text("c0@example.com")),
+        });
+        assert_eq!(first, vec![ColumnValue::int64(4500)]);
+    }
+
+    #[test]
+    fn test_index_scan_descending_column() {
+        // index.db: idx_people_name_age on people(name COLLATE NOCASE, age DESC), root
+        // page 3. bob1 is 7 years old: age > 5 is high Excluded(5) in index order.
+        let key_info = KeyInfo::new(
+            vec![SortOrder::Asc, SortOrder::Desc],
+            vec![Collation::NoCase, Collation::Binary],
+        );
+        let scan = |low, high| {
+            let bounds = IndexBounds {
+                equal: vec![text("BOB1")],
+                low,
+                high,
+            };
+            scan_ids("index.db", "people", 3, key_info.clone(), bounds)
+        };
+        let age = |age| ColumnValue::int64(age);
+        assert_eq!(
+            scan(Bound::Unbounded, Bound::Excluded(age(5))),
+            vec![ColumnValue::int64(1)]
+        );
+        assert!(scan(Bound::Unbounded, Bound::Excluded(age(7))).is_empty());
+        assert_eq!(
+            scan(Bound::Included(age(7)), Bound::Unbounded),
+            vec![ColumnValue::int64(1)]
+        );
+        assert!(scan(Bound::Excluded(age(7)), Bound::Unbounded).is_empty());
+    }
+}
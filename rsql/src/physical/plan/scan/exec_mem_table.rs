@@ -1,24 +1,43 @@
+use anyhow::Result;
 use arrow_schema::SchemaRef;
 
 use crate::model::data_record::DataRecord;
 use crate::physical::plan::exec::Exec;
 
-/// An in-memory table scan for mocking data.
+/// An in-memory table scan: the rows of VALUES, the row of a SELECT without FROM,
+/// or mock data in tests.
 #[derive(Debug)]
 pub struct ExecMemTable {
     records: Vec<DataRecord>,
     schema: SchemaRef,
+    // index of the next record to return
+    position: usize,
 }
 
 impl ExecMemTable {
-    pub fn new(_records: &[DataRecord], _schema_ref: SchemaRef) -> Self {
-        todo!()
+    pub fn new(records: &[DataRecord], schema_ref: SchemaRef) -> Self {
+        ExecMemTable {
+            records: records.to_vec(),
+            schema: schema_ref,
+            position: 0,
+        }
     }
 }
 
 impl Exec for ExecMemTable {
-    fn execute(&mut self) -> &[DataRecord] {
-        &self.records
+    fn open(&mut self) -> Result<()> {
+        self.position = 0;
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        let record = self.records.get(self.position).cloned();
+        self.position += 1;
+        Ok(record)
+    }
+
+    fn close(&mut self) -> Result<()> {
+        Ok(())
     }
 
     fn schema(&self) -> arrow_schema::SchemaRef {
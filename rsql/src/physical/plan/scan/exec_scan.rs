@@ -1,54 +1,406 @@
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
 use std::rc::Rc;
+use std::sync::Arc;
 
-use crate::btree::bt_cursor::BtCursor;
+use anyhow::{anyhow, bail, Result};
+use arrow_array::RecordBatch;
+use arrow_schema::{Schema, SchemaRef};
+
+use crate::btree::bt_cursor::{BtCursor, TableScanIterator};
+use crate::model::affinity::Affinity;
+use crate::model::column_value::ColumnValue;
 use crate::model::data_record::DataRecord;
 use crate::model::database::Database;
+use crate::model::schema::{SchemaObjType, FIELD_AFFINITY};
+use crate::physical::plan::batch::records_to_batch;
 use crate::physical::plan::exec::Exec;
 
+/// Full scan of a table: walks all leaf pages of the table B-Tree rooted at
+/// table_page_number in rowid order and decodes each cell into a record.
+/// A scan limited to a range of rowids seeks its first rowid and stops after the
+/// last one, e.g. `WHERE id = 5` reads O(depth) pages. A descending scan walks the
+/// B-Tree backward, e.g. for `ORDER BY id DESC` without sorting.
+///
+/// Records are decoded one at a time as the cursor moves, pages are read
+/// through the buffer pool: scanning a large table uses constant memory.
+/// In batch mode, each batch holds the rows of one leaf page.
+///
+/// The projection may select the hidden columns `rowid`, `oid` and `_rowid_` which come
+/// after the columns of the table (see `SchemaObject::rowid_columns`), their value is
+/// the rowid of the record.
 #[derive(Debug)]
 pub struct ExecScan {
     pub table_name: String,
     pub table_page_number: u32,
+    // index of the INTEGER PRIMARY KEY column whose value is the rowid
+    pub rowid_alias: Option<usize>,
     pub database: Rc<RefCell<Database>>,
-    bt_cursor: BtCursor,
-    records: Vec<DataRecord>,
+    // indices of the table columns returned, all columns but the hidden ones if None
+    projection: Option<Vec<usize>>,
+    // number of columns of the table
+    column_count: usize,
+    // indices of the REAL columns of the table, their integer values are read as reals
+    real_columns: Vec<usize>,
+    schema: SchemaRef,
+    // rowids of the records returned by the scan
+    rowids: RangeInclusive<i64>,
+    // records are returned in descending rowid order
+    descending: bool,
+    // cells of the table, set when the scan is open
+    scan: Option<TableScanIterator>,
+    // cursor of the point lookups by rowid, created by the first one
+    seek_cursor: Option<BtCursor>,
 }
 
 impl ExecScan {
-    pub fn new(
+    /// Creates a scan of table_name, looking up its root page and columns
+    /// in the schema of the database.
+    pub fn try_new(
         table_name: String,
-        table_page_number: u32,
+        projection: Option<Vec<usize>>,
         database: Rc<RefCell<Database>>,
-    ) -> Self {
-        let bt_cursor = BtCursor::new(database.clone(), table_page_number);
-        ExecScan {
+    ) -> Result<Self> {
+        let (table_page_number, rowid_alias, column_count, real_columns, table_schema) = {
+            let db = database.borrow();
+            let table = db
+                .db_meta
+                .schema_objects
+                .iter()
+                .find(|schema_obj| {
+                    schema_obj.obj_type == SchemaObjType::Table && schema_obj.name == table_name
+                })
+                .ok_or_else(|| anyhow!("no such table: {table_name}"))?;
+            let real_columns = table
+                .columns
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| {
+                    field.metadata().get(FIELD_AFFINITY).map(String::as_str)
+                        == Some(Affinity::Real.name())
+                })
+                .map(|(index, _)| index)
+                .collect();
+            let fields = [table.columns.clone(), table.rowid_columns()].concat();
+            let table_schema = Schema::new_with_metadata(fields, HashMap::new());
+            (
+                table.rootpage,
+                table.rowid_alias(),
+                table.columns.len(),
+                real_columns,
+                table_schema,
+            )
+        };
+        let schema = match &projection {
+            Some(indices) => table_schema.project(indices)?,
+            None => table_schema.project(&(0..column_count).collect::<Vec<_>>())?,
+        };
+
+        Ok(ExecScan {
             table_name,
             table_page_number,
+            rowid_alias,
             database,
-            bt_cursor,
-            records: vec![],
+            projection,
+            column_count,
+            real_columns,
+            schema: Arc::new(schema),
+            rowids: i64::MIN..=i64::MAX,
+            descending: false,
+            scan: None,
+            seek_cursor: None,
+        })
+    }
+
+    /// Limits the scan to the records whose rowid is in rowids.
+    pub fn with_rowid_range(mut self, rowids: RangeInclusive<i64>) -> Self {
+        self.rowids = rowids;
+        self
+    }
+
+    /// Returns the records in descending rowid order if descending.
+    pub fn with_descending(mut self, descending: bool) -> Self {
+        self.descending = descending;
+        self
+    }
+
+    /// Returns the record of the row with the given rowid, None if there is none.
+    /// The table B-Tree is searched from the root instead of scanned, the scan
+    /// does not need to be open.
+    pub fn seek_rowid(&mut self, rowid: i64) -> Result<Option<DataRecord>> {
+        let cursor = match &mut self.seek_cursor {
+            Some(cursor) => cursor,
+            None => self.seek_cursor.insert(BtCursor::new(
+                self.database.clone(),
+                self.table_page_number,
+            )?),
+        };
+        if cursor.table_move_to(rowid)?.is_ne() {
+            return Ok(None);
         }
+        let record = cursor.table_leaf_cell()?.into_record(self.rowid_alias);
+        Ok(Some(self.project(record)))
+    }
+
+    fn project(&self, mut record: DataRecord) -> DataRecord {
+        // rows written before `ALTER TABLE ADD COLUMN` have fewer values than columns
+        if record.values.len() < self.column_count {
+            record.values.resize(self.column_count, ColumnValue::Null);
+        }
+        for &index in &self.real_columns {
+            let value = std::mem::replace(&mut record.values[index], ColumnValue::Null);
+            record.values[index] = value.load_affinity(Affinity::Real);
+        }
+        if let Some(indices) = &self.projection {
+            record.values = indices
+                .iter()
+                .map(|&index| {
+                    if index < self.column_count {
+                        record.values[index].clone()
+                    } else {
+                        // hidden rowid column
+                        record.rowid.map_or(ColumnValue::Null, ColumnValue::int64)
+                    }
+                })
+                .collect();
+        }
+        record
     }
 }
 
 impl Exec for ExecScan {
-    fn execute(&mut self) -> &[DataRecord] {
-        let mut records = Vec::new();
+    fn open(&mut self) -> Result<()> {
+        let cursor = BtCursor::new(self.database.clone(), self.table_page_number)?;
+        let rowids = self.rowids.clone();
+        self.scan = Some(if self.descending {
+            cursor.scan_rowids_desc(rowids)
+        } else {
+            cursor.scan_rowids(rowids)
+        });
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        let Some(scan) = self.scan.as_mut() else {
+            bail!("scan of table {} is not open", self.table_name)
+        };
+        match scan.next() {
+            Some(cell) => Ok(Some(self.project(cell?.into_record(self.rowid_alias)))),
+            None => Ok(None),
+        }
+    }
+
+    fn next_batch(&mut self) -> Result<Option<RecordBatch>> {
+        let Some(scan) = self.scan.as_mut() else {
+            bail!("scan of table {} is not open", self.table_name)
+        };
+        let Some(cells) = scan.next_leaf_page().transpose()? else {
+            return Ok(None);
+        };
+        let records: Vec<DataRecord> = cells
+            .into_iter()
+            .map(|cell| self.project(cell.into_record(self.rowid_alias)))
+            .collect();
+        Ok(Some(records_to_batch(self.schema.clone(), &records)?))
+    }
+
+    fn close(&mut self) -> Result<()> {
+        self.scan = None;
+        self.seek_cursor = None;
+        Ok(())
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use arrow_array::{Int64Array, StringArray};
+    use arrow_schema::DataType;
+
+    use super::*;
+    use crate::physical::plan::batch::batch_to_records;
+
+    fn open_db(db_name: &str) -> Rc<RefCell<Database>> {
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/resources")
+            .join(db_name);
+        Rc::new(RefCell::new(
+            Database::new(db_path.to_str().unwrap()).unwrap(),
+        ))
+    }
 
-        while self.bt_cursor.move_to_next().is_ok() {
-            // TODO - parse record from Btree, not hardcode
-            let payload = vec![1];
-            let record = DataRecord::parse_from(1, &payload);
+    #[test]
+    fn test_scan_substitutes_rowid_alias() {
+        // apples(id integer primary key autoincrement, name text, color text)
+        let mut scan = ExecScan::try_new("apples".to_owned(), None, open_db("sample.db")).unwrap();
+        assert_eq!(scan.table_page_number, 2);
+        assert_eq!(scan.schema().fields().len(), 3);
 
+        scan.open().unwrap();
+        let mut ids = vec![];
+        while let Some(record) = scan.next().unwrap() {
+            ids.push(record.values[0].clone());
+        }
+        scan.close().unwrap();
+        assert_eq!(
+            ids,
+            vec![
+                ColumnValue::int64(1),
+                ColumnValue::int64(2),
+                ColumnValue::int64(3),
+                ColumnValue::int64(4)
+            ]
+        );
+
+        // a closed scan can be opened again from the first record
+        assert!(scan.next().is_err());
+        scan.open().unwrap();
+        assert_eq!(
+            scan.next().unwrap().unwrap().values[0],
+            ColumnValue::int64(1)
+        );
+    }
+
+    #[test]
+    fn test_scan_many_pages_with_projection() {
+        // index.db: people(id integer primary key, name text, age int), 200 rows
+        // on leaf pages under the interior root page 2.
+        let mut scan =
+            ExecScan::try_new("people".to_owned(), Some(vec![1, 0]), open_db("index.db")).unwrap();
+        let schema = scan.schema();
+        assert_eq!(schema.field(0).name(), "name");
+        assert_eq!(schema.field(1).name(), "id");
+        assert_eq!(schema.field(1).data_type(), &DataType::Int64);
+
+        scan.open().unwrap();
+        let mut records = vec![];
+        while let Some(record) = scan.next().unwrap() {
             records.push(record);
         }
+        assert_eq!(records.len(), 200);
+        assert_eq!(records[0].values.len(), 2);
+        assert_eq!(records[0].values[0], ColumnValue::Text("bob1".to_owned()));
+        assert_eq!(records[0].values[1], ColumnValue::int64(1));
+        assert_eq!(
+            records[199].values[0],
+            ColumnValue::Text("Alice0".to_owned())
+        );
+        assert_eq!(records[199].values[1], ColumnValue::int64(200));
+    }
 
-        self.records = records;
-        &self.records
+    #[test]
+    fn test_scan_batches_by_leaf_page() {
+        let mut scan =
+            ExecScan::try_new("people".to_owned(), Some(vec![0, 1]), open_db("index.db")).unwrap();
+        scan.open().unwrap();
+        let mut batches = vec![];
+        while let Some(batch) = scan.next_batch().unwrap() {
+            batches.push(batch);
+        }
+        assert!(batches.len() > 1);
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 200);
+
+        let ids = batches[0]
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(ids.value(0), 1);
+        let names = batches[0]
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "bob1");
+        let last = batches.last().unwrap();
+        assert_eq!(
+            batch_to_records(last).unwrap().last().unwrap().values,
+            vec![
+                ColumnValue::int64(200),
+                ColumnValue::Text("Alice0".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_unknown_table() {
+        assert!(ExecScan::try_new("pears".to_owned(), None, open_db("sample.db")).is_err());
     }
 
-    fn schema(&self) -> arrow_schema::SchemaRef {
-        todo!()
+    #[test]
+    fn test_seek_rowid() {
+        // orders.db: customers(id integer primary key, name text, email text), ids 3 to 6000
+        let mut scan = ExecScan::try_new(
+            "customers".to_owned(),
+            Some(vec![1, 0]),
+            open_db("orders.db"),
+        )
+        .unwrap();
+        let record = scan.seek_rowid(4521).unwrap().unwrap();
+        assert_eq!(
+            record.values,
+            vec![
+                ColumnValue::Text("customer1507".to_owned()),
+                ColumnValue::int64(4521)
+            ]
+        );
+        assert_eq!(scan.seek_rowid(4522).unwrap(), None);
+        assert_eq!(scan.seek_rowid(-1).unwrap(), None);
+
+        // the hidden columns rowid, oid and _rowid_ follow the 3 columns
+        let mut scan = ExecScan::try_new(
+            "customers".to_owned(),
+            Some(vec![3, 1]),
+            open_db("orders.db"),
+        )
+        .unwrap();
+        assert_eq!(scan.schema().field(0).name(), "rowid");
+        assert_eq!(
+            scan.seek_rowid(4521).unwrap().unwrap().values,
+            vec![
+                ColumnValue::int64(4521),
+                ColumnValue::Text("customer1507".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_rowid_range() {
+        let mut scan =
+            ExecScan::try_new("customers".to_owned(), Some(vec![0]), open_db("orders.db"))
+                .unwrap()
+                .with_rowid_range(2990..=3003);
+        scan.open().unwrap();
+        let mut ids = vec![];
+        while let Some(record) = scan.next().unwrap() {
+            ids.push(record.values[0].clone());
+        }
+        assert_eq!(
+            ids,
+            [2991, 2994, 2997, 3000, 3003]
+                .map(ColumnValue::int64)
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn test_scan_descending() {
+        let mut scan = ExecScan::try_new("people".to_owned(), Some(vec![0]), open_db("index.db"))
+            .unwrap()
+            .with_descending(true);
+        scan.open().unwrap();
+        let mut ids = vec![];
+        while let Some(batch) = scan.next_batch().unwrap() {
+            ids.extend(batch_to_records(&batch).unwrap());
+        }
+        assert_eq!(ids.len(), 200);
+        assert_eq!(ids[0].values, vec![ColumnValue::int64(200)]);
+        assert_eq!(ids[199].values, vec![ColumnValue::int64(1)]);
     }
 }
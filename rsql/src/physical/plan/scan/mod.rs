@@ -1,7 +1,7 @@
-mod exec_apples_scan;
+mod exec_index_scan;
 mod exec_mem_table;
 mod exec_scan;
 
-pub use exec_apples_scan::ExecApplesScan;
+pub use exec_index_scan::{ExecIndexScan, IndexBounds};
 pub use exec_mem_table::ExecMemTable;
 pub use exec_scan::ExecScan;
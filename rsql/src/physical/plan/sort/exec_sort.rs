@@ -0,0 +1,421 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::mem;
+use std::sync::Arc;
+use std::vec;
+
+use anyhow::{bail, Result};
+use arrow_schema::SchemaRef;
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::physical::expression::physical_expr::PhysicalExpr;
+use crate::physical::plan::exec::Exec;
+use crate::physical::plan::sort::spill::{SpillReader, SpillWriter};
+
+/// Memory used by the records of a sort before they are spilled to temporary files.
+pub const DEFAULT_SORT_MEMORY_LIMIT: usize = 64 * 1024 * 1024;
+
+/// A term of ORDER BY: `expr [ASC|DESC] [NULLS FIRST|NULLS LAST]`.
+/// Values are compared in SQLite sort order with the collation of expr.
+#[derive(Debug)]
+pub struct SortExpr {
+    pub expr: Arc<dyn PhysicalExpr>,
+    pub descending: bool,
+    /// NULLs are before or after the other values whatever the direction.
+    /// SQLite default is NULLS FIRST for ASC and NULLS LAST for DESC.
+    pub nulls_first: bool,
+}
+
+impl SortExpr {
+    fn compare(&self, left: &ColumnValue, right: &ColumnValue) -> Ordering {
+        match (left.is_null(), right.is_null()) {
+            (true, true) => Ordering::Equal,
+            (true, false) if self.nulls_first => Ordering::Less,
+            (true, false) => Ordering::Greater,
+            (false, true) if self.nulls_first => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => {
+                let collation = self.expr.collation().unwrap_or_default();
+                let ordering = left.compare(right, &collation);
+                if self.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+        }
+    }
+}
+
+fn compare_keys(sort_exprs: &[SortExpr], left: &[ColumnValue], right: &[ColumnValue]) -> Ordering {
+    sort_exprs
+        .iter()
+        .zip(left.iter().zip(right))
+        .map(|(sort_expr, (left, right))| sort_expr.compare(left, right))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+// a record with the values of the ORDER BY expressions
+struct SortRow {
+    keys: Vec<ColumnValue>,
+    record: DataRecord,
+}
+
+fn sort_row(sort_exprs: &[SortExpr], record: DataRecord) -> SortRow {
+    SortRow {
+        keys: sort_exprs
+            .iter()
+            .map(|sort_expr| sort_expr.expr.evaluate(&record))
+            .collect(),
+        record,
+    }
+}
+
+/// Sort: `SELECT * FROM t1 ORDER BY a DESC, b`.
+///
+/// open consumes the input and sorts it, records with equal keys keep their input order.
+/// - Records are sorted in memory until they use more than memory_limit. Then each batch
+///   of records is sorted and written to a temporary file (a sorted run), and next merges
+///   the runs. Like sqlite vdbesort.c.
+/// - With a fetch (`ORDER BY ... LIMIT n`), only the first n records are kept in a heap,
+///   nothing is spilled.
+#[derive(Debug)]
+pub struct ExecSort {
+    pub(crate) input: Box<dyn Exec>,
+    pub(crate) sort_exprs: Arc<Vec<SortExpr>>,
+    /// maximum number of records returned
+    pub(crate) fetch: Option<usize>,
+    memory_limit: usize,
+    schema: SchemaRef,
+    // sorted records, set when the operator is open
+    output: Option<SortOutput>,
+}
+
+enum SortOutput {
+    Memory(vec::IntoIter<DataRecord>),
+    // runs with their first record not returned yet
+    Merge(Vec<(SpillReader, Option<SortRow>)>),
+}
+
+impl std::fmt::Debug for SortOutput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SortOutput::Memory(records) => write!(f, "Memory({} records)", records.len()),
+            SortOutput::Merge(runs) => write!(f, "Merge({} runs)", runs.len()),
+        }
+    }
+}
+
+impl ExecSort {
+    pub fn new(
+        input: Box<dyn Exec>,
+        sort_exprs: Vec<SortExpr>,
+        fetch: Option<usize>,
+        schema: SchemaRef,
+    ) -> Self {
+        ExecSort {
+            input,
+            sort_exprs: Arc::new(sort_exprs),
+            fetch,
+            memory_limit: DEFAULT_SORT_MEMORY_LIMIT,
+            schema,
+            output: None,
+        }
+    }
+
+    /// Sets the memory in bytes used by records before spilling them.
+    pub fn with_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.memory_limit = memory_limit;
+        self
+    }
+
+    fn sort(&mut self) -> Result<SortOutput> {
+        if let Some(fetch) = self.fetch {
+            return self.top_n(fetch);
+        }
+        let mut rows = vec![];
+        let mut size = 0;
+        let mut runs = vec![];
+        while let Some(record) = self.input.next()? {
+            size += record_size(&record);
+            rows.push(sort_row(&self.sort_exprs, record));
+            if size > self.memory_limit {
+                runs.push(self.spill(mem::take(&mut rows))?);
+                size = 0;
+            }
+        }
+        // sort_by is stable
+        rows.sort_by(|a, b| compare_keys(&self.sort_exprs, &a.keys, &b.keys));
+        if runs.is_empty() {
+            let records: Vec<DataRecord> = rows.into_iter().map(|row| row.record).collect();
+            return Ok(SortOutput::Memory(records.into_iter()));
+        }
+        if !rows.is_empty() {
+            runs.push(self.spill(rows)?);
+        }
+        let runs = runs
+            .into_iter()
+            .map(|mut run| {
+                let head = read_row(&self.sort_exprs, &mut run)?;
+                Ok((run, head))
+            })
+            .collect::<Result<_>>()?;
+        Ok(SortOutput::Merge(runs))
+    }
+
+    fn spill(&self, mut rows: Vec<SortRow>) -> Result<SpillReader> {
+        rows.sort_by(|a, b| compare_keys(&self.sort_exprs, &a.keys, &b.keys));
+        let mut writer = SpillWriter::try_new()?;
+        for row in &rows {
+            writer.write(&row.record)?;
+        }
+        writer.finish()
+    }
+
+    // keeps the fetch smallest records in a max-heap
+    fn top_n(&mut self, fetch: usize) -> Result<SortOutput> {
+        let mut heap = BinaryHeap::with_capacity(fetch + 1);
+        let mut sequence = 0;
+        while let Some(record) = self.input.next()? {
+            heap.push(HeapRow {
+                sort_exprs: self.sort_exprs.clone(),
+                row: sort_row(&self.sort_exprs, record),
+                sequence,
+            });
+            if heap.len() > fetch {
+                heap.pop();
+            }
+            sequence += 1;
+        }
+        let records: Vec<DataRecord> = heap
+            .into_sorted_vec()
+            .into_iter()
+            .map(|heap_row| heap_row.row.record)
+            .collect();
+        Ok(SortOutput::Memory(records.into_iter()))
+    }
+}
+
+// Returns the record of the run with the smallest head, the first run on ties.
+// The runs are few, they are scanned instead of kept in a heap.
+fn merge_next(
+    sort_exprs: &[SortExpr],
+    runs: &mut [(SpillReader, Option<SortRow>)],
+) -> Result<Option<DataRecord>> {
+    let mut smallest: Option<usize> = None;
+    for (index, (_, head)) in runs.iter().enumerate() {
+        let Some(head) = head else { continue };
+        let is_smaller = match smallest.and_then(|smallest| runs[smallest].1.as_ref()) {
+            Some(current) => compare_keys(sort_exprs, &head.keys, &current.keys).is_lt(),
+            None => true,
+        };
+        if is_smaller {
+            smallest = Some(index);
+        }
+    }
+    let Some(index) = smallest else {
+        return Ok(None);
+    };
+    let (run, head) = &mut runs[index];
+    let next = read_row(sort_exprs, run)?;
+    Ok(mem::replace(head, next).map(|row| row.record))
+}
+
+fn read_row(sort_exprs: &[SortExpr], run: &mut SpillReader) -> Result<Option<SortRow>> {
+    Ok(run.read()?.map(|record| sort_row(sort_exprs, record)))
+}
+
+// Approximate memory used by a record.
+fn record_size(record: &DataRecord) -> usize {
+    mem::size_of::<DataRecord>()
+        + record
+            .values
+            .iter()
+            .map(|value| mem::size_of::<ColumnValue>() + value.content().len())
+            .sum::<usize>()
+}
+
+// Row of the top-N heap ordered by its keys then its position in the input.
+struct HeapRow {
+    sort_exprs: Arc<Vec<SortExpr>>,
+    row: SortRow,
+    sequence: usize,
+}
+
+impl Ord for HeapRow {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_keys(&self.sort_exprs, &self.row.keys, &other.row.keys)
+            .then(self.sequence.cmp(&other.sequence))
+    }
+}
+
+impl PartialOrd for HeapRow {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for HeapRow {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for HeapRow {}
+
+impl Exec for ExecSort {
+    fn open(&mut self) -> Result<()> {
+        self.input.open()?;
+        self.output = Some(self.sort()?);
+        Ok(())
+    }
+
+    fn next(&mut self) -> Result<Option<DataRecord>> {
+        match self.output.as_mut() {
+            None => bail!("sort is not open"),
+            Some(SortOutput::Memory(records)) => Ok(records.next()),
+            Some(SortOutput::Merge(runs)) => merge_next(&self.sort_exprs, runs),
+        }
+    }
+
+    fn close(&mut self) -> Result<()> {
+        // dropping the runs deletes their files
+        self.output = None;
+        self.input.close()
+    }
+
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::model::collation::Collation;
+    use crate::physical::expression::col_by_index::PhysicalColByIndex;
+    use crate::physical::plan::scan::ExecMemTable;
+
+    fn text(text: &str) -> ColumnValue {
+        ColumnValue::Text(text.to_owned())
+    }
+
+    // (id, name) records with ids 0..count and names cycling over b, NULL, A, a
+    fn input(count: i64) -> Box<dyn Exec> {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int64, false),
+            Field::new("name", DataType::Utf8, true),
+        ]);
+        let records: Vec<DataRecord> = (0..count)
+            .map(|id| DataRecord {
+                values: vec![
+                    ColumnValue::int64(id),
+                    [text("b"), ColumnValue::Null, text("A"), text("a")][id as usize % 4].clone(),
+                ],
+                rowid: None,
+            })
+            .collect();
+        Box::new(ExecMemTable::new(&records, Arc::new(schema)))
+    }
+
+    fn sort_expr(col_index: usize, descending: bool, nulls_first: bool) -> SortExpr {
+        SortExpr {
+            expr: Arc::new(PhysicalColByIndex::new(col_index)),
+            descending,
+            nulls_first,
+        }
+    }
+
+    fn collect(mut exec: ExecSort) -> Vec<Vec<ColumnValue>> {
+        exec.open().unwrap();
+        let mut rows = vec![];
+        while let Some(record) = exec.next().unwrap() {
+            rows.push(record.values);
+        }
+        exec.close().unwrap();
+        rows
+    }
+
+    fn ids(rows: &[Vec<ColumnValue>]) -> Vec<i64> {
+        rows.iter().map(|row| row[0].as_i64().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_sort_multiple_keys() {
+        let schema = input(0).schema();
+        // ORDER BY name DESC NULLS FIRST, id DESC
+        let sort = ExecSort::new(
+            input(8),
+            vec![sort_expr(1, true, true), sort_expr(0, true, false)],
+            None,
+            schema,
+        );
+        assert_eq!(ids(&collect(sort)), vec![5, 1, 4, 0, 7, 3, 6, 2]);
+    }
+
+    #[test]
+    fn test_sort_collation_is_stable() {
+        let schema = input(0).schema();
+        let name = PhysicalColByIndex {
+            col_index: 1,
+            affinity: None,
+            collation: Some(Collation::NoCase),
+        };
+        let sort_exprs = vec![SortExpr {
+            expr: Arc::new(name),
+            descending: false,
+            nulls_first: false,
+        }];
+        // A and a are equal with NOCASE, they keep their input order
+        let sort = ExecSort::new(input(8), sort_exprs, None, schema);
+        assert_eq!(ids(&collect(sort)), vec![2, 3, 6, 7, 0, 4, 1, 5]);
+    }
+
+    #[test]
+    fn test_sort_spills_and_merges() {
+        let schema = input(0).schema();
+        let sort_exprs = || vec![sort_expr(1, false, true), sort_expr(0, true, false)];
+        let in_memory = collect(ExecSort::new(
+            input(1000),
+            sort_exprs(),
+            None,
+            schema.clone(),
+        ));
+        // each run holds a few records
+        let mut spilled =
+            ExecSort::new(input(1000), sort_exprs(), None, schema).with_memory_limit(300);
+        spilled.open().unwrap();
+        assert!(matches!(
+            spilled.output,
+            Some(SortOutput::Merge(ref runs)) if runs.len() > 100
+        ));
+        spilled.close().unwrap();
+        assert_eq!(collect(spilled), in_memory);
+        assert_eq!(in_memory.len(), 1000);
+    }
+
+    #[test]
+    fn test_sort_top_n() {
+        let schema = input(0).schema();
+        let sort_exprs = || vec![sort_expr(1, false, false), sort_expr(0, false, false)];
+        let all = collect(ExecSort::new(
+            input(100),
+            sort_exprs(),
+            None,
+            schema.clone(),
+        ));
+        let top = collect(ExecSort::new(
+            input(100),
+            sort_exprs(),
+            Some(10),
+            schema.clone(),
+        ));
+        assert_eq!(top, all[..10]);
+        let none = collect(ExecSort::new(input(100), sort_exprs(), Some(0), schema));
+        assert!(none.is_empty());
+    }
+}
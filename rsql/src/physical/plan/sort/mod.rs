@@ -0,0 +1,4 @@
+mod exec_sort;
+mod spill;
+
+pub use exec_sort::{ExecSort, SortExpr, DEFAULT_SORT_MEMORY_LIMIT};
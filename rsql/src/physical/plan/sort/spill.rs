@@ -0,0 +1,136 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+
+use anyhow::{bail, Result};
+
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+
+/// A sorted run of records written to a temporary file, deleted when dropped.
+/// The equivalent of a PMA (packed memory array) in sqlite vdbesort.c.
+///
+/// Each record is written as:
+/// - the number of values (u32) and the rowid flag (u8), followed by the rowid (u64) if any
+/// - for each value, its serial type (i64) and its content bytes like in a record
+///
+/// Numbers are big-endian.
+pub struct SpillWriter {
+    writer: BufWriter<File>,
+}
+
+impl SpillWriter {
+    pub fn try_new() -> Result<Self> {
+        Ok(SpillWriter {
+            writer: BufWriter::new(tempfile::tempfile()?),
+        })
+    }
+
+    pub fn write(&mut self, record: &DataRecord) -> Result<()> {
+        let writer = &mut self.writer;
+        writer.write_all(&u32::try_from(record.values.len())?.to_be_bytes())?;
+        match record.rowid {
+            Some(rowid) => {
+                writer.write_all(&[1])?;
+                writer.write_all(&rowid.to_be_bytes())?;
+            }
+            None => writer.write_all(&[0])?,
+        }
+        for value in &record.values {
+            writer.write_all(&value.serial_type().to_be_bytes())?;
+            writer.write_all(value.content())?;
+        }
+        Ok(())
+    }
+
+    /// Flushes the records and returns a reader from the first one.
+    pub fn finish(self) -> Result<SpillReader> {
+        let mut file = self.writer.into_inner()?;
+        file.seek(SeekFrom::Start(0))?;
+        Ok(SpillReader {
+            reader: BufReader::new(file),
+        })
+    }
+}
+
+pub struct SpillReader {
+    reader: BufReader<File>,
+}
+
+impl SpillReader {
+    /// Reads the next record, None at the end of the file.
+    pub fn read(&mut self) -> Result<Option<DataRecord>> {
+        let mut count = [0; 4];
+        match self.reader.read_exact(&mut count) {
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        }
+        let rowid = match self.read_array::<1>()? {
+            [0] => None,
+            _ => Some(i64::from_be_bytes(self.read_array()?)),
+        };
+        let values = (0..u32::from_be_bytes(count))
+            .map(|_| {
+                let serial_type = i64::from_be_bytes(self.read_array()?);
+                let mut content = vec![0; content_size(serial_type)?];
+                self.reader.read_exact(&mut content)?;
+                Ok(ColumnValue::parse(serial_type, &content)?.0)
+            })
+            .collect::<Result<_>>()?;
+        Ok(Some(DataRecord { values, rowid }))
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut bytes = [0; N];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+// https://www.sqlite.org/fileformat.html#record_format
+fn content_size(serial_type: i64) -> Result<usize> {
+    Ok(match serial_type {
+        0 | 8 | 9 => 0,
+        1..=4 => serial_type as usize,
+        5 => 6,
+        6 | 7 => 8,
+        n if n >= 12 => ((n - 12) / 2) as usize,
+        n => bail!("invalid serial type {n} in spill file"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spill_round_trip() {
+        let records = vec![
+            DataRecord {
+                values: vec![
+                    ColumnValue::Null,
+                    ColumnValue::int64(-7),
+                    ColumnValue::Text("héllo".to_owned()),
+                ],
+                rowid: Some(3),
+            },
+            DataRecord {
+                values: vec![ColumnValue::real(0.5), ColumnValue::Blob(vec![1, 2])],
+                rowid: None,
+            },
+            DataRecord {
+                values: vec![],
+                rowid: None,
+            },
+        ];
+        let mut writer = SpillWriter::try_new().unwrap();
+        for record in &records {
+            writer.write(record).unwrap();
+        }
+        let mut reader = writer.finish().unwrap();
+        let mut read = vec![];
+        while let Some(record) = reader.read().unwrap() {
+            read.push(record);
+        }
+        assert_eq!(read, records);
+    }
+}
@@ -0,0 +1,302 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail, Result};
+use arrow_schema::Field;
+
+use crate::btree::bt_cursor::BtCursor;
+use crate::model::affinity::Affinity;
+use crate::model::column_value::ColumnValue;
+use crate::model::data_record::DataRecord;
+use crate::model::database::Database;
+use crate::model::index_key::KeyInfo;
+use crate::model::schema::{
+    SchemaObjDetail, SchemaObjType, TriggerEvent, FIELD_AFFINITY, FIELD_AUTOINCREMENT,
+};
+use crate::physical::physical_planner::index_key_info;
+use crate::physical::plan::exec::Exec;
+
+/// An index of the table rows are written to.
+#[derive(Debug)]
+struct TableIndex {
+    name: String,
+    root_page_number: u32,
+    unique: bool,
+    // indices of the indexed table columns
+    columns: Vec<usize>,
+    key_info: KeyInfo,
+}
+
+/// Writes the rows of a table: a row is inserted in the table btree and its key in each
+/// index btree, or deleted from all of them. Used by INSERT, DELETE and UPDATE.
+///
+/// The values of an inserted row get the affinity of the columns, then the NOT NULL
+/// and UNIQUE constraints are checked before anything is written.
+/// Tables WITHOUT ROWID, partial and expression indexes and triggers of the statement
+/// are not supported.
+#[derive(Debug)]
+pub struct TableWriter {
+    pub table_name: String,
+    database: Rc<RefCell<Database>>,
+    pub table_page_number: u32,
+    pub columns: Vec<Field>,
+    /// index of the INTEGER PRIMARY KEY column whose value is the rowid
+    pub rowid_alias: Option<usize>,
+    pub autoincrement: bool,
+    indexes: Vec<TableIndex>,
+}
+
+impl TableWriter {
+    /// Creates the writer of the rows of table_name for a statement firing the triggers
+    /// of event.
+    pub fn try_new(
+        table_name: &str,
+        event: TriggerEvent,
+        database: Rc<RefCell<Database>>,
+    ) -> Result<Self> {
+        let db = database.borrow();
+        let schema_objects = &db.db_meta.schema_objects;
+        let table = schema_objects
+            .iter()
+            .find(|obj| obj.obj_type == SchemaObjType::Table && obj.name == table_name)
+            .ok_or_else(|| anyhow!("no such table: {table_name}"))?;
+        let SchemaObjDetail::Table(table_def) = &table.detail else {
+            bail!("no such table: {table_name}")
+        };
+        if table_def.without_rowid {
+            bail!("cannot write WITHOUT ROWID table {table_name}")
+        }
+
+        let mut indexes = vec![];
+        for obj in schema_objects
+            .iter()
+            .filter(|obj| obj.tbl_name == table_name)
+        {
+            match &obj.detail {
+                SchemaObjDetail::Index(index_def) => {
+                    // an automatic index whose constraint is not found in the table
+                    if index_def.columns.is_empty() {
+                        bail!("cannot write {table_name}: unknown index {}", obj.name)
+                    }
+                    if index_def.where_clause.is_some() {
+                        bail!("cannot write {table_name}: partial index {}", obj.name)
+                    }
+                    let columns = index_def
+                        .columns
+                        .iter()
+                        .map(|indexed_column| {
+                            table
+                                .columns
+                                .iter()
+                                .position(|field| {
+                                    !indexed_column.expression
+                                        && field.name().eq_ignore_ascii_case(&indexed_column.name)
+                                })
+                                .ok_or_else(|| {
+                                    anyhow!(
+                                        "cannot write {table_name}: index {} on expression {}",
+                                        obj.name,
+                                        indexed_column.name
+                                    )
+                                })
+                        })
+                        .collect::<Result<Vec<usize>>>()?;
+                    indexes.push(TableIndex {
+                        name: obj.name.clone(),
+                        root_page_number: obj.rootpage,
+                        unique: index_def.unique,
+                        columns,
+                        key_info: index_key_info(index_def, &table.columns)?,
+                    });
+                }
+                SchemaObjDetail::Trigger(trigger_def)
+                    if std::mem::discriminant(&trigger_def.event)
+                        == std::mem::discriminant(&event) =>
+                {
+                    bail!(
+                        "cannot write {table_name}: trigger {} is not supported",
+                        obj.name
+                    )
+                }
+                _ => {}
+            }
+        }
+
+        let autoincrement = table_def.rowid_alias.is_some_and(|index| {
+            table.columns[index].metadata().get(FIELD_AUTOINCREMENT) == Some(&"true".to_owned())
+        });
+        let table_page_number = table.rootpage;
+        let columns = table.columns.clone();
+        let rowid_alias = table_def.rowid_alias;
+        drop(db);
+
+        Ok(TableWriter {
+            table_name: table_name.to_owned(),
+            database,
+            table_page_number,
+            columns,
+            rowid_alias,
+            autoincrement,
+            indexes,
+        })
+    }
+
+    /// Applies the affinity of each column to the values of a row.
+    pub fn apply_affinity(&self, values: Vec<ColumnValue>) -> Result<Vec<ColumnValue>> {
+        self.columns
+            .iter()
+            .zip(values)
+            .map(|(field, value)| {
+                let affinity = match field.metadata().get(FIELD_AFFINITY) {
+                    Some(name) => Affinity::from_name(name)?,
+                    None => Affinity::Blob,
+                };
+                Ok(value.store_affinity(affinity))
+            })
+            .collect()
+    }
+
+    /// The rowid of a row given by the value of its INTEGER PRIMARY KEY, None if the
+    /// table has none or the value is NULL.
+    pub fn alias_rowid(&self, values: &[ColumnValue]) -> Result<Option<i64>> {
+        match self.rowid_alias.map(|index| &values[index]) {
+            None | Some(ColumnValue::Null) => Ok(None),
+            Some(value) => match value.as_i64() {
+                Some(rowid) => Ok(Some(rowid)),
+                None => bail!("datatype mismatch"),
+            },
+        }
+    }
+
+    /// Inserts a row with values, after affinity, and rowid into the table and its
+    /// indexes. cursor is a cursor of the table btree.
+    pub fn insert_row(
+        &self,
+        cursor: &mut BtCursor,
+        mut values: Vec<ColumnValue>,
+        rowid: i64,
+    ) -> Result<()> {
+        for (index, field) in self.columns.iter().enumerate() {
+            if !field.is_nullable() && Some(index) != self.rowid_alias && values[index].is_null() {
+                bail!(
+                    "NOT NULL constraint failed: {}.{}",
+                    self.table_name,
+                    field.name()
+                )
+            }
+        }
+        if let Some(alias) = self.rowid_alias {
+            if cursor.table_move_to(rowid)?.is_eq() && !cursor.is_eof() {
+                bail!(
+                    "UNIQUE constraint failed: {}.{}",
+                    self.table_name,
+                    self.columns[alias].name()
+                )
+            }
+            // the value of the INTEGER PRIMARY KEY is the rowid, NULL in the record
+            values[alias] = ColumnValue::int64(rowid);
+        }
+
+        let keys: Vec<Vec<ColumnValue>> = self
+            .indexes
+            .iter()
+            .map(|index| index.columns.iter().map(|&i| values[i].clone()).collect())
+            .collect();
+        for (index, key) in self.indexes.iter().zip(&keys) {
+            // NULLs are distinct in a UNIQUE index
+            if index.unique
+                && !key.iter().any(ColumnValue::is_null)
+                && index_contains(&self.database, index.root_page_number, key, &index.key_info)?
+            {
+                let names: Vec<String> = index
+                    .columns
+                    .iter()
+                    .map(|&i| format!("{}.{}", self.table_name, self.columns[i].name()))
+                    .collect();
+                bail!("UNIQUE constraint failed: {}", names.join(", "))
+            }
+        }
+
+        if let Some(alias) = self.rowid_alias {
+            values[alias] = ColumnValue::Null;
+        }
+        let record = DataRecord {
+            values,
+            rowid: None,
+        };
+        cursor.table_insert(rowid, &record)?;
+        for (index, key) in self.indexes.iter().zip(keys) {
+            BtCursor::new(self.database.clone(), index.root_page_number)?
+                .index_insert(&index_entry(key, rowid), &index.key_info)?;
+        }
+        Ok(())
+    }
+
+    /// Deletes the row with rowid from the table and its key from each index.
+    /// cursor is a cursor of the table btree.
+    pub fn delete_row(&self, cursor: &mut BtCursor, rowid: i64) -> Result<()> {
+        if cursor.table_move_to(rowid)?.is_ne() || cursor.is_eof() {
+            bail!("no row with rowid {rowid} in {}", self.table_name)
+        }
+        let mut values = cursor
+            .table_leaf_cell()?
+            .into_record(self.rowid_alias)
+            .values;
+        // rows written before `ALTER TABLE ADD COLUMN` have fewer values than columns
+        values.resize(self.columns.len(), ColumnValue::Null);
+        for index in &self.indexes {
+            let key = index.columns.iter().map(|&i| values[i].clone()).collect();
+            BtCursor::new(self.database.clone(), index.root_page_number)?
+                .index_delete(&index_entry(key, rowid), &index.key_info)
+                .map_err(|err| anyhow!("index {}: {err}", index.name))?;
+        }
+        cursor.table_delete(rowid)
+    }
+}
+
+/// The entry of a row in an index: the indexed values followed by the rowid.
+pub fn index_entry(mut key: Vec<ColumnValue>, rowid: i64) -> DataRecord {
+    key.push(ColumnValue::int64(rowid));
+    DataRecord {
+        values: key,
+        rowid: None,
+    }
+}
+
+/// Returns true if the index rooted at root_page_number has an entry whose values
+/// equal key, whatever its rowid.
+pub fn index_contains(
+    database: &Rc<RefCell<Database>>,
+    root_page_number: u32,
+    key: &[ColumnValue],
+    key_info: &KeyInfo,
+) -> Result<bool> {
+    let mut cursor = BtCursor::new(database.clone(), root_page_number)?;
+    cursor.index_move_to(key, key_info)?;
+    Ok(!cursor.is_eof() && key_info.compare(key, &cursor.index_key()?.values).is_eq())
+}
+
+/// Ends a statement writing the database: the modified pages are written to the
+/// database file if result is Ok, otherwise they are dropped and the error returned.
+pub fn commit_or_rollback(database: &RefCell<Database>, result: Result<()>) -> Result<()> {
+    let mut database = database.borrow_mut();
+    match result.and_then(|_| database.commit()) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            database.rollback()?;
+            Err(err)
+        }
+    }
+}
+
+/// Reads all records of input before the first write, like a SELECT of the table
+/// into a temporary table.
+pub fn read_all(input: &mut dyn Exec) -> Result<Vec<DataRecord>> {
+    input.open()?;
+    let mut records = vec![];
+    while let Some(record) = input.next()? {
+        records.push(record);
+    }
+    input.close()?;
+    Ok(records)
+}
@@ -10,6 +10,8 @@ use datafusion_sql::planner::ContextProvider;
 use datafusion_sql::TableReference;
 
 use crate::model::database::Database;
+use crate::model::schema::SchemaObjType;
+use crate::sql::functions;
 
 /// SqliteContextProvider is an extension of datafusion ContextProvider
 /// for providing Catalog, Table, Schema, UDFs, etc. of sqlite and custom ones.
@@ -41,6 +43,9 @@ impl SqliteContextProvider {
         let mut tables = HashMap::new();
 
         for schema_obj in &database.db_meta.schema_objects {
+            if schema_obj.obj_type != SchemaObjType::Table {
+                continue;
+            }
             let table_source = create_table_source(schema_obj.columns.clone());
             tables.insert(schema_obj.tbl_name.clone(), table_source);
         }
@@ -50,6 +55,23 @@ impl SqliteContextProvider {
             options: Default::default(),
         }
     }
+
+    /// Adds the hidden columns `rowid`, `oid` and `_rowid_` (see
+    /// `SchemaObject::rowid_columns`) to the tables, except to the table inserted into
+    /// whose values are given for its columns.
+    pub fn with_rowid_columns(mut self, database: &Database, insert_table: Option<&str>) -> Self {
+        for schema_obj in &database.db_meta.schema_objects {
+            if schema_obj.obj_type != SchemaObjType::Table
+                || insert_table == Some(schema_obj.tbl_name.as_str())
+            {
+                continue;
+            }
+            let fields = [schema_obj.columns.clone(), schema_obj.rowid_columns()].concat();
+            self.tables
+                .insert(schema_obj.tbl_name.clone(), create_table_source(fields));
+        }
+        self
+    }
 }
 
 impl ContextProvider for SqliteContextProvider {
@@ -67,12 +89,12 @@ impl ContextProvider for SqliteContextProvider {
         }
     }
 
-    fn get_function_meta(&self, _name: &str) -> Option<Arc<ScalarUDF>> {
-        None // we don't add any ScalarUDF
+    fn get_function_meta(&self, name: &str) -> Option<Arc<ScalarUDF>> {
+        functions::scalar_udf(name)
     }
 
-    fn get_aggregate_meta(&self, _name: &str) -> Option<Arc<AggregateUDF>> {
-        None
+    fn get_aggregate_meta(&self, name: &str) -> Option<Arc<AggregateUDF>> {
+        functions::aggregate_udf(name)
     }
 
     fn get_window_meta(&self, _name: &str) -> Option<Arc<WindowUDF>> {
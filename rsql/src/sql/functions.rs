@@ -0,0 +1,134 @@
+//! SQLite functions unknown to datafusion, registered by SqliteContextProvider.
+//!
+//! Only their signature and return type are used to plan queries:
+//! the physical planner creates their expressions from their name.
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+use arrow_schema::DataType;
+use datafusion_common::{not_impl_err, DataFusionError};
+use datafusion_expr::{
+    AccumulatorFactoryFunction, AggregateUDF, ColumnarValue, ReturnTypeFunction,
+    ScalarFunctionImplementation, ScalarUDF, Signature, StateTypeFunction, Volatility,
+};
+use datafusion_sql::sqlparser::ast::{visit_expressions_mut, Expr, Ident, ObjectName, Statement};
+
+/// Suffix of the name of user-defined aggregates called with DISTINCT, see
+/// rewrite_distinct_aggregates.
+pub const DISTINCT_SUFFIX: &str = "_distinct";
+
+/// Scalar functions: glob(pattern, x), like(pattern, x[, escape]), collate(x, name),
+/// the `x COLLATE name` operator, and arithmetic_operand(x), the operands of arithmetic
+/// operators (see rewrite::sqlite_operators). length(x), coalesce(x, y, ...) and
+/// ifnull(x, y) take values of any type, unlike the datafusion built-in functions.
+pub fn scalar_udf(name: &str) -> Option<Arc<ScalarUDF>> {
+    let return_type: ReturnTypeFunction = match name {
+        "glob" | "like" => return_type_fn(DataType::Boolean),
+        "length" => return_type_fn(DataType::Int64),
+        // the value of x
+        "collate" => Arc::new(|args: &[DataType]| {
+            Ok(Arc::new(args.first().cloned().unwrap_or(DataType::Null)))
+        }),
+        // one of the values
+        "coalesce" | "ifnull" => Arc::new(|args: &[DataType]| {
+            Ok(Arc::new(
+                args.iter()
+                    .find(|data_type| **data_type != DataType::Null)
+                    .cloned()
+                    .unwrap_or(DataType::Null),
+            ))
+        }),
+        // the value of x converted to a number by the operator: TEXT, BLOB and the values
+        // of a column without a type are typed like NUMERIC columns
+        "arithmetic_operand" => Arc::new(|args: &[DataType]| {
+            Ok(Arc::new(match args.first() {
+                Some(data_type) if data_type.is_numeric() => data_type.clone(),
+                _ => DataType::Float64,
+            }))
+        }),
+        _ => return None,
+    };
+    Some(Arc::new(ScalarUDF::new(
+        name,
+        &Signature::variadic_any(Volatility::Immutable),
+        &return_type,
+        &(Arc::new(|_: &[ColumnarValue]| not_impl_err!("executed by rsql"))
+            as ScalarFunctionImplementation),
+    )))
+}
+
+/// Aggregate functions with SQLite semantics, whatever the type of their argument:
+/// sum(x), total(x), avg(x), min(x), max(x) and group_concat(x[, separator]).
+/// count is the datafusion built-in aggregate.
+pub fn aggregate_udf(name: &str) -> Option<Arc<AggregateUDF>> {
+    let return_type: ReturnTypeFunction = match name.strip_suffix(DISTINCT_SUFFIX).unwrap_or(name) {
+        // an integer if all values are integers
+        "sum" => Arc::new(|args: &[DataType]| {
+            Ok(Arc::new(match args {
+                [DataType::Int64] => DataType::Int64,
+                _ => DataType::Float64,
+            }))
+        }),
+        "total" | "avg" => return_type_fn(DataType::Float64),
+        // one of the values. The values of a column without a type have any storage
+        // class: they are typed like NUMERIC columns, Null would make `max(x) - min(x)`
+        // an arithmetic between two Null operands that datafusion rejects.
+        "min" | "max" => Arc::new(|args: &[DataType]| {
+            Ok(Arc::new(match args.first() {
+                Some(DataType::Null) | None => DataType::Float64,
+                Some(data_type) => data_type.clone(),
+            }))
+        }),
+        "group_concat" => return_type_fn(DataType::Utf8),
+        _ => return None,
+    };
+    Some(Arc::new(AggregateUDF::new(
+        name,
+        &Signature::variadic_any(Volatility::Immutable),
+        &return_type,
+        &(Arc::new(|_: &DataType| not_impl_err!("executed by rsql")) as AccumulatorFactoryFunction),
+        &(Arc::new(|_: &DataType| not_impl_err!("executed by rsql")) as StateTypeFunction),
+    )))
+}
+
+fn return_type_fn(data_type: DataType) -> ReturnTypeFunction {
+    let data_type = Arc::new(data_type);
+    Arc::new(move |_: &[DataType]| Ok(data_type.clone()))
+}
+
+/// datafusion drops DISTINCT of user-defined aggregate calls: `group_concat(DISTINCT x)`
+/// is renamed `group_concat_distinct(x)` before planning.
+pub fn rewrite_distinct_aggregates(statement: &mut Statement) {
+    let _: ControlFlow<()> = visit_expressions_mut(statement, |expr| {
+        if let Expr::Function(function) = expr {
+            let name = function.name.to_string().to_ascii_lowercase();
+            if function.distinct && aggregate_udf(&name).is_some() {
+                function.name = ObjectName(vec![Ident::new(name + DISTINCT_SUFFIX)]);
+                function.distinct = false;
+            }
+        }
+        ControlFlow::Continue(())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use datafusion_sql::sqlparser::dialect::AnsiDialect;
+    use datafusion_sql::sqlparser::parser::Parser;
+
+    use super::*;
+
+    #[test]
+    fn test_rewrite_distinct_aggregates() {
+        let sql =
+            "SELECT group_concat(DISTINCT a), TOTAL(DISTINCT b), count(DISTINCT c), total(d), \
+            Sum(DISTINCT e) FROM t";
+        let mut statement = Parser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0);
+        rewrite_distinct_aggregates(&mut statement);
+        assert_eq!(
+            statement.to_string(),
+            "SELECT group_concat_distinct(a), total_distinct(b), count(DISTINCT c), total(d), \
+            sum_distinct(e) FROM t"
+        );
+    }
+}
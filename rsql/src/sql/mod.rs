@@ -1,2 +1,4 @@
 pub mod context_provider;
+pub mod functions;
 pub mod parsing;
+pub mod rewrite;
@@ -1,42 +1,920 @@
+use std::collections::HashMap;
+
 use anyhow::{bail, Result};
 
-use arrow_schema::{DataType, Field, Schema};
-use datafusion_sql::sqlparser;
-use datafusion_sql::sqlparser::ast::Statement::CreateTable;
+use arrow_schema::{Field, Schema};
+use datafusion_sql::sqlparser::ast::Statement;
 use datafusion_sql::sqlparser::dialect::SQLiteDialect;
+use datafusion_sql::sqlparser::keywords::Keyword;
 use datafusion_sql::sqlparser::parser::Parser;
+use datafusion_sql::sqlparser::tokenizer::{Token, Tokenizer, Whitespace, Word};
 use log::{error, info};
 
-pub fn parse_columns_from_ddl(ddl: &str) -> Result<Vec<Field>> {
-    let dialect = SQLiteDialect {};
-    let statements = Parser::parse_sql(&dialect, ddl)?;
+use crate::model::affinity::Affinity;
+use crate::model::collation::Collation;
+use crate::model::index_key::SortOrder;
+use crate::model::schema::{
+    IndexDef, IndexedColumn, TableDef, TriggerDef, TriggerEvent, TriggerTiming, ViewDef,
+    FIELD_AFFINITY, FIELD_AUTOINCREMENT, FIELD_COLLATION, FIELD_DECLARED_TYPE, FIELD_DEFAULT,
+    FIELD_PRIMARY_KEY, FIELD_UNIQUE,
+};
+
+/// Keywords starting a column constraint, they end the type name of a column.
+/// https://www.sqlite.org/syntax/column-constraint.html
+const COLUMN_CONSTRAINT_KEYWORDS: [&str; 11] = [
+    "CONSTRAINT",
+    "PRIMARY",
+    "NOT",
+    "NULL",
+    "UNIQUE",
+    "CHECK",
+    "DEFAULT",
+    "COLLATE",
+    "REFERENCES",
+    "GENERATED",
+    "AS",
+];
 
-    if statements.len() != 1 {
-        bail!("Invalid DDL statement".to_string())
+/// Keywords starting a table constraint in the body of `CREATE TABLE`.
+/// https://www.sqlite.org/syntax/table-constraint.html
+const TABLE_CONSTRAINT_KEYWORDS: [&str; 5] =
+    ["CONSTRAINT", "PRIMARY", "UNIQUE", "CHECK", "FOREIGN"];
+
+/// Tokens of a DDL statement with a cursor skipping whitespace.
+///
+/// The DDL stored in sqlite_schema is read from the tokens of the sqlparser tokenizer
+/// instead of its parser: sqlparser does not accept every statement SQLite does,
+/// e.g. columns without a data type in `CREATE TABLE sqlite_sequence(name,seq)`.
+/// Whitespace tokens are kept so the text of expressions can be rebuilt.
+struct DdlTokens {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl DdlTokens {
+    fn new(sql: &str) -> Result<Self> {
+        let tokens = Tokenizer::new(&SQLiteDialect {}, sql).tokenize()?;
+        Ok(Self::from_tokens(tokens))
+    }
+
+    fn from_tokens(tokens: Vec<Token>) -> Self {
+        DdlTokens { tokens, pos: 0 }
+    }
+
+    /// Returns the position of the next token that is not whitespace.
+    fn peek_pos(&self) -> usize {
+        let mut pos = self.pos;
+        while let Some(Token::Whitespace(_)) = self.tokens.get(pos) {
+            pos += 1;
+        }
+        pos
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        match self.tokens.get(self.peek_pos()) {
+            Some(Token::EOF) | None => None,
+            token => token,
+        }
     }
 
-    if let CreateTable { columns, .. } = &statements[0] {
-        let fields: Result<Vec<Field>> = columns
+    fn next(&mut self) -> Option<Token> {
+        let token = self.peek().cloned();
+        if token.is_some() {
+            self.pos = self.peek_pos() + 1;
+        }
+        token
+    }
+
+    fn is_empty(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(
+            self.peek(),
+            Some(Token::Word(word)) if word.quote_style.is_none() && word.value.eq_ignore_ascii_case(keyword)
+        )
+    }
+
+    /// Returns true if the next tokens are the sequence of keywords.
+    fn peek_keywords(&self, keywords: &[&str]) -> bool {
+        let mut tokens = DdlTokens::from_tokens(self.tokens[self.pos..].to_vec());
+        keywords
             .iter()
-            .map(|column_def| {
-                let data_type = match &column_def.data_type {
-                    sqlparser::ast::DataType::Varchar(_) => DataType::Utf8,
-                    sqlparser::ast::DataType::Text => DataType::Utf8,
-                    sqlparser::ast::DataType::Int(_) => DataType::Int32,
-                    sqlparser::ast::DataType::Integer(_) => DataType::Int32,
-                    // Add more mappings for other data types as needed
-                    _ => {
-                        bail!(format!("Unsupported data type: {}", column_def.data_type));
+            .all(|keyword| tokens.next_if_keyword(keyword))
+    }
+
+    fn next_if_keywords(&mut self, keywords: &[&str]) -> bool {
+        let found = self.peek_keywords(keywords);
+        if found {
+            keywords.iter().for_each(|_| {
+                self.next();
+            });
+        }
+        found
+    }
+
+    fn peek_any_keyword(&self, keywords: &[&str]) -> bool {
+        keywords.iter().any(|keyword| self.peek_keyword(keyword))
+    }
+
+    fn next_if_keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek_keyword(keyword);
+        if found {
+            self.next();
+        }
+        found
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if !self.next_if_keyword(keyword) {
+            bail!("Expected {keyword}, found {:?}", self.peek())
+        }
+        Ok(())
+    }
+
+    fn expect_token(&mut self, expected: Token) -> Result<()> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            token => bail!("Expected {expected}, found {token:?}"),
+        }
+    }
+
+    /// Consumes `CREATE [TEMP|TEMPORARY] <kind> [IF NOT EXISTS] [schema.]name`
+    /// and returns the name.
+    fn next_create(&mut self, kind: &str) -> Result<String> {
+        self.expect_keyword("CREATE")?;
+        if !self.next_if_keyword("TEMP") {
+            self.next_if_keyword("TEMPORARY");
+        }
+        self.expect_keyword(kind)?;
+        self.next_if_keywords(&["IF", "NOT", "EXISTS"]);
+        self.next_object_name()
+    }
+
+    /// Consumes an identifier, possibly quoted.
+    fn next_name(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Word(word)) => Ok(word.value),
+            Some(Token::SingleQuotedString(name)) | Some(Token::DoubleQuotedString(name)) => {
+                Ok(name)
+            }
+            token => bail!("Expected a name, found {token:?}"),
+        }
+    }
+
+    /// Consumes `[schema.]name` and returns the name.
+    fn next_object_name(&mut self) -> Result<String> {
+        let mut name = self.next_name()?;
+        while self.peek() == Some(&Token::Period) {
+            self.next();
+            name = self.next_name()?;
+        }
+        Ok(name)
+    }
+
+    /// Consumes `[main.]name` and returns the name and the position of its token.
+    /// Objects are created and dropped in the main database only.
+    fn next_main_object_name(&mut self) -> Result<(String, usize)> {
+        let mut start = self.peek_pos();
+        let mut name = self.next_name()?;
+        if self.peek() == Some(&Token::Period) {
+            if !name.eq_ignore_ascii_case("main") {
+                bail!("unknown database {name}")
+            }
+            self.next();
+            start = self.peek_pos();
+            name = self.next_name()?;
+        }
+        Ok((name, start))
+    }
+
+    /// Consumes a parenthesized list and returns the tokens of each comma separated item.
+    fn next_parenthesized_list(&mut self) -> Result<Vec<DdlTokens>> {
+        self.expect_token(Token::LParen)?;
+        let mut items = vec![];
+        let mut item = vec![];
+        let mut depth = 0;
+        loop {
+            let token = self.tokens.get(self.pos).cloned();
+            self.pos += 1;
+            match token {
+                Some(Token::EOF) | None => bail!("Missing closing parenthesis"),
+                Some(Token::RParen) if depth == 0 => {
+                    items.push(DdlTokens::from_tokens(item));
+                    return Ok(items);
+                }
+                Some(Token::Comma) if depth == 0 => {
+                    items.push(DdlTokens::from_tokens(std::mem::take(&mut item)));
+                }
+                Some(token) => {
+                    match token {
+                        Token::LParen => depth += 1,
+                        Token::RParen => depth -= 1,
+                        _ => {}
                     }
-                };
+                    item.push(token);
+                }
+            }
+        }
+    }
 
-                Ok(Field::new(&column_def.name.value, data_type, false))
-            })
-            .collect();
+    /// Consumes a parenthesized expression and returns its SQL text without the parentheses.
+    fn next_parenthesized(&mut self) -> Result<String> {
+        let start = self.peek_pos() + 1;
+        self.next_parenthesized_list()?;
+        Ok(Self::sql_text(&self.tokens[start..self.pos - 1]))
+    }
+
+    /// Consumes an optional `ON CONFLICT <resolution>` clause.
+    /// https://www.sqlite.org/syntax/conflict-clause.html
+    fn next_conflict_clause(&mut self) -> Result<()> {
+        if self.next_if_keywords(&["ON", "CONFLICT"]) {
+            self.next_name()?;
+        }
+        Ok(())
+    }
+
+    /// Consumes `REFERENCES ...` up to the next constraint.
+    /// https://www.sqlite.org/syntax/foreign-key-clause.html
+    fn next_foreign_key_clause(&mut self) -> Result<()> {
+        self.next_name()?;
+        if self.peek() == Some(&Token::LParen) {
+            self.next_parenthesized_list()?;
+        }
+        loop {
+            if self.next_if_keyword("ON") {
+                // ON DELETE|UPDATE SET NULL|SET DEFAULT|CASCADE|RESTRICT|NO ACTION
+                self.next_name()?;
+                let _ = self.next_if_keyword("SET") || self.next_if_keyword("NO");
+                self.next_name()?;
+            } else if self.next_if_keyword("MATCH") {
+                self.next_name()?;
+            } else if self.next_if_keywords(&["NOT", "DEFERRABLE"])
+                || self.next_if_keyword("DEFERRABLE")
+            {
+                if self.next_if_keyword("INITIALLY") {
+                    self.next_name()?;
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Consumes tokens until one of keywords is found outside parentheses
+    /// and returns their SQL text.
+    fn next_until_keyword(&mut self, keywords: &[&str]) -> String {
+        let start = self.pos;
+        let mut depth = 0;
+        while !self.is_empty() && (depth > 0 || !self.peek_any_keyword(keywords)) {
+            match self.next() {
+                Some(Token::LParen) => depth += 1,
+                Some(Token::RParen) => depth -= 1,
+                _ => {}
+            }
+        }
+        Self::sql_text(&self.tokens[start..self.pos])
+    }
+
+    /// Returns the SQL text of the remaining tokens.
+    fn remaining_sql(&self) -> String {
+        Self::sql_text(&self.tokens[self.pos..])
+    }
+
+    fn sql_text(tokens: &[Token]) -> String {
+        tokens
+            .iter()
+            .filter(|token| **token != Token::EOF)
+            .map(ToString::to_string)
+            .collect::<String>()
+            .trim()
+            .to_owned()
+    }
+}
+
+/// Parses the columns of a `CREATE TABLE` statement.
+/// https://www.sqlite.org/lang_createtable.html
+///
+/// The arrow type of a column is given by its affinity. The column constraints
+/// are kept in the field metadata (see `FIELD_*` keys in `model::schema`).
+/// Columns are nullable unless declared NOT NULL.
+pub fn parse_columns_from_ddl(ddl: &str) -> Result<Vec<Field>> {
+    Ok(parse_table_from_ddl(ddl)?.0)
+}
+
+/// Parses a `CREATE TABLE` statement into its columns (see `parse_columns_from_ddl`)
+/// and the table definition.
+pub fn parse_table_from_ddl(ddl: &str) -> Result<(Vec<Field>, TableDef)> {
+    let mut tokens = DdlTokens::new(ddl)?;
+    tokens.next_create("TABLE")?;
+    let definitions = tokens.next_parenthesized_list()?;
+
+    let mut fields = vec![];
+    let mut table_primary_key = vec![];
+    // columns of the PRIMARY KEY and UNIQUE table constraints, true for the PRIMARY KEY
+    let mut table_constraints = vec![];
+    for definition in definitions {
+        if definition.peek_any_keyword(&TABLE_CONSTRAINT_KEYWORDS) {
+            if let Some((columns, primary_key)) = parse_table_constraint(definition)? {
+                if primary_key {
+                    table_primary_key.extend(columns.clone());
+                }
+                table_constraints.push((columns, primary_key));
+            }
+        } else {
+            fields.push(parse_column_def(definition)?);
+        }
+    }
+    let column_primary_key: Vec<usize> = (0..fields.len())
+        .filter(|&index| fields[index].metadata().contains_key(FIELD_PRIMARY_KEY))
+        .collect();
+
+    // PRIMARY KEY (col, ...) table constraint
+    let mut table_primary_key_columns = vec![];
+    for indexed_column in table_primary_key {
+        let Some(position) = fields
+            .iter()
+            .position(|field| field.name().eq_ignore_ascii_case(&indexed_column.name))
+        else {
+            bail!("No such column in PRIMARY KEY: {}", indexed_column.name)
+        };
+        let field = fields.remove(position);
+        let mut metadata = field.metadata().clone();
+        metadata.insert(
+            FIELD_PRIMARY_KEY.to_owned(),
+            sort_order_name(indexed_column.sort_order),
+        );
+        fields.insert(position, field.with_metadata(metadata));
+        table_primary_key_columns.push(position);
+    }
+
+    // table options, e.g. `) WITHOUT ROWID, STRICT`
+    let mut without_rowid = false;
+    while !tokens.is_empty() {
+        if tokens.next_if_keywords(&["WITHOUT", "ROWID"]) {
+            without_rowid = true;
+        } else if !tokens.next_if_keyword("STRICT") && tokens.next() != Some(Token::Comma) {
+            bail!("Unexpected table option: {}", tokens.remaining_sql())
+        }
+    }
+
+    // A single column PRIMARY KEY whose declared type is exactly INTEGER is an alias
+    // for the rowid, except `INTEGER PRIMARY KEY DESC` on the column definition.
+    // https://www.sqlite.org/lang_createtable.html#rowid
+    let rowid_alias = match (
+        column_primary_key.as_slice(),
+        table_primary_key_columns.as_slice(),
+    ) {
+        ([index], []) if fields[*index].metadata()[FIELD_PRIMARY_KEY] == "ASC" => Some(*index),
+        ([], [index]) => Some(*index),
+        _ => None,
+    }
+    .filter(|&index| {
+        !without_rowid
+            && fields[index].metadata()[FIELD_DECLARED_TYPE].eq_ignore_ascii_case("INTEGER")
+    });
+    // the rowid is never NULL
+    if let Some(index) = rowid_alias {
+        let field = fields.remove(index).with_nullable(false);
+        fields.insert(index, field);
+    }
+    // The automatic indexes of the constraints, in the order of the column constraints
+    // then of the table constraints. A constraint on the same columns with the same
+    // collations as a previous one has no index.
+    let mut autoindexes: Vec<IndexDef> = vec![];
+    let mut add_autoindex = |columns: Vec<IndexedColumn>| {
+        let key = |columns: &[IndexedColumn]| -> Vec<(String, Collation)> {
+            columns
+                .iter()
+                .map(|column| {
+                    let collation = column.collation.unwrap_or_else(|| {
+                        fields
+                            .iter()
+                            .find(|field| field.name().eq_ignore_ascii_case(&column.name))
+                            .and_then(|field| field.metadata().get(FIELD_COLLATION))
+                            .and_then(|name| Collation::from_name(name).ok())
+                            .unwrap_or_default()
+                    });
+                    (column.name.to_ascii_lowercase(), collation)
+                })
+                .collect()
+        };
+        if !autoindexes
+            .iter()
+            .any(|index| key(&index.columns) == key(&columns))
+        {
+            autoindexes.push(IndexDef {
+                unique: true,
+                columns,
+                where_clause: None,
+            });
+        }
+    };
+    for (index, field) in fields.iter().enumerate() {
+        let metadata = field.metadata();
+        let primary_key = column_primary_key.contains(&index) && rowid_alias != Some(index);
+        if primary_key || metadata.contains_key(FIELD_UNIQUE) {
+            let sort_order = match metadata.get(FIELD_PRIMARY_KEY) {
+                Some(order) if primary_key && order == "DESC" => SortOrder::Desc,
+                _ => SortOrder::Asc,
+            };
+            add_autoindex(vec![IndexedColumn {
+                name: field.name().clone(),
+                expression: false,
+                collation: None,
+                sort_order,
+            }]);
+        }
+    }
+    for (columns, primary_key) in table_constraints {
+        if !(primary_key && rowid_alias.is_some()) {
+            add_autoindex(columns);
+        }
+    }
+
+    Ok((
+        fields,
+        TableDef {
+            rowid_alias,
+            without_rowid,
+            autoindexes,
+        },
+    ))
+}
+
+/// Parses `column-name [type-name] [column-constraint ...]`.
+/// https://www.sqlite.org/syntax/column-def.html
+fn parse_column_def(mut tokens: DdlTokens) -> Result<Field> {
+    let name = tokens.next_name()?;
+    // the type name is optional and made of any words followed by an optional
+    // size, e.g. `UNSIGNED BIG INT` or `VARCHAR(20)`
+    let decl_type = tokens.next_until_keyword(&COLUMN_CONSTRAINT_KEYWORDS);
+    let affinity = Affinity::from_decl_type(&decl_type);
+    let mut metadata = HashMap::from([
+        (FIELD_AFFINITY.to_owned(), affinity.name().to_owned()),
+        (FIELD_DECLARED_TYPE.to_owned(), decl_type),
+    ]);
+    let mut nullable = true;
+
+    while !tokens.is_empty() {
+        if tokens.next_if_keyword("CONSTRAINT") {
+            tokens.next_name()?;
+        } else if tokens.next_if_keywords(&["PRIMARY", "KEY"]) {
+            let sort_order = if tokens.next_if_keyword("DESC") {
+                SortOrder::Desc
+            } else {
+                tokens.next_if_keyword("ASC");
+                SortOrder::Asc
+            };
+            metadata.insert(FIELD_PRIMARY_KEY.to_owned(), sort_order_name(sort_order));
+            tokens.next_conflict_clause()?;
+            if tokens.next_if_keyword("AUTOINCREMENT") {
+                metadata.insert(FIELD_AUTOINCREMENT.to_owned(), "true".to_owned());
+            }
+        } else if tokens.next_if_keywords(&["NOT", "NULL"]) {
+            nullable = false;
+            tokens.next_conflict_clause()?;
+        } else if tokens.next_if_keyword("NULL") {
+            nullable = true;
+        } else if tokens.next_if_keyword("UNIQUE") {
+            metadata.insert(FIELD_UNIQUE.to_owned(), "true".to_owned());
+            tokens.next_conflict_clause()?;
+        } else if tokens.next_if_keyword("CHECK") {
+            tokens.next_parenthesized()?;
+        } else if tokens.next_if_keyword("DEFAULT") {
+            let default = parse_default(&mut tokens)?;
+            metadata.insert(FIELD_DEFAULT.to_owned(), default);
+        } else if tokens.next_if_keyword("COLLATE") {
+            let collation = tokens.next_name()?.to_ascii_uppercase();
+            metadata.insert(FIELD_COLLATION.to_owned(), collation);
+        } else if tokens.next_if_keyword("REFERENCES") {
+            tokens.next_foreign_key_clause()?;
+        } else if tokens.next_if_keywords(&["GENERATED", "ALWAYS", "AS"])
+            || tokens.next_if_keyword("AS")
+        {
+            tokens.next_parenthesized()?;
+            if !tokens.next_if_keyword("STORED") {
+                tokens.next_if_keyword("VIRTUAL");
+            }
+        } else {
+            bail!(
+                "Unexpected token in the definition of column {name}: {:?}",
+                tokens.peek()
+            )
+        }
+    }
+
+    Ok(Field::new(name, affinity.data_type(), nullable).with_metadata(metadata))
+}
+
+/// Parses the value of `DEFAULT`: a parenthesized expression,
+/// a literal or a signed number. Returns its SQL text.
+fn parse_default(tokens: &mut DdlTokens) -> Result<String> {
+    if tokens.peek() == Some(&Token::LParen) {
+        return tokens.next_parenthesized();
+    }
+    let sign = match tokens.peek() {
+        Some(Token::Minus) => "-",
+        Some(Token::Plus) => "+",
+        _ => "",
+    };
+    if !sign.is_empty() {
+        tokens.next();
+    }
+    match tokens.next() {
+        Some(token) => Ok(format!("{sign}{token}")),
+        None => bail!("Missing DEFAULT value"),
+    }
+}
 
-        fields
+/// Parses a table constraint, returning the columns of a `PRIMARY KEY (...)` or
+/// `UNIQUE (...)` constraint and true for the PRIMARY KEY. None for other constraints,
+/// which do not change the columns.
+/// https://www.sqlite.org/syntax/table-constraint.html
+fn parse_table_constraint(mut tokens: DdlTokens) -> Result<Option<(Vec<IndexedColumn>, bool)>> {
+    if tokens.next_if_keyword("CONSTRAINT") {
+        tokens.next_name()?;
+    }
+    let primary_key = if tokens.next_if_keywords(&["PRIMARY", "KEY"]) {
+        true
+    } else if tokens.next_if_keyword("UNIQUE") {
+        false
     } else {
-        bail!("Invalid DDL statement".to_string())
+        return Ok(None);
+    };
+    let columns = tokens
+        .next_parenthesized_list()?
+        .into_iter()
+        .map(parse_indexed_column)
+        .collect::<Result<_>>()?;
+    Ok(Some((columns, primary_key)))
+}
+
+fn sort_order_name(sort_order: SortOrder) -> String {
+    match sort_order {
+        SortOrder::Asc => "ASC".to_owned(),
+        SortOrder::Desc => "DESC".to_owned(),
+    }
+}
+
+/// Parses a `CREATE INDEX` statement.
+/// https://www.sqlite.org/lang_createindex.html
+pub fn parse_index_from_ddl(ddl: &str) -> Result<IndexDef> {
+    let mut tokens = DdlTokens::new(ddl)?;
+    tokens.expect_keyword("CREATE")?;
+    let unique = tokens.next_if_keyword("UNIQUE");
+    tokens.expect_keyword("INDEX")?;
+    tokens.next_if_keywords(&["IF", "NOT", "EXISTS"]);
+    tokens.next_object_name()?;
+    tokens.expect_keyword("ON")?;
+    tokens.next_name()?;
+    let columns = tokens
+        .next_parenthesized_list()?
+        .into_iter()
+        .map(parse_indexed_column)
+        .collect::<Result<Vec<_>>>()?;
+    let where_clause = if tokens.next_if_keyword("WHERE") {
+        Some(tokens.remaining_sql())
+    } else {
+        None
+    };
+
+    Ok(IndexDef {
+        unique,
+        columns,
+        where_clause,
+    })
+}
+
+/// Parses `expr [COLLATE collation-name] [ASC|DESC]`.
+/// https://www.sqlite.org/syntax/indexed-column.html
+fn parse_indexed_column(mut tokens: DdlTokens) -> Result<IndexedColumn> {
+    let expression_start = tokens.peek_pos();
+    let name = tokens.next_until_keyword(&["COLLATE", "ASC", "DESC"]);
+    let expression_tokens = tokens.tokens[expression_start..tokens.pos]
+        .iter()
+        .filter(|token| !matches!(token, Token::Whitespace(_)))
+        .collect::<Vec<_>>();
+    let (name, expression) = match expression_tokens.as_slice() {
+        [Token::Word(word)] => (word.value.clone(), false),
+        [] => bail!("Indexed column is empty"),
+        _ => (name, true),
+    };
+    let collation = if tokens.next_if_keyword("COLLATE") {
+        Some(Collation::from_name(&tokens.next_name()?)?)
+    } else {
+        None
+    };
+    let sort_order = if tokens.next_if_keyword("DESC") {
+        SortOrder::Desc
+    } else {
+        tokens.next_if_keyword("ASC");
+        SortOrder::Asc
+    };
+    if !tokens.is_empty() {
+        bail!(
+            "Unexpected tokens in indexed column: {}",
+            tokens.remaining_sql()
+        )
+    }
+
+    Ok(IndexedColumn {
+        name,
+        expression,
+        collation,
+        sort_order,
+    })
+}
+
+/// Parses a `CREATE VIEW` statement.
+/// https://www.sqlite.org/lang_createview.html
+pub fn parse_view_from_ddl(ddl: &str) -> Result<ViewDef> {
+    let mut tokens = DdlTokens::new(ddl)?;
+    tokens.next_create("VIEW")?;
+    let columns = if tokens.peek() == Some(&Token::LParen) {
+        tokens
+            .next_parenthesized_list()?
+            .iter_mut()
+            .map(DdlTokens::next_name)
+            .collect::<Result<Vec<_>>>()?
+    } else {
+        vec![]
+    };
+    tokens.expect_keyword("AS")?;
+
+    Ok(ViewDef {
+        columns,
+        select: tokens.remaining_sql(),
+    })
+}
+
+/// Parses the timing and event of a `CREATE TRIGGER` statement.
+/// https://www.sqlite.org/lang_createtrigger.html
+pub fn parse_trigger_from_ddl(ddl: &str) -> Result<TriggerDef> {
+    let mut tokens = DdlTokens::new(ddl)?;
+    tokens.next_create("TRIGGER")?;
+    let timing = if tokens.next_if_keyword("AFTER") {
+        TriggerTiming::After
+    } else if tokens.next_if_keyword("INSTEAD") {
+        tokens.expect_keyword("OF")?;
+        TriggerTiming::InsteadOf
+    } else {
+        // BEFORE is the default when no timing is given
+        tokens.next_if_keyword("BEFORE");
+        TriggerTiming::Before
+    };
+    let event = if tokens.next_if_keyword("DELETE") {
+        TriggerEvent::Delete
+    } else if tokens.next_if_keyword("INSERT") {
+        TriggerEvent::Insert
+    } else if tokens.next_if_keyword("UPDATE") {
+        let mut columns = vec![];
+        if tokens.next_if_keyword("OF") {
+            columns.push(tokens.next_name()?);
+            while tokens.peek() == Some(&Token::Comma) {
+                tokens.next();
+                columns.push(tokens.next_name()?);
+            }
+        }
+        TriggerEvent::Update { columns }
+    } else {
+        bail!(
+            "Expected DELETE, INSERT or UPDATE, found {:?}",
+            tokens.peek()
+        )
+    };
+    tokens.expect_keyword("ON")?;
+    tokens.next_name()?;
+
+    Ok(TriggerDef { timing, event })
+}
+
+/// A DDL statement changing the database schema, executed without a logical plan:
+/// datafusion plans `CREATE TABLE` as a memory table without the SQLite constraints
+/// and does not plan `CREATE INDEX` and `DROP INDEX`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DdlStatement {
+    /// sql is the text stored in sqlite_schema, see `parse_ddl_statement`.
+    CreateTable {
+        name: String,
+        if_not_exists: bool,
+        sql: String,
+    },
+    CreateIndex {
+        name: String,
+        table_name: String,
+        if_not_exists: bool,
+        sql: String,
+    },
+    DropTable {
+        name: String,
+        if_exists: bool,
+    },
+    DropIndex {
+        name: String,
+        if_exists: bool,
+    },
+}
+
+/// Parses `CREATE TABLE`, `CREATE [UNIQUE] INDEX`, `DROP TABLE` and `DROP INDEX`
+/// statements, None for other statements.
+///
+/// The sql of a created object is normalized like sqlite does before storing it in
+/// sqlite_schema: `CREATE TABLE` or `CREATE [UNIQUE] INDEX` followed by the text of the
+/// statement from the object name, without `IF NOT EXISTS`, the schema name and the
+/// trailing semicolon.
+/// https://www.sqlite.org/schematab.html#interpretation_of_the_schema_table
+pub fn parse_ddl_statement(sql: &str) -> Result<Option<DdlStatement>> {
+    let mut tokens = DdlTokens::new(sql)?;
+    // trailing semicolons and whitespace end the statement
+    while matches!(
+        tokens.tokens.last(),
+        Some(Token::SemiColon | Token::Whitespace(_) | Token::EOF)
+    ) {
+        tokens.tokens.pop();
+    }
+
+    if tokens.next_if_keyword("CREATE") {
+        if tokens.peek_any_keyword(&["TEMP", "TEMPORARY"]) {
+            bail!("temporary tables and indexes are not supported")
+        }
+        if tokens.next_if_keyword("VIEW") {
+            bail!("CREATE VIEW is not supported")
+        }
+        if tokens.next_if_keyword("VIRTUAL") {
+            bail!("CREATE VIRTUAL TABLE is not supported")
+        }
+        if tokens.next_if_keyword("TRIGGER") {
+            bail!("CREATE TRIGGER is not supported")
+        }
+        let unique = tokens.next_if_keyword("UNIQUE");
+        let kind = if !unique && tokens.next_if_keyword("TABLE") {
+            "TABLE"
+        } else if tokens.next_if_keyword("INDEX") {
+            if unique {
+                "UNIQUE INDEX"
+            } else {
+                "INDEX"
+            }
+        } else {
+            return Ok(None);
+        };
+        let if_not_exists = tokens.next_if_keywords(&["IF", "NOT", "EXISTS"]);
+        let (name, name_start) = tokens.next_main_object_name()?;
+        let normalized = format!(
+            "CREATE {kind} {}",
+            DdlTokens::sql_text(&tokens.tokens[name_start..])
+        );
+        if kind == "TABLE" {
+            if tokens.peek_keyword("AS") {
+                bail!("CREATE TABLE ... AS SELECT is not supported")
+            }
+            return Ok(Some(DdlStatement::CreateTable {
+                name,
+                if_not_exists,
+                sql: normalized,
+            }));
+        }
+        tokens.expect_keyword("ON")?;
+        let table_name = tokens.next_name()?;
+        return Ok(Some(DdlStatement::CreateIndex {
+            name,
+            table_name,
+            if_not_exists,
+            sql: normalized,
+        }));
+    }
+
+    if tokens.next_if_keyword("DROP") {
+        let table = if tokens.next_if_keyword("TABLE") {
+            true
+        } else if tokens.next_if_keyword("INDEX") {
+            false
+        } else {
+            return Ok(None);
+        };
+        let if_exists = tokens.next_if_keywords(&["IF", "EXISTS"]);
+        let (name, _) = tokens.next_main_object_name()?;
+        if !tokens.is_empty() {
+            bail!("Unexpected tokens after DROP: {}", tokens.remaining_sql())
+        }
+        return Ok(Some(if table {
+            DdlStatement::DropTable { name, if_exists }
+        } else {
+            DdlStatement::DropIndex { name, if_exists }
+        }));
+    }
+    Ok(None)
+}
+
+/// Splits sql into its statements separated by semicolons, without empty statements.
+pub fn split_statements(sql: &str) -> Result<Vec<String>> {
+    let tokens = Tokenizer::new(&SQLiteDialect {}, sql).tokenize()?;
+    Ok(tokens
+        .split(|token| *token == Token::SemiColon)
+        .map(DdlTokens::sql_text)
+        .filter(|statement| !statement.is_empty())
+        .collect())
+}
+
+/// Parses the first statement of sql, None if there is none.
+///
+/// sqlparser does not parse the SQLite operator `x [NOT] GLOB pattern`: it is parsed as
+/// `x [NOT] SIMILAR TO pattern`, an operator SQLite does not have, that
+/// rewrite::sqlite_operators rewrites to the glob function.
+pub fn parse_statement(sql: &str) -> Result<Option<Statement>> {
+    let dialect = SQLiteDialect {};
+    let tokens = Tokenizer::new(&dialect, sql).tokenize()?;
+    let mut rewritten = Vec::with_capacity(tokens.len());
+    for (pos, token) in tokens.iter().enumerate() {
+        if is_glob_operator(&tokens, pos) {
+            rewritten.push(Token::make_keyword("SIMILAR"));
+            rewritten.push(Token::Whitespace(Whitespace::Space));
+            rewritten.push(Token::make_keyword("TO"));
+        } else {
+            rewritten.push(token.clone());
+        }
+    }
+    let statements = Parser::new(&dialect)
+        .with_tokens(rewritten)
+        .parse_statements()?;
+    Ok(statements.into_iter().next())
+}
+
+// Keywords followed by an expression or a name, a GLOB after them is not the operator.
+const EXPRESSION_START_KEYWORDS: [Keyword; 32] = [
+    Keyword::ALL,
+    Keyword::AND,
+    Keyword::AS,
+    Keyword::BETWEEN,
+    Keyword::BY,
+    Keyword::CASE,
+    Keyword::DISTINCT,
+    Keyword::ELSE,
+    Keyword::ESCAPE,
+    Keyword::FROM,
+    Keyword::HAVING,
+    Keyword::IN,
+    Keyword::INTO,
+    Keyword::IS,
+    Keyword::JOIN,
+    Keyword::LIKE,
+    Keyword::LIMIT,
+    Keyword::NOT,
+    Keyword::OFFSET,
+    Keyword::ON,
+    Keyword::OR,
+    Keyword::RETURNING,
+    Keyword::SELECT,
+    Keyword::SET,
+    Keyword::TABLE,
+    Keyword::THEN,
+    Keyword::UPDATE,
+    Keyword::USING,
+    Keyword::VALUES,
+    Keyword::WHEN,
+    Keyword::WHERE,
+    Keyword::WITH,
+];
+
+// Whether the token at pos is the GLOB operator: it follows the end of an expression,
+// possibly with NOT, while the glob function or a column named glob does not, e.g.
+// `SELECT glob('a*', x) FROM t WHERE NOT glob('b*', x)`.
+fn is_glob_operator(tokens: &[Token], pos: usize) -> bool {
+    let is_glob = matches!(&tokens[pos], Token::Word(Word { value, quote_style: None, .. })
+        if value.eq_ignore_ascii_case("GLOB"));
+    if !is_glob {
+        return false;
+    }
+    let mut previous = tokens[..pos]
+        .iter()
+        .rev()
+        .filter(|token| !matches!(token, Token::Whitespace(_)));
+    let mut previous_token = previous.next();
+    if let Some(Token::Word(Word {
+        keyword: Keyword::NOT,
+        quote_style: None,
+        ..
+    })) = previous_token
+    {
+        previous_token = previous.next();
+    }
+    match previous_token {
+        Some(Token::Word(Word {
+            keyword,
+            quote_style: None,
+            ..
+        })) => !EXPRESSION_START_KEYWORDS.contains(keyword),
+        Some(
+            Token::Word(_)
+            | Token::RParen
+            | Token::Number(..)
+            | Token::SingleQuotedString(_)
+            | Token::HexStringLiteral(_)
+            | Token::Placeholder(_),
+        ) => true,
+        _ => false,
     }
 }
 
@@ -57,6 +935,8 @@ fn main() {
 
 #[cfg(test)]
 mod tests {
+    use arrow_schema::DataType;
+
     use super::*;
 
     #[test]
@@ -72,23 +952,351 @@ mod tests {
         assert_eq!(fields.len(), 3);
 
         assert_eq!(fields[0].name(), "id");
-        assert_eq!(fields[0].data_type(), &DataType::Int32);
+        assert_eq!(fields[0].data_type(), &DataType::Int64);
+        assert_eq!(fields[0].metadata()[FIELD_PRIMARY_KEY], "ASC");
+        assert_eq!(fields[0].metadata()[FIELD_AUTOINCREMENT], "true");
 
         assert_eq!(fields[1].name(), "name");
         assert_eq!(fields[1].data_type(), &DataType::Utf8);
 
         assert_eq!(fields[2].name(), "description");
         assert_eq!(fields[2].data_type(), &DataType::Utf8);
+        assert!(fields[2].is_nullable());
+        assert!(!fields[2].metadata().contains_key(FIELD_PRIMARY_KEY));
     }
 
-    #[ignore = "sqlparser cannot parse SQLite statement without data type"]
     #[test]
     fn test_parse_columns_from_ddl() {
         let ddl = "CREATE TABLE sqlite_sequence(name,seq)";
-        // TODO - handle this sqlparser cannot parse SQLite statement without data type
         let fields = parse_columns_from_ddl(ddl).unwrap();
 
         assert_eq!(fields.len(), 2);
+        assert_eq!(fields[0].name(), "name");
+        assert_eq!(fields[1].name(), "seq");
+    }
+
+    #[test]
+    fn test_parse_columns_with_constraints() {
+        let ddl = "CREATE TABLE IF NOT EXISTS main.\"order items\" (\
+            \"item id\" INTEGER NOT NULL DEFAULT (1 + 2), \
+            note varchar(20) COLLATE nocase CHECK (length(note) < 20) DEFAULT 'n/a', \
+            price DECIMAL(10, 2) CONSTRAINT positive CHECK (price > 0) DEFAULT -1.5, \
+            owner REFERENCES users(id) ON DELETE SET NULL NOT DEFERRABLE NOT NULL, \
+            data, \
+            CONSTRAINT pk PRIMARY KEY (\"item id\" DESC, note), \
+            UNIQUE (note, data)\
+        )";
+        let fields = parse_columns_from_ddl(ddl).unwrap();
+
+        assert_eq!(fields.len(), 5);
+        assert_eq!(fields[0].name(), "item id");
+        assert_eq!(fields[0].data_type(), &DataType::Int64);
+        assert!(!fields[0].is_nullable());
+        assert_eq!(fields[0].metadata()[FIELD_DEFAULT], "1 + 2");
+        assert_eq!(fields[0].metadata()[FIELD_PRIMARY_KEY], "DESC");
+
+        assert_eq!(fields[1].name(), "note");
+        assert_eq!(fields[1].data_type(), &DataType::Utf8);
+        assert_eq!(fields[1].metadata()[FIELD_DECLARED_TYPE], "varchar(20)");
+        assert_eq!(fields[1].metadata()[FIELD_COLLATION], "NOCASE");
+        assert_eq!(fields[1].metadata()[FIELD_DEFAULT], "'n/a'");
+        assert_eq!(fields[1].metadata()[FIELD_PRIMARY_KEY], "ASC");
+
+        assert_eq!(fields[2].data_type(), &DataType::Float64);
+        assert_eq!(fields[2].metadata()[FIELD_AFFINITY], "NUMERIC");
+        assert_eq!(fields[2].metadata()[FIELD_DEFAULT], "-1.5");
+
+        assert_eq!(fields[3].name(), "owner");
+        assert_eq!(fields[3].data_type(), &DataType::Null);
+        assert!(!fields[3].is_nullable());
+
+        assert_eq!(fields[4].name(), "data");
+        assert_eq!(fields[4].data_type(), &DataType::Null);
+        assert_eq!(fields[4].metadata()[FIELD_AFFINITY], "BLOB");
+        assert_eq!(fields[4].metadata()[FIELD_DECLARED_TYPE], "");
+        assert!(fields[4].is_nullable());
+    }
+
+    #[test]
+    fn test_parse_table_rowid_alias() {
+        let rowid_alias = |ddl: &str| parse_table_from_ddl(ddl).unwrap().1.rowid_alias;
+
+        assert_eq!(
+            rowid_alias("CREATE TABLE t(id integer primary key, a)"),
+            Some(0)
+        );
+        assert_eq!(
+            rowid_alias("CREATE TABLE t(a, id INTEGER, PRIMARY KEY(id))"),
+            Some(1)
+        );
+        assert_eq!(
+            rowid_alias("CREATE TABLE t(a, id INTEGER, PRIMARY KEY(id DESC))"),
+            Some(1)
+        );
+        // not an alias
+        assert_eq!(rowid_alias("CREATE TABLE t(id int primary key, a)"), None);
+        assert_eq!(
+            rowid_alias("CREATE TABLE t(id integer primary key desc)"),
+            None
+        );
+        assert_eq!(
+            rowid_alias("CREATE TABLE t(a integer, b integer, primary key(a, b))"),
+            None
+        );
+        assert_eq!(rowid_alias("CREATE TABLE t(id text primary key)"), None);
+        assert_eq!(rowid_alias("CREATE TABLE t(id integer)"), None);
+
+        let (fields, table) =
+            parse_table_from_ddl("CREATE TABLE t(id integer primary key, a) WITHOUT ROWID")
+                .unwrap();
+        assert!(table.without_rowid);
+        assert_eq!(table.rowid_alias, None);
+        assert!(fields[0].is_nullable());
+
+        let (fields, _) =
+            parse_table_from_ddl("CREATE TABLE t(id integer primary key, a)").unwrap();
+        assert!(!fields[0].is_nullable());
+    }
+
+    #[test]
+    fn test_parse_table_autoindexes() {
+        let autoindexes = |ddl: &str| -> Vec<Vec<(String, SortOrder)>> {
+            parse_table_from_ddl(ddl)
+                .unwrap()
+                .1
+                .autoindexes
+                .into_iter()
+                .map(|index| {
+                    assert!(index.unique);
+                    index
+                        .columns
+                        .into_iter()
+                        .map(|column| (column.name, column.sort_order))
+                        .collect()
+                })
+                .collect()
+        };
+        let column = |name: &str| vec![(name.to_owned(), SortOrder::Asc)];
+
+        assert!(autoindexes("CREATE TABLE t(id integer primary key, a)").is_empty());
+        assert!(autoindexes("CREATE TABLE t(id integer, a, primary key (id))").is_empty());
+        assert!(autoindexes("CREATE TABLE t(a, b)").is_empty());
+        assert_eq!(
+            autoindexes("CREATE TABLE t(id int primary key, a)"),
+            [column("id")]
+        );
+        assert_eq!(
+            autoindexes("CREATE TABLE t(x primary key desc, y unique unique)"),
+            [vec![("x".to_owned(), SortOrder::Desc)], column("y")]
+        );
+        // numbered like sqlite3: one index for the same columns and collations
+        assert_eq!(
+            autoindexes(
+                "CREATE TABLE t(a unique primary key, b text collate nocase, c, \
+                 unique(b, c desc), unique(c), unique(A), unique(b collate binary))"
+            ),
+            [
+                column("a"),
+                vec![
+                    ("b".to_owned(), SortOrder::Asc),
+                    ("c".to_owned(), SortOrder::Desc)
+                ],
+                column("c"),
+                column("b"),
+            ]
+        );
+        // the PRIMARY KEY of a WITHOUT ROWID table is the first one
+        assert_eq!(
+            autoindexes("CREATE TABLE t(a text primary key, b) WITHOUT ROWID"),
+            [column("a")]
+        );
+        assert_eq!(
+            autoindexes(
+                "CREATE TABLE t(a, b, CONSTRAINT u UNIQUE (a, b), PRIMARY KEY (b)) WITHOUT ROWID"
+            ),
+            [
+                vec![
+                    ("a".to_owned(), SortOrder::Asc),
+                    ("b".to_owned(), SortOrder::Asc)
+                ],
+                column("b")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_columns_affinity() {
+        let ddl = "CREATE TABLE t(a REAL, b DOUBLE PRECISION, c BLOB, d NUMERIC, \
+            e BOOLEAN, f DATETIME, g BIGINT, h CLOB)";
+        let data_types: Vec<DataType> = parse_columns_from_ddl(ddl)
+            .unwrap()
+            .iter()
+            .map(|field| field.data_type().clone())
+            .collect();
+
+        assert_eq!(
+            data_types,
+            vec![
+                DataType::Float64,
+                DataType::Float64,
+                DataType::Null,
+                DataType::Float64,
+                DataType::Float64,
+                DataType::Float64,
+                DataType::Int64,
+                DataType::Utf8,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_index_from_ddl() {
+        let ddl = "CREATE INDEX idx_people_name_age on people(name collate nocase, age desc)";
+        let index = parse_index_from_ddl(ddl).unwrap();
+        assert!(!index.unique);
+        assert_eq!(index.where_clause, None);
+        assert_eq!(
+            index.columns,
+            vec![
+                IndexedColumn {
+                    name: "name".to_owned(),
+                    expression: false,
+                    collation: Some(Collation::NoCase),
+                    sort_order: SortOrder::Asc,
+                },
+                IndexedColumn {
+                    name: "age".to_owned(),
+                    expression: false,
+                    collation: None,
+                    sort_order: SortOrder::Desc,
+                },
+            ]
+        );
+
+        let ddl =
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx ON t (lower(email) ASC) WHERE email IS NOT NULL";
+        let index = parse_index_from_ddl(ddl).unwrap();
+        assert!(index.unique);
+        assert_eq!(index.columns[0].name, "lower(email)");
+        assert!(index.columns[0].expression);
+        assert_eq!(index.where_clause, Some("email IS NOT NULL".to_owned()));
+
+        assert!(parse_index_from_ddl("CREATE INDEX idx ON t (a COLLATE unknown)").is_err());
+    }
+
+    #[test]
+    fn test_parse_view_from_ddl() {
+        let view = parse_view_from_ddl("CREATE VIEW v AS SELECT a, b FROM t WHERE a > 1").unwrap();
+        assert!(view.columns.is_empty());
+        assert_eq!(view.select, "SELECT a, b FROM t WHERE a > 1");
+
+        let view =
+            parse_view_from_ddl("CREATE TEMP VIEW v(x, y) AS\n  SELECT a, b FROM t").unwrap();
+        assert_eq!(view.columns, vec!["x".to_owned(), "y".to_owned()]);
+        assert_eq!(view.select, "SELECT a, b FROM t");
+    }
+
+    #[test]
+    fn test_parse_trigger_from_ddl() {
+        let trigger = parse_trigger_from_ddl(
+            "CREATE TRIGGER trg AFTER UPDATE OF name, email ON users BEGIN SELECT 1; END",
+        )
+        .unwrap();
+        assert_eq!(trigger.timing, TriggerTiming::After);
+        assert_eq!(
+            trigger.event,
+            TriggerEvent::Update {
+                columns: vec!["name".to_owned(), "email".to_owned()]
+            }
+        );
+
+        let trigger =
+            parse_trigger_from_ddl("CREATE TRIGGER trg DELETE ON users BEGIN SELECT 1; END")
+                .unwrap();
+        assert_eq!(trigger.timing, TriggerTiming::Before);
+        assert_eq!(trigger.event, TriggerEvent::Delete);
+
+        let trigger =
+            parse_trigger_from_ddl("CREATE TRIGGER trg INSTEAD OF INSERT ON v BEGIN SELECT 1; END")
+                .unwrap();
+        assert_eq!(trigger.timing, TriggerTiming::InsteadOf);
+        assert_eq!(trigger.event, TriggerEvent::Insert);
+    }
+
+    #[test]
+    fn test_parse_ddl_statement() {
+        let parse = |sql: &str| parse_ddl_statement(sql).unwrap();
+
+        assert_eq!(
+            parse("  create table if not exists main.t ( a,\n b ) ;"),
+            Some(DdlStatement::CreateTable {
+                name: "t".to_owned(),
+                if_not_exists: true,
+                sql: "CREATE TABLE t ( a,\n b )".to_owned(),
+            })
+        );
+        assert_eq!(
+            parse("CREATE  unique INDEX \"my idx\" ON t (a DESC)"),
+            Some(DdlStatement::CreateIndex {
+                name: "my idx".to_owned(),
+                table_name: "t".to_owned(),
+                if_not_exists: false,
+                sql: "CREATE UNIQUE INDEX \"my idx\" ON t (a DESC)".to_owned(),
+            })
+        );
+        assert_eq!(
+            parse("drop table if exists t;"),
+            Some(DdlStatement::DropTable {
+                name: "t".to_owned(),
+                if_exists: true,
+            })
+        );
+        assert_eq!(
+            parse("DROP INDEX main.i"),
+            Some(DdlStatement::DropIndex {
+                name: "i".to_owned(),
+                if_exists: false,
+            })
+        );
+
+        // other statements are planned by datafusion
+        assert_eq!(parse("select * from t"), None);
+        assert_eq!(parse("drop view v"), None);
+
+        assert!(parse_ddl_statement("create temp table t(a)").is_err());
+        assert!(parse_ddl_statement("create view v as select 1").is_err());
+        assert!(parse_ddl_statement("create table t as select 1").is_err());
+        assert!(parse_ddl_statement("create table temp.t(a)").is_err());
+        assert!(parse_ddl_statement("drop table t cascade").is_err());
+    }
+
+    #[test]
+    fn test_parse_statement_glob_operator() {
+        let sql = |sql: &str| parse_statement(sql).unwrap().unwrap().to_string();
+        assert_eq!(
+            sql("select name glob 'G*', name not glob ('F*') from apples"),
+            "SELECT name SIMILAR TO 'G*', name NOT SIMILAR TO ('F*') FROM apples"
+        );
+        // the glob function
+        assert_eq!(
+            sql("select glob('G*', name) from apples where not glob ('F*', name)"),
+            "SELECT glob('G*', name) FROM apples WHERE NOT glob('F*', name)"
+        );
+        // a column named glob
+        assert_eq!(
+            sql("select glob, 1 as glob from t"),
+            "SELECT glob, 1 AS glob FROM t"
+        );
+        assert!(parse_statement("  ").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("create table t(a); insert into t values ('a;b');;\n").unwrap(),
+            vec!["create table t(a)", "insert into t values ('a;b')"]
+        );
+        assert!(split_statements(" ; ").unwrap().is_empty());
     }
 
     #[test]
@@ -0,0 +1,639 @@
+//! Rewrites of parsed statements where datafusion differs from SQLite.
+use std::ops::ControlFlow;
+
+use anyhow::{bail, Result};
+use datafusion_common::DFSchema;
+use datafusion_sql::planner::{ContextProvider, PlannerContext, SqlToRel};
+use datafusion_sql::sqlparser::ast::{
+    visit_expressions, visit_expressions_mut, BinaryOperator, DataType as SqlDataType,
+    ExcludeSelectItem, Expr, Function, FunctionArg, FunctionArgExpr, Ident, ObjectName, Offset,
+    OffsetRows, Query, Select, SelectItem, SetExpr, Statement, TableFactor, UnaryOperator, Value,
+    VisitMut, VisitorMut, WildcardAdditionalOptions,
+};
+
+use crate::model::affinity::Affinity;
+use crate::model::data_record::DataRecord;
+use crate::model::database::Database;
+use crate::model::schema::{SchemaObjType, ROWID_NAMES};
+use crate::physical::physical_planner::create_physical_expr;
+
+/// NULLs are smaller than any value in SQLite: they are first with ORDER BY x ASC
+/// and last with ORDER BY x DESC. datafusion defaults to the opposite, so ORDER BY terms
+/// without NULLS FIRST or NULLS LAST get the SQLite one.
+pub fn sqlite_nulls_order(statement: &mut Statement) {
+    let _ = visit_queries(statement, |query| {
+        for order_by in &mut query.order_by {
+            if order_by.nulls_first.is_none() {
+                order_by.nulls_first = Some(order_by.asc.unwrap_or(true));
+            }
+        }
+        Ok(())
+    });
+}
+
+/// SQLite operators that datafusion does not plan are rewritten to expressions it plans
+/// and that rsql evaluates like SQLite does:
+/// - `x [NOT] GLOB y`, parsed as `x [NOT] SIMILAR TO y` (see parsing::parse_statement),
+///   is `[NOT] glob(y, x)`.
+/// - The operands of AND, OR and NOT and the WHERE and HAVING conditions that are not
+///   booleans, e.g. in `x AND 1`, are cast to BOOLEAN: the NUMERIC affinity of the cast
+///   keeps their truth value.
+/// - The operands of `||` are cast to TEXT, except the text literals and concatenations.
+/// - The operands of `+ - * / %` and of unary `-` and `+` are `arithmetic_operand(x)`,
+///   except the number literals and the arithmetic expressions: datafusion rejects
+///   arithmetic on TEXT like `'3abc' + 1`, which rsql converts to numbers like SQLite.
+/// - `CAST(x AS BLOB)` is `CAST(x AS BYTEA)`, the binary type of datafusion.
+/// - `x COLLATE name` is `collate(x, 'name')`.
+pub fn sqlite_operators(statement: &mut Statement) {
+    // the children of an expression are rewritten before it
+    let _: ControlFlow<()> = visit_expressions_mut(statement, |expr| {
+        match expr {
+            Expr::SimilarTo {
+                negated,
+                expr: value,
+                pattern,
+                escape_char: None,
+            } => {
+                let glob = Expr::Function(Function {
+                    name: ObjectName(vec![Ident::new("glob")]),
+                    args: [pattern, value]
+                        .map(|arg| {
+                            let arg = std::mem::replace(arg.as_mut(), Expr::Value(Value::Null));
+                            FunctionArg::Unnamed(FunctionArgExpr::Expr(arg))
+                        })
+                        .to_vec(),
+                    filter: None,
+                    null_treatment: None,
+                    over: None,
+                    distinct: false,
+                    special: false,
+                    order_by: vec![],
+                });
+                *expr = match negated {
+                    true => Expr::UnaryOp {
+                        op: UnaryOperator::Not,
+                        expr: Box::new(glob),
+                    },
+                    false => glob,
+                };
+            }
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::And | BinaryOperator::Or,
+                right,
+            } => {
+                cast_operand(left, SqlDataType::Boolean, is_boolean);
+                cast_operand(right, SqlDataType::Boolean, is_boolean);
+            }
+            Expr::UnaryOp {
+                op: UnaryOperator::Not,
+                expr: operand,
+            } => cast_operand(operand, SqlDataType::Boolean, is_boolean),
+            Expr::BinaryOp {
+                left,
+                op: BinaryOperator::StringConcat,
+                right,
+            } => {
+                let is_text = |expr: &Expr| {
+                    matches!(
+                        expr,
+                        Expr::Value(Value::SingleQuotedString(_))
+                            | Expr::BinaryOp {
+                                op: BinaryOperator::StringConcat,
+                                ..
+                            }
+                    )
+                };
+                cast_operand(left, SqlDataType::Text, is_text);
+                cast_operand(right, SqlDataType::Text, is_text);
+            }
+            Expr::BinaryOp {
+                left,
+                op:
+                    BinaryOperator::Plus
+                    | BinaryOperator::Minus
+                    | BinaryOperator::Multiply
+                    | BinaryOperator::Divide
+                    | BinaryOperator::Modulo,
+                right,
+            } => {
+                arithmetic_operand(left);
+                arithmetic_operand(right);
+            }
+            Expr::UnaryOp {
+                op: UnaryOperator::Minus | UnaryOperator::Plus,
+                expr: operand,
+            } => arithmetic_operand(operand),
+            Expr::Collate {
+                expr: value,
+                collation,
+            } => {
+                let value = std::mem::replace(value.as_mut(), Expr::Value(Value::Null));
+                let name = Expr::Value(Value::SingleQuotedString(collation.to_string()));
+                *expr = Expr::Function(Function {
+                    name: ObjectName(vec![Ident::new("collate")]),
+                    args: [value, name]
+                        .map(|arg| FunctionArg::Unnamed(FunctionArgExpr::Expr(arg)))
+                        .to_vec(),
+                    filter: None,
+                    null_treatment: None,
+                    over: None,
+                    distinct: false,
+                    special: false,
+                    order_by: vec![],
+                });
+            }
+            Expr::Cast { data_type, .. } | Expr::TryCast { data_type, .. }
+                if matches!(data_type, SqlDataType::Blob(_)) =>
+            {
+                *data_type = SqlDataType::Bytea;
+            }
+            _ => {}
+        }
+        ControlFlow::Continue(())
+    });
+    match statement {
+        Statement::Update { selection, .. } | Statement::Delete { selection, .. } => {
+            if let Some(selection) = selection {
+                cast_operand(selection, SqlDataType::Boolean, is_boolean);
+            }
+        }
+        _ => {}
+    }
+    let _ = visit_queries(statement, |query| {
+        cast_conditions(&mut query.body);
+        Ok(())
+    });
+}
+
+// Casts the WHERE and HAVING conditions of the selects of set_expr to BOOLEAN.
+fn cast_conditions(set_expr: &mut SetExpr) {
+    match set_expr {
+        SetExpr::Select(select) => {
+            for condition in [&mut select.selection, &mut select.having]
+                .into_iter()
+                .flatten()
+            {
+                cast_operand(condition, SqlDataType::Boolean, is_boolean);
+            }
+        }
+        SetExpr::SetOperation { left, right, .. } => {
+            cast_conditions(left);
+            cast_conditions(right);
+        }
+        SetExpr::Query(query) => cast_conditions(&mut query.body),
+        _ => {}
+    }
+}
+
+// Casts operand to data_type unless keep returns true for it.
+fn cast_operand(operand: &mut Expr, data_type: SqlDataType, keep: fn(&Expr) -> bool) {
+    if keep(operand) {
+        return;
+    }
+    let expr = std::mem::replace(operand, Expr::Value(Value::Null));
+    *operand = Expr::Cast {
+        expr: Box::new(expr),
+        data_type,
+        format: None,
+    };
+}
+
+// Wraps operand in arithmetic_operand unless datafusion plans it as a number.
+fn arithmetic_operand(operand: &mut Expr) {
+    if is_number(operand) {
+        return;
+    }
+    let expr = std::mem::replace(operand, Expr::Value(Value::Null));
+    *operand = Expr::Function(Function {
+        name: ObjectName(vec![Ident::new("arithmetic_operand")]),
+        args: vec![FunctionArg::Unnamed(FunctionArgExpr::Expr(expr))],
+        filter: None,
+        null_treatment: None,
+        over: None,
+        distinct: false,
+        special: false,
+        order_by: vec![],
+    });
+}
+
+// Whether expr is a number literal or an arithmetic expression.
+fn is_number(expr: &Expr) -> bool {
+    match expr {
+        Expr::Value(Value::Number(..)) => true,
+        Expr::BinaryOp { op, .. } => matches!(
+            op,
+            BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::Multiply
+                | BinaryOperator::Divide
+                | BinaryOperator::Modulo
+        ),
+        Expr::UnaryOp {
+            op: UnaryOperator::Minus | UnaryOperator::Plus,
+            ..
+        } => true,
+        Expr::Nested(expr) => is_number(expr),
+        Expr::Function(function) => function
+            .name
+            .to_string()
+            .eq_ignore_ascii_case("arithmetic_operand"),
+        _ => false,
+    }
+}
+
+// Whether datafusion plans expr as a boolean.
+fn is_boolean(expr: &Expr) -> bool {
+    match expr {
+        Expr::BinaryOp { op, .. } => matches!(
+            op,
+            BinaryOperator::Eq
+                | BinaryOperator::NotEq
+                | BinaryOperator::Lt
+                | BinaryOperator::LtEq
+                | BinaryOperator::Gt
+                | BinaryOperator::GtEq
+                | BinaryOperator::And
+                | BinaryOperator::Or
+        ),
+        Expr::UnaryOp {
+            op: UnaryOperator::Not,
+            ..
+        } => true,
+        Expr::Nested(expr) => is_boolean(expr),
+        Expr::Function(function) => {
+            let name = function.name.to_string();
+            name.eq_ignore_ascii_case("glob") || name.eq_ignore_ascii_case("like")
+        }
+        Expr::Value(Value::Boolean(_))
+        | Expr::IsNull(_)
+        | Expr::IsNotNull(_)
+        | Expr::IsTrue(_)
+        | Expr::IsNotTrue(_)
+        | Expr::IsFalse(_)
+        | Expr::IsNotFalse(_)
+        | Expr::IsDistinctFrom(..)
+        | Expr::IsNotDistinctFrom(..)
+        | Expr::InList { .. }
+        | Expr::InSubquery { .. }
+        | Expr::Between { .. }
+        | Expr::Like { .. }
+        | Expr::ILike { .. }
+        | Expr::Exists { .. } => true,
+        _ => false,
+    }
+}
+
+/// LIMIT and OFFSET take any constant expression in SQLite, e.g. `LIMIT 2 * 5` or
+/// `LIMIT '10'`, datafusion only integer literals. The expressions are evaluated and
+/// replaced by their integer value.
+/// https://www.sqlite.org/lang_select.html#the_limit_clause
+///
+/// - A value that is not an integer after NUMERIC affinity is an error.
+/// - A negative LIMIT means no limit, a negative OFFSET is 0.
+pub fn sqlite_limit_values<S: ContextProvider>(
+    statement: &mut Statement,
+    sql_to_rel: &SqlToRel<S>,
+) -> Result<()> {
+    visit_queries(statement, |query| {
+        if let Some(limit) = query.limit.take() {
+            let limit = evaluate_integer(limit, sql_to_rel)?;
+            query.limit = (limit >= 0).then(|| integer_literal(limit));
+        }
+        if let Some(offset) = query.offset.take() {
+            let offset = evaluate_integer(offset.value, sql_to_rel)?;
+            query.offset = Some(Offset {
+                value: integer_literal(offset.max(0)),
+                rows: OffsetRows::None,
+            });
+        }
+        Ok(())
+    })
+}
+
+/// The hidden columns `rowid`, `oid` and `_rowid_` of a table (see
+/// `SchemaObject::rowid_columns`) are not returned by `SELECT *`. When the statement
+/// names one of them, the tables are planned with their hidden columns (see
+/// `SqliteContextProvider::with_rowid_columns`) and the wildcards exclude them:
+/// `SELECT * FROM t` is rewritten to `SELECT * EXCLUDE (rowid, oid, _rowid_) FROM t`,
+/// `SELECT * FROM t JOIN u` to `SELECT t.* EXCLUDE (...), u.* EXCLUDE (...) FROM ...`.
+/// Returns whether the statement names a hidden column.
+pub fn sqlite_rowid_columns(statement: &mut Statement, database: &Database) -> bool {
+    let names_rowid = visit_expressions(statement, |expr| {
+        let ident = match expr {
+            Expr::Identifier(ident) => Some(ident),
+            Expr::CompoundIdentifier(idents) => idents.last(),
+            _ => None,
+        };
+        match ident {
+            Some(ident)
+                if ROWID_NAMES
+                    .iter()
+                    .any(|name| ident.value.eq_ignore_ascii_case(name)) =>
+            {
+                ControlFlow::Break(())
+            }
+            _ => ControlFlow::Continue(()),
+        }
+    })
+    .is_break();
+    if names_rowid {
+        let rewriter = RowidWildcards(database);
+        if let Statement::Insert { source, .. } = statement {
+            rewriter.rewrite_set_expr(&mut source.body);
+        }
+        let _ = visit_queries(statement, |query| {
+            rewriter.rewrite_set_expr(&mut query.body);
+            Ok(())
+        });
+    }
+    names_rowid
+}
+
+// Rewrites the wildcards of the selects on the tables of a database.
+struct RowidWildcards<'a>(&'a Database);
+
+impl RowidWildcards<'_> {
+    fn rewrite_set_expr(&self, set_expr: &mut SetExpr) {
+        match set_expr {
+            SetExpr::Select(select) => self.rewrite_select(select),
+            SetExpr::SetOperation { left, right, .. } => {
+                self.rewrite_set_expr(left);
+                self.rewrite_set_expr(right);
+            }
+            _ => {}
+        }
+    }
+
+    fn rewrite_select(&self, select: &mut Select) {
+        // qualifier and hidden columns of each relation in FROM
+        let relations: Vec<(Option<ObjectName>, Vec<Ident>)> = select
+            .from
+            .iter()
+            .flat_map(|table| {
+                std::iter::once(&table.relation)
+                    .chain(table.joins.iter().map(|join| &join.relation))
+            })
+            .map(|relation| self.relation_rowid_columns(relation))
+            .collect();
+
+        let mut projection = vec![];
+        for item in select.projection.drain(..) {
+            match item {
+                SelectItem::Wildcard(options) if relations.len() == 1 => {
+                    projection.push(SelectItem::Wildcard(exclude(options, &relations[0].1)));
+                }
+                // the wildcard of each relation
+                SelectItem::Wildcard(options)
+                    if relations.iter().all(|(qualifier, _)| qualifier.is_some())
+                        && relations.iter().any(|(_, hidden)| !hidden.is_empty()) =>
+                {
+                    for (qualifier, hidden) in &relations {
+                        projection.push(SelectItem::QualifiedWildcard(
+                            qualifier.clone().unwrap(),
+                            exclude(options.clone(), hidden),
+                        ));
+                    }
+                }
+                SelectItem::QualifiedWildcard(name, options) => {
+                    let hidden = relations
+                        .iter()
+                        .find(|(qualifier, _)| {
+                            qualifier.as_ref().is_some_and(|qualifier| {
+                                qualifier
+                                    .to_string()
+                                    .eq_ignore_ascii_case(&name.to_string())
+                            })
+                        })
+                        .map_or(&[][..], |(_, hidden)| hidden);
+                    projection.push(SelectItem::QualifiedWildcard(
+                        name,
+                        exclude(options, hidden),
+                    ));
+                }
+                item => projection.push(item),
+            }
+        }
+        select.projection = projection;
+    }
+
+    // The name qualifying the columns of relation and its hidden rowid columns, none if
+    // it is not a table.
+    fn relation_rowid_columns(&self, relation: &TableFactor) -> (Option<ObjectName>, Vec<Ident>) {
+        match relation {
+            TableFactor::Table { name, alias, .. } => {
+                let hidden = name
+                    .0
+                    .last()
+                    .and_then(|table_name| {
+                        self.0.db_meta.schema_objects.iter().find(|schema_obj| {
+                            schema_obj.obj_type == SchemaObjType::Table
+                                && schema_obj.name.eq_ignore_ascii_case(&table_name.value)
+                        })
+                    })
+                    .map_or(vec![], |table| {
+                        table
+                            .rowid_columns()
+                            .iter()
+                            .map(|field| Ident::new(field.name()))
+                            .collect()
+                    });
+                let qualifier = match alias {
+                    Some(alias) => ObjectName(vec![alias.name.clone()]),
+                    None => name.clone(),
+                };
+                (Some(qualifier), hidden)
+            }
+            TableFactor::Derived {
+                alias: Some(alias), ..
+            } => (Some(ObjectName(vec![alias.name.clone()])), vec![]),
+            _ => (None, vec![]),
+        }
+    }
+}
+
+// The wildcard options excluding the hidden columns.
+fn exclude(mut options: WildcardAdditionalOptions, hidden: &[Ident]) -> WildcardAdditionalOptions {
+    if !hidden.is_empty() && options.opt_exclude.is_none() {
+        options.opt_exclude = Some(ExcludeSelectItem::Multiple(hidden.to_vec()));
+    }
+    options
+}
+
+fn evaluate_integer<S: ContextProvider>(expr: Expr, sql_to_rel: &SqlToRel<S>) -> Result<i64> {
+    let schema = DFSchema::empty();
+    let logical_expr = sql_to_rel.sql_to_expr(expr, &schema, &mut PlannerContext::new())?;
+    let record = DataRecord {
+        values: vec![],
+        rowid: None,
+    };
+    let value = create_physical_expr(&logical_expr, &schema)?
+        .evaluate(&record)
+        .apply_affinity(Affinity::Numeric);
+    match (value.as_i64(), value.as_f64()) {
+        (Some(int), _) => Ok(int),
+        // reals with an integer value like 1.0
+        (None, Some(real))
+            if real.fract() == 0.0 && real >= i64::MIN as f64 && real < i64::MAX as f64 =>
+        {
+            Ok(real as i64)
+        }
+        _ => bail!("datatype mismatch"),
+    }
+}
+
+fn integer_literal(int: i64) -> Expr {
+    Expr::Value(Value::Number(int.to_string(), false))
+}
+
+// Calls f on the statement query and its subqueries: in FROM, in expressions and CTEs.
+fn visit_queries(statement: &mut Statement, f: impl FnMut(&mut Query) -> Result<()>) -> Result<()> {
+    match statement.visit(&mut QueryVisitor(f)) {
+        ControlFlow::Break(err) => Err(err),
+        ControlFlow::Continue(()) => Ok(()),
+    }
+}
+
+struct QueryVisitor<F>(F);
+
+impl<F: FnMut(&mut Query) -> Result<()>> QueryVisitor<F> {
+    fn visit_query(&mut self, query: &mut Query) -> ControlFlow<anyhow::Error> {
+        if let Err(err) = (self.0)(query) {
+            return ControlFlow::Break(err);
+        }
+        if let Some(with) = &mut query.with {
+            for cte in &mut with.cte_tables {
+                self.visit_query(&mut cte.query)?;
+            }
+        }
+        ControlFlow::Continue(())
+    }
+}
+
+impl<F: FnMut(&mut Query) -> Result<()>> VisitorMut for QueryVisitor<F> {
+    type Break = anyhow::Error;
+
+    fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
+        match statement {
+            Statement::Query(query) => self.visit_query(query),
+            _ => ControlFlow::Continue(()),
+        }
+    }
+
+    fn pre_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<Self::Break> {
+        match table_factor {
+            TableFactor::Derived { subquery, .. } => self.visit_query(subquery),
+            _ => ControlFlow::Continue(()),
+        }
+    }
+
+    fn pre_visit_expr(&mut self, expr: &mut Expr) -> ControlFlow<Self::Break> {
+        match expr {
+            Expr::Subquery(subquery)
+            | Expr::InSubquery { subquery, .. }
+            | Expr::Exists { subquery, .. } => self.visit_query(subquery),
+            _ => ControlFlow::Continue(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use datafusion_sql::sqlparser::dialect::AnsiDialect;
+    use datafusion_sql::sqlparser::parser::Parser;
+
+    use super::*;
+    use crate::model::database::Database;
+    use crate::sql::context_provider::SqliteContextProvider;
+
+    fn parse(sql: &str) -> Statement {
+        Parser::parse_sql(&AnsiDialect {}, sql).unwrap().remove(0)
+    }
+
+    #[test]
+    fn test_sqlite_nulls_order() {
+        let mut statement = parse(
+            "SELECT a FROM (SELECT a, b FROM t ORDER BY b DESC) \
+             WHERE a IN (SELECT c FROM u ORDER BY c) ORDER BY a, b NULLS LAST",
+        );
+        sqlite_nulls_order(&mut statement);
+        assert_eq!(
+            statement.to_string(),
+            "SELECT a FROM (SELECT a, b FROM t ORDER BY b DESC NULLS LAST) \
+             WHERE a IN (SELECT c FROM u ORDER BY c NULLS FIRST) \
+             ORDER BY a NULLS FIRST, b NULLS LAST"
+        );
+    }
+
+    #[test]
+    fn test_sqlite_operators() {
+        let mut statement = crate::sql::parsing::parse_statement(
+            "SELECT a AND 1, NOT b, a = 1 OR (b IS NULL), 1 || 'x' || b, CAST(a AS BLOB), \
+             a + 1, -(b * 2.5), 'x' % +c \
+             FROM t WHERE a GLOB 'x*' AND b NOT GLOB c AND a HAVING count(*) \
+             ORDER BY a COLLATE NOCASE",
+        )
+        .unwrap()
+        .unwrap();
+        sqlite_operators(&mut statement);
+        assert_eq!(
+            statement.to_string(),
+            "SELECT CAST(a AS BOOLEAN) AND CAST(1 AS BOOLEAN), NOT CAST(b AS BOOLEAN), \
+             a = 1 OR (b IS NULL), CAST(1 AS TEXT) || 'x' || CAST(b AS TEXT), \
+             CAST(a AS BYTEA), arithmetic_operand(a) + 1, \
+             -(arithmetic_operand(b) * 2.5), arithmetic_operand('x') % +arithmetic_operand(c) \
+             FROM t WHERE glob('x*', a) AND NOT glob(c, b) \
+             AND CAST(a AS BOOLEAN) HAVING CAST(count(*) AS BOOLEAN) \
+             ORDER BY collate(a, 'NOCASE')"
+        );
+    }
+
+    #[test]
+    fn test_sqlite_limit_values() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sales.db");
+        let database = Database::new(path.to_str().unwrap()).unwrap();
+        let provider = SqliteContextProvider::new_for_db(&database);
+        let sql_to_rel = SqlToRel::new(&provider);
+        let mut statement =
+            parse("SELECT a FROM (SELECT a FROM t LIMIT -1 OFFSET 1 + 1) LIMIT '3' OFFSET -2 * 5");
+        sqlite_limit_values(&mut statement, &sql_to_rel).unwrap();
+        assert_eq!(
+            statement.to_string(),
+            "SELECT a FROM (SELECT a FROM t OFFSET 2) LIMIT 3 OFFSET 0"
+        );
+
+        for sql in ["SELECT a FROM t LIMIT 1.5", "SELECT a FROM t LIMIT NULL"] {
+            let err = sqlite_limit_values(&mut parse(sql), &sql_to_rel).unwrap_err();
+            assert_eq!(err.to_string(), "datatype mismatch");
+        }
+    }
+
+    #[test]
+    fn test_sqlite_rowid_columns() {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sample.db");
+        let database = Database::new(path.to_str().unwrap()).unwrap();
+        let mut statement = parse("SELECT * FROM apples");
+        assert!(!sqlite_rowid_columns(&mut statement, &database));
+        assert_eq!(statement.to_string(), "SELECT * FROM apples");
+
+        let mut statement = parse("SELECT *, rowid FROM apples");
+        assert!(sqlite_rowid_columns(&mut statement, &database));
+        assert_eq!(
+            statement.to_string(),
+            "SELECT * EXCLUDE (rowid, oid, _rowid_), rowid FROM apples"
+        );
+        let mut statement =
+            parse("SELECT * FROM apples a JOIN (SELECT * FROM oranges WHERE OID > 1) x");
+        assert!(sqlite_rowid_columns(&mut statement, &database));
+        assert_eq!(
+            statement.to_string(),
+            "SELECT a.* EXCLUDE (rowid, oid, _rowid_), x.* FROM apples AS a \
+             JOIN (SELECT * EXCLUDE (rowid, oid, _rowid_) FROM oranges WHERE OID > 1) AS x"
+        );
+    }
+}
@@ -1,11 +1,12 @@
 use crate::model::page::Page;
 use crate::model::page_id::PageId;
 use crate::storage::disk_manager::DiskManager;
-use std::fs;
-use std::fs::OpenOptions;
-use std::io::{Seek, SeekFrom, Write};
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
 
+use anyhow::Context;
+
 /// Provides a logic abstraction for physical file on disk operations.
 #[derive(Debug)]
 pub struct DefaultDiskManager {
@@ -23,29 +24,62 @@ impl DefaultDiskManager {
         })
     }
 
-    fn db_bytes(&self) -> Vec<u8> {
+    fn db_path(&self) -> PathBuf {
         // CARGO_MANIFEST_DIR is project root /../rust-sqlite
-        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(&self.db_file_path);
-        // TODO read only the needed page instead of the whole thing into mem
-
-        fs::read(db_path).unwrap()
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(&self.db_file_path)
     }
 }
 
 impl DiskManager for DefaultDiskManager {
     /// Read a file from the database file.
     fn read_page(&self, page_id: PageId) -> anyhow::Result<Page> {
-        let db = self.db_bytes();
-        Page::parse(page_id.page_number, self.page_size, db.as_slice())
+        let data = self.read_raw_page(page_id)?;
+        Page::from_bytes(page_id.page_number, data)
     }
 
-    /// Write a file to the database file.
+    /// Read the bytes of a page without parsing a b-tree page header.
+    /// Only the page is read from the file, not the whole database.
+    fn read_raw_page(&self, page_id: PageId) -> anyhow::Result<Vec<u8>> {
+        if page_id.page_number == 0 {
+            anyhow::bail!("Page numbers start at 1")
+        }
+        let page_offset = u64::from(page_id.page_number - 1) * self.page_size as u64;
+        let mut file = File::open(self.db_path())
+            .with_context(|| format!("Cannot open database file {}", self.db_file_path))?;
+        file.seek(SeekFrom::Start(page_offset))?;
+        let mut data = vec![0; self.page_size];
+        file.read_exact(&mut data)
+            .with_context(|| format!("Page {} is out of the database file", page_id.page_number))?;
+        Ok(data)
+    }
+
+    /// Write a page to the database file.
     fn write_page(&mut self, page_id: PageId, page: &Page) -> anyhow::Result<()> {
-        let mut file = OpenOptions::new().write(true).open(&self.db_file_path)?;
+        self.write_raw_page(page_id, &page.data)
+    }
 
-        let position = SeekFrom::Start((page_id.page_number * self.page_size as u32) as u64);
-        file.seek(position)?;
-        file.write_all(&page.data)?;
+    /// Write the bytes of a page to the database file, extending the file
+    /// when the page is past its end.
+    fn write_raw_page(&mut self, page_id: PageId, data: &[u8]) -> anyhow::Result<()> {
+        if page_id.page_number == 0 {
+            anyhow::bail!("Page numbers start at 1")
+        }
+        if data.len() != self.page_size {
+            anyhow::bail!(
+                "Page {} has {} bytes instead of {}",
+                page_id.page_number,
+                data.len(),
+                self.page_size
+            )
+        }
+        // pages are 1-indexed: page 1 is at offset 0
+        let page_offset = u64::from(page_id.page_number - 1) * self.page_size as u64;
+        let mut file = OpenOptions::new()
+            .write(true)
+            .open(self.db_path())
+            .with_context(|| format!("Cannot open database file {}", self.db_file_path))?;
+        file.seek(SeekFrom::Start(page_offset))?;
+        file.write_all(data)?;
         self.num_writes += 1;
 
         Ok(())
@@ -69,5 +103,63 @@ mod tests {
         assert!(page.page_header.is_leaf());
         assert_eq!(page.page_id.page_number, 2);
         assert_eq!(page.page_header.number_of_cells, 4);
+        assert_eq!(page.data.len(), 4096);
+    }
+
+    #[test]
+    fn test_write_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("sample.db");
+        std::fs::copy(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sample.db"),
+            &db_path,
+        )
+        .unwrap();
+        let mut dm = DefaultDiskManager::new(db_path.to_str().unwrap(), 4096).unwrap();
+        let page = dm.read_page(PageId::new(2)).unwrap();
+        // page 2 is written at the offset of page 5, after the end of the file
+        dm.write_page(PageId::new(5), &page).unwrap();
+        dm.write_raw_page(PageId::new(3), &[7; 4096]).unwrap();
+
+        assert_eq!(dm.read_raw_page(PageId::new(5)).unwrap(), page.data);
+        assert_eq!(dm.read_raw_page(PageId::new(3)).unwrap(), vec![7; 4096]);
+        assert_eq!(
+            dm.read_raw_page(PageId::new(2)).unwrap(),
+            page.data,
+            "page 2 is unchanged"
+        );
+        assert!(dm.write_raw_page(PageId::new(4), &[0; 10]).is_err());
+    }
+
+    #[test]
+    fn test_write_modified_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("sample.db");
+        std::fs::copy(
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sample.db"),
+            &db_path,
+        )
+        .unwrap();
+        let mut dm = DefaultDiskManager::new(db_path.to_str().unwrap(), 4096).unwrap();
+        let mut page = dm.read_page(PageId::new(2)).unwrap();
+        page.remove_cell(1, 4096).unwrap();
+        page.insert_cell(3, &[3, 5, 1, 9, 42], 4096).unwrap();
+        dm.write_page(PageId::new(2), &page).unwrap();
+
+        // the header and cells are read back as written
+        let mut written = dm.read_page(PageId::new(2)).unwrap();
+        assert_eq!(written.page_header, page.page_header);
+        assert_eq!(written.get_number_of_cells(), 4);
+        assert_eq!(written.cells(4096), page.cells(4096));
+        assert_eq!(written.cell(3, 4096), &[3, 5, 1, 9, 42]);
+    }
+
+    #[test]
+    fn test_read_page_out_of_file() {
+        let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sample.db");
+        let dm = DefaultDiskManager::new(db_path.to_str().unwrap(), 4096).unwrap();
+
+        assert!(dm.read_page(PageId { page_number: 1000 }).is_err());
+        assert!(dm.read_raw_page(PageId { page_number: 0 }).is_err());
     }
 }
@@ -13,5 +13,11 @@ pub type SharedDiskManager = Rc<RefCell<dyn DiskManager>>;
 pub trait DiskManager: Debug {
     fn read_page(&self, page_id: PageId) -> Result<Page>;
 
+    /// Read the bytes of a page that is not a b-tree page (e.g. an overflow page).
+    fn read_raw_page(&self, page_id: PageId) -> Result<Vec<u8>>;
+
     fn write_page(&mut self, page_id: PageId, page: &Page) -> Result<()>;
+
+    /// Write the bytes of a page that is not a b-tree page (e.g. an overflow page).
+    fn write_raw_page(&mut self, page_id: PageId, data: &[u8]) -> Result<()>;
 }
@@ -11,7 +11,15 @@ impl DiskManager for NoOpDiskManager {
         Ok(Page::dummy())
     }
 
+    fn read_raw_page(&self, _page_id: PageId) -> anyhow::Result<Vec<u8>> {
+        Ok(vec![])
+    }
+
     fn write_page(&mut self, _page_id: PageId, _page: &Page) -> anyhow::Result<()> {
         Ok(())
     }
+
+    fn write_raw_page(&mut self, _page_id: PageId, _data: &[u8]) -> anyhow::Result<()> {
+        Ok(())
+    }
 }
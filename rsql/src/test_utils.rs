@@ -23,6 +23,18 @@ pub fn db_bytes() -> Vec<u8> {
     fs::read(db_path).unwrap()
 }
 
+/// Copies a database of tests/resources to a temporary directory, for the tests
+/// writing to it. The directory is deleted when the returned TempDir is dropped.
+pub fn copy_db(db_name: &str) -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(db_name);
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/resources")
+        .join(db_name);
+    fs::copy(source, &path).unwrap();
+    (dir, path)
+}
+
 pub fn ref_disk_manager() -> SharedDiskManager {
     let db_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/resources/sample.db");
     let disk_manager = DefaultDiskManager::new(db_path.to_str().unwrap(), 4096).unwrap();
@@ -10,7 +10,7 @@ pub fn wal_checksum_bytes(cksum: &mut u32, data: &mut [u8]) {
     let mut checksum = *cksum;
     for &byte in data.iter() {
         checksum = checksum.wrapping_add(byte as u32);
-        checksum = (checksum << 1) | (checksum >> 31);
+        checksum = checksum.rotate_left(1);
     }
     *cksum = checksum;
 }
@@ -1,15 +1,30 @@
+use anyhow::Result;
+use arrow_array::RecordBatch;
+
 use crate::model::data_record::DataRecord;
+use crate::physical::plan::batch::batch_to_records;
 
 /// show CLI output in SQLite format
 pub fn sqlite_show(records: &[DataRecord]) {
     for record in records {
-        let formatted_values: Vec<String> = record
-            .values
-            .iter()
-            .map(|value| value.to_string())
-            .collect();
-
-        let output = formatted_values.join("|");
-        println!("{}", output);
+        sqlite_show_record(record);
     }
 }
+
+/// show one record in SQLite format: values separated by |
+pub fn sqlite_show_record(record: &DataRecord) {
+    let formatted_values: Vec<String> = record
+        .values
+        .iter()
+        .map(|value| value.to_string())
+        .collect();
+
+    let output = formatted_values.join("|");
+    println!("{}", output);
+}
+
+/// show the rows of a batch in SQLite format
+pub fn sqlite_show_batch(batch: &RecordBatch) -> Result<()> {
+    sqlite_show(&batch_to_records(batch)?);
+    Ok(())
+}
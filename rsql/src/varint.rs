@@ -32,6 +32,39 @@ pub fn decode_varint(bytes: &[u8]) -> (i64, usize) {
     (varint, bytes_read)
 }
 
+/// Encode value as a varint of 1 to 9 bytes, the inverse of decode_varint.
+/// The first 8 bytes hold 7 bits each with the high bit set when another byte follows,
+/// the 9th byte holds 8 bits: values >= 2^56 and negative values take 9 bytes.
+pub fn encode_varint(value: i64) -> Vec<u8> {
+    let value = value as u64;
+    if value >> 56 != 0 {
+        let mut bytes = vec![0; 9];
+        bytes[8] = value as u8;
+        let mut rest = value >> 8;
+        for byte in bytes[..8].iter_mut().rev() {
+            *byte = (rest & 0x7f) as u8 | 0x80;
+            rest >>= 7;
+        }
+        return bytes;
+    }
+    let mut bytes = vec![(value & 0x7f) as u8];
+    let mut rest = value >> 7;
+    while rest != 0 {
+        bytes.push((rest & 0x7f) as u8 | 0x80);
+        rest >>= 7;
+    }
+    bytes.reverse();
+    bytes
+}
+
+/// Number of bytes of the varint of value.
+pub fn varint_len(value: i64) -> usize {
+    match value as u64 {
+        u if u >> 56 != 0 => 9,
+        u => (64 - u.leading_zeros() as usize).max(1).div_ceil(7),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +103,47 @@ mod tests {
         assert_eq!(decode_varint(&[0x01; 10]), (1, 1));
         assert_eq!(decode_varint(&[0xff; 10]), (-1, 9));
     }
+
+    #[test]
+    fn write_varint() {
+        assert_eq!(encode_varint(0), [0x00]);
+        assert_eq!(encode_varint(127), [0x7f]);
+        assert_eq!(encode_varint(128), [0x81, 0x00]);
+        assert_eq!(encode_varint(255), [0x81, 0x7f]);
+        assert_eq!(encode_varint(16384), [0x81, 0x80, 0x00]);
+        assert_eq!(encode_varint(-1), [0xff; 9]);
+        // 2^56 - 1 is the largest value on 8 bytes
+        assert_eq!(
+            encode_varint((1 << 56) - 1),
+            [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f]
+        );
+        assert_eq!(
+            encode_varint(1 << 56),
+            [0x80, 0xc0, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x00]
+        );
+    }
+
+    #[test]
+    fn write_and_read_varint_round_trip() {
+        for value in [
+            0,
+            1,
+            127,
+            128,
+            240,
+            2287,
+            16383,
+            16384,
+            (1 << 49) + 3,
+            (1 << 56) - 1,
+            1 << 56,
+            i64::MAX,
+            i64::MIN,
+            -2,
+        ] {
+            let bytes = encode_varint(value);
+            assert_eq!(bytes.len(), varint_len(value));
+            assert_eq!(decode_varint(&bytes), (value, bytes.len()));
+        }
+    }
 }
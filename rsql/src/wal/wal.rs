@@ -89,7 +89,7 @@ impl Wal {
     ///
     /// Why need an explicit close?
     /// - Data Integrity: writing remaining data in WAL back to database before
-    /// ending current session.
+    ///   ending current session.
     /// - Resource Management: releasing resources like file handle, etc.
     pub fn close(&mut self) -> Result<()> {
         Ok(())
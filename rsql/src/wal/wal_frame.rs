@@ -40,8 +40,8 @@ impl WalFrameHeader {
 /// A frame is considered valid if and only if the following conditions are true:
 ///   1. The salt-1 and salt-2 values in the frame-header match salt values in the wal-header.
 ///   2. The checksum values in the final 8 bytes of the frame-header exactly match
-///   the checksum computed consecutively on the first 24 bytes of the WAL header and
-///   the first 8 bytes and the content of all frames up to and including the current frame.
+///      the checksum computed consecutively on the first 24 bytes of the WAL header and
+///      the first 8 bytes and the content of all frames up to and including the current frame.
 #[derive(Debug)]
 pub struct WalFrame {
     pub header: WalFrameHeader,
@@ -0,0 +1,38 @@
+//! Helpers of the CLI tests writing to a database.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use assert_cmd::prelude::*;
+
+/// Copies a database of tests/resources to a temporary directory: statements writing
+/// to the database file work on the copy. The directory is deleted when the returned
+/// TempDir is dropped, like `rsql::test_utils::copy_db` of the unit tests.
+pub fn copy_db(db_name: &str) -> (tempfile::TempDir, PathBuf) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(db_name);
+    let source = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/resources")
+        .join(db_name);
+    std::fs::copy(source, &path).unwrap();
+    (dir, path)
+}
+
+/// Runs `rsql sql <db_path> <sql>`.
+#[allow(dead_code)]
+pub fn sql(db_path: &Path, sql: &str) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args(["sql", db_path.to_str().unwrap(), sql])
+        .assert()
+}
+
+/// Runs `sqlite3 <db_path> <sql>` to check a database written by rsql with SQLite, e.g.
+/// `PRAGMA integrity_check` is `ok` when the b-trees and the indexes agree with the tables.
+#[allow(dead_code)]
+pub fn sqlite3(db_path: &Path, sql: &str) -> String {
+    let output = Command::new("sqlite3")
+        .args([db_path.to_str().unwrap(), sql])
+        .output()
+        .expect("the sqlite3 shell checks the databases written by the tests");
+    String::from_utf8(output.stdout).unwrap()
+}
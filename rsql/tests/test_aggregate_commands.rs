@@ -0,0 +1,125 @@
+use assert_cmd::prelude::*;
+use predicates::ord::eq;
+use std::process::Command;
+
+mod common;
+use common::{copy_db, sqlite3};
+
+const SALES_DB: &str = "tests/resources/sales.db";
+
+fn sql(sql: &str) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args(["sql", SALES_DB, sql])
+        .assert()
+}
+
+#[test]
+fn cli_sql_aggregate_distinct() {
+    sql("select count(distinct product), sum(distinct quantity), \
+         group_concat(distinct product), total(distinct quantity) from sales")
+    .success()
+    .stdout(eq("3|26|apple,pear,plum|26.0\n"));
+}
+
+#[test]
+fn cli_sql_aggregate_group_by_having() {
+    sql("select product, sum(quantity) from sales group by product having count(*) > 1")
+        .success()
+        .stdout(eq("apple|28\npear|12\n"));
+}
+
+#[test]
+fn cli_sql_aggregate_group_by_nocase() {
+    // north and North are the same group, NULL is a group
+    sql("select count(*), avg(quantity) from sales group by region")
+        .success()
+        .stdout(eq("1|1.0\n1|3.0\n3|8.33333333333333\n3|7.0\n"));
+}
+
+#[test]
+fn cli_sql_aggregate_empty_input() {
+    sql("select count(*), total(quantity), group_concat(product, ';') from sales where id > 100")
+        .success()
+        .stdout(eq("0|0.0|NULL\n"));
+}
+
+#[test]
+fn cli_sql_aggregate_sum_integer_overflow() {
+    sql("select sum(x) from big")
+        .failure()
+        .code(1)
+        .stderr(predicates::str::contains("integer overflow"));
+}
+
+#[test]
+fn cli_sql_aggregate_text_and_untyped_columns() {
+    let (_dir, db) = copy_db("sales.db");
+    sqlite3(
+        &db,
+        "create table g (k text, v, t text); \
+         insert into g values ('a', 1, '10'), ('a', '2.5', 'x'), ('b', 3, '3'), \
+         ('b', NULL, '4'), ('c', 'abc', NULL)",
+    );
+    // same rows as sqlite3 returns: the values are converted to numbers by sum, total
+    // and avg, min and max compare them in SQLite sort order
+    common::sql(
+        &db,
+        "select k, sum(v), total(v), avg(v), min(v), max(v), sum(t), avg(t), min(t), max(t) \
+         from g group by k",
+    )
+    .success()
+    .stdout(eq("a|3.5|3.5|1.75|1|2.5|10.0|5.0|10|x\n\
+                b|3|3.0|3.0|3|3|7|3.5|3|4\n\
+                c|0.0|0.0|0.0|abc|abc|NULL|NULL|NULL|NULL\n"));
+    common::sql(
+        &db,
+        "select sum(t) + 1, max(v) from g where k <> 'c' having min(t) < 'a'",
+    )
+    .success()
+    .stdout(eq("18.0|2.5\n"));
+}
+
+#[test]
+fn cli_sql_aggregate_untyped_and_blob_columns() {
+    let (_dir, db) = copy_db("sales.db");
+    sqlite3(
+        &db,
+        "create table m (v, b blob); \
+         insert into m values (1, x'41'), ('a', x'4243'), (2.5, NULL), (x'44', x'')",
+    );
+    // same rows as sqlite3 returns: min and max of a column without a type are operands
+    // of arithmetic, group_concat converts blobs to text
+    common::sql(
+        &db,
+        "select max(v) - min(v), min(v) + 1, max(b) || '!', \
+         group_concat(b), group_concat(b, '-'), group_concat(distinct b) from m",
+    )
+    .success()
+    .stdout(eq("-1|2|BC!|A,BC,|A-BC-|A,BC,\n"));
+}
+
+#[test]
+fn cli_sql_aggregate_and_join_batches() {
+    // hash aggregates and hash joins of batches: same rows as sqlite3 returns
+    let sales_db = std::path::Path::new(SALES_DB);
+    for query in [
+        "select product, count(*), sum(quantity * 2), max(price) from sales \
+         where quantity > 1 and price < 100 group by product order by product",
+        "select a.id, b.id, ifnull(a.quantity + b.quantity, 'none') from sales a join sales b \
+         on a.product = b.product and a.region = b.region where a.id <> b.id order by a.id, b.id",
+        "select s.product, count(b.x) from sales s left join big b on s.quantity = b.x \
+         group by s.product order by 1",
+    ] {
+        let expected = sqlite3(sales_db, query);
+        for extra_args in [vec![], vec!["--batch"]] {
+            Command::cargo_bin("rsql")
+                .unwrap()
+                .args(["sql", SALES_DB, query])
+                .args(extra_args)
+                .assert()
+                .success()
+                .stdout(eq(expected.clone()));
+        }
+    }
+}
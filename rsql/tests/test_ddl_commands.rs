@@ -0,0 +1,234 @@
+use predicates::ord::eq;
+
+mod common;
+use common::{copy_db, sql, sqlite3};
+
+#[test]
+fn cli_sql_create_table() {
+    let (_dir, db) = copy_db("sample.db");
+    // the statements after CREATE TABLE see the new table
+    sql(
+        &db,
+        "create table scratch (id integer primary key, name text not null, score real); \
+         insert into scratch (name, score) values ('a', 1.5), ('b', 2.5); \
+         select * from scratch",
+    )
+    .success()
+    .stdout(eq("1|a|1.5\n2|b|2.5\n"));
+    sql(&db, "insert into scratch (name) values ('c')").success();
+    sql(&db, "select count(*) from scratch")
+        .success()
+        .stdout(eq("3\n"));
+
+    sql(&db, "create table if not exists scratch (x)").success();
+    sql(&db, "create table scratch (x)")
+        .failure()
+        .stderr(predicates::str::contains("table scratch already exists"));
+    sql(&db, "create table sqlite_x (x)")
+        .failure()
+        .stderr(predicates::str::contains(
+            "object name reserved for internal use: sqlite_x",
+        ));
+}
+
+#[test]
+fn cli_sql_create_autoincrement_table() {
+    let (_dir, db) = copy_db("index.db");
+    // the first AUTOINCREMENT table creates sqlite_sequence
+    sql(
+        &db,
+        "create table events (id integer primary key autoincrement, name text); \
+         insert into events (name) values ('start'), ('stop'); \
+         select * from sqlite_sequence",
+    )
+    .success()
+    .stdout(eq("events|2\n"));
+}
+
+#[test]
+fn cli_sql_create_index() {
+    let (_dir, db) = copy_db("sample.db");
+    // the index is built from the rows of the table, then used by the filter
+    sql(&db, "create index idx_apples_color on apples (color desc)").success();
+    sql(&db, "select name from apples where color = 'Red'")
+        .success()
+        .stdout(eq("Fuji\n"));
+    // new rows are inserted in the index
+    sql(
+        &db,
+        "insert into apples (name, color) values ('Gala', 'Red')",
+    )
+    .success();
+    sql(
+        &db,
+        "select name from apples where color >= 'Red' order by name",
+    )
+    .success()
+    .stdout(eq("Fuji\nGala\nGolden Delicious\n"));
+
+    sql(
+        &db,
+        "create unique index idx_apples_unique on apples (color)",
+    )
+    .failure()
+    .stderr(predicates::str::contains(
+        "UNIQUE constraint failed: apples.color",
+    ));
+    // the failed statement created nothing
+    sql(
+        &db,
+        "create unique index idx_apples_unique on apples (name)",
+    )
+    .success();
+    sql(&db, "create index idx_oranges on oranges (size)")
+        .failure()
+        .stderr(predicates::str::contains("no such column: size"));
+}
+
+#[test]
+fn cli_sql_drop_table_and_index() {
+    let (_dir, db) = copy_db("orders.db");
+    sql(&db, "drop index idx_customers_email").success();
+    sql(&db, "drop index idx_customers_email")
+        .failure()
+        .stderr(predicates::str::contains(
+            "no such index: idx_customers_email",
+        ));
+    // the filter scans the table without the index
+    sql(
+        &db,
+        "select id from customers where email = 'C3@example.com'",
+    )
+    .success()
+    .stdout(eq("9\n4509\n"));
+
+    sql(&db, "drop table customers; drop table if exists customers").success();
+    sql(&db, "select count(*) from customers")
+        .failure()
+        .stderr(predicates::str::contains("Table not found: customers"));
+    // the freed pages are used by the next table
+    sql(
+        &db,
+        "create table notes (body text); insert into notes values ('freed')",
+    )
+    .success();
+    sql(&db, "select * from notes")
+        .success()
+        .stdout(eq("freed\n"));
+}
+
+#[test]
+fn cli_sql_create_table_with_autoindexes() {
+    let (_dir, db) = copy_db("sample.db");
+    // sqlite_autoindex_users_1 for the column UNIQUE, 2 for the PRIMARY KEY and 3 for
+    // the table UNIQUE
+    sql(
+        &db,
+        "create table users (email text unique, name text primary key, \
+         first text, last text, unique (first, last)); \
+         insert into users values ('a@x', 'ann', 'Ann', 'Lee'), ('b@x', 'bob', 'Bob', 'Lee')",
+    )
+    .success();
+    assert_eq!(
+        sqlite3(
+            &db,
+            "select name, sql is null from sqlite_schema where tbl_name = 'users'"
+        ),
+        "users|0\n\
+         sqlite_autoindex_users_1|1\n\
+         sqlite_autoindex_users_2|1\n\
+         sqlite_autoindex_users_3|1\n"
+    );
+
+    sql(&db, "insert into users values ('a@x', 'cat', 'Cat', 'Ray')")
+        .failure()
+        .stderr(predicates::str::contains(
+            "UNIQUE constraint failed: users.email",
+        ));
+    sql(&db, "insert into users values ('c@x', 'cat', 'Bob', 'Lee')")
+        .failure()
+        .stderr(predicates::str::contains(
+            "UNIQUE constraint failed: users.first, users.last",
+        ));
+    sql(
+        &db,
+        "insert into users values ('c@x', 'cat', 'Cat', 'Lee'); \
+         update users set email = 'd@x' where name = 'bob'; \
+         delete from users where name = 'ann'",
+    )
+    .success();
+    sql(&db, "select email from users where email = 'd@x'")
+        .success()
+        .stdout(eq("d@x\n"));
+    assert_eq!(sqlite3(&db, "pragma integrity_check"), "ok\n");
+
+    sql(&db, "drop index sqlite_autoindex_users_1")
+        .failure()
+        .stderr(predicates::str::contains(
+            "index associated with UNIQUE or PRIMARY KEY constraint cannot be dropped",
+        ));
+    sql(&db, "drop table users").success();
+    assert_eq!(
+        sqlite3(
+            &db,
+            "select count(*) from sqlite_schema where tbl_name = 'users'"
+        ),
+        "0\n"
+    );
+    assert_eq!(sqlite3(&db, "pragma integrity_check"), "ok\n");
+}
+
+#[test]
+fn cli_sql_create_unsupported_objects() {
+    let (_dir, db) = copy_db("sample.db");
+    for (statement, error) in [
+        (
+            "create table kv (k text primary key, v) without rowid",
+            "cannot create table kv: WITHOUT ROWID tables are not supported",
+        ),
+        (
+            "create table red as select * from apples",
+            "CREATE TABLE ... AS SELECT is not supported",
+        ),
+        (
+            "create view red as select * from apples",
+            "CREATE VIEW is not supported",
+        ),
+        (
+            "create virtual table docs using fts5(body)",
+            "CREATE VIRTUAL TABLE is not supported",
+        ),
+        (
+            "create trigger t after insert on apples begin select 1; end",
+            "CREATE TRIGGER is not supported",
+        ),
+        (
+            "create index idx_lower on apples (lower(name))",
+            "cannot create index idx_lower on expression lower(name)",
+        ),
+    ] {
+        sql(&db, statement)
+            .failure()
+            .stderr(predicates::str::contains(error));
+    }
+    // nothing was written to the schema
+    assert_eq!(
+        sqlite3(&db, "select group_concat(name) from sqlite_schema"),
+        "apples,sqlite_sequence,oranges\n"
+    );
+}
+
+#[test]
+fn cli_sql_create_table_sqlite_version_number() {
+    let (_dir, db) = copy_db("sample.db");
+    sql(&db, "create table scratch (x)").success();
+    // the version-valid-for number is the file change counter, and the sqlite version
+    // number is the one of the SQLite file format written by rsql
+    let header = std::fs::read(&db).unwrap();
+    assert_eq!(header[24..28], header[92..96]);
+    assert_eq!(
+        u32::from_be_bytes(header[96..100].try_into().unwrap()),
+        3_051_002
+    );
+    assert_eq!(sqlite3(&db, "pragma integrity_check"), "ok\n");
+}
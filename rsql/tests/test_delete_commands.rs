@@ -0,0 +1,56 @@
+use predicates::ord::eq;
+
+mod common;
+use common::{copy_db, sql};
+
+#[test]
+fn cli_sql_delete_where() {
+    let (_dir, db) = copy_db("sample.db");
+    sql(&db, "delete from apples where color like '%red%'")
+        .success()
+        .stdout(eq(""));
+    sql(&db, "select * from apples").success().stdout(eq(
+        "1|Granny Smith|Light Green\n4|Golden Delicious|Yellow\n",
+    ));
+    sql(&db, "delete from oranges").success();
+    sql(&db, "select count(*) from oranges")
+        .success()
+        .stdout(eq("0\n"));
+}
+
+#[test]
+fn cli_sql_delete_merges_pages() {
+    let (_dir, db) = copy_db("sample.db");
+    // 4 rows doubled 10 times span many pages
+    for _ in 0..10 {
+        sql(
+            &db,
+            "insert into apples (name, color) select name || id, color from apples",
+        )
+        .success();
+    }
+    sql(&db, "delete from apples where id % 100 <> 0").success();
+    sql(&db, "select count(*), min(id), max(id) from apples")
+        .success()
+        .stdout(eq("40|100|4000\n"));
+    // the rows are found again after the pages are merged
+    sql(&db, "select name from apples where id = 4000")
+        .success()
+        .stdout(eq("Golden Delicious4816321604169281952\n"));
+}
+
+#[test]
+fn cli_sql_delete_updates_indexes() {
+    let (_dir, db) = copy_db("orders.db");
+    sql(&db, "delete from customers where id > 100").success();
+    // searched with the index on email COLLATE NOCASE
+    sql(
+        &db,
+        "select id from customers where email in ('C3@example.com', 'c35@example.com')",
+    )
+    .success()
+    .stdout(eq("9\n"));
+    sql(&db, "select count(*) from customers where email >= 'c'")
+        .success()
+        .stdout(eq("33\n"));
+}
@@ -0,0 +1,119 @@
+use predicates::ord::eq;
+
+mod common;
+use common::{copy_db, sql, sqlite3};
+
+#[test]
+fn cli_sql_insert_values() {
+    let (_dir, db) = copy_db("sample.db");
+    sql(
+        &db,
+        "insert into apples (color, name) values ('Green', 'Gala'), ('Red', NULL)",
+    )
+    .success()
+    .stdout(eq(""));
+    // the INTEGER PRIMARY KEY is the rowid, the largest one plus 1 if not given
+    sql(&db, "insert into apples values (10, 'Jazz', 3)").success();
+    sql(&db, "insert into apples (name) values ('Envy')").success();
+    sql(&db, "select * from apples where id > 4")
+        .success()
+        .stdout(eq("5|Gala|Green\n6|NULL|Red\n10|Jazz|3\n11|Envy|NULL\n"));
+    // AUTOINCREMENT keeps the largest rowid in sqlite_sequence
+    sql(&db, "select * from sqlite_sequence")
+        .success()
+        .stdout(eq("apples|11\noranges|6\n"));
+}
+
+#[test]
+fn cli_sql_insert_select_splits_pages() {
+    let (_dir, db) = copy_db("sample.db");
+    // 4 rows doubled 10 times span many pages
+    for _ in 0..10 {
+        sql(
+            &db,
+            "insert into apples (name, color) select name || id, color from apples",
+        )
+        .success();
+    }
+    sql(&db, "select count(*), min(id), max(id) from apples")
+        .success()
+        .stdout(eq("4096|1|4096\n"));
+    sql(&db, "select name from apples where id = 4096")
+        .success()
+        .stdout(eq("Golden Delicious4816326412825651210242048\n"));
+}
+
+#[test]
+fn cli_sql_insert_constraints() {
+    let (_dir, db) = copy_db("sample.db");
+    sql(&db, "insert into apples values (2, 'Gala', 'Green')")
+        .failure()
+        .stderr(predicates::str::contains(
+            "UNIQUE constraint failed: apples.id",
+        ));
+    sql(&db, "insert into apples values ('x', 'Gala', 'Green')")
+        .failure()
+        .stderr(predicates::str::contains("datatype mismatch"));
+    // a failed statement writes nothing
+    sql(
+        &db,
+        "insert into apples (id, name) select id + 4, name from apples where id < 3 \
+              union all select 1, 'duplicate'",
+    )
+    .failure();
+    sql(&db, "select count(*) from apples")
+        .success()
+        .stdout(eq("4\n"));
+}
+
+#[test]
+fn cli_sql_insert_updates_indexes() {
+    let (_dir, db) = copy_db("orders.db");
+    sql(
+        &db,
+        "insert into customers (name, email) values ('new', 'NEW@example.com')",
+    )
+    .success();
+    // found with the index on email COLLATE NOCASE
+    sql(
+        &db,
+        "select id, name from customers where email = 'new@EXAMPLE.com'",
+    )
+    .success()
+    .stdout(eq("6001|new\n"));
+}
+
+#[test]
+fn cli_sql_insert_mixed_storage_classes() {
+    let (_dir, db) = copy_db("sample.db");
+    // each value keeps its storage class in the columns without a type and the BLOB
+    // column, the REAL column converts the integers and the numeric text
+    sql(
+        &db,
+        "create table mixed (a, b blob, r real); \
+         insert into mixed values (1, 'two', 3), (2.5, x'0102', '4.5'); \
+         insert into mixed (b, a) values (NULL, 'x'), (7, 1.0)",
+    )
+    .success();
+    sql(&db, "insert into mixed values (1)")
+        .failure()
+        .stderr(predicates::str::contains(
+            "table mixed has 3 columns but 1 values were supplied",
+        ));
+    sql(&db, "insert into mixed (c) values (1)")
+        .failure()
+        .stderr(predicates::str::contains(
+            "table mixed has no column named c",
+        ));
+    assert_eq!(
+        sqlite3(
+            &db,
+            "select typeof(a), typeof(b), typeof(r), quote(a), quote(b), quote(r) from mixed"
+        ),
+        "integer|text|real|1|'two'|3.0\n\
+         real|blob|real|2.5|X'0102'|4.5\n\
+         text|null|null|'x'|NULL|NULL\n\
+         real|integer|null|1.0|7|NULL\n"
+    );
+    assert_eq!(sqlite3(&db, "pragma integrity_check"), "ok\n");
+}
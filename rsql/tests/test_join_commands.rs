@@ -42,3 +42,113 @@ fn cli_sql_inner_join_two_tables() {
 4|Golden Delicious|Yellow|4|Seville|Bitter Orange"#,
         ));
 }
+
+fn sales_sql(sql: &str) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args(["sql", "tests/resources/sales.db", sql])
+        .assert()
+}
+
+#[test]
+fn cli_sql_left_join() {
+    sales_sql("select s.id, b.x from sales s left join big b on s.quantity = b.x order by s.id")
+        .success()
+        .stdout(predicates::ord::eq(
+            "1|NULL\n2|NULL\n3|NULL\n4|NULL\n5|NULL\n6|NULL\n7|1\n8|NULL\n",
+        ));
+}
+
+#[test]
+fn cli_sql_full_join() {
+    sales_sql("select b.x, s.id from big b full join sales s on s.quantity = b.x order by 1, 2")
+        .success()
+        .stdout(predicates::ord::eq(
+            "NULL|1\nNULL|2\nNULL|3\nNULL|4\nNULL|5\nNULL|6\nNULL|8\n1|7\n9223372036854775807|NULL\n",
+        ));
+}
+
+#[test]
+fn cli_sql_join_residual_filter() {
+    // equality on product is the hash key, the rest of the condition filters the matches
+    sales_sql(
+        "select a.id, b.id from sales a join sales b \
+         on a.product = b.product and a.id < b.id and b.quantity > 5 order by a.id, b.id",
+    )
+    .success()
+    .stdout(predicates::ord::eq("1|4\n1|8\n2|6\n3|4\n3|8\n4|8\n7|8\n"));
+}
+
+#[test]
+fn cli_sql_join_using_collation() {
+    // region is COLLATE NOCASE: north and North match
+    sales_sql("select a.id, b.id from sales a join sales b using (region) where a.id = 2")
+        .success()
+        .stdout(predicates::ord::eq("2|1\n2|2\n2|8\n"));
+}
+
+#[test]
+fn cli_sql_theta_join() {
+    // no equality in the condition: nested loop join
+    sales_sql(
+        "select s.id, b.x from sales s join big b \
+         on s.quantity between b.x and b.x + 9 order by s.id",
+    )
+    .success()
+    .stdout(predicates::ord::eq("1|1\n2|1\n4|1\n5|1\n6|1\n7|1\n8|1\n"));
+}
+
+#[test]
+fn cli_sql_cross_join() {
+    sales_sql("select s.id, b.x from sales s, big b where s.id < 3 order by s.id, b.x")
+        .success()
+        .stdout(predicates::ord::eq(
+            "1|1\n1|9223372036854775807\n2|1\n2|9223372036854775807\n",
+        ));
+}
+
+#[test]
+fn cli_sql_rowid_merge_join() {
+    // both sides are read in rowid order: sort-merge join
+    sales_sql(
+        "select a.id, b.region from sales a \
+         left join (select * from sales where quantity > 5) b on a.id = b.id",
+    )
+    .success()
+    .stdout(predicates::ord::eq(
+        "1|north\n2|NULL\n3|NULL\n4|south\n5|NULL\n6|south\n7|NULL\n8|north\n",
+    ));
+}
+
+fn orders_sql(sql: &str) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args(["sql", "tests/resources/orders.db", sql])
+        .assert()
+}
+
+#[test]
+fn cli_sql_join_rowid_lookup() {
+    // customers is searched by rowid for each order, the missing orders have no customer
+    orders_sql(
+        "select o.id, c.name from orders o join customers c \
+         on o.cust_id = c.id where o.id < 15",
+    )
+    .success()
+    .stdout(predicates::ord::eq(
+        "3|customer37\n6|customer74\n7|customer21\n9|customer111\n12|customer148\n14|customer42\n",
+    ));
+}
+
+#[test]
+fn cli_sql_join_index_lookup() {
+    // customers is searched with idx_customers_email, its emails are COLLATE NOCASE
+    orders_sql(
+        "select o.id, c.id from orders o left join customers c \
+         on c.email = o.email where o.id between 135 and 138",
+    )
+    .success()
+    .stdout(predicates::ord::eq(
+        "135|4455\n136|4488\n137|NULL\n138|NULL\n",
+    ));
+}
@@ -0,0 +1,54 @@
+use assert_cmd::prelude::*;
+use predicates::ord::eq;
+use std::process::Command;
+
+fn sql(db: &str, sql: &str) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args(["sql", &format!("tests/resources/{db}"), sql])
+        .assert()
+}
+
+#[test]
+fn cli_sql_limit_offset() {
+    // table of 200 rows on several pages
+    sql("index.db", "select * from people limit 3 offset 100")
+        .success()
+        .stdout(eq("101|frank1|77\n102|Grace2|84\n103|heidi3|1\n"));
+    sql("sales.db", "select id from sales limit -1 offset 6")
+        .success()
+        .stdout(eq("7\n8\n"));
+}
+
+#[test]
+fn cli_sql_limit_expression() {
+    sql("sales.db", "select id from sales limit 1 + 1 offset '3'")
+        .success()
+        .stdout(eq("4\n5\n"));
+    sql("sales.db", "select id from sales limit 1.5")
+        .failure()
+        .stderr(predicates::str::contains("datatype mismatch"));
+}
+
+#[test]
+fn cli_sql_order_by_limit_offset() {
+    sql(
+        "sales.db",
+        "select id, product from sales order by product, id limit 2 offset 2",
+    )
+    .success()
+    .stdout(eq("4|apple\n7|apple\n"));
+}
+
+#[test]
+fn cli_sql_distinct() {
+    // region is COLLATE NOCASE: north and North are the same
+    sql("sales.db", "select distinct region from sales")
+        .success()
+        .stdout(eq("north\nsouth\neast\nNULL\n"));
+    sql("sales.db", "select distinct product, quantity from sales")
+        .success()
+        .stdout(eq(
+            "apple|10\npear|5\napple|NULL\napple|7\nplum|3\npear|7\napple|1\n",
+        ));
+}
@@ -0,0 +1,116 @@
+use assert_cmd::prelude::*;
+use predicates::ord::eq;
+use std::process::Command;
+
+const SALES_DB: &str = "tests/resources/sales.db";
+
+fn sql(sql: &str) -> assert_cmd::assert::Assert {
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args(["sql", SALES_DB, sql])
+        .assert()
+}
+
+#[test]
+fn cli_sql_order_by_nulls_like_sqlite() {
+    // NULL is the smallest value: first with ASC, last with DESC
+    sql("select id, quantity from sales order by quantity, id desc")
+        .success()
+        .stdout(eq("3|NULL\n7|1\n5|3\n2|5\n6|7\n4|7\n8|10\n1|10\n"));
+    sql("select id from sales order by quantity desc, id")
+        .success()
+        .stdout(eq("1\n8\n4\n6\n2\n5\n7\n3\n"));
+    sql("select id from sales order by quantity desc nulls first, id")
+        .success()
+        .stdout(eq("3\n1\n8\n4\n6\n2\n5\n7\n"));
+}
+
+#[test]
+fn cli_sql_order_by_collation() {
+    // region is COLLATE NOCASE: north and North are equal
+    sql("select id, region from sales where region like 'n%' order by region, id desc")
+        .success()
+        .stdout(eq("8|north\n2|North\n1|north\n"));
+}
+
+#[test]
+fn cli_sql_order_by_collate_operator() {
+    // the COLLATE operator takes precedence over the collation of the column: same rows
+    // as sqlite3 returns
+    sql("select id, region from sales order by region collate nocase desc, id")
+        .success()
+        .stdout(eq(
+            "3|south\n4|south\n6|south\n1|north\n2|North\n8|north\n5|east\n7|NULL\n",
+        ));
+    sql("select id, region from sales where region like 'n%' order by region collate binary, id")
+        .success()
+        .stdout(eq("2|North\n1|north\n8|north\n"));
+    sql("select id from sales order by region collate foo")
+        .failure()
+        .stderr(predicates::str::contains("no such collation sequence: foo"));
+}
+
+#[test]
+fn cli_sql_order_by_aggregate() {
+    sql("select product, sum(quantity) s from sales group by product order by s desc")
+        .success()
+        .stdout(eq("apple|28\npear|12\nplum|3\n"));
+}
+
+#[test]
+fn cli_sql_order_by_limit() {
+    sql("select id, product from sales order by product desc, id limit 3")
+        .success()
+        .stdout(eq("5|plum\n2|pear\n6|pear\n"));
+}
+
+#[test]
+fn cli_sql_order_by_rowid_desc() {
+    // the customers table B-Tree is walked backward from the last rowid <= 4000
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args([
+            "sql",
+            "tests/resources/orders.db",
+            "select id, name from customers where id <= 4000 order by id desc limit 3",
+        ])
+        .assert()
+        .success()
+        .stdout(eq(
+            "3999|customer1333\n3996|customer1332\n3993|customer1331\n",
+        ));
+    Command::cargo_bin("rsql")
+        .unwrap()
+        .args([
+            "sql",
+            "tests/resources/orders.db",
+            "select max(id), min(id) from customers where id between 10 and 20",
+        ])
+        .assert()
+        .success()
+        .stdout(eq("18|12\n"));
+}
+
+#[test]
+fn cli_sql_order_by_rowid_desc_deleted_interior_key() {
+    // the rows 101, 102 and 138 of deleted.db were deleted by sqlite3, the interior keys
+    // 101 and 138 were kept: same rows as sqlite3 returns
+    for (sql, rows) in [
+        (
+            "select id from t where id <= 102 order by id desc limit 3",
+            "100\n99\n98\n",
+        ),
+        (
+            "select id from t where id < 139 order by id desc limit 2",
+            "137\n136\n",
+        ),
+        ("select max(id) from t where id <= 101", "100\n"),
+    ] {
+        Command::cargo_bin("rsql")
+            .unwrap()
+            .args(["sql", "tests/resources/deleted.db", sql])
+            .assert()
+            .success()
+            .stdout(eq(rows));
+    }
+}
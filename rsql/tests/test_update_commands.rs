@@ -0,0 +1,103 @@
+use predicates::ord::eq;
+
+mod common;
+use common::{copy_db, sql, sqlite3};
+
+#[test]
+fn cli_sql_update_where() {
+    let (_dir, db) = copy_db("sample.db");
+    sql(
+        &db,
+        "update apples set color = name || ' ' || color, name = 'x' where id >= 3",
+    )
+    .success()
+    .stdout(eq(""));
+    // the INTEGER PRIMARY KEY is the rowid, the row moves
+    sql(
+        &db,
+        "update apples set id = id + 10, color = 1 where id = 1",
+    )
+    .success();
+    sql(&db, "select * from apples").success().stdout(eq(
+        "2|Fuji|Red\n3|x|Honeycrisp Blush Red\n4|x|Golden Delicious Yellow\n11|Granny Smith|1\n",
+    ));
+    sql(&db, "select count(*) from apples where id = 1")
+        .success()
+        .stdout(eq("0\n"));
+}
+
+#[test]
+fn cli_sql_update_constraints() {
+    let (_dir, db) = copy_db("sample.db");
+    // rows are updated in rowid order: 1 becomes 2 before 2 becomes 3
+    sql(&db, "update apples set id = id + 1")
+        .failure()
+        .stderr(predicates::str::contains(
+            "UNIQUE constraint failed: apples.id",
+        ));
+    sql(&db, "update apples set id = NULL where id = 2")
+        .failure()
+        .stderr(predicates::str::contains("datatype mismatch"));
+    sql(&db, "select id from apples")
+        .success()
+        .stdout(eq("1\n2\n3\n4\n"));
+}
+
+#[test]
+fn cli_sql_update_overflow_and_indexes() {
+    let (_dir, db) = copy_db("orders.db");
+    // a value larger than a page spills to overflow pages, freed by the next update
+    let long_name = "n".repeat(10000);
+    sql(
+        &db,
+        &format!("update customers set name = '{long_name}' where id = 9"),
+    )
+    .success();
+    sql(
+        &db,
+        &format!("select id from customers where name = '{long_name}'"),
+    )
+    .success()
+    .stdout(eq("9\n"));
+    sql(
+        &db,
+        "update customers set name = 'short', email = 'NEW@example.com' where id = 9",
+    )
+    .success();
+    // searched with the index on email COLLATE NOCASE
+    sql(
+        &db,
+        "select id, name from customers where email = 'new@example.com'",
+    )
+    .success()
+    .stdout(eq("9|short\n"));
+    // customer 4509 has the same email
+    sql(
+        &db,
+        "select id from customers where email = 'c3@example.com'",
+    )
+    .success()
+    .stdout(eq("4509\n"));
+}
+
+#[test]
+fn cli_sql_update_mixed_storage_classes() {
+    let (_dir, db) = copy_db("sample.db");
+    // the new values are not cast to the arrow types of the columns, they get the
+    // affinity of the columns
+    sql(
+        &db,
+        "create table mixed (a, r real, t text); \
+         insert into mixed values (1, 1, 1), (2, 2, 2); \
+         update mixed set a = 'x', r = '2.5', t = 3.0 where a = 1; \
+         update mixed set a = a * 1.5, r = x'01' where rowid = 2",
+    )
+    .success();
+    sql(&db, "update mixed set c = 1")
+        .failure()
+        .stderr(predicates::str::contains("no such column: c"));
+    assert_eq!(
+        sqlite3(&db, "select quote(a), quote(r), quote(t) from mixed"),
+        "'x'|2.5|'3.0'\n3.0|X'01'|'2'\n"
+    );
+}